@@ -33,6 +33,7 @@ export default function StakingSettings() {
   const [stakeRatePerSecond, setStakeRatePerSecond] = useState<string>('0.0000000125');
   const [stakeThreshold, setStakeThreshold] = useState<string>('10.0');
   const [unstakeThreshold, setUnstakeThreshold] = useState<string>('10.0');
+  const [minTopup, setMinTopup] = useState<string>('0.0');
   const [harvestThreshold, setHarvestThreshold] = useState<string>('1.0');
   
   // Default to the requested rate (1.25e-7) if needed and log what we're doing
@@ -85,6 +86,10 @@ export default function StakingSettings() {
         setUnstakeThreshold(settings.unstakeThreshold.toString());
         console.log(`Setting unstake threshold from database: ${settings.unstakeThreshold}`);
       }
+      
+      if (settings.minTopup !== undefined) {
+        setMinTopup(settings.minTopup.toString());
+      }
     }
   }, [settings]);
   
@@ -199,6 +204,7 @@ export default function StakingSettings() {
       // Validate and cap input values to prevent overflow errors
       let stakeThresholdValue = parseFloat(stakeThreshold);
       let unstakeThresholdValue = parseFloat(unstakeThreshold);
+      let minTopupValue = parseFloat(minTopup);
       
       // Safety caps for database parameters
       const MAX_STAKE_THRESHOLD = 1000000;
@@ -237,24 +243,44 @@ export default function StakingSettings() {
         return;
       }
       
+      // Zero is allowed: top-ups of any size are accepted
+      if (isNaN(minTopupValue) || minTopupValue < 0) {
+        toast({
+          title: "Invalid Minimum Top-up",
+          description: "Minimum top-up must be zero or a positive number",
+          variant: "destructive",
+        });
+        return;
+      } else if (minTopupValue > MAX_STAKE_THRESHOLD) {
+        toast({
+          title: "Minimum Top-up Too Large",
+          description: `Maximum allowed value is ${MAX_STAKE_THRESHOLD}`,
+          variant: "destructive",
+        });
+        return;
+      }
+      
       // Cap the values for safety
       stakeThresholdValue = Math.min(stakeThresholdValue, MAX_STAKE_THRESHOLD);
       unstakeThresholdValue = Math.min(unstakeThresholdValue, MAX_UNSTAKE_THRESHOLD);
+      minTopupValue = Math.min(minTopupValue, MAX_STAKE_THRESHOLD);
       
       console.log("Saving database thresholds:", {
         stakeThreshold: stakeThresholdValue,
-        unstakeThreshold: unstakeThresholdValue
+        unstakeThreshold: unstakeThresholdValue,
+        minTopup: minTopupValue
       });
       
-      // Update database settings with stake/unstake thresholds
+      // Update database settings with stake/unstake thresholds and minimum top-up
       updateSettings({
         stakeThreshold: stakeThresholdValue.toString(),
-        unstakeThreshold: unstakeThresholdValue.toString()
+        unstakeThreshold: unstakeThresholdValue.toString(),
+        minTopup: minTopupValue.toString()
       });
       
       toast({
         title: 'Database Settings Updated',
-        description: 'The minimum stake and unstake thresholds have been updated.',
+        description: 'The minimum stake, top-up and unstake thresholds have been updated.',
       });
     } catch (error) {
       toast({
@@ -342,7 +368,29 @@ export default function StakingSettings() {
                 disabled={isUpdatingDatabase || !isAdmin}
               />
               <p className="text-sm text-muted-foreground">
-                Users must stake at least this amount of YOT tokens.
+                Users must stake at least this amount of YOT tokens to open a position.
+              </p>
+            </div>
+            
+            <div className="space-y-2">
+              <Label htmlFor="minTopup">Minimum Top-up (YOT)</Label>
+              <Input
+                id="minTopup"
+                type="text"
+                inputMode="decimal"
+                pattern="[0-9]*\.?[0-9]*"
+                value={minTopup}
+                onChange={(e) => {
+                  // Remove any non-numeric characters except decimal point
+                  const sanitizedValue = e.target.value.replace(/[^0-9.]/g, '');
+                  const parts = sanitizedValue.split('.');
+                  setMinTopup(parts[0] + (parts.length > 1 ? '.' + parts[1] : ''));
+                }}
+                placeholder="0.0"
+                disabled={isUpdatingDatabase || !isAdmin}
+              />
+              <p className="text-sm text-muted-foreground">
+                Users adding to an existing position must stake at least this amount of YOT tokens.
               </p>
             </div>
             
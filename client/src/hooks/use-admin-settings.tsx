@@ -14,6 +14,7 @@ type UpdateSettingsData = Partial<{
   harvestThreshold: string;
   stakeThreshold: string;
   unstakeThreshold: string;
+  minTopup: string;
 }>;
 
 export function useAdminSettings() {
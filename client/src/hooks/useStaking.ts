@@ -78,6 +78,7 @@ interface StakingRates {
   // Additional thresholds (optional for backward compatibility)
   stakeThreshold?: number;
   unstakeThreshold?: number;
+  minTopup?: number;
 }
 
 // Add interface for global staking statistics
@@ -168,8 +169,14 @@ export function useStaking() {
         const rates = await getStakingProgramState();
         console.log("Staking rates for threshold check:", rates);
         
-        // Check if stake amount meets the minimum threshold
-        if (amount < (rates.stakeThreshold || 10)) {
+        // The stake threshold only gates opening a position; top-ups use minTopup
+        const currentStake = await getStakingInfo(publicKey.toString());
+        if (currentStake.stakedAmount > 0) {
+          const minTopup = rates.minTopup || 0;
+          if (amount < minTopup) {
+            throw new Error(`Top-up amount (${amount.toFixed(2)} YOT) is below the minimum top-up (${minTopup.toFixed(2)} YOT). Please stake more tokens.`);
+          }
+        } else if (amount < (rates.stakeThreshold || 10)) {
           throw new Error(`Staking amount (${amount.toFixed(2)} YOT) is below the minimum threshold (${(rates.stakeThreshold || 10).toFixed(2)} YOT). Please stake more tokens.`);
         }
         
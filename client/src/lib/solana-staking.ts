@@ -7,6 +7,7 @@ import {
   sendAndConfirmTransaction, 
   LAMPORTS_PER_SOL,
   SYSVAR_CLOCK_PUBKEY,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
   TransactionInstruction
 } from '@solana/web3.js';
 import { sendTransaction } from './transaction-helper';
@@ -506,6 +507,20 @@ function findProgramAuthorityAddress(): [PublicKey, number] {
   );
 }
 
+function findPauseStateAddress(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('pause_state')],
+    new PublicKey(STAKING_PROGRAM_ID)
+  );
+}
+
+function findRunwayConfigAddress(): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('runway_config')],
+    new PublicKey(STAKING_PROGRAM_ID)
+  );
+}
+
 function encodeInitializeInstruction(
   yotMint: PublicKey,
   yosMint: PublicKey,
@@ -935,6 +950,7 @@ export async function stakeYOTTokens(
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },   // token_program
         { pubkey: new PublicKey('SysvarC1ock11111111111111111111111111111111'), isSigner: false, isWritable: false },  // clock sysvar
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },  // system_program
+        { pubkey: findPauseStateAddress()[0], isSigner: false, isWritable: false }, // pause_state
       ],
       programId: new PublicKey(STAKING_PROGRAM_ID),
       data: encodeStakeInstruction(amount)
@@ -1131,6 +1147,9 @@ export async function prepareUnstakeTransaction(
       { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },      // token_program
       { pubkey: programAuthority, isSigner: false, isWritable: false },      // program_authority
       { pubkey: new PublicKey('SysvarC1ock11111111111111111111111111111111'), isSigner: false, isWritable: false }, // clock sysvar
+      { pubkey: findPauseStateAddress()[0], isSigner: false, isWritable: false }, // pause_state
+      { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false }, // instructions sysvar
+      { pubkey: findRunwayConfigAddress()[0], isSigner: false, isWritable: false }, // runway_config
     ],
     programId: new PublicKey(STAKING_PROGRAM_ID),
     data: encodeUnstakeInstruction(amount)
@@ -1227,6 +1246,9 @@ export async function unstakeYOTTokens(
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },      // token_program
         { pubkey: programAuthority, isSigner: false, isWritable: false },      // program_authority
         { pubkey: new PublicKey('SysvarC1ock11111111111111111111111111111111'), isSigner: false, isWritable: false }, // clock sysvar
+        { pubkey: findPauseStateAddress()[0], isSigner: false, isWritable: false }, // pause_state
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false }, // instructions sysvar
+        { pubkey: findRunwayConfigAddress()[0], isSigner: false, isWritable: false }, // runway_config
       ],
       programId: new PublicKey(STAKING_PROGRAM_ID),
       data: encodeUnstakeInstruction(amount)
@@ -1298,8 +1320,10 @@ export async function harvestYOSRewards(wallet: any): Promise<string> {
     const [stakingAccount] = findStakingAccountAddress(walletPublicKey);
     const [programAuthority] = findProgramAuthorityAddress();
     
-    // Get program authority's token address
+    // Get program authority's token addresses; the YOT vault sizes the
+    // reward runway
     const programYosATA = await getAssociatedTokenAddress(yosMint, programAuthority, true);
+    const programYotATA = await getAssociatedTokenAddress(new PublicKey(YOT_TOKEN_ADDRESS), programAuthority, true);
     
     // Get the staking info to verify rewards
     const stakingInfo = await getStakingInfo(walletPublicKey.toString());
@@ -1377,6 +1401,10 @@ export async function harvestYOSRewards(wallet: any): Promise<string> {
         { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },      // token_program
         { pubkey: programAuthority, isSigner: false, isWritable: false },      // program_authority
         { pubkey: new PublicKey('SysvarC1ock11111111111111111111111111111111'), isSigner: false, isWritable: false }, // clock sysvar
+        { pubkey: findPauseStateAddress()[0], isSigner: false, isWritable: false }, // pause_state
+        { pubkey: SYSVAR_INSTRUCTIONS_PUBKEY, isSigner: false, isWritable: false }, // instructions sysvar
+        { pubkey: findRunwayConfigAddress()[0], isSigner: false, isWritable: false }, // runway_config
+        { pubkey: programYotATA, isSigner: false, isWritable: false },         // program_yot_token_account
       ],
       programId: new PublicKey(STAKING_PROGRAM_ID),
       data: encodeHarvestInstruction() // No parameters needed - program calculates rewards
@@ -1722,15 +1750,15 @@ export async function getGlobalStakingStats(): Promise<{
       };
     }
     
-    // Find all staking accounts
-    const programAccounts = await connection.getProgramAccounts(
-      new PublicKey(STAKING_PROGRAM_ID),
-      {
-        filters: [
-          { dataSize: 64 }, // Staking account size (owner + amount + timestamps)
-        ],
-      }
-    );
+    // Find all staking accounts: 64 bytes (owner + amount + timestamps +
+    // harvested) for older accounts, 72 with a personal harvest threshold,
+    // 80 with a stake lock
+    const programAccounts = (await Promise.all(
+      [64, 72, 80].map((dataSize) => connection.getProgramAccounts(
+        new PublicKey(STAKING_PROGRAM_ID),
+        { filters: [{ dataSize }] }
+      ))
+    )).flat();
     
     // Total YOT staked calculation
     let totalStaked = 0;
@@ -1798,6 +1826,7 @@ export async function getStakingProgramState(): Promise<{
   yosMint?: string;
   stakeThreshold?: number;
   unstakeThreshold?: number;
+  minTopup?: number;
 }> {
   // Define time constants once, outside the function scope
   const TIME_CONSTANTS = {
@@ -1858,6 +1887,8 @@ export async function getStakingProgramState(): Promise<{
       // Read stake and unstake thresholds (8 bytes each, 64-bit unsigned integer)
       let stakeThreshold = 10;
       let unstakeThreshold = 10;
+      // Top-ups of an existing position are not gated by stakeThreshold
+      const minTopup = 0;
       
       // Check if the program state includes stake and unstake thresholds (newer program version)
       if (programStateInfo.data.length >= 32 + 32 + 32 + 8 + 8 + 8) {
@@ -1895,6 +1926,7 @@ export async function getStakingProgramState(): Promise<{
         harvestThreshold,
         stakeThreshold,
         unstakeThreshold,
+        minTopup,
         dailyAPR,
         weeklyAPR,
         monthlyAPR,
@@ -1921,6 +1953,7 @@ export async function getStakingProgramState(): Promise<{
     const harvestThreshold = 1;
     const stakeThreshold = 10;
     const unstakeThreshold = 10;
+    const minTopup = 0;
     
     const dailyAPR = stakeRatePerSecond * TIME_CONSTANTS.secondsPerDay;
     const weeklyAPR = stakeRatePerSecond * TIME_CONSTANTS.secondsPerWeek;
@@ -1944,6 +1977,7 @@ export async function getStakingProgramState(): Promise<{
       harvestThreshold,
       stakeThreshold,
       unstakeThreshold,
+      minTopup,
       dailyAPR,
       weeklyAPR,
       monthlyAPR,
@@ -1964,6 +1998,7 @@ export async function getStakingProgramState(): Promise<{
     const harvestThreshold = 1;
     const stakeThreshold = 10;
     const unstakeThreshold = 10;
+    const minTopup = 0;
     
     // Calculate linear rates (not compound)
     const dailyAPR = stakeRatePerSecond * TIME_CONSTANTS.secondsPerDay;
@@ -1990,6 +2025,7 @@ export async function getStakingProgramState(): Promise<{
       harvestThreshold,
       stakeThreshold,
       unstakeThreshold,
+      minTopup,
       dailyAPR,
       weeklyAPR,
       monthlyAPR,
@@ -388,7 +388,9 @@ export default function Stake() {
                       </div>
                       <div className="text-xs text-amber-400 flex items-center">
                         <span className="mr-1">⚠️</span>
-                        Minimum stake: {stakingRates?.stakeThreshold || 0} YOT
+                        {stakingInfo.stakedAmount > 0
+                          ? `Minimum top-up: ${stakingRates?.minTopup || 0} YOT`
+                          : `Minimum stake: ${stakingRates?.stakeThreshold || 0} YOT`}
                       </div>
                     </div>
                     
@@ -0,0 +1,3 @@
+-- Add min_topup column to admin_settings table; stake_threshold now only applies to new positions
+ALTER TABLE admin_settings 
+ADD COLUMN IF NOT EXISTS min_topup DECIMAL DEFAULT 0.0 NOT NULL;
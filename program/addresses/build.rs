@@ -0,0 +1,138 @@
+// Generates addresses.rs, JSON artifact included, from seeds.txt.
+//
+// The table is validated here, so a malformed line or a prefix reused within
+// a program fails the build instead of deriving a colliding address.
+use std::{collections::BTreeSet, env, fmt::Write as _, fs, path::Path};
+
+const KINDS: [(&str, &str); 4] = [("pubkey", "Pubkey"), ("u16", "U16"), ("u64", "U64"), ("bytes32", "Bytes32")];
+
+struct Scheme {
+    program: String,
+    name: String,
+    prefix: String,
+    components: Vec<(String, String)>,
+}
+
+fn is_identifier(value: &str) -> bool {
+    value.starts_with(|c: char| c.is_ascii_lowercase())
+        && value.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn parse(table: &str) -> Vec<Scheme> {
+    let mut schemes: Vec<Scheme> = Vec::new();
+    for (index, line) in table.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 {
+            panic!("seeds.txt:{}: expected `program name \"prefix\" components...`", line_number);
+        }
+        let (program, name) = (fields[0], fields[1]);
+        if !is_identifier(program) || !is_identifier(name) {
+            panic!("seeds.txt:{}: program and scheme names must be snake_case", line_number);
+        }
+        let prefix = fields[2]
+            .strip_prefix('"')
+            .and_then(|prefix| prefix.strip_suffix('"'))
+            .filter(|prefix| is_identifier(prefix) && prefix.len() <= 32)
+            .unwrap_or_else(|| panic!("seeds.txt:{}: the prefix must be a quoted snake_case seed", line_number));
+        let components = fields[3..]
+            .iter()
+            .map(|component| match component.split_once(':') {
+                Some((name, kind)) if is_identifier(name) && KINDS.iter().any(|(known, _)| *known == kind) => {
+                    (name.to_string(), kind.to_string())
+                }
+                _ => panic!("seeds.txt:{}: bad component `{}`", line_number, component),
+            })
+            .collect();
+        for scheme in schemes.iter().filter(|scheme| scheme.program == program) {
+            if scheme.name == name || scheme.prefix == prefix {
+                panic!("seeds.txt:{}: {} reuses the name or prefix of {}", line_number, name, scheme.name);
+            }
+        }
+        schemes.push(Scheme {
+            program: program.to_string(),
+            name: name.to_string(),
+            prefix: prefix.to_string(),
+            components,
+        });
+    }
+    schemes
+}
+
+fn kind_variant(kind: &str) -> &'static str {
+    KINDS.iter().find(|(known, _)| *known == kind).map(|(_, variant)| *variant).unwrap()
+}
+
+fn generate_rust(schemes: &[Scheme], json: &str) -> String {
+    let mut out = String::from("// Generated by build.rs from seeds.txt; edit the table instead.\n");
+    let programs: BTreeSet<&str> = schemes.iter().map(|scheme| scheme.program.as_str()).collect();
+    for program in programs {
+        writeln!(out, "\npub mod {} {{", program).unwrap();
+        for scheme in schemes.iter().filter(|scheme| scheme.program == program) {
+            let mut seeds = format!("\"{}\"", scheme.prefix);
+            for (name, _) in &scheme.components {
+                write!(seeds, ", {}", name).unwrap();
+            }
+            writeln!(out, "    // [{}]", seeds).unwrap();
+            writeln!(out, "    pub const {}: &[u8] = b\"{}\";", scheme.name.to_uppercase(), scheme.prefix).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+    writeln!(out, "\npub const SCHEMES: &[SeedScheme] = &[").unwrap();
+    for scheme in schemes {
+        let components: Vec<String> = scheme
+            .components
+            .iter()
+            .map(|(name, kind)| format!("(\"{}\", SeedKind::{})", name, kind_variant(kind)))
+            .collect();
+        writeln!(
+            out,
+            "    SeedScheme {{ program: \"{}\", name: \"{}\", prefix: b\"{}\", components: &[{}] }},",
+            scheme.program,
+            scheme.name,
+            scheme.prefix,
+            components.join(", ")
+        )
+        .unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    writeln!(out, "\npub const SCHEMES_JSON: &str = {:?};", json).unwrap();
+    out
+}
+
+// Every name and prefix is snake_case, so nothing needs escaping
+fn generate_json(schemes: &[Scheme]) -> String {
+    let mut out = String::from("[\n");
+    for (index, scheme) in schemes.iter().enumerate() {
+        let components: Vec<String> = scheme
+            .components
+            .iter()
+            .map(|(name, kind)| format!("{{ \"name\": \"{}\", \"kind\": \"{}\" }}", name, kind))
+            .collect();
+        write!(
+            out,
+            "  {{ \"program\": \"{}\", \"name\": \"{}\", \"prefix\": \"{}\", \"components\": [{}] }}",
+            scheme.program,
+            scheme.name,
+            scheme.prefix,
+            components.join(", ")
+        )
+        .unwrap();
+        out.push_str(if index + 1 < schemes.len() { ",\n" } else { "\n" });
+    }
+    out.push_str("]\n");
+    out
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=seeds.txt");
+    let table = fs::read_to_string("seeds.txt").expect("seeds.txt is readable");
+    let schemes = parse(&table);
+    let json = generate_json(&schemes);
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    fs::write(Path::new(&out_dir).join("addresses.rs"), generate_rust(&schemes, &json)).unwrap();
+}
@@ -0,0 +1,47 @@
+// PDA seed schemes of the swap and staking programs.
+//
+// Everything below the type definitions is generated by build.rs from
+// seeds.txt: a module of prefix constants per program, which the programs and
+// the SDK derive addresses with, and the same table as data and as the JSON
+// checked in at shared/pda-seeds.json for the web client. The crate is no_std
+// and dependency-free so the on-chain programs can use it.
+#![no_std]
+
+// How a seed component after the prefix is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeedKind {
+    // A 32-byte public key
+    Pubkey,
+    // A little-endian u16
+    U16,
+    // A little-endian u64
+    U64,
+    // 32 raw bytes
+    Bytes32,
+}
+
+impl SeedKind {
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            SeedKind::Pubkey | SeedKind::Bytes32 => 32,
+            SeedKind::U16 => 2,
+            SeedKind::U64 => 8,
+        }
+    }
+}
+
+// One PDA: a constant prefix followed by the named components
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedScheme {
+    pub program: &'static str,
+    pub name: &'static str,
+    pub prefix: &'static [u8],
+    pub components: &'static [(&'static str, SeedKind)],
+}
+
+// The scheme `name` of `program`
+pub fn find_scheme(program: &str, name: &str) -> Option<&'static SeedScheme> {
+    SCHEMES.iter().find(|scheme| scheme.program == program && scheme.name == name)
+}
+
+include!(concat!(env!("OUT_DIR"), "/addresses.rs"));
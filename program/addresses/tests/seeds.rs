@@ -0,0 +1,31 @@
+use tswap_addresses::*;
+
+const CHECKED_IN_JSON: &str = include_str!("../../../shared/pda-seeds.json");
+
+#[test]
+fn checked_in_json_matches_the_seed_table() {
+    // Regenerate shared/pda-seeds.json from SCHEMES_JSON after editing seeds.txt
+    assert_eq!(CHECKED_IN_JSON, SCHEMES_JSON);
+}
+
+#[test]
+fn each_program_keeps_its_own_state_seed() {
+    // The three state seed conventions, deliberately different per program
+    assert_eq!(swap::PROGRAM_STATE, b"state");
+    assert_eq!(staking::PROGRAM_STATE, b"program_state");
+    assert_eq!(swap_v2::PROGRAM_STATE, b"state_v2");
+
+    let pool = find_scheme("swap", "pool").unwrap();
+    assert_eq!(pool.prefix, swap::POOL);
+    assert_eq!(pool.components, &[("mint_a", SeedKind::Pubkey), ("mint_b", SeedKind::Pubkey)]);
+}
+
+#[test]
+fn every_scheme_fits_a_program_address() {
+    // At most 16 seeds of at most 32 bytes, leaving room for the bump
+    for scheme in SCHEMES {
+        assert!(scheme.prefix.len() <= 32, "{}", scheme.name);
+        assert!(scheme.components.len() < 15, "{}", scheme.name);
+        assert!(scheme.components.iter().all(|(_, kind)| kind.encoded_len() <= 32), "{}", scheme.name);
+    }
+}
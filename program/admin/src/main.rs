@@ -0,0 +1,498 @@
+// tswap-admin: admin operations for the deployed swap and staking programs.
+//
+// Every instruction is built by tswap-sdk, so account order and PDA seeds
+// stay in one place. Each command prints the confirmed transaction signature.
+use borsh::BorshDeserialize;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::RpcFilterType,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::{error::Error, path::PathBuf, str::FromStr};
+use tswap_sdk::{snapshot::SnapshotTree, staking, swap, ONE_TOKEN};
+
+#[derive(Parser)]
+#[command(name = "tswap-admin", about = "Administer the swap and staking programs")]
+struct Args {
+    /// RPC endpoint
+    #[arg(long, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Admin keypair (defaults to the Solana CLI keypair)
+    #[arg(long)]
+    keypair: Option<PathBuf>,
+    /// Deployed swap program id
+    #[arg(long, default_value_t = swap::program_id())]
+    swap_program_id: Pubkey,
+    /// Deployed staking program id
+    #[arg(long, default_value_t = staking::PROGRAM_ID)]
+    staking_program_id: Pubkey,
+    /// Sign a swap admin instruction as a wallet the permission matrix
+    /// grants it to, rather than as the admin
+    #[arg(long)]
+    delegated: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Program {
+    Swap,
+    Staking,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create program state
+    Initialize {
+        program: Program,
+        #[arg(long)]
+        yot_mint: Pubkey,
+        #[arg(long)]
+        yos_mint: Pubkey,
+        /// Staking: YOS reward rate per staked YOT per second
+        #[arg(long, default_value_t = 12)]
+        stake_rate_per_second: u64,
+        /// Staking: minimum YOS rewards before a harvest, in base units
+        #[arg(long, default_value_t = ONE_TOKEN)]
+        harvest_threshold: u64,
+    },
+    /// Update program parameters; swap rates not given keep their value
+    UpdateParams {
+        program: Program,
+        /// Swap: LP contribution rate in basis points
+        #[arg(long)]
+        lp_contribution_rate: Option<u64>,
+        /// Swap: admin fee rate in basis points
+        #[arg(long)]
+        admin_fee_rate: Option<u64>,
+        /// Swap: YOS cashback rate in basis points
+        #[arg(long)]
+        yos_cashback_rate: Option<u64>,
+        /// Swap: swap fee rate in basis points
+        #[arg(long)]
+        swap_fee_rate: Option<u64>,
+        /// Swap: referral rate in basis points
+        #[arg(long)]
+        referral_rate: Option<u64>,
+        /// Staking: YOS reward rate per staked YOT per second
+        #[arg(long, required_if_eq("program", "staking"))]
+        stake_rate_per_second: Option<u64>,
+        /// Staking: minimum YOS rewards before a harvest, in base units
+        #[arg(long, required_if_eq("program", "staking"))]
+        harvest_threshold: Option<u64>,
+    },
+    /// Replace the pause mask; 0 resumes everything
+    Pause {
+        program: Program,
+        #[arg(long)]
+        mask: u8,
+    },
+    /// Hand the swap program admin role to another key
+    SetAdmin {
+        #[arg(long)]
+        new_admin: Pubkey,
+    },
+    /// Transfer YOS from the admin into the staking reward vault
+    FundRewards {
+        /// Amount in YOS base units
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Set the staking reward runway below which the rate scales down; 0 disables
+    SetMinRunway {
+        /// Seconds of emissions the YOS vault should cover
+        #[arg(long)]
+        seconds: i64,
+    },
+    /// Freeze staking reward accrual while principal stays movable
+    FreezeRewards {
+        /// Resume accrual instead
+        #[arg(long)]
+        resume: bool,
+    },
+    /// Set the share of YOS harvests paid to the treasury; 0 disables
+    SetHarvestFee {
+        /// Fee in basis points, at most 1000
+        #[arg(long)]
+        bps: u16,
+    },
+    /// Snapshot every staking balance for a bonus campaign, writing the
+    /// claim proofs to a file before recording the snapshot on chain
+    RecordSnapshot {
+        #[arg(long)]
+        campaign_id: u64,
+        /// YOS shared between the balances, in base units
+        #[arg(long)]
+        bonus_pool: u64,
+        /// Where to write the snapshot and its claim proofs
+        #[arg(long)]
+        output: PathBuf,
+    },
+    /// Fund the vault of a snapshot recorded before snapshots had vaults
+    /// with its unclaimed bonus, so its claims can be paid
+    FundSnapshotVault {
+        #[arg(long)]
+        campaign_id: u64,
+    },
+    /// Withdraw collected SOL fees from the swap program to its admin
+    WithdrawFees {
+        #[arg(long)]
+        lamports: u64,
+        /// Treasury approver keypair, required above the approval amount
+        /// (repeatable)
+        #[arg(long = "approver")]
+        approvers: Vec<PathBuf>,
+    },
+    /// Pay tokens held by the swap program's treasury to a token account
+    WithdrawTreasury {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Token account of `mint` receiving the withdrawal
+        #[arg(long)]
+        recipient: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Treasury approver keypair, required above the approval amount
+        /// (repeatable)
+        #[arg(long = "approver")]
+        approvers: Vec<PathBuf>,
+    },
+    /// Register a market-maker wallet, waiving its swap fee and cashback
+    SetMarketMaker {
+        #[arg(long)]
+        wallet: Pubkey,
+        /// Remove the fee exemption instead of granting it
+        #[arg(long)]
+        revoke: bool,
+    },
+    /// Require YOT or an approved hub token on one side of every swap
+    SetRequireHubSide {
+        /// Allow any pair again
+        #[arg(long)]
+        disable: bool,
+    },
+    /// Approve a mint as a swap hub token
+    SetHubToken {
+        #[arg(long)]
+        mint: Pubkey,
+        /// Revoke the approval instead of granting it
+        #[arg(long)]
+        revoke: bool,
+    },
+    /// Replace the hub tokens routes go through besides YOT
+    SetHubTokens {
+        /// Hub mint (repeatable)
+        #[arg(long = "mint")]
+        mints: Vec<Pubkey>,
+    },
+    /// Close the swap authority's empty vault of a delisted mint, returning
+    /// its rent to the treasury
+    CloseVault {
+        #[arg(long)]
+        mint: Pubkey,
+    },
+    /// Cap a swap pool's reserve of one of its mints
+    SetDepositCap {
+        /// The pool's other mint
+        #[arg(long)]
+        pair: Pubkey,
+        /// The capped mint
+        #[arg(long)]
+        mint: Pubkey,
+        /// Largest reserve in base units; 0 removes the cap
+        #[arg(long)]
+        max_reserve: u64,
+    },
+    /// Flag a pair as sensitive, allowing swaps only along the given routes
+    SetAllowedRoutes {
+        #[arg(long)]
+        mint_a: Pubkey,
+        #[arg(long)]
+        mint_b: Pubkey,
+        /// Allowed route, in either direction (repeatable): "direct", or the
+        /// comma-separated mints it passes between mint-a and mint-b; none
+        /// clears the flag
+        #[arg(long = "route")]
+        routes: Vec<String>,
+    },
+    /// Start tracking a pool's time-weighted reserves, which swaps need to
+    /// record liquidity contributions as LP shares
+    InitializePoolTwap {
+        #[arg(long)]
+        mint_a: Pubkey,
+        #[arg(long)]
+        mint_b: Pubkey,
+    },
+    /// Grant a wallet swap admin instructions by discriminant, replacing its
+    /// earlier grants (admin or governance)
+    SetPermissions {
+        #[arg(long)]
+        holder: Pubkey,
+        /// Discriminant of a granted instruction (repeatable); none revokes all
+        #[arg(long = "instruction")]
+        instructions: Vec<u8>,
+    },
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let keypair_path = match args.keypair {
+        Some(path) => path,
+        None => PathBuf::from(std::env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    let admin = read_keypair_file(&keypair_path)
+        .map_err(|error| format!("failed to read keypair {}: {}", keypair_path.display(), error))?;
+    let rpc = RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed());
+    let swap_program_id = args.swap_program_id;
+    let staking_program_id = args.staking_program_id;
+
+    let mut signers = Vec::new();
+    let instruction = match args.command {
+        Command::Initialize {
+            program: Program::Swap,
+            yot_mint,
+            yos_mint,
+            ..
+        } => swap::initialize(
+            &swap_program_id,
+            &admin.pubkey(),
+            &yot_mint,
+            &yos_mint,
+            swap::SwapRates::default(),
+        ),
+        Command::Initialize {
+            program: Program::Staking,
+            yot_mint,
+            yos_mint,
+            stake_rate_per_second,
+            harvest_threshold,
+        } => staking::initialize(
+            &staking_program_id,
+            &admin.pubkey(),
+            &yot_mint,
+            &yos_mint,
+            stake_rate_per_second,
+            harvest_threshold,
+        ),
+        Command::UpdateParams {
+            program: Program::Swap,
+            lp_contribution_rate,
+            admin_fee_rate,
+            yos_cashback_rate,
+            swap_fee_rate,
+            referral_rate,
+            ..
+        } => swap::update_parameters(
+            &swap_program_id,
+            &admin.pubkey(),
+            lp_contribution_rate,
+            admin_fee_rate,
+            yos_cashback_rate,
+            swap_fee_rate,
+            referral_rate,
+        ),
+        Command::UpdateParams {
+            program: Program::Staking,
+            stake_rate_per_second,
+            harvest_threshold,
+            ..
+        } => staking::update_parameters(
+            &staking_program_id,
+            &admin.pubkey(),
+            stake_rate_per_second.ok_or("--stake-rate-per-second is required for staking")?,
+            harvest_threshold.ok_or("--harvest-threshold is required for staking")?,
+        ),
+        Command::Pause {
+            program: Program::Swap,
+            mask,
+        } => swap::set_paused_mask(&swap_program_id, &admin.pubkey(), mask),
+        Command::Pause {
+            program: Program::Staking,
+            mask,
+        } => staking::set_paused_mask(&staking_program_id, &admin.pubkey(), mask),
+        Command::SetAdmin { new_admin } => swap::set_admin(&swap_program_id, &admin.pubkey(), &new_admin),
+        Command::FundRewards { amount } => {
+            // Rewards are paid in the mint recorded in staking state
+            let (state_address, _) = staking::find_program_state_address(&staking_program_id);
+            let state = staking::ProgramState::try_from_slice(&rpc.get_account_data(&state_address)?)?;
+            staking::fund_rewards(&staking_program_id, &admin.pubkey(), &state.yos_mint, amount)
+        }
+        Command::SetMinRunway { seconds } => staking::set_min_runway(&staking_program_id, &admin.pubkey(), seconds),
+        Command::FreezeRewards { resume } => {
+            staking::set_reward_freeze(&staking_program_id, &admin.pubkey(), !resume)
+        }
+        Command::SetHarvestFee { bps } => staking::set_harvest_fee(&staking_program_id, &admin.pubkey(), bps),
+        Command::RecordSnapshot {
+            campaign_id,
+            bonus_pool,
+            output,
+        } => {
+            let slot = rpc.get_slot()?;
+            let tree = SnapshotTree::new(get_staking_balances(&rpc, &staking_program_id)?);
+            let claims: Vec<_> = tree
+                .balances()
+                .iter()
+                .map(|(owner, staked_amount)| {
+                    let (_, proof) = tree.proof(owner).expect("every balance has a proof");
+                    json!({
+                        "owner": owner.to_string(),
+                        "stakedAmount": staked_amount,
+                        "proof": proof.iter().map(|node| Hash::new_from_array(*node).to_string()).collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
+            let snapshot = json!({
+                "campaignId": campaign_id,
+                "slot": slot,
+                "merkleRoot": Hash::new_from_array(tree.root()).to_string(),
+                "totalStaked": tree.total_staked(),
+                "bonusPool": bonus_pool,
+                "claims": claims,
+            });
+            std::fs::write(&output, serde_json::to_string_pretty(&snapshot)?)?;
+            // The bonus is paid in the mint recorded in staking state
+            let (state_address, _) = staking::find_program_state_address(&staking_program_id);
+            let state = staking::ProgramState::try_from_slice(&rpc.get_account_data(&state_address)?)?;
+            staking::record_snapshot(
+                &staking_program_id,
+                &admin.pubkey(),
+                &state.yos_mint,
+                campaign_id,
+                slot,
+                tree.root(),
+                tree.total_staked(),
+                bonus_pool,
+            )
+        }
+        Command::FundSnapshotVault { campaign_id } => {
+            // The bonus is paid in the mint recorded in staking state
+            let (state_address, _) = staking::find_program_state_address(&staking_program_id);
+            let state = staking::ProgramState::try_from_slice(&rpc.get_account_data(&state_address)?)?;
+            staking::fund_snapshot_vault(&staking_program_id, &admin.pubkey(), &state.yos_mint, campaign_id)
+        }
+        Command::WithdrawFees { lamports, approvers } => {
+            for path in approvers {
+                signers.push(
+                    read_keypair_file(&path)
+                        .map_err(|error| format!("failed to read keypair {}: {}", path.display(), error))?,
+                );
+            }
+            let approvers: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+            // The fees are always paid to the admin recorded in swap state
+            let (state_address, _) = swap::find_program_state_address(&swap_program_id);
+            let state = swap::ProgramState::deserialize(&mut &rpc.get_account_data(&state_address)?[..])?;
+            swap::withdraw_sol_fees(&swap_program_id, &admin.pubkey(), &state.admin, lamports, &approvers)
+        }
+        Command::WithdrawTreasury {
+            mint,
+            recipient,
+            amount,
+            approvers,
+        } => {
+            for path in approvers {
+                signers.push(
+                    read_keypair_file(&path)
+                        .map_err(|error| format!("failed to read keypair {}: {}", path.display(), error))?,
+                );
+            }
+            let approvers: Vec<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+            swap::withdraw_treasury(&swap_program_id, &admin.pubkey(), &mint, &recipient, amount, &approvers)
+        }
+        Command::SetMarketMaker { wallet, revoke } => {
+            swap::set_market_maker(&swap_program_id, &admin.pubkey(), &wallet, !revoke)
+        }
+        Command::SetRequireHubSide { disable } => {
+            swap::set_require_hub_side(&swap_program_id, &admin.pubkey(), !disable)
+        }
+        Command::SetHubToken { mint, revoke } => swap::set_hub_token(&swap_program_id, &admin.pubkey(), &mint, !revoke),
+        Command::SetHubTokens { mints } => swap::set_hub_tokens(&swap_program_id, &admin.pubkey(), mints),
+        Command::CloseVault { mint } => swap::close_vault(&swap_program_id, &admin.pubkey(), &mint),
+        Command::SetDepositCap {
+            pair,
+            mint,
+            max_reserve,
+        } => swap::set_pool_deposit_cap(&swap_program_id, &admin.pubkey(), &mint, &pair, &mint, max_reserve),
+        Command::SetAllowedRoutes { mint_a, mint_b, routes } => {
+            let vias = routes.iter().map(|route| parse_route(route)).collect::<Result<Vec<_>, _>>()?;
+            swap::set_allowed_routes(
+                &swap_program_id,
+                &admin.pubkey(),
+                &mint_a,
+                &mint_b,
+                swap::route_hashes(&mint_a, &mint_b, &vias),
+            )
+        }
+        Command::InitializePoolTwap { mint_a, mint_b } => {
+            swap::initialize_pool_twap(&swap_program_id, &admin.pubkey(), &mint_a, &mint_b)
+        }
+        Command::SetPermissions { holder, instructions } => {
+            swap::set_permissions(&swap_program_id, &admin.pubkey(), &holder, instructions)
+        }
+    };
+    let instruction = if args.delegated && instruction.program_id == swap_program_id {
+        swap::with_permission_matrix(&swap_program_id, instruction)
+    } else {
+        instruction
+    };
+
+    let signature = send(&rpc, &admin, &[instruction], &signers)?;
+    println!("{}", signature);
+    Ok(())
+}
+
+// Mints an allowed route passes between the pair's, none for "direct"
+fn parse_route(route: &str) -> Result<Vec<Pubkey>, Box<dyn Error>> {
+    if route == "direct" {
+        return Ok(vec![]);
+    }
+    Ok(route.split(',').map(Pubkey::from_str).collect::<Result<_, _>>()?)
+}
+
+// Every staking account's owner and staked amount, legacy-sized accounts included
+fn get_staking_balances(rpc: &RpcClient, program_id: &Pubkey) -> Result<Vec<(Pubkey, u64)>, Box<dyn Error>> {
+    let mut balances = Vec::new();
+    for size in [
+        staking::LEGACY_STAKING_ACCOUNT_LEN,
+        staking::PRE_LOCK_STAKING_ACCOUNT_LEN,
+        staking::PRE_PENDING_STAKING_ACCOUNT_LEN,
+        std::mem::size_of::<staking::StakingAccount>(),
+    ] {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![RpcFilterType::DataSize(size as u64)]),
+            account_config: RpcAccountInfoConfig::default(),
+            ..RpcProgramAccountsConfig::default()
+        };
+        for (_, account) in rpc.get_program_accounts_with_config(program_id, config)? {
+            let staking_account = staking::StakingAccount::try_from_slice(&account.data)?;
+            balances.push((staking_account.owner, staking_account.staked_amount));
+        }
+    }
+    Ok(balances)
+}
+
+fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[Keypair],
+) -> Result<Signature, Box<dyn Error>> {
+    let mut all_signers = vec![payer];
+    all_signers.extend(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        rpc.get_latest_blockhash()?,
+    );
+    Ok(rpc.send_and_confirm_transaction(&transaction)?)
+}
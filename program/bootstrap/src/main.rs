@@ -0,0 +1,249 @@
+// tswap-bootstrap: prepares a local validator for end-to-end demos.
+//
+// With both programs already deployed it creates the YOT and YOS mints,
+// initializes the swap and staking program state, seeds the YOT/YOS pool and
+// the staking reward vault, hands the YOS mint authority to the swap program
+// and prints every address as JSON.
+use clap::Parser;
+use multihub_swap_v3::{
+    find_liquidity_position_address, find_pool_address, find_program_authority, find_program_state_address,
+};
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    native_token::LAMPORTS_PER_SOL,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{get_associated_token_address, instruction::create_associated_token_account};
+use std::{error::Error, path::PathBuf};
+use tswap_sdk::{
+    staking,
+    swap::{self, SwapRates},
+    ONE_TOKEN, TOKEN_DECIMALS,
+};
+
+#[derive(Parser)]
+#[command(name = "tswap-bootstrap", about = "Bootstrap YOT/YOS, pools and vaults on a local validator")]
+struct Args {
+    /// RPC endpoint of the local validator
+    #[arg(long, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Payer and admin keypair (defaults to the Solana CLI keypair)
+    #[arg(long)]
+    keypair: Option<PathBuf>,
+    /// Deployed swap program id
+    #[arg(long, default_value_t = multihub_swap_v3::id())]
+    swap_program_id: Pubkey,
+    /// Deployed staking program id
+    #[arg(long, default_value_t = staking::PROGRAM_ID)]
+    staking_program_id: Pubkey,
+    /// Whole YOT and YOS tokens deposited into the YOT/YOS pool
+    #[arg(long, default_value_t = 100_000)]
+    pool_liquidity: u64,
+    /// Whole YOS tokens funding the staking reward vault
+    #[arg(long, default_value_t = 100_000)]
+    staking_rewards: u64,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let keypair_path = match args.keypair {
+        Some(path) => path,
+        None => PathBuf::from(std::env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    let payer = read_keypair_file(&keypair_path)
+        .map_err(|error| format!("failed to read keypair {}: {}", keypair_path.display(), error))?;
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+
+    // Local validators airdrop freely
+    if rpc.get_balance(&payer.pubkey())? < 2 * LAMPORTS_PER_SOL {
+        let signature = rpc.request_airdrop(&payer.pubkey(), 10 * LAMPORTS_PER_SOL)?;
+        rpc.poll_for_signature(&signature)?;
+    }
+
+    // Mints, with the payer holding the initial supply
+    let yot_mint = create_mint(&rpc, &payer)?;
+    let yos_mint = create_mint(&rpc, &payer)?;
+    let supply = (args.pool_liquidity + args.staking_rewards) * ONE_TOKEN;
+    let payer_yot = create_token_account(&rpc, &payer, &payer.pubkey(), &yot_mint)?;
+    let payer_yos = create_token_account(&rpc, &payer, &payer.pubkey(), &yos_mint)?;
+    send(
+        &rpc,
+        &payer,
+        &[
+            spl_token::instruction::mint_to(&spl_token::id(), &yot_mint, &payer_yot, &payer.pubkey(), &[], supply)?,
+            spl_token::instruction::mint_to(&spl_token::id(), &yos_mint, &payer_yos, &payer.pubkey(), &[], supply)?,
+        ],
+        &[],
+    )?;
+
+    // Swap program state with the production rates
+    let swap_program_id = args.swap_program_id;
+    let (swap_state, _) = find_program_state_address(&swap_program_id);
+    let (swap_authority, _) = find_program_authority(&swap_program_id);
+    send(
+        &rpc,
+        &payer,
+        &[swap::initialize(&swap_program_id, &payer.pubkey(), &yot_mint, &yos_mint, SwapRates::default())],
+        &[],
+    )?;
+
+    // YOT/YOS pool with authority-owned vaults, seeded 1:1
+    let (pool, _) = find_pool_address(&swap_program_id, &yot_mint, &yos_mint);
+    let (position, _) = find_liquidity_position_address(&swap_program_id, &pool, &payer.pubkey());
+    // Initialize created the authority's vaults
+    let swap_yot_vault = get_associated_token_address(&swap_authority, &yot_mint);
+    let swap_yos_vault = get_associated_token_address(&swap_authority, &yos_mint);
+    let pool_amount = args.pool_liquidity * ONE_TOKEN;
+    send(
+        &rpc,
+        &payer,
+        &[
+            swap::initialize_pool(&swap_program_id, &payer.pubkey(), &yot_mint, &yos_mint),
+            swap::add_liquidity(
+                &swap_program_id,
+                &payer.pubkey(),
+                &yot_mint,
+                &yos_mint,
+                pool_amount,
+                pool_amount,
+                0,
+            ),
+        ],
+        &[],
+    )?;
+
+    // Staking program state and its reward vault
+    let staking_program_id = args.staking_program_id;
+    let (staking_state, _) = staking::find_program_state_address(&staking_program_id);
+    let (staking_authority, _) = staking::find_program_authority(&staking_program_id);
+    send(
+        &rpc,
+        &payer,
+        &[staking::initialize(
+            &staking_program_id,
+            &payer.pubkey(),
+            &yot_mint,
+            &yos_mint,
+            12,
+            ONE_TOKEN,
+        )],
+        &[],
+    )?;
+    let staking_yot_vault = get_associated_token_address(&staking_authority, &yot_mint);
+    let staking_yos_vault = get_associated_token_address(&staking_authority, &yos_mint);
+
+    // Fund staking rewards, then let the swap program mint YOS cashback
+    send(
+        &rpc,
+        &payer,
+        &[
+            spl_token::instruction::transfer(
+                &spl_token::id(),
+                &payer_yos,
+                &staking_yos_vault,
+                &payer.pubkey(),
+                &[],
+                args.staking_rewards * ONE_TOKEN,
+            )?,
+            spl_token::instruction::set_authority(
+                &spl_token::id(),
+                &yos_mint,
+                Some(&swap_authority),
+                spl_token::instruction::AuthorityType::MintTokens,
+                &payer.pubkey(),
+                &[],
+            )?,
+        ],
+        &[],
+    )?;
+
+    let addresses = json!({
+        "rpcUrl": args.url,
+        "admin": payer.pubkey().to_string(),
+        "yotMint": yot_mint.to_string(),
+        "yosMint": yos_mint.to_string(),
+        "swap": {
+            "programId": swap_program_id.to_string(),
+            "programState": swap_state.to_string(),
+            "programAuthority": swap_authority.to_string(),
+            "yotYosPool": pool.to_string(),
+            "yotVault": swap_yot_vault.to_string(),
+            "yosVault": swap_yos_vault.to_string(),
+            "adminLpPosition": position.to_string(),
+        },
+        "staking": {
+            "programId": staking_program_id.to_string(),
+            "programState": staking_state.to_string(),
+            "programAuthority": staking_authority.to_string(),
+            "yotVault": staking_yot_vault.to_string(),
+            "yosVault": staking_yos_vault.to_string(),
+        },
+        "adminTokenAccounts": {
+            "yot": payer_yot.to_string(),
+            "yos": payer_yos.to_string(),
+        },
+    });
+    println!("{}", serde_json::to_string_pretty(&addresses)?);
+    Ok(())
+}
+
+fn send(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), Box<dyn Error>> {
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        rpc.get_latest_blockhash()?,
+    );
+    rpc.send_and_confirm_transaction(&transaction)?;
+    Ok(())
+}
+
+fn create_mint(rpc: &RpcClient, payer: &Keypair) -> Result<Pubkey, Box<dyn Error>> {
+    let mint = Keypair::new();
+    let rent = rpc.get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+    send(
+        rpc,
+        payer,
+        &[
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &mint.pubkey(),
+                rent,
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::id(),
+            ),
+            spl_token::instruction::initialize_mint(&spl_token::id(), &mint.pubkey(), &payer.pubkey(), None, TOKEN_DECIMALS)?,
+        ],
+        &[&mint],
+    )?;
+    Ok(mint.pubkey())
+}
+
+fn create_token_account(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Pubkey, Box<dyn Error>> {
+    send(
+        rpc,
+        payer,
+        &[create_associated_token_account(&payer.pubkey(), owner, mint, &spl_token::id())],
+        &[],
+    )?;
+    Ok(get_associated_token_address(owner, mint))
+}
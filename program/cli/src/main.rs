@@ -0,0 +1,253 @@
+// tswap: swap and stake from the command line.
+//
+// Token amounts are given in base units and shown in whole tokens. The YOT and YOS mints are
+// read from staking program state, and every transaction uses the wallet's
+// associated token accounts.
+use clap::{Parser, Subcommand};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    hash::Hash,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use std::{error::Error, path::PathBuf};
+use tswap_sdk::{accounts, get_associated_token_address, snapshot::SnapshotProof, staking, swap};
+
+#[derive(Parser)]
+#[command(name = "tswap", about = "Swap and stake YOT and YOS")]
+struct Args {
+    /// RPC endpoint
+    #[arg(long, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Wallet keypair (defaults to the Solana CLI keypair)
+    #[arg(long)]
+    keypair: Option<PathBuf>,
+    /// Deployed swap program id
+    #[arg(long, default_value_t = swap::program_id())]
+    swap_program_id: Pubkey,
+    /// Deployed staking program id
+    #[arg(long, default_value_t = staking::PROGRAM_ID)]
+    staking_program_id: Pubkey,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Swap between two mints
+    Swap {
+        #[arg(long)]
+        mint_in: Pubkey,
+        #[arg(long)]
+        mint_out: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Fail unless at least this much output is received
+        #[arg(long, default_value_t = 0)]
+        min_amount_out: u64,
+        /// Skip the liquidity contribution and pay the higher opt-out swap fee
+        #[arg(long)]
+        no_contribution: bool,
+    },
+    /// Stake YOT
+    Stake {
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Swap SOL to YOT through the SOL-YOT pool and stake it in one step
+    StakeSol {
+        /// Lamports of SOL to swap
+        #[arg(long)]
+        lamports: u64,
+        /// Fail unless the swap yields at least this much YOT
+        #[arg(long, default_value_t = 0)]
+        min_yot: u64,
+        /// Keep the position unstakeable for this many seconds
+        #[arg(long, default_value_t = 0)]
+        lock_seconds: i64,
+    },
+    /// Unstake YOT, paying out pending rewards
+    Unstake {
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Harvest pending YOS rewards
+    Harvest,
+    /// Harvest once rewards reach this many raw units instead of the
+    /// program-wide threshold; 0 goes back to the program's
+    SetHarvestThreshold {
+        #[arg(long)]
+        amount: u64,
+    },
+    /// Claim a snapshot campaign bonus using the file written by
+    /// `tswap-admin record-snapshot`
+    ClaimBonus {
+        #[arg(long)]
+        snapshot_file: PathBuf,
+    },
+    /// Show token balances, the staking position and the YOT/YOS LP position
+    Positions,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let keypair_path = match args.keypair {
+        Some(path) => path,
+        None => PathBuf::from(std::env::var("HOME")?).join(".config/solana/id.json"),
+    };
+    let wallet = read_keypair_file(&keypair_path)
+        .map_err(|error| format!("failed to read keypair {}: {}", keypair_path.display(), error))?;
+    let rpc = RpcClient::new_with_commitment(args.url, CommitmentConfig::confirmed());
+    let swap_program_id = args.swap_program_id;
+    let staking_program_id = args.staking_program_id;
+
+    let staking_state = accounts::get_staking_program_state(&rpc, &staking_program_id)
+        .await?
+        .ok_or("staking program is not initialized")?
+        .state;
+    let (yot_mint, yos_mint) = (staking_state.yot_mint, staking_state.yos_mint);
+
+    let instruction = match args.command {
+        Command::Swap {
+            mint_in,
+            mint_out,
+            amount,
+            min_amount_out,
+            no_contribution,
+        } => {
+            let build = if no_contribution { swap::swap_without_contribution } else { swap::swap };
+            let mut instruction = build(
+                &swap_program_id,
+                &wallet.pubkey(),
+                &mint_in,
+                &mint_out,
+                &yos_mint,
+                amount,
+                min_amount_out,
+            );
+            // Pass the pool, hub entry and other accounts the deployed configuration calls for
+            instruction.accounts =
+                accounts::resolve_swap_accounts(&rpc, &swap_program_id, &wallet.pubkey(), &mint_in, &mint_out).await?;
+            let signature = send(&rpc, &wallet, &[swap::swap_compute_budget(false), instruction]).await?;
+            println!("{}", signature);
+            return Ok(());
+        }
+        Command::Stake { amount } => staking::stake(&staking_program_id, &wallet.pubkey(), &yot_mint, amount),
+        Command::StakeSol {
+            lamports,
+            min_yot,
+            lock_seconds,
+        } => {
+            let wrapped_sol_account = staking::create_wrapped_sol_account(&wallet.pubkey(), &wallet.pubkey());
+            let stake = staking::stake_with_sol(
+                &staking_program_id,
+                &wallet.pubkey(),
+                &yot_mint,
+                &yos_mint,
+                lamports,
+                min_yot,
+                lock_seconds,
+            );
+            let signature = send(&rpc, &wallet, &[wrapped_sol_account, stake]).await?;
+            println!("{}", signature);
+            return Ok(());
+        }
+        Command::Unstake { amount } => {
+            staking::unstake(&staking_program_id, &wallet.pubkey(), &yot_mint, &yos_mint, amount)
+        }
+        Command::Harvest => staking::harvest(&staking_program_id, &wallet.pubkey(), &yot_mint, &yos_mint),
+        Command::SetHarvestThreshold { amount } => {
+            staking::set_harvest_threshold(&staking_program_id, &wallet.pubkey(), amount)
+        }
+        Command::ClaimBonus { snapshot_file } => {
+            let snapshot: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&snapshot_file)?)?;
+            let (staked_amount, proof) = snapshot_claim(&snapshot, &wallet.pubkey())?;
+            let campaign_id = snapshot["campaignId"].as_u64().ok_or("snapshot file has no campaignId")?;
+            staking::claim_snapshot_bonus(
+                &staking_program_id,
+                &wallet.pubkey(),
+                &yos_mint,
+                campaign_id,
+                staked_amount,
+                proof,
+            )
+        }
+        Command::Positions => {
+            return print_positions(&rpc, &wallet.pubkey(), &swap_program_id, &staking_program_id, &yot_mint, &yos_mint)
+                .await
+        }
+    };
+
+    let signature = send(&rpc, &wallet, &[instruction]).await?;
+    println!("{}", signature);
+    Ok(())
+}
+
+// The wallet's balance and proof from a snapshot file
+fn snapshot_claim(snapshot: &serde_json::Value, wallet: &Pubkey) -> Result<(u64, SnapshotProof), Box<dyn Error>> {
+    let owner = wallet.to_string();
+    let claim = snapshot["claims"]
+        .as_array()
+        .and_then(|claims| claims.iter().find(|claim| claim["owner"] == owner.as_str()))
+        .ok_or("wallet is not in this snapshot")?;
+    let staked_amount = claim["stakedAmount"].as_u64().ok_or("snapshot claim has no stakedAmount")?;
+    let proof = claim["proof"]
+        .as_array()
+        .ok_or("snapshot claim has no proof")?
+        .iter()
+        .map(|node| {
+            let node = node.as_str().ok_or("proof entries must be strings")?;
+            Ok(node.parse::<Hash>()?.to_bytes())
+        })
+        .collect::<Result<_, Box<dyn Error>>>()?;
+    Ok((staked_amount, proof))
+}
+
+async fn print_positions(
+    rpc: &RpcClient,
+    wallet: &Pubkey,
+    swap_program_id: &Pubkey,
+    staking_program_id: &Pubkey,
+    yot_mint: &Pubkey,
+    yos_mint: &Pubkey,
+) -> Result<(), Box<dyn Error>> {
+    println!("wallet: {}", wallet);
+    for (name, mint) in [("YOT", yot_mint), ("YOS", yos_mint)] {
+        let balance = match rpc.get_token_account_balance(&get_associated_token_address(wallet, mint)).await {
+            Ok(balance) => balance.ui_amount_string,
+            Err(_) => "no token account".to_string(),
+        };
+        println!("{} balance: {}", name, balance);
+    }
+
+    match accounts::get_staking_account(rpc, staking_program_id, wallet).await? {
+        Some(position) => {
+            println!("staked YOT: {}", position.staked_amount);
+            println!("staked since: {}", position.account.start_timestamp);
+            println!("last harvest: {}", position.account.last_harvest_time);
+            println!("total harvested YOS: {}", position.total_harvested);
+            println!("pending YOS: {}", position.pending_rewards);
+        }
+        None => println!("staked YOT: 0"),
+    }
+
+    let shares = accounts::get_liquidity_position(rpc, swap_program_id, (yot_mint, yos_mint), wallet)
+        .await?
+        .map_or(0, |position| position.shares);
+    println!("YOT/YOS LP shares: {}", shares);
+    Ok(())
+}
+
+async fn send(rpc: &RpcClient, payer: &Keypair, instructions: &[Instruction]) -> Result<Signature, Box<dyn Error>> {
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &[payer],
+        rpc.get_latest_blockhash().await?,
+    );
+    Ok(rpc.send_and_confirm_transaction(&transaction).await?)
+}
@@ -0,0 +1,17 @@
+#![no_main]
+
+// multihub_swap.rs is deployed on its own rather than built as a crate, so
+// its manual byte-slicing dispatcher is compiled straight from source
+#[allow(dead_code, unused_imports, unused_macros)]
+#[path = "../../src/multihub_swap.rs"]
+mod legacy;
+
+use libfuzzer_sys::fuzz_target;
+use solana_program::pubkey::Pubkey;
+
+// The legacy program has no fixed id; any address exercises the same paths
+const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+
+fuzz_target!(|input: &[u8]| {
+    tswap_fuzz::run(&PROGRAM_ID, input, legacy::process_instruction);
+});
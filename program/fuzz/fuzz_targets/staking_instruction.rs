@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use solana_program::{pubkey, pubkey::Pubkey};
+
+const PROGRAM_ID: Pubkey = pubkey!("6yw2VmZEJw5QkSG7svt4QL8DyCMxUKRtLqqBPTzLZHT6");
+
+fuzz_target!(|input: &[u8]| {
+    tswap_fuzz::run(&PROGRAM_ID, input, yot_staking::process_instruction);
+});
@@ -0,0 +1,7 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &[u8]| {
+    tswap_fuzz::run(&multihub_swap_v3::id(), input, multihub_swap_v3::process_instruction);
+});
@@ -0,0 +1,140 @@
+// No-CPI harness shared by the fuzz targets.
+//
+// A fuzz input is split into an account list and instruction data, then fed
+// to a program's process_instruction. Syscalls are stubbed so logging is
+// silent, the clock and rent sysvars resolve, and every CPI fails instead of
+// reaching a runtime. Any panic is a bug: malformed input must only ever
+// produce a ProgramError.
+//
+// Run a target from program/ with cargo-fuzz on nightly, for example
+// `cargo +nightly fuzz run swap_v3_instruction -- -max_len=512`.
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use std::sync::Once;
+
+// Most accounts any instruction takes, optional trailing accounts included
+const MAX_ACCOUNTS: usize = 24;
+
+// Data lengths an account can take, covering empty accounts, PDA state
+// sizes and SPL token accounts and mints
+const DATA_LENGTHS: [usize; 8] = [0, 33, 64, 82, 112, 165, 512, 1024];
+
+// Error every stubbed CPI returns
+pub const CPI_UNAVAILABLE: u32 = 0xF022;
+
+struct NoCpiStubs;
+
+impl SyscallStubs for NoCpiStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(
+        &self,
+        _instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        Err(ProgramError::Custom(CPI_UNAVAILABLE))
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            slot: 250_000_000,
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Clock, clock) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Rent, Rent::default()) };
+        SUCCESS
+    }
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(NoCpiStubs));
+    });
+}
+
+// Owned backing storage for the AccountInfos handed to a program
+struct FuzzAccount {
+    key: Pubkey,
+    owner: Pubkey,
+    lamports: u64,
+    data: Vec<u8>,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+// Decode one account from a descriptor byte: bits 0-1 pick the key, 2-3 the
+// owner, 4 signer, 5 writable, 6-7 with the index the data length. Fixed keys
+// reach the program id placeholder and program-account checks; the rest are
+// unique so PDA and uniqueness checks see distinct addresses.
+fn fuzz_account(index: usize, descriptor: u8, program_id: &Pubkey) -> FuzzAccount {
+    let unique = Pubkey::new_from_array([index as u8 + 1; 32]);
+    let pick = |bits: u8| match bits & 0b11 {
+        0 => unique,
+        1 => *program_id,
+        2 => spl_token::id(),
+        _ => system_program::id(),
+    };
+    FuzzAccount {
+        key: pick(descriptor),
+        owner: pick(descriptor >> 2),
+        lamports: 1_000_000_000,
+        data: vec![0; DATA_LENGTHS[(((descriptor >> 6) as usize) << 1 | index & 1) % DATA_LENGTHS.len()]],
+        is_signer: descriptor & 0b1_0000 != 0,
+        is_writable: descriptor & 0b10_0000 != 0,
+    }
+}
+
+// Run `process` on `input`: the first byte is the account count, followed by
+// one descriptor byte per account, and the remainder is instruction data
+pub fn run<F>(program_id: &Pubkey, input: &[u8], process: F)
+where
+    F: for<'a, 'b> Fn(&Pubkey, &'a [AccountInfo<'b>], &[u8]) -> ProgramResult,
+{
+    install_stubs();
+    let Some((&count, rest)) = input.split_first() else {
+        return;
+    };
+    let count = (count as usize % (MAX_ACCOUNTS + 1)).min(rest.len());
+    let (descriptors, instruction_data) = rest.split_at(count);
+
+    let mut accounts: Vec<FuzzAccount> = descriptors
+        .iter()
+        .enumerate()
+        .map(|(index, &descriptor)| fuzz_account(index, descriptor, program_id))
+        .collect();
+    let account_infos: Vec<AccountInfo> = accounts
+        .iter_mut()
+        .map(|account| {
+            AccountInfo::new(
+                &account.key,
+                account.is_signer,
+                account.is_writable,
+                &mut account.lamports,
+                &mut account.data,
+                &account.owner,
+                false,
+                0,
+            )
+        })
+        .collect();
+
+    let _ = process(program_id, &account_infos, instruction_data);
+}
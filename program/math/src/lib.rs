@@ -0,0 +1,290 @@
+// Swap and staking formulas shared by the on-chain programs and the SDK.
+//
+// The programs compile these exact functions, so client quotes and reward
+// estimates always match what a transaction will do on chain. The crate is
+// no_std and dependency-free so any on-chain or client target can use it.
+#![no_std]
+
+// Denominator of every rate expressed in basis points
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
+// Staking reward transfers are divided by this factor to work around wallets
+// mis-scaling YOS. Derived from 262,285.36 / 28.32 = 9,260.43
+pub const YOS_DISPLAY_NORMALIZATION_FACTOR: u64 = 9_260;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    // An intermediate value does not fit
+    Overflow,
+    // Both the input reserve and the input amount are zero
+    EmptyReserves,
+}
+
+// `amount * rate_bps / 10000`, rounded down
+pub fn bps_of(amount: u64, rate_bps: u64) -> u64 {
+    (amount as u128 * rate_bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+// How a swap's input amount is divided before it reaches the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FeeSplit {
+    // Contributed to the pool's liquidity
+    pub lp_contribution: u64,
+    // Admin commission
+    pub admin_fee: u64,
+    // Swap fee kept by the pool
+    pub swap_fee: u64,
+    // Paid to the referrer, net of the referee's rebate
+    pub referral: u64,
+    // Input left to trade against the pool
+    pub net_amount_in: u64,
+}
+
+// Split `amount_in` by the program's rates. The referee's rebate is a share
+// of the referral fee that is never taken from them, so it stays in the swap.
+pub fn split_amount_in(
+    amount_in: u64,
+    lp_contribution_rate: u64,
+    admin_fee_rate: u64,
+    swap_fee_rate: u64,
+    referral_rate: u64,
+    referral_rebate_bps: u64,
+) -> FeeSplit {
+    let lp_contribution = bps_of(amount_in, lp_contribution_rate);
+    let admin_fee = bps_of(amount_in, admin_fee_rate);
+    let swap_fee = bps_of(amount_in, swap_fee_rate);
+    let referral = bps_of(amount_in, referral_rate);
+    let referral = referral - bps_of(referral, referral_rebate_bps);
+    FeeSplit {
+        lp_contribution,
+        admin_fee,
+        swap_fee,
+        referral,
+        net_amount_in: amount_in - lp_contribution - admin_fee - swap_fee - referral,
+    }
+}
+
+// `value * numerator / denominator` in 128-bit, truncated to u64
+pub fn mul_div(value: u64, numerator: u64, denominator: u64) -> u64 {
+    (value as u128 * numerator as u128 / denominator as u128) as u64
+}
+
+// `amount` increased by `bonus_bps`, saturating
+pub fn apply_bonus_bps(amount: u64, bonus_bps: u64) -> u64 {
+    amount.saturating_add((amount as u128 * bonus_bps as u128 / BPS_DENOMINATOR as u128) as u64)
+}
+
+// YOS cashback earned on `amount_in`
+pub fn cashback_amount(amount_in: u64, yos_cashback_rate: u64) -> u64 {
+    bps_of(amount_in, yos_cashback_rate)
+}
+
+// YOS paid for `amount` of contributed liquidity over `periods` reward
+// periods at `rate_bps` per period, raised by `loyalty_bonus_bps`
+pub fn contribution_reward(amount: u64, rate_bps: u64, periods: u64, loyalty_bonus_bps: u64) -> u64 {
+    apply_bonus_bps(bps_of(amount, rate_bps).saturating_mul(periods), loyalty_bonus_bps)
+}
+
+// Largest input that moves the price by at most `max_price_impact_bps`
+pub fn max_amount_in(reserve_in: u64, max_price_impact_bps: u64) -> u64 {
+    (reserve_in as u128 * max_price_impact_bps as u128 / (BPS_DENOMINATOR - max_price_impact_bps) as u128) as u64
+}
+
+// Constant-product output for `amount_in` against the given reserves
+pub fn constant_product_amount_out(reserve_in: u64, reserve_out: u64, amount_in: u64) -> Result<u64, MathError> {
+    let numerator = (amount_in as u128)
+        .checked_mul(reserve_out as u128)
+        .ok_or(MathError::Overflow)?;
+    let denominator = (reserve_in as u128)
+        .checked_add(amount_in as u128)
+        .ok_or(MathError::Overflow)?;
+    if denominator == 0 {
+        return Err(MathError::EmptyReserves);
+    }
+    Ok((numerator / denominator) as u64)
+}
+
+// Least output accepted for `net_amount_in` within `slippage_bps` of the
+// pool's price before the swap, so the tolerance covers price impact and any
+// movement since the client quoted, but not fees
+pub fn slippage_min_amount_out(net_amount_in: u64, reserve_in: u64, reserve_out: u64, slippage_bps: u64) -> u64 {
+    if reserve_in == 0 {
+        return 0;
+    }
+    let spot_amount_out = mul_div(net_amount_in, reserve_out, reserve_in);
+    spot_amount_out - bps_of(spot_amount_out, slippage_bps.min(BPS_DENOMINATOR))
+}
+
+// Integer square root (floor)
+pub fn integer_sqrt(value: u128) -> u128 {
+    if value < 2 {
+        return value;
+    }
+    let mut x = value;
+    let mut y = x.div_ceil(2);
+    while y < x {
+        x = y;
+        y = (x + value / x) / 2;
+    }
+    x
+}
+
+// LP shares for a pool's first deposit: the geometric mean of the amounts,
+// before the minimum liquidity lock is taken out
+pub fn initial_lp_shares(amount_a: u64, amount_b: u64) -> u64 {
+    integer_sqrt(amount_a as u128 * amount_b as u128) as u64
+}
+
+// LP shares for a later deposit, proportional to the smaller side
+pub fn proportional_lp_shares(amount_a: u64, amount_b: u64, reserve_a: u64, reserve_b: u64, total_shares: u64) -> u64 {
+    let shares_a = amount_a as u128 * total_shares as u128 / reserve_a as u128;
+    let shares_b = amount_b as u128 * total_shares as u128 / reserve_b as u128;
+    shares_a.min(shares_b) as u64
+}
+
+// LP shares for a deposit into one side only. Half the value of a one-sided
+// deposit is swapped away by arbitrage, so it is priced as both sides at half
+// the amount each.
+pub fn single_sided_lp_shares(amount: u64, reserve: u64, total_shares: u64) -> u64 {
+    (amount as u128 * total_shares as u128 / (2 * reserve as u128)) as u64
+}
+
+// Like `single_sided_lp_shares`, but the pool's other side is valued at the
+// time-weighted reserve ratio (twap_in, twap_out) instead of the current one,
+// so momentarily skewed reserves cannot inflate the shares. Equal to
+// `single_sided_lp_shares` when the ratios agree.
+pub fn twap_single_sided_lp_shares(
+    amount: u64,
+    (reserve_in, reserve_out): (u64, u64),
+    (twap_in, twap_out): (u64, u64),
+    total_shares: u64,
+) -> u64 {
+    let other_side_value = (reserve_out as u128 * twap_in as u128)
+        .checked_div(twap_out as u128)
+        .unwrap_or(reserve_in as u128);
+    let pool_value = reserve_in as u128 + other_side_value;
+    (amount as u128 * total_shares as u128 / pool_value).min(u64::MAX as u128) as u64
+}
+
+// Move a time-weighted reserve toward `current`. The new observation weighs
+// `elapsed / (elapsed + window)`, so a skew shifts the average only in
+// proportion to how long it was held, and not at all within one second.
+pub fn time_weighted_reserve(average: u64, current: u64, elapsed: i64, window: i64) -> u64 {
+    if elapsed <= 0 {
+        return average;
+    }
+    let elapsed = elapsed as i128;
+    let step = (current as i128 - average as i128) * elapsed / (elapsed + window.max(0) as i128);
+    (average as i128 + step) as u64
+}
+
+// Amount of one side paid out for burning `shares`
+pub fn withdrawal_amount(shares: u64, reserve: u64, total_shares: u64) -> u64 {
+    (shares as u128 * reserve as u128 / total_shares as u128) as u64
+}
+
+// Fixed-point precision of bonding curve prices
+pub const CURVE_PRICE_PRECISION: u64 = 1_000_000_000;
+
+// Quote paid for `amount` tokens after `sold` on a linear bonding curve rising
+// from `start_price` at zero to `end_price` at `supply` tokens sold. Prices
+// are quote base units per token base unit scaled by CURVE_PRICE_PRECISION.
+// The area under the curve is rounded up so buyers never underpay.
+pub fn bonding_curve_cost(sold: u64, amount: u64, supply: u64, start_price: u64, end_price: u64) -> Result<u64, MathError> {
+    if supply == 0 || end_price < start_price {
+        return Err(MathError::Overflow);
+    }
+    // amount * (p(sold) + p(sold + amount)) / 2, over a common denominator
+    let start_term = (start_price as u128 * supply as u128).checked_mul(2);
+    let slope_term = ((end_price - start_price) as u128).checked_mul(2 * sold as u128 + amount as u128);
+    let price_sum = start_term
+        .zip(slope_term)
+        .and_then(|(start_term, slope_term)| start_term.checked_add(slope_term))
+        .ok_or(MathError::Overflow)?;
+    let numerator = price_sum.checked_mul(amount as u128).ok_or(MathError::Overflow)?;
+    let denominator = 2 * supply as u128 * CURVE_PRICE_PRECISION as u128;
+    u64::try_from(numerator.div_ceil(denominator)).map_err(|_| MathError::Overflow)
+}
+
+// Seconds from `since` to `now`, as read from the Clock sysvar. Validators
+// vote on the timestamp, so a later transaction can read an earlier time than
+// the one recorded; that skew counts as no time passing rather than going
+// negative, and transactions in the same slot see zero elapsed time.
+pub fn elapsed_seconds(since: i64, now: i64) -> i64 {
+    now.saturating_sub(since).max(0)
+}
+
+// A u64 UNIX timestamp as the i64 every program account stores, None past
+// i64::MAX
+pub fn timestamp_from_u64(timestamp: u64) -> Option<i64> {
+    i64::try_from(timestamp).ok()
+}
+
+// An i64 UNIX timestamp as u64, None before the epoch
+pub fn timestamp_to_u64(timestamp: i64) -> Option<u64> {
+    u64::try_from(timestamp).ok()
+}
+
+// Amount of a `deposit` released linearly from `start_time` to `end_time` at
+// `now`: nothing before the start, everything from the end on, rounded down
+// in between. A stream ending at or before its start releases everything
+pub fn streamed_amount(deposit: u64, start_time: i64, end_time: i64, now: i64) -> u64 {
+    if now >= end_time || end_time <= start_time {
+        return deposit;
+    }
+    let elapsed = elapsed_seconds(start_time, now) as u64;
+    mul_div(deposit, elapsed, end_time.saturating_sub(start_time) as u64)
+}
+
+// `stake_rate_per_second` is a percentage scaled by 1,000,000, so a fraction
+// of the principal scaled by this
+const STAKE_RATE_DENOMINATOR: u128 = 100 * 1_000_000;
+
+// Linear staking rewards in YOS base units: principal * rate * time, where
+// `stake_rate_per_second` is a percentage scaled by 1,000,000. Computed in
+// 128-bit integers and rounded down once at the end, saturating at u64::MAX
+// instead of wrapping; negative durations accrue nothing.
+pub fn accrued_rewards(staked_amount: u64, stake_rate_per_second: u64, seconds: i64) -> u64 {
+    let seconds = seconds.max(0) as u128;
+    // The product of two u64 values always fits in u128
+    let per_second = staked_amount as u128 * stake_rate_per_second as u128;
+    match per_second.checked_mul(seconds) {
+        Some(scaled) => u64::try_from(scaled / STAKE_RATE_DENOMINATOR).unwrap_or(u64::MAX),
+        // Anything past u128 is far beyond u64::MAX once descaled
+        None => u64::MAX,
+    }
+}
+
+// YOS actually transferred for `accrued` rewards
+pub fn reward_payout(accrued: u64) -> u64 {
+    accrued / YOS_DISPLAY_NORMALIZATION_FACTOR
+}
+
+// Part of `accrued` rewards a reward vault holding `vault_balance` YOS can pay
+pub fn affordable_rewards(accrued: u64, vault_balance: u64) -> u64 {
+    accrued.min(vault_balance.saturating_mul(YOS_DISPLAY_NORMALIZATION_FACTOR))
+}
+
+// YOS a reward vault must hold to pay `total_staked` YOT for
+// `runway_seconds` at `stake_rate_per_second`
+pub fn required_reward_reserve(total_staked: u64, stake_rate_per_second: u64, runway_seconds: i64) -> u64 {
+    reward_payout(accrued_rewards(total_staked, stake_rate_per_second, runway_seconds))
+}
+
+// Staking rate in effect when the vault holds `reward_reserve` YOS. While the
+// reserve covers `min_runway_seconds` of emissions the configured rate
+// applies; below that it scales down in proportion to the shortfall, so
+// rewards taper off as the vault drains instead of stopping abruptly.
+pub fn runway_adjusted_rate(
+    stake_rate_per_second: u64,
+    total_staked: u64,
+    reward_reserve: u64,
+    min_runway_seconds: i64,
+) -> u64 {
+    let required = required_reward_reserve(total_staked, stake_rate_per_second, min_runway_seconds);
+    if reward_reserve >= required {
+        stake_rate_per_second
+    } else {
+        mul_div(stake_rate_per_second, reward_reserve, required)
+    }
+}
@@ -0,0 +1,165 @@
+use tswap_math::*;
+
+#[test]
+fn fee_split_accounts_for_every_unit() {
+    // Production rates: 20% contribution, 0.1% admin, 0.3% swap, 0.5% referral
+    let split = split_amount_in(1_000_000, 2000, 10, 30, 50, 0);
+    assert_eq!(split.lp_contribution, 200_000);
+    assert_eq!(split.admin_fee, 1_000);
+    assert_eq!(split.swap_fee, 3_000);
+    assert_eq!(split.referral, 5_000);
+    assert_eq!(split.net_amount_in, 791_000);
+
+    // A full rebate leaves the whole referral fee in the swap
+    let rebated = split_amount_in(1_000_000, 2000, 10, 30, 50, 10_000);
+    assert_eq!(rebated.referral, 0);
+    assert_eq!(rebated.net_amount_in, 796_000);
+}
+
+#[test]
+fn constant_product_keeps_k() {
+    let amount_out = constant_product_amount_out(1_000_000, 2_000_000, 10_000).unwrap();
+    assert_eq!(amount_out, 19_801);
+    assert!((1_000_000u128 + 10_000) * (2_000_000u128 - amount_out as u128) >= 1_000_000u128 * 2_000_000);
+
+    assert_eq!(constant_product_amount_out(0, 0, 0), Err(MathError::EmptyReserves));
+    assert_eq!(constant_product_amount_out(1, u64::MAX, u64::MAX), Ok(u64::MAX - 1));
+}
+
+#[test]
+fn slippage_bounds_follow_the_spot_price() {
+    // 1% below 10,000 at the 1:2 spot price
+    assert_eq!(slippage_min_amount_out(10_000, 1_000_000, 2_000_000, 100), 19_800);
+    // The constant-product output clears a 1% bound but not a 0.5% one
+    let amount_out = constant_product_amount_out(1_000_000, 2_000_000, 10_000).unwrap();
+    assert!(amount_out >= slippage_min_amount_out(10_000, 1_000_000, 2_000_000, 100));
+    assert!(amount_out < slippage_min_amount_out(10_000, 1_000_000, 2_000_000, 50));
+    assert_eq!(slippage_min_amount_out(10_000, 1_000_000, 2_000_000, 20_000), 0);
+    assert_eq!(slippage_min_amount_out(10_000, 0, 2_000_000, 100), 0);
+}
+
+#[test]
+fn liquidity_shares() {
+    assert_eq!(integer_sqrt(0), 0);
+    assert_eq!(integer_sqrt(99), 9);
+    assert_eq!(integer_sqrt(u64::MAX as u128 * u64::MAX as u128), u64::MAX as u128);
+    assert_eq!(initial_lp_shares(4_000, 9_000), 6_000);
+    // The smaller side sets the shares
+    assert_eq!(proportional_lp_shares(100, 300, 1_000, 2_000, 500), 50);
+    // A one-sided deposit counts as half its amount on each side
+    assert_eq!(single_sided_lp_shares(100, 1_000, 500), 25);
+    assert_eq!(single_sided_lp_shares(u64::MAX, u64::MAX, u64::MAX), u64::MAX / 2);
+    assert_eq!(withdrawal_amount(25, 1_100, 525), 52);
+}
+
+#[test]
+fn contributions_are_valued_at_time_weighted_reserves() {
+    // Agrees with the spot valuation while reserves sit at their average
+    assert_eq!(twap_single_sided_lp_shares(100, (1_000, 4_000), (1_000, 4_000), 500), 25);
+    // Draining the input side to a quarter would quadruple spot-priced
+    // shares; at the average ratio they barely move
+    assert_eq!(single_sided_lp_shares(100, 250, 500), 100);
+    assert_eq!(twap_single_sided_lp_shares(100, (250, 16_000), (1_000, 4_000), 500), 11);
+    // An empty average falls back to the spot valuation
+    assert_eq!(twap_single_sided_lp_shares(100, (1_000, 4_000), (0, 0), 500), 25);
+
+    assert_eq!(time_weighted_reserve(1_000, 5_000, 0, 1_800), 1_000);
+    assert_eq!(time_weighted_reserve(1_000, 5_000, 1_800, 1_800), 3_000);
+    assert_eq!(time_weighted_reserve(5_000, 1_000, 200, 1_800), 4_600);
+}
+
+#[test]
+fn staking_rewards_are_linear() {
+    // 1000 YOT at 0.0000125% per second for a day
+    let day = accrued_rewards(1_000_000_000_000, 125, 86_400);
+    assert_eq!(day, accrued_rewards(1_000_000_000_000, 125, 43_200) * 2);
+    assert_eq!(reward_payout(day), day / YOS_DISPLAY_NORMALIZATION_FACTOR);
+    assert_eq!(accrued_rewards(1_000_000_000_000, 125, 0), 0);
+
+    // A short vault pays what it holds and leaves the rest owed
+    let paid = affordable_rewards(day, 1_000);
+    assert_eq!(paid, 1_000 * YOS_DISPLAY_NORMALIZATION_FACTOR);
+    assert_eq!(reward_payout(paid), 1_000);
+    assert_eq!(affordable_rewards(day, u64::MAX), day);
+    assert_eq!(affordable_rewards(day, 0), 0);
+}
+
+#[test]
+fn bonuses_saturate() {
+    assert_eq!(apply_bonus_bps(1_000, 2_500), 1_250);
+    assert_eq!(apply_bonus_bps(u64::MAX, 10_000), u64::MAX);
+    assert_eq!(mul_div(u64::MAX, 3, 4), (u64::MAX as u128 * 3 / 4) as u64);
+}
+
+#[test]
+fn contribution_rewards_scale_with_loyalty() {
+    // 1.92% of 1000 tokens per week for two weeks
+    assert_eq!(contribution_reward(1_000_000_000_000, 192, 2, 0), 38_400_000_000);
+    // A 50% loyalty bonus on the same period
+    assert_eq!(contribution_reward(1_000_000_000_000, 192, 2, 5_000), 57_600_000_000);
+    assert_eq!(contribution_reward(1_000_000_000_000, 192, 0, 5_000), 0);
+    assert_eq!(contribution_reward(u64::MAX, 10_000, u64::MAX, 10_000), u64::MAX);
+}
+
+#[test]
+fn bonding_curve_charges_the_area_under_the_price() {
+    // Flat curve at 0.5 quote per token
+    assert_eq!(bonding_curve_cost(0, 1_000, 10_000, 500_000_000, 500_000_000), Ok(500));
+    // Rising from 1 to 3: the first half averages 1.5, the second half 2.5
+    assert_eq!(bonding_curve_cost(0, 5_000, 10_000, 1_000_000_000, 3_000_000_000), Ok(7_500));
+    assert_eq!(bonding_curve_cost(5_000, 5_000, 10_000, 1_000_000_000, 3_000_000_000), Ok(12_500));
+    // Fractions round up against the buyer
+    assert_eq!(bonding_curve_cost(0, 1, 10_000, 1_000_000_000, 3_000_000_000), Ok(2));
+    assert_eq!(bonding_curve_cost(0, 1, 0, 1, 1), Err(MathError::Overflow));
+    assert_eq!(bonding_curve_cost(0, 1, 1, 2, 1), Err(MathError::Overflow));
+    assert_eq!(bonding_curve_cost(0, u64::MAX, u64::MAX, u64::MAX, u64::MAX), Err(MathError::Overflow));
+}
+
+#[test]
+fn elapsed_time_tolerates_clock_skew() {
+    assert_eq!(elapsed_seconds(1_700_000_000, 1_700_000_060), 60);
+    // Two operations in the same slot share a timestamp
+    assert_eq!(elapsed_seconds(1_700_000_000, 1_700_000_000), 0);
+    assert_eq!(accrued_rewards(1_000_000_000_000, 125, elapsed_seconds(1_700_000_000, 1_700_000_000)), 0);
+    // A clock reading behind the recorded time accrues nothing
+    assert_eq!(elapsed_seconds(1_700_000_060, 1_700_000_000), 0);
+    assert_eq!(elapsed_seconds(i64::MAX, i64::MIN), 0);
+    assert_eq!(elapsed_seconds(i64::MIN, i64::MAX), i64::MAX);
+}
+
+#[test]
+fn streams_release_linearly() {
+    // 1000 YOT over 1000 seconds
+    assert_eq!(streamed_amount(1_000_000_000_000, 1_700_000_000, 1_700_001_000, 1_699_999_000), 0);
+    assert_eq!(streamed_amount(1_000_000_000_000, 1_700_000_000, 1_700_001_000, 1_700_000_250), 250_000_000_000);
+    assert_eq!(streamed_amount(1_000_000_000_000, 1_700_000_000, 1_700_001_000, 1_700_002_000), 1_000_000_000_000);
+    // Partial units round down until the end pays the remainder
+    assert_eq!(streamed_amount(10, 0, 3, 1), 3);
+    assert_eq!(streamed_amount(10, 0, 3, 3), 10);
+    assert_eq!(streamed_amount(u64::MAX, 0, 2, 1), u64::MAX / 2);
+    assert_eq!(streamed_amount(10, 5, 5, 0), 10);
+}
+
+#[test]
+fn timestamp_conversions_are_checked() {
+    assert_eq!(timestamp_from_u64(1_700_000_000), Some(1_700_000_000));
+    assert_eq!(timestamp_from_u64(i64::MAX as u64), Some(i64::MAX));
+    assert_eq!(timestamp_from_u64(i64::MAX as u64 + 1), None);
+    assert_eq!(timestamp_to_u64(1_700_000_000), Some(1_700_000_000));
+    assert_eq!(timestamp_to_u64(-1), None);
+}
+
+#[test]
+fn rate_scales_with_reward_runway() {
+    // 1000 YOT staked needs 4,257 YOS to cover a day at the launch rate
+    let required = required_reward_reserve(1_000_000_000_000, 125, 86_400);
+    assert_eq!(required, 108_000_000_000 / YOS_DISPLAY_NORMALIZATION_FACTOR);
+    assert_eq!(runway_adjusted_rate(125, 1_000_000_000_000, required, 86_400), 125);
+    assert_eq!(runway_adjusted_rate(125, 1_000_000_000_000, required * 10, 86_400), 125);
+    // Half the reserve halves the rate, an empty vault stops emissions
+    assert_eq!(runway_adjusted_rate(125, 1_000_000_000_000, required / 2, 86_400), 62);
+    assert_eq!(runway_adjusted_rate(125, 1_000_000_000_000, 0, 86_400), 0);
+    // No runway requirement or nothing staked leaves the rate alone
+    assert_eq!(runway_adjusted_rate(125, 1_000_000_000_000, 0, 0), 125);
+    assert_eq!(runway_adjusted_rate(125, 0, 0, 86_400), 125);
+}
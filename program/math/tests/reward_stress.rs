@@ -0,0 +1,65 @@
+use tswap_math::*;
+
+// Stakes and durations at the edges of what the staking program can see.
+// Rewards must saturate rather than wrap and must never round a long stake
+// down to nothing.
+
+const YEAR: i64 = 365 * 24 * 60 * 60;
+const ONE_YOT: u64 = 1_000_000_000;
+// 0.0000125% per second, the rate the program launched with
+const LAUNCH_RATE: u64 = 125;
+
+#[test]
+fn max_stake_saturates_over_years() {
+    for years in [1, 5, 10, 50] {
+        assert_eq!(accrued_rewards(u64::MAX, LAUNCH_RATE, years * YEAR), u64::MAX);
+    }
+    assert_eq!(accrued_rewards(u64::MAX, u64::MAX, i64::MAX), u64::MAX);
+    assert_eq!(reward_payout(u64::MAX), u64::MAX / YOS_DISPLAY_NORMALIZATION_FACTOR);
+}
+
+#[test]
+fn near_max_stake_is_exact() {
+    // One second at the smallest rate is small enough to be exact
+    let staked = u64::MAX - 1;
+    assert_eq!(accrued_rewards(staked, 1, 1), (staked as u128 / 100_000_000) as u64);
+
+    // Exactly at the saturation point, and one second short of it
+    assert_eq!(accrued_rewards(u64::MAX, 1, 100_000_000), u64::MAX);
+    assert_eq!(
+        accrued_rewards(u64::MAX, 1, 99_999_999),
+        (u64::MAX as u128 * 99_999_999 / 100_000_000) as u64
+    );
+}
+
+#[test]
+fn dust_stakes_accrue_over_years() {
+    // A single base unit earns nothing per second but must not truncate to
+    // zero once enough time passes
+    assert_eq!(accrued_rewards(1, LAUNCH_RATE, 1), 0);
+    assert_eq!(accrued_rewards(1, LAUNCH_RATE, 10 * YEAR), 394);
+    assert_eq!(accrued_rewards(1, 1, 50 * YEAR), 15);
+}
+
+#[test]
+fn one_year_of_a_thousand_yot() {
+    // The incident paid millions of tokens for a stake of this size; the
+    // launch rate is 3,942% a year, so 1,000 YOT earns 39,420 YOT
+    let accrued = accrued_rewards(1_000 * ONE_YOT, LAUNCH_RATE, YEAR);
+    assert_eq!(accrued, 39_420 * ONE_YOT);
+    assert_eq!(reward_payout(accrued), 39_420 * ONE_YOT / YOS_DISPLAY_NORMALIZATION_FACTOR);
+}
+
+#[test]
+fn large_stakes_stay_linear_across_years() {
+    let staked = u64::MAX / 1_000;
+    let mut previous = 0;
+    for years in 1..=20 {
+        let accrued = accrued_rewards(staked, LAUNCH_RATE, years * YEAR);
+        assert!(accrued > previous);
+        // Rounding happens once, so splitting the period loses at most a unit
+        let one_year = accrued_rewards(staked, LAUNCH_RATE, YEAR);
+        assert!(accrued - one_year * years as u64 <= years as u64);
+        previous = accrued;
+    }
+}
@@ -0,0 +1,302 @@
+// Test-only AMM that swaps between two mints at a fixed rate. Integration
+// tests deploy it next to the swap program so CPI routing can be exercised
+// against a real program without forking mainnet AMMs.
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction, system_program,
+    sysvar::Sysvar,
+};
+use spl_token::{instruction as token_instruction, state::Account as TokenAccount};
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Instructions supported by the mock AMM
+#[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+pub enum MockAmmInstruction {
+    // Create the pool PDA for a mint pair with a fixed exchange rate
+    // Accounts: payer (signer), pool, mint_in, mint_out, system_program
+    InitializePool {
+        // Output tokens per `rate_denominator` input tokens
+        rate_numerator: u64,
+        // Input tokens per `rate_numerator` output tokens
+        rate_denominator: u64,
+    },
+    // Swap at the pool's fixed rate, paying out of the authority's vault
+    // Accounts: user (signer), user_source, user_destination, pool,
+    // vault_in, vault_out, authority, token_program
+    Swap {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+    },
+}
+
+// Fixed-rate pool for one mint pair
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MockPool {
+    // Is this pool initialized?
+    pub is_initialized: bool,
+    // Mint the pool buys
+    pub mint_in: Pubkey,
+    // Mint the pool sells
+    pub mint_out: Pubkey,
+    // Output tokens per `rate_denominator` input tokens
+    pub rate_numerator: u64,
+    // Input tokens per `rate_numerator` output tokens
+    pub rate_denominator: u64,
+}
+
+impl MockPool {
+    // Output for `amount_in`, rounded down
+    pub fn quote(&self, amount_in: u64) -> Option<u64> {
+        let amount_out = amount_in as u128 * self.rate_numerator as u128 / self.rate_denominator as u128;
+        u64::try_from(amount_out).ok()
+    }
+}
+
+// Pool address - PDA per mint pair and direction
+pub fn find_pool_address(program_id: &Pubkey, mint_in: &Pubkey, mint_out: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"pool", mint_in.as_ref(), mint_out.as_ref()], program_id)
+}
+
+// Authority address - PDA owning every vault
+pub fn find_authority_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"authority"], program_id)
+}
+
+// Build an InitializePool instruction
+pub fn initialize_pool(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    rate_numerator: u64,
+    rate_denominator: u64,
+) -> Instruction {
+    let (pool, _) = find_pool_address(program_id, mint_in, mint_out);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(*mint_in, false),
+            AccountMeta::new_readonly(*mint_out, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: MockAmmInstruction::InitializePool {
+            rate_numerator,
+            rate_denominator,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+// Build a Swap instruction
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    user_source: &Pubkey,
+    user_destination: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    vault_in: &Pubkey,
+    vault_out: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let (pool, _) = find_pool_address(program_id, mint_in, mint_out);
+    let (authority, _) = find_authority_address(program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*user, true),
+            AccountMeta::new(*user_source, false),
+            AccountMeta::new(*user_destination, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(*vault_in, false),
+            AccountMeta::new(*vault_out, false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+        data: MockAmmInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        }
+        .try_to_vec()
+        .unwrap(),
+    }
+}
+
+// Program logic
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    match MockAmmInstruction::try_from_slice(instruction_data)? {
+        MockAmmInstruction::InitializePool {
+            rate_numerator,
+            rate_denominator,
+        } => process_initialize_pool(program_id, accounts, rate_numerator, rate_denominator),
+        MockAmmInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        } => process_swap(program_id, accounts, amount_in, min_amount_out),
+    }
+}
+
+fn process_initialize_pool(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rate_numerator: u64,
+    rate_denominator: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let mint_in_account = next_account_info(account_info_iter)?;
+    let mint_out_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    if !payer_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if rate_numerator == 0 || rate_denominator == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let (pool_address, pool_bump) = find_pool_address(program_id, mint_in_account.key, mint_out_account.key);
+    if pool_address != *pool_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let pool_size = std::mem::size_of::<MockPool>();
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            pool_account.key,
+            Rent::get()?.minimum_balance(pool_size),
+            pool_size as u64,
+            program_id,
+        ),
+        &[
+            payer_account.clone(),
+            pool_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            b"pool",
+            mint_in_account.key.as_ref(),
+            mint_out_account.key.as_ref(),
+            &[pool_bump],
+        ]],
+    )?;
+
+    let pool = MockPool {
+        is_initialized: true,
+        mint_in: *mint_in_account.key,
+        mint_out: *mint_out_account.key,
+        rate_numerator,
+        rate_denominator,
+    };
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    msg!("Mock pool initialized at {}/{}", rate_numerator, rate_denominator);
+    Ok(())
+}
+
+fn process_swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_in: u64,
+    min_amount_out: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let user_account = next_account_info(account_info_iter)?;
+    let user_source_account = next_account_info(account_info_iter)?;
+    let user_destination_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let vault_in_account = next_account_info(account_info_iter)?;
+    let vault_out_account = next_account_info(account_info_iter)?;
+    let authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    if !user_account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if pool_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let pool = MockPool::deserialize(&mut &pool_account.data.borrow()[..])?;
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let (authority_address, authority_bump) = find_authority_address(program_id);
+    if authority_address != *authority_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    for (vault_account, mint) in [(vault_in_account, &pool.mint_in), (vault_out_account, &pool.mint_out)] {
+        let vault = TokenAccount::unpack(&vault_account.data.borrow())?;
+        if vault.mint != *mint || vault.owner != authority_address {
+            msg!("Mock vault has incorrect mint or owner");
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    let amount_out = pool.quote(amount_in).ok_or(ProgramError::ArithmeticOverflow)?;
+    if amount_out < min_amount_out {
+        msg!("Slippage exceeded: {} < {}", amount_out, min_amount_out);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            user_source_account.key,
+            vault_in_account.key,
+            user_account.key,
+            &[],
+            amount_in,
+        )?,
+        &[
+            user_source_account.clone(),
+            vault_in_account.clone(),
+            user_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+    invoke_signed(
+        &token_instruction::transfer(
+            token_program_account.key,
+            vault_out_account.key,
+            user_destination_account.key,
+            authority_account.key,
+            &[],
+            amount_out,
+        )?,
+        &[
+            vault_out_account.clone(),
+            user_destination_account.clone(),
+            authority_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[b"authority", &[authority_bump]]],
+    )?;
+
+    msg!("Mock swap: {} in, {} out", amount_in, amount_out);
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use borsh::BorshDeserialize;
+use mock_amm::{find_pool_address, swap, MockAmmInstruction, MockPool};
+use solana_program::pubkey::Pubkey;
+
+#[test]
+fn quote_applies_the_fixed_rate_rounding_down() {
+    let pool = MockPool {
+        is_initialized: true,
+        mint_in: Pubkey::new_unique(),
+        mint_out: Pubkey::new_unique(),
+        rate_numerator: 3,
+        rate_denominator: 2,
+    };
+    assert_eq!(pool.quote(1_000), Some(1_500));
+    assert_eq!(pool.quote(1), Some(1));
+    assert_eq!(pool.quote(u64::MAX), None);
+}
+
+#[test]
+fn swap_instruction_targets_the_pair_pool() {
+    let program_id = Pubkey::new_unique();
+    let (mint_in, mint_out) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let accounts: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+    let instruction = swap(
+        &program_id,
+        &accounts[0],
+        &accounts[1],
+        &accounts[2],
+        &mint_in,
+        &mint_out,
+        &accounts[3],
+        &accounts[4],
+        500,
+        700,
+    );
+
+    let (pool, _) = find_pool_address(&program_id, &mint_in, &mint_out);
+    assert_eq!(instruction.accounts[3].pubkey, pool);
+    assert!(instruction.accounts[0].is_signer);
+    assert_eq!(
+        MockAmmInstruction::try_from_slice(&instruction.data).unwrap(),
+        MockAmmInstruction::Swap {
+            amount_in: 500,
+            min_amount_out: 700,
+        }
+    );
+}
@@ -0,0 +1,93 @@
+// Benchmarks for the math and account (de)serialization every swap, stake
+// and harvest runs. Compare against a saved baseline before deploying math
+// changes: `cargo bench -p tswap-sdk -- --save-baseline main`, then
+// `cargo bench -p tswap-sdk -- --baseline main` on the branch.
+use borsh::{BorshDeserialize, BorshSerialize};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::mem::size_of;
+use tswap_sdk::{estimate, staking, swap};
+
+// Accounts are allocated with size_of and start zeroed
+fn zeroed<T: BorshDeserialize>() -> (T, Vec<u8>) {
+    let data = vec![0u8; size_of::<T>()];
+    (T::deserialize(&mut &data[..]).unwrap(), data)
+}
+
+fn staking_state() -> staking::ProgramState {
+    staking::ProgramState {
+        admin: Default::default(),
+        yot_mint: Default::default(),
+        yos_mint: Default::default(),
+        stake_rate_per_second: 125,
+        harvest_threshold: 1_000_000_000,
+    }
+}
+
+fn swap_state() -> swap::ProgramState {
+    let (mut state, _) = zeroed::<swap::ProgramState>();
+    state.lp_contribution_rate = 2000;
+    state.admin_fee_rate = 10;
+    state.yos_cashback_rate = 300;
+    state.swap_fee_rate = 30;
+    state.referral_rate = 50;
+    state
+}
+
+fn math(c: &mut Criterion) {
+    let staking_state = staking_state();
+    let swap_state = swap_state();
+
+    c.bench_function("reward_accrual", |b| {
+        b.iter(|| estimate::estimate_rewards(black_box(5_000_000_000_000), black_box(31_536_000), &staking_state))
+    });
+    c.bench_function("swap_quote_pool", |b| {
+        b.iter(|| {
+            estimate::quote_swap(
+                black_box(1_000_000_000),
+                black_box((500_000_000_000_000, 250_000_000_000_000)),
+                &swap_state,
+            )
+        })
+    });
+    c.bench_function("lp_initial_shares", |b| {
+        b.iter(|| tswap_math::initial_lp_shares(black_box(u64::MAX / 3), black_box(u64::MAX / 7)))
+    });
+}
+
+fn serialization(c: &mut Criterion) {
+    let (_, swap_state_data) = zeroed::<swap::ProgramState>();
+    let (_, pool_data) = zeroed::<swap::PoolState>();
+    let staking_account = staking::StakingAccount {
+        owner: Default::default(),
+        staked_amount: 5_000_000_000_000,
+        start_timestamp: 1_700_000_000,
+        last_harvest_time: 1_700_000_000,
+        total_harvested: 0,
+        harvest_threshold: 0,
+        locked_until: 0,
+        pending_rewards: 0,
+    };
+    let staking_account_data = staking_account.try_to_vec().unwrap();
+
+    c.bench_function("swap_program_state_deserialize", |b| {
+        b.iter(|| swap::ProgramState::deserialize(&mut black_box(&swap_state_data[..])).unwrap())
+    });
+    c.bench_function("swap_program_state_serialize", |b| {
+        let state = swap_state();
+        let mut buffer = swap_state_data.clone();
+        b.iter(|| black_box(&state).serialize(&mut &mut buffer[..]).unwrap())
+    });
+    c.bench_function("pool_state_deserialize", |b| {
+        b.iter(|| swap::PoolState::deserialize(&mut black_box(&pool_data[..])).unwrap())
+    });
+    c.bench_function("staking_account_roundtrip", |b| {
+        let mut buffer = staking_account_data.clone();
+        b.iter(|| {
+            let account = staking::StakingAccount::try_from_slice(black_box(&staking_account_data)).unwrap();
+            account.serialize(&mut &mut buffer[..]).unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, math, serialization);
+criterion_main!(benches);
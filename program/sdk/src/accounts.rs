@@ -0,0 +1,309 @@
+// Fetch-and-decode helpers returning typed program accounts alongside
+// decimal-adjusted amounts for display. Missing accounts come back as None.
+use borsh::BorshDeserialize;
+use multihub_swap_v3::{
+    find_cashback_ledger_address, find_contribution_ledger_address, find_contribution_targets_address,
+    find_hub_token_address, find_market_maker_address, find_sol_fee_vault_address, find_swap_history_address,
+    find_user_stats_address, ContributionTargets, HubToken,
+};
+use solana_client::{client_error::ClientError, nonblocking::rpc_client::RpcClient};
+use solana_program::{
+    instruction::AccountMeta, native_token::LAMPORTS_PER_SOL, program_pack::Pack, pubkey::Pubkey, system_program,
+};
+use spl_associated_token_account::get_associated_token_address;
+use thiserror::Error;
+
+use crate::{staking, swap, token_swap::TokenSwapPool, TOKEN_DECIMALS};
+
+#[derive(Error, Debug)]
+pub enum FetchError {
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("Account {0} could not be decoded")]
+    Decode(Pubkey),
+    #[error("Account {0} does not exist")]
+    Missing(Pubkey),
+}
+
+impl From<ClientError> for FetchError {
+    fn from(error: ClientError) -> Self {
+        FetchError::Rpc(Box::new(error))
+    }
+}
+
+// Swap program state
+#[derive(Debug)]
+pub struct SwapProgramStateInfo {
+    pub address: Pubkey,
+    pub state: swap::ProgramState,
+    // Lifetime SOL admin commission, in SOL
+    pub sol_fees_collected: f64,
+}
+
+// Staking program state
+#[derive(Debug)]
+pub struct StakingProgramStateInfo {
+    pub address: Pubkey,
+    pub state: staking::ProgramState,
+    // Minimum rewards before a harvest, in YOS
+    pub harvest_threshold: f64,
+}
+
+// A wallet's staking position
+#[derive(Debug)]
+pub struct StakingPosition {
+    pub address: Pubkey,
+    pub account: staking::StakingAccount,
+    // Staked amount, in YOT
+    pub staked_amount: f64,
+    // Lifetime harvested rewards, in YOS
+    pub total_harvested: f64,
+    // Rewards credited at unstake awaiting harvest, in YOS as paid out
+    pub pending_rewards: f64,
+}
+
+// A pair pool with reserves adjusted by each mint's decimals
+#[derive(Debug)]
+pub struct PoolInfo {
+    pub address: Pubkey,
+    pub state: swap::PoolState,
+    pub decimals_a: u8,
+    pub decimals_b: u8,
+    pub reserve_a: f64,
+    pub reserve_b: f64,
+}
+
+impl PoolInfo {
+    // Price of one mint_a token in mint_b tokens, None while the pool is empty
+    pub fn price_a_in_b(&self) -> Option<f64> {
+        (self.reserve_a > 0.0).then(|| self.reserve_b / self.reserve_a)
+    }
+}
+
+// Convert base units to whole tokens
+pub fn ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+async fn get_decoded<T: BorshDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<Option<T>, FetchError> {
+    let account = rpc.get_account_with_commitment(address, rpc.commitment()).await?.value;
+    // Accounts may be allocated larger than their Borsh encoding
+    account
+        .map(|account| T::deserialize(&mut &account.data[..]).map_err(|_| FetchError::Decode(*address)))
+        .transpose()
+}
+
+async fn exists(rpc: &RpcClient, address: &Pubkey) -> Result<bool, FetchError> {
+    Ok(rpc.get_account_with_commitment(address, rpc.commitment()).await?.value.is_some())
+}
+
+async fn get_mint_decimals(rpc: &RpcClient, mint: &Pubkey) -> Result<u8, FetchError> {
+    let data = rpc.get_account_data(mint).await?;
+    let mint_state = spl_token::state::Mint::unpack(&data).map_err(|_| FetchError::Decode(*mint))?;
+    Ok(mint_state.decimals)
+}
+
+// Swap program state
+pub async fn get_program_state(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<SwapProgramStateInfo>, FetchError> {
+    let (address, _) = swap::find_program_state_address(program_id);
+    Ok(get_decoded::<swap::ProgramState>(rpc, &address).await?.map(|state| SwapProgramStateInfo {
+        address,
+        sol_fees_collected: state.sol_fees_collected as f64 / LAMPORTS_PER_SOL as f64,
+        state,
+    }))
+}
+
+// Staking program state
+pub async fn get_staking_program_state(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+) -> Result<Option<StakingProgramStateInfo>, FetchError> {
+    let (address, _) = staking::find_program_state_address(program_id);
+    Ok(get_decoded::<staking::ProgramState>(rpc, &address).await?.map(|state| StakingProgramStateInfo {
+        address,
+        harvest_threshold: ui_amount(state.harvest_threshold, TOKEN_DECIMALS),
+        state,
+    }))
+}
+
+// Staking position of `wallet`
+pub async fn get_staking_account(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    wallet: &Pubkey,
+) -> Result<Option<StakingPosition>, FetchError> {
+    let (address, _) = staking::find_staking_address(program_id, wallet);
+    Ok(get_decoded::<staking::StakingAccount>(rpc, &address).await?.map(|account| StakingPosition {
+        address,
+        staked_amount: ui_amount(account.staked_amount, TOKEN_DECIMALS),
+        total_harvested: ui_amount(account.total_harvested, TOKEN_DECIMALS),
+        pending_rewards: ui_amount(tswap_math::reward_payout(account.pending_rewards), TOKEN_DECIMALS),
+        account,
+    }))
+}
+
+// Pool for a mint pair, given in either order
+pub async fn get_pool(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    pair: (&Pubkey, &Pubkey),
+) -> Result<Option<PoolInfo>, FetchError> {
+    let (address, _) = swap::find_pool_address(program_id, pair.0, pair.1);
+    let Some(state) = get_decoded::<swap::PoolState>(rpc, &address).await? else {
+        return Ok(None);
+    };
+    let decimals_a = get_mint_decimals(rpc, &state.mint_a).await?;
+    let decimals_b = get_mint_decimals(rpc, &state.mint_b).await?;
+    Ok(Some(PoolInfo {
+        address,
+        decimals_a,
+        decimals_b,
+        reserve_a: ui_amount(state.reserve_a, decimals_a),
+        reserve_b: ui_amount(state.reserve_b, decimals_b),
+        state,
+    }))
+}
+
+// Liquidity position of `wallet` in the pool for a mint pair
+pub async fn get_liquidity_position(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    pair: (&Pubkey, &Pubkey),
+    wallet: &Pubkey,
+) -> Result<Option<swap::LiquidityPosition>, FetchError> {
+    let (pool, _) = swap::find_pool_address(program_id, pair.0, pair.1);
+    let (address, _) = swap::find_liquidity_position_address(program_id, &pool, wallet);
+    get_decoded(rpc, &address).await
+}
+
+// An spl-token-swap pool with the balances of its token_a and token_b
+// accounts, ready for route::best_plan. Pools failing validation are reported
+// as undecodable.
+pub async fn get_token_swap_pool(
+    rpc: &RpcClient,
+    address: &Pubkey,
+) -> Result<Option<(TokenSwapPool, (u64, u64))>, FetchError> {
+    let Some(account) = rpc.get_account_with_commitment(address, rpc.commitment()).await?.value else {
+        return Ok(None);
+    };
+    let pool = TokenSwapPool::unpack(&account.owner, address, &account.data).map_err(|_| FetchError::Decode(*address))?;
+    let balance = |data: Vec<u8>, address: &Pubkey| {
+        spl_token::state::Account::unpack(&data)
+            .map(|token_account| token_account.amount)
+            .map_err(|_| FetchError::Decode(*address))
+    };
+    let balance_a = balance(rpc.get_account_data(&pool.token_a).await?, &pool.token_a)?;
+    let balance_b = balance(rpc.get_account_data(&pool.token_b).await?, &pool.token_b)?;
+    Ok(Some((pool, (balance_a, balance_b))))
+}
+
+// The ordered accounts a swap of `mint_in` for `mint_out` by `user` needs,
+// resolved against the deployed program's configuration, for any variant
+// taking Swap's accounts. Optional slots the program would skip are filled
+// with the program id, and the referral slots are always left empty. The
+// pair's pool and the authority's vaults of the two mints are always passed;
+// a pool that has not been created is reported as missing.
+// Included when they apply: the cashback ledger in place of the YOS account
+// in ledger mode, the swap history, the pool's time-weighted reserves, the
+// SOL fee vault for SOL input, the user's stats, contribution ledger and
+// market maker entry, the contribution targets with their pools,
+// an approved hub token entry when swaps must touch a hub, and the route
+// allowlist of a sensitive pair.
+pub async fn resolve_swap_accounts(
+    rpc: &RpcClient,
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+) -> Result<Vec<AccountMeta>, FetchError> {
+    let (program_state, _) = swap::find_program_state_address(program_id);
+    let state = get_decoded::<swap::ProgramState>(rpc, &program_state)
+        .await?
+        .ok_or(FetchError::Missing(program_state))?;
+    let placeholder = AccountMeta::new_readonly(*program_id, false);
+    // A program-owned PDA, or the placeholder until it is created
+    let optional = |address: Pubkey, exists: bool| {
+        if exists {
+            AccountMeta::new(address, false)
+        } else {
+            placeholder.clone()
+        }
+    };
+
+    let yos_slot = if state.accrue_cashback {
+        find_cashback_ledger_address(program_id, user).0
+    } else {
+        get_associated_token_address(user, &state.yos_mint)
+    };
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(program_state, false),
+        AccountMeta::new(swap::find_program_authority(program_id).0, false),
+        AccountMeta::new(get_associated_token_address(user, mint_in), false),
+        AccountMeta::new(get_associated_token_address(user, mint_out), false),
+        AccountMeta::new(yos_slot, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+
+    let (swap_history, _) = find_swap_history_address(program_id);
+    accounts.push(optional(swap_history, exists(rpc, &swap_history).await?));
+    let (pool, _) = swap::find_pool_address(program_id, mint_in, mint_out);
+    if !exists(rpc, &pool).await? {
+        return Err(FetchError::Missing(pool));
+    }
+    accounts.push(AccountMeta::new(pool, false));
+    accounts.push(AccountMeta::new(swap::vault_address(program_id, mint_in), false));
+    accounts.push(AccountMeta::new(swap::vault_address(program_id, mint_out), false));
+    if *mint_in == spl_token::native_mint::id() {
+        accounts.push(AccountMeta::new(find_sol_fee_vault_address(program_id).0, false));
+        accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+    } else {
+        accounts.extend([placeholder.clone(), placeholder.clone()]);
+    }
+    // Referrer, referrer token account and campaign
+    accounts.extend([placeholder.clone(), placeholder.clone(), placeholder.clone()]);
+    let (user_stats, _) = find_user_stats_address(program_id, user);
+    accounts.push(optional(user_stats, exists(rpc, &user_stats).await?));
+    // NFT token account and metadata
+    accounts.extend([placeholder.clone(), placeholder.clone()]);
+
+    // Positional extras, each skipped by the program when absent
+    let (contribution_targets, _) = find_contribution_targets_address(program_id);
+    if let Some(targets) = get_decoded::<ContributionTargets>(rpc, &contribution_targets).await? {
+        accounts.push(AccountMeta::new_readonly(contribution_targets, false));
+        let pools = &targets.pools[..targets.count as usize];
+        accounts.extend(pools.iter().map(|pool| AccountMeta::new(*pool, false)));
+    }
+    for (address, writable) in [
+        (find_contribution_ledger_address(program_id, user).0, true),
+        (find_market_maker_address(program_id, user).0, false),
+    ] {
+        if exists(rpc, &address).await? {
+            accounts.push(AccountMeta { pubkey: address, is_signer: false, is_writable: writable });
+        }
+    }
+    if state.require_hub_side && !state.is_hub(mint_in) && !state.is_hub(mint_out) {
+        for mint in [mint_in, mint_out] {
+            let (hub_token, _) = find_hub_token_address(program_id, mint);
+            let entry = get_decoded::<HubToken>(rpc, &hub_token).await?;
+            if entry.is_some_and(|entry| entry.is_initialized && entry.approved) {
+                accounts.push(AccountMeta::new_readonly(hub_token, false));
+                break;
+            }
+        }
+    }
+    let (route_allowlist, _) = swap::find_route_allowlist_address(program_id, mint_in, mint_out);
+    if state.is_sensitive_pair(&route_allowlist) {
+        accounts.push(AccountMeta::new_readonly(route_allowlist, false));
+    }
+
+    // Found by address wherever it sits
+    let (pool_twap, _) = swap::find_pool_twap_address(program_id, &pool);
+    if exists(rpc, &pool_twap).await? {
+        accounts.push(AccountMeta::new(pool_twap, false));
+    }
+    Ok(accounts)
+}
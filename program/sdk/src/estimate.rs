@@ -0,0 +1,100 @@
+// Reward and swap estimates computed with the same tswap-math functions the
+// programs run, so displayed numbers match on-chain results.
+use tswap_math::{
+    accrued_rewards, cashback_amount, constant_product_amount_out, max_amount_in, reward_payout, runway_adjusted_rate,
+    split_amount_in, FeeSplit, MathError,
+};
+
+use crate::{staking, swap};
+
+pub use multihub_swap_v3::MAX_PRICE_IMPACT_BPS;
+
+// Rewards for a staking position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardEstimate {
+    // Rewards recorded in the staking account's accounting
+    pub accrued: u64,
+    // YOS transferred to the wallet for those rewards
+    pub payout: u64,
+}
+
+// Expected result of a swap without a referrer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    pub fees: FeeSplit,
+    pub amount_out: u64,
+    // YOS cashback before any boosts
+    pub yos_cashback: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteError {
+    // The swap would move the price past MAX_PRICE_IMPACT_BPS
+    PriceImpactExceeded { max_amount_in: u64 },
+    // The pool holds no reserves on a side, so the program rejects the swap
+    EmptyPool,
+    Math(MathError),
+}
+
+// Rewards earned by `staked` YOT over `seconds` at the configured rate
+pub fn estimate_rewards(staked: u64, seconds: i64, state: &staking::ProgramState) -> RewardEstimate {
+    estimate_rewards_at_rate(staked, seconds, state.stake_rate_per_second)
+}
+
+// Rewards earned by `staked` YOT over `seconds` at `stake_rate_per_second`
+pub fn estimate_rewards_at_rate(staked: u64, seconds: i64, stake_rate_per_second: u64) -> RewardEstimate {
+    let accrued = accrued_rewards(staked, stake_rate_per_second, seconds);
+    RewardEstimate {
+        accrued,
+        payout: reward_payout(accrued),
+    }
+}
+
+// Staking rate the program will apply given the vault balances, scaled down
+// when the YOS vault covers less than the runway config's minimum
+pub fn effective_stake_rate(
+    state: &staking::ProgramState,
+    runway: Option<&staking::RunwayConfig>,
+    total_staked: u64,
+    reward_reserve: u64,
+) -> u64 {
+    match runway {
+        Some(runway) => runway_adjusted_rate(
+            state.stake_rate_per_second,
+            total_staked,
+            reward_reserve,
+            runway.min_runway_seconds,
+        ),
+        None => state.stake_rate_per_second,
+    }
+}
+
+// Quote a swap of `amount_in` against `reserves` given as (input side,
+// output side). Pools without reserves cannot be quoted, as the program
+// rejects swaps through them.
+pub fn quote_swap(amount_in: u64, reserves: (u64, u64), state: &swap::ProgramState) -> Result<SwapQuote, QuoteError> {
+    let (reserve_in, reserve_out) = reserves;
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(QuoteError::EmptyPool);
+    }
+    let max_amount_in = max_amount_in(reserve_in, MAX_PRICE_IMPACT_BPS);
+    if amount_in > max_amount_in {
+        return Err(QuoteError::PriceImpactExceeded { max_amount_in });
+    }
+
+    let fees = split_amount_in(
+        amount_in,
+        state.lp_contribution_rate,
+        state.admin_fee_rate,
+        state.swap_fee_rate,
+        0,
+        0,
+    );
+    let amount_out =
+        constant_product_amount_out(reserve_in, reserve_out, fees.net_amount_in).map_err(QuoteError::Math)?;
+    Ok(SwapQuote {
+        fees,
+        amount_out,
+        yos_cashback: cashback_amount(amount_in, state.yos_cashback_rate),
+    })
+}
@@ -0,0 +1,33 @@
+// Client SDK for the swap and staking programs: PDA helpers, instruction
+// builders with the account order each handler expects, and typed account
+// fetchers, so tools never craft raw instruction data or decode by hand.
+pub mod accounts;
+pub mod estimate;
+pub mod route;
+pub mod snapshot;
+pub mod staking;
+pub mod swap;
+pub mod token_swap;
+pub mod tokens;
+
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+pub use spl_associated_token_account::get_associated_token_address;
+pub use tswap_addresses as addresses;
+
+// YOT and YOS both use 9 decimals
+pub const TOKEN_DECIMALS: u8 = 9;
+
+// Base units in one whole YOT or YOS token
+pub const ONE_TOKEN: u64 = 1_000_000_000;
+
+// Let `fee_payer` fund the rent of accounts `instruction` creates for its
+// user. The staking and swap programs take a signer passed after the
+// documented accounts as the payer in the user's place.
+pub fn with_fee_payer(mut instruction: Instruction, fee_payer: &Pubkey) -> Instruction {
+    instruction.accounts.push(AccountMeta::new(*fee_payer, true));
+    instruction
+}
@@ -0,0 +1,213 @@
+// Route planning through hub tokens. Routes are either a direct swap, when
+// one side is a hub, or two swaps meeting at a hub, so the candidates are
+// bounded by the hub list and every hop trades a hub on one side.
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+
+use crate::{
+    estimate::{quote_swap, QuoteError},
+    swap,
+    token_swap::TokenSwapPool,
+};
+
+// A quoted route from its first to its last mint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteQuote {
+    // Mints along the route, one more than the hops
+    pub mints: Vec<Pubkey>,
+    // Input of each hop; the first is the route's input
+    pub hop_amounts_in: Vec<u64>,
+    // Output of the last hop
+    pub amount_out: u64,
+}
+
+// Where a swap executes: along the program's own pools, or in one external
+// spl-token-swap pool holding the pair
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoutePlan {
+    Pools(RouteQuote),
+    TokenSwap {
+        pool: Box<TokenSwapPool>,
+        mint_in: Pubkey,
+        amount_in: u64,
+        amount_out: u64,
+    },
+}
+
+impl RoutePlan {
+    pub fn amount_out(&self) -> u64 {
+        match self {
+            RoutePlan::Pools(route) => route.amount_out,
+            RoutePlan::TokenSwap { amount_out, .. } => *amount_out,
+        }
+    }
+}
+
+// Candidate routes from `mint_in` to `mint_out` through `hubs`: the direct
+// pair when either side is a hub, otherwise one two-hop route per hub
+pub fn candidate_routes(mint_in: &Pubkey, mint_out: &Pubkey, hubs: &[Pubkey]) -> Vec<Vec<Pubkey>> {
+    if hubs.contains(mint_in) || hubs.contains(mint_out) {
+        return vec![vec![*mint_in, *mint_out]];
+    }
+    hubs.iter().map(|hub| vec![*mint_in, *hub, *mint_out]).collect()
+}
+
+// Quote `amount_in` along `mints`, looking up each hop's pool reserves as
+// (input side, output side). None when a hop has no pool with reserves.
+pub fn quote_route(
+    amount_in: u64,
+    mints: &[Pubkey],
+    reserves: impl Fn(&Pubkey, &Pubkey) -> Option<(u64, u64)>,
+    state: &swap::ProgramState,
+) -> Result<Option<RouteQuote>, QuoteError> {
+    let mut hop_amounts_in = Vec::with_capacity(mints.len() - 1);
+    let mut amount = amount_in;
+    for hop in mints.windows(2) {
+        let hop_reserves =
+            reserves(&hop[0], &hop[1]).filter(|&(reserve_in, reserve_out)| reserve_in > 0 && reserve_out > 0);
+        let Some(hop_reserves) = hop_reserves else {
+            return Ok(None);
+        };
+        hop_amounts_in.push(amount);
+        amount = quote_swap(amount, hop_reserves, state)?.amount_out;
+    }
+    Ok(Some(RouteQuote {
+        mints: mints.to_vec(),
+        hop_amounts_in,
+        amount_out: amount,
+    }))
+}
+
+// Best-paying route from `mint_in` to `mint_out` through the program's hubs.
+// Routes that cannot be quoted, or exceed the price-impact limit, are skipped.
+pub fn best_route(
+    amount_in: u64,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    reserves: impl Fn(&Pubkey, &Pubkey) -> Option<(u64, u64)>,
+    state: &swap::ProgramState,
+) -> Option<RouteQuote> {
+    candidate_routes(mint_in, mint_out, &state.hub_mints())
+        .iter()
+        .filter_map(|mints| quote_route(amount_in, mints, &reserves, state).ok().flatten())
+        .max_by_key(|route| route.amount_out)
+}
+
+// Best-paying plan from `mint_in` to `mint_out`: `best_route` through the
+// program's hubs, or a direct swap in any of `token_swap_pools`, each given
+// with the balances of its token_a and token_b accounts
+pub fn best_plan(
+    amount_in: u64,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    reserves: impl Fn(&Pubkey, &Pubkey) -> Option<(u64, u64)>,
+    state: &swap::ProgramState,
+    token_swap_pools: &[(TokenSwapPool, (u64, u64))],
+) -> Option<RoutePlan> {
+    let token_swap_plans = token_swap_pools
+        .iter()
+        .filter(|(pool, _)| pool.trades(mint_in, mint_out))
+        .filter_map(|(pool, balances)| {
+            let amount_out = pool.quote(amount_in, mint_in, *balances)?.ok()?;
+            Some(RoutePlan::TokenSwap {
+                pool: Box::new(pool.clone()),
+                mint_in: *mint_in,
+                amount_in,
+                amount_out,
+            })
+        });
+    best_route(amount_in, mint_in, mint_out, reserves, state)
+        .map(RoutePlan::Pools)
+        .into_iter()
+        .chain(token_swap_plans)
+        .filter(|plan| plan.amount_out() > 0)
+        .max_by_key(RoutePlan::amount_out)
+}
+
+// Instructions executing `plan`, the last swap returning at least
+// `min_amount_out`. `intermediate_balances` are as for `route_instructions`.
+pub fn plan_instructions(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    yos_mint: &Pubkey,
+    plan: &RoutePlan,
+    intermediate_balances: &[u64],
+    min_amount_out: u64,
+) -> Vec<Instruction> {
+    match plan {
+        RoutePlan::Pools(route) => {
+            route_instructions(program_id, user, yos_mint, route, intermediate_balances, min_amount_out)
+        }
+        RoutePlan::TokenSwap {
+            pool,
+            mint_in,
+            amount_in,
+            ..
+        } => vec![swap::swap_through_token_swap(program_id, user, pool, mint_in, *amount_in, min_amount_out)],
+    }
+}
+
+// Minimum output of each hop of `route`. The slippage budget, how far
+// `min_amount_out` sits below the quote, is split evenly between the hops so a
+// single manipulated pool cannot consume all of it; the last hop's minimum is
+// `min_amount_out` itself.
+pub fn hop_minimums(route: &RouteQuote, min_amount_out: u64) -> Vec<u64> {
+    let hops = route.hop_amounts_in.len() as u128;
+    let budget_bps = (route.amount_out.saturating_sub(min_amount_out) as u128 * 10000)
+        .checked_div(route.amount_out as u128)
+        .unwrap_or(0);
+    let hop_tolerance_bps = budget_bps / hops;
+    let mut minimums: Vec<u64> = route.hop_amounts_in[1..]
+        .iter()
+        .map(|&quoted_out| (quoted_out as u128 * (10000 - hop_tolerance_bps) / 10000) as u64)
+        .collect();
+    minimums.push(min_amount_out);
+    minimums
+}
+
+// One pool-priced swap per hop of `route`, each bounded on-chain by its
+// `hop_minimums`. A hop after the first spends everything its predecessor
+// actually paid out: the user's balance of the hop's input mint above what
+// they held before the route, given in `intermediate_balances` for each
+// intermediate mint (0 when the user has no account for it). Intermediate
+// tokens pass through the user's own token accounts, never a program-owned
+// one. Those accounts are created first when missing, so a user needs no
+// account for a hub token they never meant to hold.
+pub fn route_instructions(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    yos_mint: &Pubkey,
+    route: &RouteQuote,
+    intermediate_balances: &[u64],
+    min_amount_out: u64,
+) -> Vec<Instruction> {
+    let intermediate_mints = &route.mints[1..route.mints.len() - 1];
+    let create_accounts = intermediate_mints
+        .iter()
+        .map(|mint| create_associated_token_account_idempotent(user, user, mint, &spl_token::id()));
+    let minimums = hop_minimums(route, min_amount_out);
+    // Routes through a hub carry the whole route on every hop, so the
+    // allowlist of the pair they trade end to end can check it
+    let swaps = (0..minimums.len()).map(|index| {
+        let (mint_in, mint_out) = (&route.mints[index], &route.mints[index + 1]);
+        if minimums.len() == 1 {
+            return swap::swap_through_pool(
+                program_id,
+                user,
+                mint_in,
+                mint_out,
+                yos_mint,
+                route.hop_amounts_in[0],
+                minimums[0],
+            );
+        }
+        // The first hop swaps the route's input; later hops keep the
+        // balance held before the route
+        let amount = match index {
+            0 => route.hop_amounts_in[0],
+            _ => intermediate_balances.get(index - 1).copied().unwrap_or(0),
+        };
+        swap::swap_route_hop(program_id, user, &route.mints, index, yos_mint, amount, minimums[index])
+    });
+    create_accounts.chain(swaps).collect()
+}
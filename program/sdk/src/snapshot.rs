@@ -0,0 +1,63 @@
+// Merkle trees over stake snapshot balances, hashed exactly as the staking
+// program verifies ClaimSnapshotBonus proofs.
+use solana_program::pubkey::Pubkey;
+use yot_staking::{snapshot_leaf, snapshot_node};
+
+// Sibling hashes from a leaf up to the root
+pub type SnapshotProof = Vec<[u8; 32]>;
+
+pub struct SnapshotTree {
+    // Balances ordered by owner, matching the leaf order
+    balances: Vec<(Pubkey, u64)>,
+    // Leaf hashes first, the root last
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl SnapshotTree {
+    // Tree over `balances`. Owners are sorted so the same balances always
+    // produce the same root; zero balances are dropped.
+    pub fn new(mut balances: Vec<(Pubkey, u64)>) -> Self {
+        balances.retain(|(_, amount)| *amount > 0);
+        balances.sort_by_key(|(owner, _)| *owner);
+
+        let mut levels = vec![balances.iter().map(|(owner, amount)| snapshot_leaf(owner, *amount)).collect::<Vec<_>>()];
+        while levels.last().is_some_and(|level| level.len() > 1) {
+            // An unpaired node moves up unchanged and adds nothing to proofs
+            let next = levels.last().unwrap().chunks(2).map(|pair| match pair {
+                [left, right] => snapshot_node(left, right),
+                [single] => *single,
+                _ => unreachable!(),
+            });
+            levels.push(next.collect());
+        }
+        SnapshotTree { balances, levels }
+    }
+
+    // Root recorded on chain; all zeroes for an empty snapshot
+    pub fn root(&self) -> [u8; 32] {
+        self.levels.last().and_then(|level| level.first()).copied().unwrap_or_default()
+    }
+
+    // Sum of the snapshot balances
+    pub fn total_staked(&self) -> u64 {
+        self.balances.iter().map(|(_, amount)| amount).sum()
+    }
+
+    pub fn balances(&self) -> &[(Pubkey, u64)] {
+        &self.balances
+    }
+
+    // `owner`'s snapshot balance and the proof for claiming its bonus
+    pub fn proof(&self, owner: &Pubkey) -> Option<(u64, SnapshotProof)> {
+        let mut index = self.balances.binary_search_by_key(owner, |(owner, _)| *owner).ok()?;
+        let amount = self.balances[index].1;
+        let mut proof = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+        Some((amount, proof))
+    }
+}
@@ -0,0 +1,542 @@
+// Staking program instruction builders
+use borsh::BorshSerialize;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+use tswap_addresses::staking as seeds;
+use yot_staking::StakingInstruction;
+
+use crate::swap;
+
+pub use yot_staking::{
+    find_snapshot_address, find_snapshot_claim_address, treasury_yos_vault, HarvestFeeConfig, HealthReport, PauseState,
+    ProgramState, RateScaledEvent, RewardSnapshot, RunwayConfig, SnapshotClaim, SolvencyReport, StakingAccount,
+    HEALTH_HARVEST_PAUSED, HEALTH_REWARDS_EXHAUSTED, HEALTH_RUNWAY_LOW, HEALTH_STAKE_PAUSED, HEALTH_UNSTAKE_PAUSED,
+    LEGACY_STAKING_ACCOUNT_LEN, MAX_HARVEST_FEE_BPS, MAX_RATE_CHECKPOINTS, MAX_REWARD_FREEZE_WINDOWS,
+    MIN_HARVEST_THRESHOLD, PRE_LOCK_STAKING_ACCOUNT_LEN, PRE_PENDING_STAKING_ACCOUNT_LEN, RateHistory, RewardFreeze,
+    RewardSchedule,
+};
+
+// Deployed staking program id
+pub const PROGRAM_ID: Pubkey = solana_program::pubkey!("6yw2VmZEJw5QkSG7svt4QL8DyCMxUKRtLqqBPTzLZHT6");
+
+// Program state address - PDA
+pub fn find_program_state_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id)
+}
+
+// Program authority - PDA holding the YOT and YOS vaults
+pub fn find_program_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::AUTHORITY], program_id)
+}
+
+// Pause state address - PDA
+pub fn find_pause_state_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id)
+}
+
+// Reward freeze address - PDA
+pub fn find_reward_freeze_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::REWARD_FREEZE], program_id)
+}
+
+// Rate history address - PDA
+pub fn find_rate_history_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::RATE_HISTORY], program_id)
+}
+
+// Runway config address - PDA
+pub fn find_runway_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::RUNWAY_CONFIG], program_id)
+}
+
+// Harvest fee config address - PDA
+pub fn find_harvest_fee_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::HARVEST_FEE_CONFIG], program_id)
+}
+
+// Staking account address - PDA per user
+pub fn find_staking_address(program_id: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::STAKING, user.as_ref()], program_id)
+}
+
+// Associated token account of the program authority holding `mint`
+pub fn vault_address(program_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (program_authority, _) = find_program_authority(program_id);
+    spl_associated_token_account::get_associated_token_address(&program_authority, mint)
+}
+
+fn instruction(program_id: &Pubkey, data: StakingInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: data.try_to_vec().expect("staking instructions always serialize"),
+    }
+}
+
+// Initialize program state with `admin` as admin and payer, creating the
+// authority's YOT and YOS vaults
+pub fn initialize(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yot_mint: &Pubkey,
+    yos_mint: &Pubkey,
+    stake_rate_per_second: u64,
+    harvest_threshold: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::Initialize {
+            yot_mint: *yot_mint,
+            yos_mint: *yos_mint,
+            stake_rate_per_second,
+            harvest_threshold,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*yot_mint, false),
+            AccountMeta::new_readonly(*yos_mint, false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new(vault_address(program_id, yot_mint), false),
+            AccountMeta::new(vault_address(program_id, yos_mint), false),
+        ],
+    )
+}
+
+// Update the staking rate and harvest threshold
+pub fn update_parameters(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    stake_rate_per_second: u64,
+    harvest_threshold: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (rate_history, _) = find_rate_history_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::UpdateParameters {
+            stake_rate_per_second,
+            harvest_threshold,
+        },
+        vec![
+            // The admin funds the rate history on the first rate change
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new(rate_history, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Replace the pause mask (pauser or admin), creating the pause state on
+// first use with `authority` paying
+pub fn set_paused_mask(program_id: &Pubkey, authority: &Pubkey, paused_mask: u8) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::SetPausedMask { paused_mask },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(pause_state, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Freeze or resume reward accrual, creating the reward freeze PDA on first
+// use with `authority`, the pauser or admin, paying
+pub fn set_reward_freeze(program_id: &Pubkey, authority: &Pubkey, frozen: bool) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    let (reward_freeze, _) = find_reward_freeze_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::SetRewardFreeze { frozen },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(pause_state, false),
+            AccountMeta::new(reward_freeze, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Set the share of YOS harvests paid to the treasury, at most
+// MAX_HARVEST_FEE_BPS, creating the harvest fee config on first use with
+// `admin` paying
+pub fn set_harvest_fee(program_id: &Pubkey, admin: &Pubkey, harvest_fee_bps: u16) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (harvest_fee_config, _) = find_harvest_fee_config_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::SetHarvestFee { harvest_fee_bps },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(harvest_fee_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Set the reward runway below which the staking rate scales down, creating
+// the runway config on first use with `admin` paying
+pub fn set_min_runway(program_id: &Pubkey, admin: &Pubkey, min_runway_seconds: i64) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (runway_config, _) = find_runway_config_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::SetMinRunway { min_runway_seconds },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(runway_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Move `amount` YOS from the funder's associated account into the reward vault
+pub fn fund_rewards(program_id: &Pubkey, funder: &Pubkey, yos_mint: &Pubkey, amount: u64) -> Instruction {
+    spl_token::instruction::transfer(
+        &spl_token::id(),
+        &spl_associated_token_account::get_associated_token_address(funder, yos_mint),
+        &vault_address(program_id, yos_mint),
+        funder,
+        &[],
+        amount,
+    )
+    .expect("token transfers always build")
+}
+
+// Stake `amount` YOT from the user's associated account, creating the
+// staking account on first use
+pub fn stake(program_id: &Pubkey, user: &Pubkey, yot_mint: &Pubkey, amount: u64) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (staking_account, _) = find_staking_address(program_id, user);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::Stake { amount },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yot_mint), false),
+            AccountMeta::new(vault_address(program_id, yot_mint), false),
+            AccountMeta::new(staking_account, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(pause_state, false),
+        ],
+    )
+}
+
+// Wrap `lamports` of SOL, swap them to YOT through the swap program's SOL-YOT
+// pool and stake the proceeds, locked for `lock_seconds`. The user's wrapped
+// SOL, YOT and YOS associated token accounts must already exist; a wrapped
+// SOL account created empty by `create_wrapped_sol_account` in the same
+// transaction is closed again, so no account is left behind.
+pub fn stake_with_sol(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    yot_mint: &Pubkey,
+    yos_mint: &Pubkey,
+    lamports: u64,
+    min_yot: u64,
+    lock_seconds: i64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (staking_account, _) = find_staking_address(program_id, user);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    let swap_program_id = swap::program_id();
+    let (swap_state, _) = swap::find_program_state_address(&swap_program_id);
+    let (swap_authority, _) = swap::find_program_authority(&swap_program_id);
+    let (sol_yot_pool, _) = swap::find_pool_address(&swap_program_id, &spl_token::native_mint::id(), yot_mint);
+    let (sol_fee_vault, _) = multihub_swap_v3::find_sol_fee_vault_address(&swap_program_id);
+    instruction(
+        program_id,
+        StakingInstruction::StakeWithSol {
+            lamports,
+            min_yot,
+            lock_seconds,
+        },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yot_mint), false),
+            AccountMeta::new(vault_address(program_id, yot_mint), false),
+            AccountMeta::new(staking_account, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(pause_state, false),
+            AccountMeta::new(
+                spl_associated_token_account::get_associated_token_address(user, &spl_token::native_mint::id()),
+                false,
+            ),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yos_mint), false),
+            AccountMeta::new_readonly(swap_program_id, false),
+            AccountMeta::new(swap_state, false),
+            AccountMeta::new(swap_authority, false),
+            AccountMeta::new(sol_yot_pool, false),
+            AccountMeta::new(swap::vault_address(&swap_program_id, &spl_token::native_mint::id()), false),
+            AccountMeta::new(swap::vault_address(&swap_program_id, yot_mint), false),
+            AccountMeta::new(sol_fee_vault, false),
+        ],
+    )
+}
+
+// Create the user's wrapped SOL account that StakeWithSol wraps into, if it
+// does not exist yet, with `payer` funding the rent; StakeWithSol closes it
+// again once the stake is made
+pub fn create_wrapped_sol_account(payer: &Pubkey, user: &Pubkey) -> Instruction {
+    spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+        payer,
+        user,
+        &spl_token::native_mint::id(),
+        &spl_token::id(),
+    )
+}
+
+// Unstake `amount` YOT back to the user, crediting the YOS rewards accrued
+// so far to the position for a later harvest
+pub fn unstake(program_id: &Pubkey, user: &Pubkey, yot_mint: &Pubkey, yos_mint: &Pubkey, amount: u64) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    let (staking_account, _) = find_staking_address(program_id, user);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    let (runway_config, _) = find_runway_config_address(program_id);
+    let (reward_freeze, _) = find_reward_freeze_address(program_id);
+    let (rate_history, _) = find_rate_history_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::Unstake { amount },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yot_mint), false),
+            AccountMeta::new(vault_address(program_id, yot_mint), false),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yos_mint), false),
+            AccountMeta::new(vault_address(program_id, yos_mint), false),
+            AccountMeta::new(staking_account, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(pause_state, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new_readonly(runway_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(reward_freeze, false),
+            AccountMeta::new_readonly(rate_history, false),
+        ],
+    )
+}
+
+// Harvest pending YOS rewards to the user's associated account; any part the
+// reward vault cannot cover stays owed to the position
+pub fn harvest(program_id: &Pubkey, user: &Pubkey, yot_mint: &Pubkey, yos_mint: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    let (staking_account, _) = find_staking_address(program_id, user);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    let (runway_config, _) = find_runway_config_address(program_id);
+    let (harvest_fee_config, _) = find_harvest_fee_config_address(program_id);
+    let (reward_freeze, _) = find_reward_freeze_address(program_id);
+    let (rate_history, _) = find_rate_history_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::Harvest,
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yos_mint), false),
+            AccountMeta::new(vault_address(program_id, yos_mint), false),
+            AccountMeta::new(staking_account, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            AccountMeta::new_readonly(pause_state, false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+            AccountMeta::new_readonly(runway_config, false),
+            AccountMeta::new_readonly(vault_address(program_id, yot_mint), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            // The harvest fee, when one is set, goes to the treasury's YOS vault
+            AccountMeta::new_readonly(harvest_fee_config, false),
+            AccountMeta::new(treasury_yos_vault(yos_mint), false),
+            AccountMeta::new_readonly(reward_freeze, false),
+            AccountMeta::new_readonly(rate_history, false),
+        ],
+    )
+}
+
+// Report the liabilities of `staking_accounts` against the vaults, for
+// simulation; the SolvencyReport comes back as return data
+pub fn solvency_report(
+    program_id: &Pubkey,
+    yot_mint: &Pubkey,
+    yos_mint: &Pubkey,
+    staking_accounts: &[Pubkey],
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (runway_config, _) = find_runway_config_address(program_id);
+    let (reward_freeze, _) = find_reward_freeze_address(program_id);
+    let (rate_history, _) = find_rate_history_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new_readonly(runway_config, false),
+        AccountMeta::new_readonly(vault_address(program_id, yot_mint), false),
+        AccountMeta::new_readonly(vault_address(program_id, yos_mint), false),
+        AccountMeta::new_readonly(reward_freeze, false),
+        AccountMeta::new_readonly(rate_history, false),
+    ];
+    accounts.extend(staking_accounts.iter().map(|staking_account| AccountMeta::new_readonly(*staking_account, false)));
+    instruction(program_id, StakingInstruction::SolvencyReport, accounts)
+}
+
+// Probe the program's health, for simulation; the HealthReport comes back as
+// return data
+pub fn ping(program_id: &Pubkey, yot_mint: &Pubkey, yos_mint: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    let (runway_config, _) = find_runway_config_address(program_id);
+    instruction(
+        program_id,
+        StakingInstruction::Ping,
+        vec![
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(pause_state, false),
+            AccountMeta::new_readonly(runway_config, false),
+            AccountMeta::new_readonly(vault_address(program_id, yot_mint), false),
+            AccountMeta::new_readonly(vault_address(program_id, yos_mint), false),
+        ],
+    )
+}
+
+// Set the user's personal harvest threshold in raw rewards (zero follows the
+// program's), growing a legacy staking account with the user paying rent
+pub fn set_harvest_threshold(program_id: &Pubkey, user: &Pubkey, harvest_threshold: u64) -> Instruction {
+    let (staking_account, _) = find_staking_address(program_id, user);
+    instruction(
+        program_id,
+        StakingInstruction::SetHarvestThreshold { harvest_threshold },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(staking_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// YOS vault holding a snapshot's bonus, the snapshot PDA's associated account
+pub fn snapshot_vault_address(program_id: &Pubkey, campaign_id: u64, yos_mint: &Pubkey) -> Pubkey {
+    let (snapshot, _) = find_snapshot_address(program_id, campaign_id);
+    spl_associated_token_account::get_associated_token_address(&snapshot, yos_mint)
+}
+
+// Record a stake snapshot for `campaign_id` with `bonus_pool` YOS shared by
+// the balances under `merkle_root`, moving the bonus from the admin's
+// associated YOS account into the snapshot's vault
+#[allow(clippy::too_many_arguments)]
+pub fn record_snapshot(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yos_mint: &Pubkey,
+    campaign_id: u64,
+    slot: u64,
+    merkle_root: [u8; 32],
+    total_staked: u64,
+    bonus_pool: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        StakingInstruction::RecordSnapshot {
+            campaign_id,
+            slot,
+            merkle_root,
+            total_staked,
+            bonus_pool,
+        },
+        snapshot_funding_accounts(program_id, admin, yos_mint, campaign_id),
+    )
+}
+
+// Give a snapshot recorded before snapshots had vaults its own vault,
+// funded from the admin's associated YOS account with the unclaimed bonus
+pub fn fund_snapshot_vault(program_id: &Pubkey, admin: &Pubkey, yos_mint: &Pubkey, campaign_id: u64) -> Instruction {
+    instruction(
+        program_id,
+        StakingInstruction::FundSnapshotVault { campaign_id },
+        snapshot_funding_accounts(program_id, admin, yos_mint, campaign_id),
+    )
+}
+
+// Accounts of the instructions moving a snapshot's bonus from the admin into
+// the snapshot's vault
+fn snapshot_funding_accounts(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yos_mint: &Pubkey,
+    campaign_id: u64,
+) -> Vec<AccountMeta> {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (snapshot, _) = find_snapshot_address(program_id, campaign_id);
+    vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new(snapshot, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new(spl_associated_token_account::get_associated_token_address(admin, yos_mint), false),
+        AccountMeta::new(snapshot_vault_address(program_id, campaign_id, yos_mint), false),
+        AccountMeta::new_readonly(*yos_mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ]
+}
+
+// Claim the snapshot bonus for `staked_amount` to the user's associated
+// YOS account
+pub fn claim_snapshot_bonus(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    yos_mint: &Pubkey,
+    campaign_id: u64,
+    staked_amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pause_state, _) = find_pause_state_address(program_id);
+    let (snapshot, _) = find_snapshot_address(program_id, campaign_id);
+    let (claim, _) = find_snapshot_claim_address(program_id, &snapshot, user);
+    instruction(
+        program_id,
+        StakingInstruction::ClaimSnapshotBonus {
+            campaign_id,
+            staked_amount,
+            proof,
+        },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yos_mint), false),
+            AccountMeta::new(snapshot, false),
+            AccountMeta::new(claim, false),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(pause_state, false),
+            AccountMeta::new(snapshot_vault_address(program_id, campaign_id, yos_mint), false),
+            AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        ],
+    )
+}
@@ -0,0 +1,1680 @@
+// Swap program (multihub v3) instruction builders
+use borsh::BorshSerialize;
+use multihub_swap_v3::{
+    find_contribution_ledger_address, find_contribution_targets_address, find_daily_stats_address,
+    find_cross_chain_config_address, find_distribution_thread_address, find_emergency_withdrawal_address,
+    find_hub_token_address, find_idempotency_log_address, find_intent_receipt_address, find_market_maker_address,
+    find_parameter_history_address, find_sol_fee_vault_address, find_treasury_address, find_user_stats_address,
+    SwapInstruction, CLOCKWORK_THREAD_PROGRAM_ID, SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+};
+use spl_associated_token_account::get_associated_token_address;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program, sysvar,
+};
+
+pub use multihub_swap_v3::{
+    admin_instruction, find_launch_address, find_liquidity_position_address, find_order_address,
+    find_otc_offer_address, find_permission_matrix_address, find_pool_address, find_pool_twap_address,
+    find_program_authority, find_program_state_address, find_route_allowlist_address, find_stream_address,
+    find_swap_receipt_address, find_token_metadata_address, id as program_id, stats_day, swap_route_hash,
+    ActivityRecord, DailyStats, Launch, LiquidityPosition, Order, OtcOffer, PermissionMatrix, PoolState, PoolTwap,
+    ProgramState, PurchaseIntent, RouteAllowlist, Stream, SwapReceipt, SwapSimulation, MAX_ALLOWED_ROUTES,
+    MAX_PERMISSION_HOLDERS, SWAP_COMPUTE_UNITS, SWAP_LITE_COMPUTE_UNITS, TWAP_WINDOW_SECONDS,
+};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+
+use crate::token_swap::TokenSwapPool;
+
+// Fee and reward rates set at Initialize, in basis points
+#[derive(Clone, Copy, Debug)]
+pub struct SwapRates {
+    pub lp_contribution_rate: u64,
+    pub admin_fee_rate: u64,
+    pub yos_cashback_rate: u64,
+    pub swap_fee_rate: u64,
+    pub referral_rate: u64,
+}
+
+impl Default for SwapRates {
+    // Production rates: 20% contribution, 0.1% admin fee, 3% cashback,
+    // 0.3% swap fee, 0.5% referral
+    fn default() -> Self {
+        SwapRates {
+            lp_contribution_rate: 2000,
+            admin_fee_rate: 10,
+            yos_cashback_rate: 300,
+            swap_fee_rate: 30,
+            referral_rate: 50,
+        }
+    }
+}
+
+fn instruction(program_id: &Pubkey, data: SwapInstruction, accounts: Vec<AccountMeta>) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: data.try_to_vec().expect("swap instructions always serialize"),
+    }
+}
+
+// Initialize program state with `admin` as admin and payer, creating the
+// authority's YOT, YOS and wSOL vaults and the treasury's YOT and YOS vaults.
+// Both mints must already have their Metaplex metadata.
+pub fn initialize(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yot_mint: &Pubkey,
+    yos_mint: &Pubkey,
+    rates: SwapRates,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    let (treasury, _) = find_treasury_address(program_id);
+    let native_mint = spl_token::native_mint::id();
+    instruction(
+        program_id,
+        SwapInstruction::Initialize {
+            admin: *admin,
+            yot_mint: *yot_mint,
+            yos_mint: *yos_mint,
+            lp_contribution_rate: rates.lp_contribution_rate,
+            admin_fee_rate: rates.admin_fee_rate,
+            yos_cashback_rate: rates.yos_cashback_rate,
+            swap_fee_rate: rates.swap_fee_rate,
+            referral_rate: rates.referral_rate,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(find_token_metadata_address(yot_mint).0, false),
+            AccountMeta::new_readonly(find_token_metadata_address(yos_mint).0, false),
+            AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*yot_mint, false),
+            AccountMeta::new_readonly(*yos_mint, false),
+            AccountMeta::new_readonly(native_mint, false),
+            AccountMeta::new_readonly(treasury, false),
+            AccountMeta::new(vault_address(program_id, yot_mint), false),
+            AccountMeta::new(vault_address(program_id, yos_mint), false),
+            AccountMeta::new(vault_address(program_id, &native_mint), false),
+            AccountMeta::new(get_associated_token_address(&treasury, yot_mint), false),
+            AccountMeta::new(get_associated_token_address(&treasury, yos_mint), false),
+        ],
+    )
+}
+
+// Update any subset of the rates; unset rates keep their value
+pub fn update_parameters(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    lp_contribution_rate: Option<u64>,
+    admin_fee_rate: Option<u64>,
+    yos_cashback_rate: Option<u64>,
+    swap_fee_rate: Option<u64>,
+    referral_rate: Option<u64>,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (parameter_history, _) = find_parameter_history_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::UpdateParameters {
+            lp_contribution_rate,
+            admin_fee_rate,
+            yos_cashback_rate,
+            swap_fee_rate,
+            referral_rate,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new(parameter_history, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Hand the admin role to `new_admin`
+pub fn set_admin(program_id: &Pubkey, admin: &Pubkey, new_admin: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetAdmin { new_admin: *new_admin },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(program_state, false),
+        ],
+    )
+}
+
+// Replace the pause mask (pauser or admin)
+pub fn set_paused_mask(program_id: &Pubkey, authority: &Pubkey, paused_mask: u8) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetPausedMask { paused_mask },
+        vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(program_state, false),
+        ],
+    )
+}
+
+// Withdraw SOL fees to the program's `admin`, signed by the admin or a
+// permission holder; `approvers` must also sign when the amount is above the
+// treasury's approval amount
+pub fn withdraw_sol_fees(
+    program_id: &Pubkey,
+    signer: &Pubkey,
+    admin: &Pubkey,
+    lamports: u64,
+    approvers: &[Pubkey],
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (sol_fee_vault, _) = find_sol_fee_vault_address(program_id);
+    let (treasury, _) = find_treasury_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*signer, true),
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new(sol_fee_vault, false),
+        AccountMeta::new_readonly(treasury, false),
+        AccountMeta::new(*admin, false),
+    ];
+    accounts.extend(approvers.iter().map(|approver| AccountMeta::new_readonly(*approver, true)));
+    instruction(program_id, SwapInstruction::WithdrawSolFees { amount: lamports }, accounts)
+}
+
+// Pay `amount` of `mint` from the treasury's associated token account to
+// `recipient_token_account`; `approvers` must also sign when the amount is
+// above the treasury's approval amount
+pub fn withdraw_treasury(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint: &Pubkey,
+    recipient_token_account: &Pubkey,
+    amount: u64,
+    approvers: &[Pubkey],
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (treasury, _) = find_treasury_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new_readonly(treasury, false),
+        AccountMeta::new(get_associated_token_address(&treasury, mint), false),
+        AccountMeta::new(*recipient_token_account, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    accounts.extend(approvers.iter().map(|approver| AccountMeta::new_readonly(*approver, true)));
+    instruction(program_id, SwapInstruction::WithdrawTreasury { amount }, accounts)
+}
+
+// Move the admin fees of the `mint_x`/`mint_y` pool from the authority's
+// vaults to the treasury's associated token accounts; anyone may send it
+pub fn collect_admin_fees(program_id: &Pubkey, mint_x: &Pubkey, mint_y: &Pubkey) -> Instruction {
+    let (mint_a, mint_b) = if mint_x < mint_y { (mint_x, mint_y) } else { (mint_y, mint_x) };
+    let (pool, _) = find_pool_address(program_id, mint_a, mint_b);
+    let (treasury, _) = find_treasury_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::CollectAdminFees {},
+        vec![
+            AccountMeta::new(pool, false),
+            AccountMeta::new(vault_address(program_id, mint_a), false),
+            AccountMeta::new(vault_address(program_id, mint_b), false),
+            AccountMeta::new(get_associated_token_address(&treasury, mint_a), false),
+            AccountMeta::new(get_associated_token_address(&treasury, mint_b), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Start the timelock on unwinding the treasury's LP position in the
+// spl-token-swap pool at `pool`
+pub fn schedule_emergency_withdrawal(program_id: &Pubkey, admin: &Pubkey, pool: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (emergency_withdrawal, _) = find_emergency_withdrawal_address(program_id, pool);
+    instruction(
+        program_id,
+        SwapInstruction::ScheduleEmergencyWithdrawal,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new(emergency_withdrawal, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Drop the scheduled emergency withdrawal from the pool at `pool`
+pub fn cancel_emergency_withdrawal(program_id: &Pubkey, admin: &Pubkey, pool: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (emergency_withdrawal, _) = find_emergency_withdrawal_address(program_id, pool);
+    instruction(
+        program_id,
+        SwapInstruction::CancelEmergencyWithdrawal,
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new_readonly(*pool, false),
+            AccountMeta::new(emergency_withdrawal, false),
+        ],
+    )
+}
+
+// Withdraw the treasury's whole LP position in `pool` into the treasury's
+// associated token accounts, which must already exist; `withdraw_treasury`
+// pays them out from there
+pub fn execute_emergency_withdrawal(program_id: &Pubkey, admin: &Pubkey, pool: &TokenSwapPool) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (emergency_withdrawal, _) = find_emergency_withdrawal_address(program_id, &pool.address);
+    let (treasury, _) = find_treasury_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new(program_state, false),
+        AccountMeta::new(emergency_withdrawal, false),
+    ];
+    accounts.extend(venue_accounts(pool));
+    accounts.extend([
+        AccountMeta::new_readonly(treasury, false),
+        AccountMeta::new(get_associated_token_address(&treasury, &pool.pool_mint), false),
+        AccountMeta::new(get_associated_token_address(&treasury, &pool.mint_a), false),
+        AccountMeta::new(get_associated_token_address(&treasury, &pool.mint_b), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(pool.program_id, false),
+    ]);
+    instruction(program_id, SwapInstruction::ExecuteEmergencyWithdrawal, accounts)
+}
+
+// Withdraw `amount` of the treasury's LP tokens of `from_pool` and deposit
+// the proceeds into `to_pool`. The treasury's associated token accounts for
+// both pool mints and both tokens must already exist.
+pub fn migrate_liquidity(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    from_pool: &TokenSwapPool,
+    to_pool: &TokenSwapPool,
+    amount: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (treasury, _) = find_treasury_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(program_state, false),
+        AccountMeta::new_readonly(treasury, false),
+    ];
+    for pool in [from_pool, to_pool] {
+        accounts.extend(venue_accounts(pool));
+        accounts.push(AccountMeta::new(get_associated_token_address(&treasury, &pool.pool_mint), false));
+    }
+    accounts.extend([
+        AccountMeta::new(get_associated_token_address(&treasury, &from_pool.mint_a), false),
+        AccountMeta::new(get_associated_token_address(&treasury, &from_pool.mint_b), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(from_pool.program_id, false),
+    ]);
+    instruction(
+        program_id,
+        SwapInstruction::MigrateLiquidity {
+            from_pool: from_pool.address,
+            to_pool: to_pool.address,
+            amount,
+        },
+        accounts,
+    )
+}
+
+// Swap `amount_in` of `mint_in` through the external spl-token-swap `pool`,
+// from `user`'s associated token account into the one for the other mint.
+// The program checks `min_amount_out` against what that account gained.
+pub fn swap_through_token_swap(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    pool: &TokenSwapPool,
+    mint_in: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let mint_out = if *mint_in == pool.mint_a { pool.mint_b } else { pool.mint_a };
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*user, true),
+        AccountMeta::new(program_state, false),
+    ];
+    accounts.extend(venue_accounts(pool));
+    accounts.extend([
+        AccountMeta::new(get_associated_token_address(user, mint_in), false),
+        AccountMeta::new(get_associated_token_address(user, &mint_out), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(pool.program_id, false),
+    ]);
+    instruction(
+        program_id,
+        SwapInstruction::SwapThroughTokenSwap {
+            amount_in,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// The accounts the swap program takes for an spl-token-swap pool
+fn venue_accounts(pool: &TokenSwapPool) -> [AccountMeta; 6] {
+    [
+        AccountMeta::new_readonly(pool.address, false),
+        AccountMeta::new_readonly(pool.authority, false),
+        AccountMeta::new(pool.pool_mint, false),
+        AccountMeta::new(pool.token_a, false),
+        AccountMeta::new(pool.token_b, false),
+        AccountMeta::new(pool.fee_account, false),
+    ]
+}
+
+// Trust a Wormhole emitter's purchase intents paid in `quote_mint` (admin only)
+pub fn set_cross_chain_emitter(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+    quote_mint: &Pubkey,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (cross_chain_config, _) = find_cross_chain_config_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetCrossChainEmitter {
+            emitter_chain,
+            emitter_address,
+            quote_mint: *quote_mint,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(cross_chain_config, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Token account of `quote_mint` that bridged purchase intent funds are
+// delivered to
+pub fn intent_escrow_address(program_id: &Pubkey, quote_mint: &Pubkey) -> Pubkey {
+    let (cross_chain_config, _) = find_cross_chain_config_address(program_id);
+    get_associated_token_address(&cross_chain_config, quote_mint)
+}
+
+// Execute the purchase intent in `posted_vaa`, paying the YOT to `target`.
+// The emitter and VAA sequence locate the replay receipt
+#[allow(clippy::too_many_arguments)]
+pub fn execute_purchase_intent(
+    program_id: &Pubkey,
+    relayer: &Pubkey,
+    posted_vaa: &Pubkey,
+    emitter_chain: u16,
+    emitter_address: &[u8; 32],
+    sequence: u64,
+    quote_mint: &Pubkey,
+    yot_mint: &Pubkey,
+    target: &Pubkey,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (cross_chain_config, _) = find_cross_chain_config_address(program_id);
+    let (intent_receipt, _) = find_intent_receipt_address(program_id, emitter_chain, emitter_address, sequence);
+    let (pool, _) = find_pool_address(program_id, quote_mint, yot_mint);
+    let (authority, _) = find_program_authority(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::ExecutePurchaseIntent,
+        vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new_readonly(cross_chain_config, false),
+            AccountMeta::new_readonly(*posted_vaa, false),
+            AccountMeta::new(intent_receipt, false),
+            AccountMeta::new(intent_escrow_address(program_id, quote_mint), false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(vault_address(program_id, quote_mint), false),
+            AccountMeta::new(vault_address(program_id, yot_mint), false),
+            AccountMeta::new(get_associated_token_address(target, yot_mint), false),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Set the K-of-N treasury approver set. `payer` is the admin on first use;
+// afterwards `current_approvers` must sign
+pub fn set_treasury_approvers(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+    approval_amount: u64,
+    current_approvers: &[Pubkey],
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (treasury, _) = find_treasury_address(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new_readonly(program_state, false),
+        AccountMeta::new(treasury, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    accounts.extend(current_approvers.iter().map(|approver| AccountMeta::new_readonly(*approver, true)));
+    instruction(
+        program_id,
+        SwapInstruction::SetTreasuryApprovers {
+            approvers,
+            threshold,
+            approval_amount,
+        },
+        accounts,
+    )
+}
+
+// Split the liquidity contribution between `targets` pools by weight in
+// basis points; an empty list keeps it in the swapped pool
+pub fn set_contribution_targets(program_id: &Pubkey, admin: &Pubkey, targets: Vec<(Pubkey, u16)>) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (contribution_targets, _) = find_contribution_targets_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetContributionTargets { targets },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(contribution_targets, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Set the swap fee paid by swaps that skip the liquidity contribution
+// (0 disables opting out)
+pub fn set_opt_out_swap_fee(program_id: &Pubkey, admin: &Pubkey, swap_fee_rate: u64) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetOptOutSwapFee { swap_fee_rate },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(program_state, false),
+        ],
+    )
+}
+
+// Create the pool PDA for a mint pair
+pub fn initialize_pool(program_id: &Pubkey, admin: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pool, _) = find_pool_address(program_id, mint_a, mint_b);
+    instruction(
+        program_id,
+        SwapInstruction::InitializePool {
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Create the time-weighted reserves of the pool for a mint pair, seeded with
+// its current reserves
+pub fn initialize_pool_twap(program_id: &Pubkey, admin: &Pubkey, mint_a: &Pubkey, mint_b: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pool, _) = find_pool_address(program_id, mint_a, mint_b);
+    let (pool_twap, _) = find_pool_twap_address(program_id, &pool);
+    instruction(
+        program_id,
+        SwapInstruction::InitializePoolTwap {},
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(pool, false),
+            AccountMeta::new(pool_twap, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Deposit into a pool from the user's associated token accounts into the
+// authority's associated vaults. Amounts follow the argument mint order.
+pub fn add_liquidity(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    amount_x: u64,
+    amount_y: u64,
+    min_shares: u64,
+) -> Instruction {
+    let (pool, _) = find_pool_address(program_id, mint_x, mint_y);
+    let (position, _) = find_liquidity_position_address(program_id, &pool, user);
+    let (program_authority, _) = find_program_authority(program_id);
+    // Pools order their mints by address
+    let ((mint_a, amount_a), (mint_b, amount_b)) = if mint_x < mint_y {
+        ((mint_x, amount_x), (mint_y, amount_y))
+    } else {
+        ((mint_y, amount_y), (mint_x, amount_x))
+    };
+    instruction(
+        program_id,
+        SwapInstruction::AddLiquidity {
+            amount_a,
+            amount_b,
+            min_shares,
+        },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, mint_a), false),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, mint_b), false),
+            AccountMeta::new(vault_address(program_id, mint_a), false),
+            AccountMeta::new(vault_address(program_id, mint_b), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Burn `shares` of the user's position in a pool, paying out into the user's
+// associated token accounts. Minimums follow the argument mint order.
+pub fn remove_liquidity(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_x: &Pubkey,
+    mint_y: &Pubkey,
+    shares: u64,
+    min_amount_x: u64,
+    min_amount_y: u64,
+) -> Instruction {
+    let (pool, _) = find_pool_address(program_id, mint_x, mint_y);
+    let (position, _) = find_liquidity_position_address(program_id, &pool, user);
+    let (program_authority, _) = find_program_authority(program_id);
+    // Pools order their mints by address
+    let ((mint_a, min_amount_a), (mint_b, min_amount_b)) = if mint_x < mint_y {
+        ((mint_x, min_amount_x), (mint_y, min_amount_y))
+    } else {
+        ((mint_y, min_amount_y), (mint_x, min_amount_x))
+    };
+    instruction(
+        program_id,
+        SwapInstruction::RemoveLiquidity {
+            shares,
+            min_amount_a,
+            min_amount_b,
+        },
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, mint_a), false),
+            AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, mint_b), false),
+            AccountMeta::new(vault_address(program_id, mint_a), false),
+            AccountMeta::new(vault_address(program_id, mint_b), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Associated token account of the program authority holding `mint`
+pub fn vault_address(program_id: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (program_authority, _) = find_program_authority(program_id);
+    spl_associated_token_account::get_associated_token_address(&program_authority, mint)
+}
+
+// Swap `amount_in` of `mint_in` for `mint_out` between the user's associated
+// token accounts, crediting YOS cashback to the user's YOS account
+pub fn swap(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        SwapInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        },
+        swap_accounts(program_id, user, mint_in, mint_out, yos_mint),
+    )
+}
+
+// Like `swap`, also passing the pair's route allowlist and time-weighted
+// reserves
+pub fn swap_through_pool(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    accounts.extend(pool_accounts(program_id, mint_in, mint_out));
+    instruction(
+        program_id,
+        SwapInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// Like `swap_through_pool`, spending whatever the user's `mint_in` account
+// holds above `keep_balance`
+#[allow(clippy::too_many_arguments)]
+pub fn swap_balance_through_pool(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    keep_balance: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    accounts.extend(pool_accounts(program_id, mint_in, mint_out));
+    instruction(
+        program_id,
+        SwapInstruction::SwapBalance {
+            keep_balance,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// Hop `hop` of the swap route along `route`, carrying the whole route. The
+// first hop swaps `amount`; later hops spend whatever the user's input
+// account holds above `amount`, as `swap_balance_through_pool` does.
+#[allow(clippy::too_many_arguments)]
+pub fn swap_route_hop(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    route: &[Pubkey],
+    hop: usize,
+    yos_mint: &Pubkey,
+    amount: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let (mint_in, mint_out) = (&route[hop], &route[hop + 1]);
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    let mut pool_accounts = pool_accounts(program_id, mint_in, mint_out);
+    // The route's end pair allowlist follows the hop pair's, ahead of the
+    // time-weighted reserves
+    let (first, last) = (&route[0], &route[route.len() - 1]);
+    if (first, last) != (mint_in, mint_out) {
+        let (route_allowlist, _) = find_route_allowlist_address(program_id, first, last);
+        pool_accounts.insert(pool_accounts.len() - 1, AccountMeta::new_readonly(route_allowlist, false));
+    }
+    accounts.extend(pool_accounts);
+    instruction(
+        program_id,
+        SwapInstruction::SwapRouteHop {
+            route: route.to_vec(),
+            amount,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// Like `swap_through_pool`, skipping cashback, stats, referral and events
+// for the smaller SWAP_LITE_COMPUTE_UNITS budget
+pub fn swap_lite(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, &Pubkey::default());
+    // Lite swaps take no YOS account
+    accounts.remove(5);
+    accounts.extend(pool_accounts(program_id, mint_in, mint_out));
+    instruction(
+        program_id,
+        SwapInstruction::SwapTokenLite {
+            amount_in,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// ComputeBudget instruction to put ahead of a swap, sized for SwapTokenLite
+// when `lite` and for every other swap variant otherwise
+pub fn swap_compute_budget(lite: bool) -> Instruction {
+    let units = if lite { SWAP_LITE_COMPUTE_UNITS } else { SWAP_COMPUTE_UNITS };
+    ComputeBudgetInstruction::set_compute_unit_limit(units)
+}
+
+// Preflight of `swap`, any swap instruction built here, with the same
+// accounts. Send it through simulateTransaction and decode the return data
+// as a SwapSimulation; it moves no tokens and writes no account.
+pub fn simulate_swap(swap: Instruction) -> Instruction {
+    let program_id = swap.program_id;
+    instruction(&program_id, SwapInstruction::SimulateSwap { swap_data: swap.data }, swap.accounts)
+}
+
+// Like `swap`, but skip the liquidity contribution and pay the opt-out swap
+// fee instead
+pub fn swap_without_contribution(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    instruction(
+        program_id,
+        SwapInstruction::SwapWithoutContribution {
+            amount_in,
+            min_amount_out,
+        },
+        swap_accounts(program_id, user, mint_in, mint_out, yos_mint),
+    )
+}
+
+// Swap that the program rejects if `idempotency_key` was already used by
+// `user` recently, so a wallet can resubmit it after a timeout
+#[allow(clippy::too_many_arguments)]
+pub fn swap_idempotent(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    idempotency_key: [u8; 16],
+) -> Instruction {
+    let (idempotency_log, _) = find_idempotency_log_address(program_id, user);
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    // Skip the eight positional optional accounts before the log
+    accounts.extend((0..8).map(|_| AccountMeta::new_readonly(*program_id, false)));
+    accounts.push(AccountMeta::new(idempotency_log, false));
+    instruction(
+        program_id,
+        SwapInstruction::SwapIdempotent {
+            amount_in,
+            min_amount_out,
+            idempotency_key,
+        },
+        accounts,
+    )
+}
+
+// Create the user's idempotency log PDA
+pub fn initialize_idempotency_log(program_id: &Pubkey, user: &Pubkey) -> Instruction {
+    let (idempotency_log, _) = find_idempotency_log_address(program_id, user);
+    instruction(
+        program_id,
+        SwapInstruction::InitializeIdempotencyLog,
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(idempotency_log, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Swap from a registered market-maker wallet, passing its registry entry so
+// a fee exemption applies
+pub fn swap_as_market_maker(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let (market_maker, _) = find_market_maker_address(program_id, user);
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    // Skip the eight positional optional accounts before the registry entry
+    accounts.extend((0..8).map(|_| AccountMeta::new_readonly(*program_id, false)));
+    accounts.push(AccountMeta::new_readonly(market_maker, false));
+    instruction(
+        program_id,
+        SwapInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// Swap that also writes the `user`-funded SwapReceipt PDA for `receipt_id`
+#[allow(clippy::too_many_arguments)]
+pub fn swap_with_receipt(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+    receipt_id: u64,
+) -> Instruction {
+    let (swap_receipt, _) = find_swap_receipt_address(program_id, user, receipt_id);
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    // Skip the eight positional optional accounts before the receipt, except
+    // the system program that creates it
+    let mut optional_accounts = vec![AccountMeta::new_readonly(*program_id, false); 8];
+    optional_accounts[1] = AccountMeta::new_readonly(system_program::id(), false);
+    accounts.extend(optional_accounts);
+    accounts.push(AccountMeta::new(swap_receipt, false));
+    instruction(
+        program_id,
+        SwapInstruction::SwapWithReceipt {
+            amount_in,
+            min_amount_out,
+            receipt_id,
+        },
+        accounts,
+    )
+}
+
+// Swap that accepts up to `slippage_bps` less than the program's own quote
+// against the pair's pool
+pub fn swap_with_slippage(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    amount_in: u64,
+    slippage_bps: u16,
+) -> Instruction {
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    accounts.extend(pool_accounts(program_id, mint_in, mint_out));
+    instruction(
+        program_id,
+        SwapInstruction::SwapWithSlippage { amount_in, slippage_bps },
+        accounts,
+    )
+}
+
+// Register `wallet` as a market maker, or update its fee exemption
+pub fn set_market_maker(program_id: &Pubkey, admin: &Pubkey, wallet: &Pubkey, fee_exempt: bool) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (market_maker, _) = find_market_maker_address(program_id, wallet);
+    instruction(
+        program_id,
+        SwapInstruction::SetMarketMaker {
+            wallet: *wallet,
+            fee_exempt,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(market_maker, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Swap between two non-YOT mints through `hub_mint`, one of them, passing
+// its hub token registry entry for the hub-side rule
+#[allow(clippy::too_many_arguments)]
+pub fn swap_via_hub(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+    hub_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let (hub_token, _) = find_hub_token_address(program_id, hub_mint);
+    let mut accounts = swap_accounts(program_id, user, mint_in, mint_out, yos_mint);
+    // Skip the eight positional optional accounts before the registry entry
+    accounts.extend((0..8).map(|_| AccountMeta::new_readonly(*program_id, false)));
+    accounts.push(AccountMeta::new_readonly(hub_token, false));
+    instruction(
+        program_id,
+        SwapInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        },
+        accounts,
+    )
+}
+
+// Require YOT or an approved hub token on one side of every swap
+pub fn set_require_hub_side(program_id: &Pubkey, admin: &Pubkey, required: bool) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetRequireHubSide { required },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(program_state, false),
+        ],
+    )
+}
+
+// Approve `mint` as a hub token, or revoke it
+pub fn set_hub_token(program_id: &Pubkey, admin: &Pubkey, mint: &Pubkey, approved: bool) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (hub_token, _) = find_hub_token_address(program_id, mint);
+    instruction(
+        program_id,
+        SwapInstruction::SetHubToken { mint: *mint, approved },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(hub_token, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Replace the hub tokens routes go through besides YOT
+pub fn set_hub_tokens(program_id: &Pubkey, admin: &Pubkey, hub_tokens: Vec<Pubkey>) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetHubTokens { hub_tokens },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(program_state, false),
+        ],
+    )
+}
+
+// Close the authority's empty vault of a delisted `mint`, sending its rent to
+// the treasury
+pub fn close_vault(program_id: &Pubkey, admin: &Pubkey, mint: &Pubkey) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    let (hub_token, _) = find_hub_token_address(program_id, mint);
+    let (treasury, _) = find_treasury_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::CloseVault {},
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new(vault_address(program_id, mint), false),
+            AccountMeta::new_readonly(hub_token, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Cap the reserve of `mint` in the `mint_a`/`mint_b` pool at `max_reserve`,
+// 0 for no cap
+pub fn set_pool_deposit_cap(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    mint: &Pubkey,
+    max_reserve: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (pool, _) = find_pool_address(program_id, mint_a, mint_b);
+    instruction(
+        program_id,
+        SwapInstruction::SetPoolDepositCap { mint: *mint, max_reserve },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(pool, false),
+        ],
+    )
+}
+
+// The pair's route allowlist after the positional slots following the pool,
+// so sensitive pairs can be traded, and the pool's time-weighted reserves,
+// which contributions are valued at; both are ignored while they do not exist
+fn pool_accounts(program_id: &Pubkey, mint_in: &Pubkey, mint_out: &Pubkey) -> Vec<AccountMeta> {
+    let (pool, _) = find_pool_address(program_id, mint_in, mint_out);
+    let (route_allowlist, _) = find_route_allowlist_address(program_id, mint_in, mint_out);
+    let (pool_twap, _) = find_pool_twap_address(program_id, &pool);
+    let mut accounts: Vec<AccountMeta> = (0..8).map(|_| AccountMeta::new_readonly(*program_id, false)).collect();
+    accounts.push(AccountMeta::new_readonly(route_allowlist, false));
+    accounts.push(AccountMeta::new(pool_twap, false));
+    accounts
+}
+
+// Route hashes of swaps between `mint_a` and `mint_b` through each of `vias`,
+// the mints a route passes in between from `mint_a` (empty for the direct
+// swap), in both directions, for publishing with `set_allowed_routes`
+pub fn route_hashes(mint_a: &Pubkey, mint_b: &Pubkey, vias: &[Vec<Pubkey>]) -> Vec<[u8; 32]> {
+    vias.iter()
+        .flat_map(|via| {
+            let mut route: Vec<Pubkey> = std::iter::once(*mint_a).chain(via.iter().copied()).collect();
+            route.push(*mint_b);
+            let forward = swap_route_hash(&route);
+            route.reverse();
+            [forward, swap_route_hash(&route)]
+        })
+        .collect()
+}
+
+// Flag the `mint_a`/`mint_b` pair as sensitive, allowing only swaps whose
+// route hash is in `route_hashes`; an empty list clears the flag
+pub fn set_allowed_routes(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    route_hashes: Vec<[u8; 32]>,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (route_allowlist, _) = find_route_allowlist_address(program_id, mint_a, mint_b);
+    instruction(
+        program_id,
+        SwapInstruction::SetAllowedRoutes {
+            mint_a: *mint_a,
+            mint_b: *mint_b,
+            route_hashes,
+        },
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new(route_allowlist, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Grant `holder` the admin instructions with the given `admin_instruction`
+// discriminants, replacing its earlier grants; `authority` is the admin or
+// governance and pays for the matrix on first use
+pub fn set_permissions(program_id: &Pubkey, authority: &Pubkey, holder: &Pubkey, instructions: Vec<u8>) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (permission_matrix, _) = find_permission_matrix_address(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::SetPermissions {
+            holder: *holder,
+            instructions,
+        },
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(permission_matrix, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Let a wallet granted `instruction` by the permission matrix sign it in the
+// admin's place
+pub fn with_permission_matrix(program_id: &Pubkey, mut instruction: Instruction) -> Instruction {
+    let (permission_matrix, _) = find_permission_matrix_address(program_id);
+    instruction.accounts.push(AccountMeta::new_readonly(permission_matrix, false));
+    instruction
+}
+
+// Clear the expired cashback boosts of `users`; anyone may crank it, and
+// users without an expired boost are skipped
+pub fn expire_boosts(program_id: &Pubkey, users: &[Pubkey]) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let mut accounts = vec![AccountMeta::new(program_state, false)];
+    accounts.extend(users.iter().map(|user| AccountMeta::new(find_user_stats_address(program_id, user).0, false)));
+    instruction(program_id, SwapInstruction::ExpireBoosts {}, accounts)
+}
+
+fn swap_accounts(
+    program_id: &Pubkey,
+    user: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    yos_mint: &Pubkey,
+) -> Vec<AccountMeta> {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    let (pool, _) = find_pool_address(program_id, mint_in, mint_out);
+    vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(program_state, false),
+        AccountMeta::new(program_authority, false),
+        AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, mint_in), false),
+        AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, mint_out), false),
+        AccountMeta::new(spl_associated_token_account::get_associated_token_address(user, yos_mint), false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        // The swap history placeholder, then the pair's pool and the
+        // authority's vaults of the two mints, which every swap passes
+        AccountMeta::new_readonly(*program_id, false),
+        AccountMeta::new(pool, false),
+        AccountMeta::new(vault_address(program_id, mint_in), false),
+        AccountMeta::new(vault_address(program_id, mint_out), false),
+    ]
+}
+
+// Limit or DCA order parameters chosen by the maker
+#[derive(Clone, Copy, Debug)]
+pub struct OrderTerms {
+    pub amount_in: u64,
+    pub amount_per_fill: u64,
+    pub min_amount_out_per_fill: u64,
+    pub interval_seconds: i64,
+    pub bounty_bps: u16,
+    // Unix time after which the order stops filling (0 never expires)
+    pub expires_at: i64,
+}
+
+// Associated token account of an order holding its escrowed input
+pub fn order_escrow_address(program_id: &Pubkey, maker: &Pubkey, order_id: u64, mint_in: &Pubkey) -> Pubkey {
+    let (order, _) = find_order_address(program_id, maker, order_id);
+    get_associated_token_address(&order, mint_in)
+}
+
+// Escrow the maker's input for an order. The escrow ATA must already exist
+pub fn create_order(
+    program_id: &Pubkey,
+    maker: &Pubkey,
+    order_id: u64,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    terms: OrderTerms,
+) -> Instruction {
+    let (order, _) = find_order_address(program_id, maker, order_id);
+    instruction(
+        program_id,
+        SwapInstruction::CreateOrder {
+            order_id,
+            mint_out: *mint_out,
+            amount_in: terms.amount_in,
+            amount_per_fill: terms.amount_per_fill,
+            min_amount_out_per_fill: terms.min_amount_out_per_fill,
+            interval_seconds: terms.interval_seconds,
+            bounty_bps: terms.bounty_bps,
+            expires_at: terms.expires_at,
+        },
+        vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new(order, false),
+            AccountMeta::new(get_associated_token_address(maker, mint_in), false),
+            AccountMeta::new(order_escrow_address(program_id, maker, order_id, mint_in), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Execute an order's next fill, paying the bounty to the keeper's output ATA
+pub fn fill_order(
+    program_id: &Pubkey,
+    keeper: &Pubkey,
+    maker: &Pubkey,
+    order_id: u64,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (order, _) = find_order_address(program_id, maker, order_id);
+    let (pool, _) = find_pool_address(program_id, mint_in, mint_out);
+    let (program_authority, _) = find_program_authority(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::FillOrder,
+        vec![
+            AccountMeta::new_readonly(*keeper, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(order_escrow_address(program_id, maker, order_id, mint_in), false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(vault_address(program_id, mint_in), false),
+            AccountMeta::new(vault_address(program_id, mint_out), false),
+            AccountMeta::new(get_associated_token_address(maker, mint_out), false),
+            AccountMeta::new(get_associated_token_address(keeper, mint_out), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Refund an order's remaining escrow and close it
+pub fn cancel_order(program_id: &Pubkey, maker: &Pubkey, order_id: u64, mint_in: &Pubkey) -> Instruction {
+    let (order, _) = find_order_address(program_id, maker, order_id);
+    instruction(
+        program_id,
+        SwapInstruction::CancelOrder,
+        vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new(order, false),
+            AccountMeta::new(order_escrow_address(program_id, maker, order_id, mint_in), false),
+            AccountMeta::new(get_associated_token_address(maker, mint_in), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Refund an expired order to its maker, tipping `keeper` out of its rent
+pub fn sweep_expired_order(
+    program_id: &Pubkey,
+    keeper: &Pubkey,
+    maker: &Pubkey,
+    order_id: u64,
+    mint_in: &Pubkey,
+) -> Instruction {
+    let (order, _) = find_order_address(program_id, maker, order_id);
+    instruction(
+        program_id,
+        SwapInstruction::SweepExpiredOrder,
+        vec![
+            AccountMeta::new(*keeper, true),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(order_escrow_address(program_id, maker, order_id, mint_in), false),
+            AccountMeta::new(get_associated_token_address(maker, mint_in), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Associated token account of an OTC offer holding its escrowed side
+pub fn otc_escrow_address(program_id: &Pubkey, maker: &Pubkey, offer_id: u64, mint_offered: &Pubkey) -> Pubkey {
+    let (offer, _) = find_otc_offer_address(program_id, maker, offer_id);
+    get_associated_token_address(&offer, mint_offered)
+}
+
+// Escrow `amount_offered` of `mint_offered` for `amount_requested` of
+// `mint_requested`, acceptable by `taker` only unless it is the default
+// pubkey. The escrow ATA must already exist
+#[allow(clippy::too_many_arguments)]
+pub fn create_otc_offer(
+    program_id: &Pubkey,
+    maker: &Pubkey,
+    offer_id: u64,
+    taker: &Pubkey,
+    mint_offered: &Pubkey,
+    amount_offered: u64,
+    mint_requested: &Pubkey,
+    amount_requested: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (offer, _) = find_otc_offer_address(program_id, maker, offer_id);
+    instruction(
+        program_id,
+        SwapInstruction::CreateOtcOffer {
+            offer_id,
+            taker: *taker,
+            mint_requested: *mint_requested,
+            amount_offered,
+            amount_requested,
+        },
+        vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(offer, false),
+            AccountMeta::new(get_associated_token_address(maker, mint_offered), false),
+            AccountMeta::new(otc_escrow_address(program_id, maker, offer_id, mint_offered), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Pay an OTC offer's price and take its escrow, less the admin fee
+pub fn accept_otc_offer(
+    program_id: &Pubkey,
+    taker: &Pubkey,
+    maker: &Pubkey,
+    offer_id: u64,
+    mint_offered: &Pubkey,
+    mint_requested: &Pubkey,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (offer, _) = find_otc_offer_address(program_id, maker, offer_id);
+    instruction(
+        program_id,
+        SwapInstruction::AcceptOtcOffer,
+        vec![
+            AccountMeta::new_readonly(*taker, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(offer, false),
+            AccountMeta::new(*maker, false),
+            AccountMeta::new(otc_escrow_address(program_id, maker, offer_id, mint_offered), false),
+            AccountMeta::new(get_associated_token_address(taker, mint_offered), false),
+            AccountMeta::new(get_associated_token_address(taker, mint_requested), false),
+            AccountMeta::new(get_associated_token_address(maker, mint_requested), false),
+            AccountMeta::new(vault_address(program_id, mint_offered), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Refund an OTC offer's escrow and close it
+pub fn cancel_otc_offer(program_id: &Pubkey, maker: &Pubkey, offer_id: u64, mint_offered: &Pubkey) -> Instruction {
+    let (offer, _) = find_otc_offer_address(program_id, maker, offer_id);
+    instruction(
+        program_id,
+        SwapInstruction::CancelOtcOffer,
+        vec![
+            AccountMeta::new(*maker, true),
+            AccountMeta::new(offer, false),
+            AccountMeta::new(otc_escrow_address(program_id, maker, offer_id, mint_offered), false),
+            AccountMeta::new(get_associated_token_address(maker, mint_offered), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Associated token account of a launch holding `mint`: the launched token
+// for the sale escrow, the quote mint for the proceeds
+pub fn launch_escrow_address(program_id: &Pubkey, launch_mint: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (launch, _) = find_launch_address(program_id, launch_mint);
+    get_associated_token_address(&launch, mint)
+}
+
+// Open a bonding-curve launch of `mint` priced in `quote_mint`, escrowing
+// `supply + liquidity_tokens` from the creator. The token escrow ATA must
+// already exist
+#[allow(clippy::too_many_arguments)]
+pub fn create_launch(
+    program_id: &Pubkey,
+    creator: &Pubkey,
+    mint: &Pubkey,
+    quote_mint: &Pubkey,
+    supply: u64,
+    liquidity_tokens: u64,
+    start_price: u64,
+    end_price: u64,
+    end_time: i64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (launch, _) = find_launch_address(program_id, mint);
+    instruction(
+        program_id,
+        SwapInstruction::CreateLaunch {
+            quote_mint: *quote_mint,
+            supply,
+            liquidity_tokens,
+            start_price,
+            end_price,
+            end_time,
+        },
+        vec![
+            AccountMeta::new(*creator, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(launch, false),
+            AccountMeta::new(get_associated_token_address(creator, mint), false),
+            AccountMeta::new(launch_escrow_address(program_id, mint, mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Buy `amount` launch tokens for at most `max_cost` of the quote mint. The
+// proceeds escrow ATA must already exist
+pub fn buy_launch(
+    program_id: &Pubkey,
+    buyer: &Pubkey,
+    mint: &Pubkey,
+    quote_mint: &Pubkey,
+    amount: u64,
+    max_cost: u64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (launch, _) = find_launch_address(program_id, mint);
+    instruction(
+        program_id,
+        SwapInstruction::BuyLaunch { amount, max_cost },
+        vec![
+            AccountMeta::new_readonly(*buyer, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(launch, false),
+            AccountMeta::new(launch_escrow_address(program_id, mint, mint), false),
+            AccountMeta::new(launch_escrow_address(program_id, mint, quote_mint), false),
+            AccountMeta::new(get_associated_token_address(buyer, quote_mint), false),
+            AccountMeta::new(get_associated_token_address(buyer, mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Close a finished launch, seeding its pool with the creator's LP position
+// and refunding unsold tokens. Anyone can pay for it
+pub fn close_launch(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    creator: &Pubkey,
+    mint: &Pubkey,
+    quote_mint: &Pubkey,
+) -> Instruction {
+    let (launch, _) = find_launch_address(program_id, mint);
+    let (pool, _) = find_pool_address(program_id, mint, quote_mint);
+    let (position, _) = find_liquidity_position_address(program_id, &pool, creator);
+    let (program_authority, _) = find_program_authority(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::CloseLaunch,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(launch, false),
+            AccountMeta::new(*creator, false),
+            AccountMeta::new(launch_escrow_address(program_id, mint, mint), false),
+            AccountMeta::new(launch_escrow_address(program_id, mint, quote_mint), false),
+            AccountMeta::new(get_associated_token_address(creator, mint), false),
+            AccountMeta::new(pool, false),
+            AccountMeta::new(position, false),
+            AccountMeta::new(vault_address(program_id, mint), false),
+            AccountMeta::new(vault_address(program_id, quote_mint), false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Associated token account of a stream holding its escrowed YOT
+pub fn stream_escrow_address(program_id: &Pubkey, sender: &Pubkey, stream_id: u64, yot_mint: &Pubkey) -> Pubkey {
+    let (stream, _) = find_stream_address(program_id, sender, stream_id);
+    get_associated_token_address(&stream, yot_mint)
+}
+
+// Stream `amount` YOT from the sender to `recipient` between `start_time` and
+// `end_time`. The escrow ATA must already exist
+#[allow(clippy::too_many_arguments)]
+pub fn create_stream(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    stream_id: u64,
+    recipient: &Pubkey,
+    yot_mint: &Pubkey,
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (stream, _) = find_stream_address(program_id, sender, stream_id);
+    instruction(
+        program_id,
+        SwapInstruction::CreateStream {
+            stream_id,
+            recipient: *recipient,
+            amount,
+            start_time,
+            end_time,
+        },
+        vec![
+            AccountMeta::new(*sender, true),
+            AccountMeta::new_readonly(program_state, false),
+            AccountMeta::new(stream, false),
+            AccountMeta::new(get_associated_token_address(sender, yot_mint), false),
+            AccountMeta::new(stream_escrow_address(program_id, sender, stream_id, yot_mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Withdraw everything a stream has paid out so far to the recipient
+pub fn withdraw_stream(
+    program_id: &Pubkey,
+    recipient: &Pubkey,
+    sender: &Pubkey,
+    stream_id: u64,
+    yot_mint: &Pubkey,
+) -> Instruction {
+    let (stream, _) = find_stream_address(program_id, sender, stream_id);
+    instruction(
+        program_id,
+        SwapInstruction::WithdrawStream,
+        vec![
+            AccountMeta::new_readonly(*recipient, true),
+            AccountMeta::new(stream, false),
+            AccountMeta::new(stream_escrow_address(program_id, sender, stream_id, yot_mint), false),
+            AccountMeta::new(get_associated_token_address(recipient, yot_mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Settle a stream with its recipient and refund the rest to the sender
+pub fn cancel_stream(
+    program_id: &Pubkey,
+    sender: &Pubkey,
+    stream_id: u64,
+    recipient: &Pubkey,
+    yot_mint: &Pubkey,
+) -> Instruction {
+    let (stream, _) = find_stream_address(program_id, sender, stream_id);
+    instruction(
+        program_id,
+        SwapInstruction::CancelStream,
+        vec![
+            AccountMeta::new(*sender, true),
+            AccountMeta::new(stream, false),
+            AccountMeta::new(stream_escrow_address(program_id, sender, stream_id, yot_mint), false),
+            AccountMeta::new(get_associated_token_address(sender, yot_mint), false),
+            AccountMeta::new(get_associated_token_address(recipient, yot_mint), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+        ],
+    )
+}
+
+// Create the Clockwork thread that distributes contribution rewards weekly to
+// the ledgers of `owners`, funded with `amount` lamports from the admin
+pub fn register_distribution_thread(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yos_mint: &Pubkey,
+    thread_id: Vec<u8>,
+    amount: u64,
+    owners: &[Pubkey],
+) -> Instruction {
+    let (thread, _) = find_distribution_thread_address(admin, &thread_id);
+    let accounts = distribution_thread_accounts(program_id, admin, yos_mint, &thread, owners);
+    instruction(program_id, SwapInstruction::RegisterDistributionThread { thread_id, amount }, accounts)
+}
+
+// Make the registered distribution `thread` (ProgramState.distribution_thread)
+// distribute to the ledgers of `owners` instead; `admin` must be its authority
+pub fn update_distribution_thread(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yos_mint: &Pubkey,
+    thread: &Pubkey,
+    owners: &[Pubkey],
+) -> Instruction {
+    let accounts = distribution_thread_accounts(program_id, admin, yos_mint, thread, owners);
+    instruction(program_id, SwapInstruction::UpdateDistributionThread, accounts)
+}
+
+// The accounts RegisterDistributionThread and UpdateDistributionThread take
+fn distribution_thread_accounts(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    yos_mint: &Pubkey,
+    thread: &Pubkey,
+    owners: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    let mut accounts = vec![
+        AccountMeta::new(*admin, true),
+        AccountMeta::new(program_state, false),
+        AccountMeta::new_readonly(program_authority, false),
+        AccountMeta::new(*thread, false),
+        AccountMeta::new_readonly(CLOCKWORK_THREAD_PROGRAM_ID, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*yos_mint, false),
+        AccountMeta::new_readonly(spl_token::id(), false),
+    ];
+    for owner in owners {
+        let (contribution_ledger, _) = find_contribution_ledger_address(program_id, owner);
+        accounts.push(AccountMeta::new_readonly(contribution_ledger, false));
+        accounts.push(AccountMeta::new_readonly(get_associated_token_address(owner, yos_mint), false));
+    }
+    accounts
+}
+
+// Snapshot the live statistics counters, which started on `day`
+// (ProgramState.stats_day), into that day's DailyStats PDA
+pub fn snapshot_daily_stats(program_id: &Pubkey, payer: &Pubkey, day: u64) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (daily_stats, _) = find_daily_stats_address(program_id, day);
+    instruction(
+        program_id,
+        SwapInstruction::SnapshotDailyStats,
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new(daily_stats, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+    )
+}
+
+// Initialize `merkle_tree`, already allocated and owned by the account
+// compression program, as the activity tree swaps append receipts to
+pub fn initialize_activity_tree(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    merkle_tree: &Pubkey,
+    noop_program: &Pubkey,
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> Instruction {
+    let (program_state, _) = find_program_state_address(program_id);
+    let (program_authority, _) = find_program_authority(program_id);
+    instruction(
+        program_id,
+        SwapInstruction::InitializeActivityTree { max_depth, max_buffer_size },
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(program_state, false),
+            AccountMeta::new(*merkle_tree, false),
+            AccountMeta::new_readonly(program_authority, false),
+            AccountMeta::new_readonly(SPL_ACCOUNT_COMPRESSION_PROGRAM_ID, false),
+            AccountMeta::new_readonly(*noop_program, false),
+        ],
+    )
+}
@@ -0,0 +1,174 @@
+// Adapter for standard spl-token-swap pools, such as the original SOL-YOT
+// pool on devnet, so liquidity that predates the swap program can be quoted
+// and traded alongside its own pools. Trades go through the swap program's
+// SwapThroughTokenSwap, which holds them to the output the user's account
+// actually received. Pool accounts are decoded and checked by hand against
+// the SwapV1 layout: only initialized constant-product pools over the SPL
+// Token program, with sane fees under MAX_TOKEN_SWAP_FEE_BPS, are accepted.
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+use tswap_math::{constant_product_amount_out, MathError};
+
+// The spl-token-swap deployment the web client routes through
+pub const TOKEN_SWAP_PROGRAM_ID: Pubkey = solana_program::pubkey!("SwaPpA9LAaLfeLi3a68M4DjnLqgtticKg6CnyNwgAC8");
+
+// Size of a SwapV1 pool account, including the leading version byte
+pub const TOKEN_SWAP_POOL_LEN: usize = 324;
+
+// Highest combined trade and owner fee of a pool the router will use
+pub const MAX_TOKEN_SWAP_FEE_BPS: u64 = 100;
+
+// Version byte of SwapV1, the only layout spl-token-swap has shipped
+const SWAP_V1: u8 = 1;
+
+// Curve type of a constant-product pool
+const CONSTANT_PRODUCT_CURVE: u8 = 0;
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenSwapError {
+    #[error("Pool account is {0} bytes, expected 324")]
+    InvalidLength(usize),
+    #[error("Unsupported pool version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Pool is not initialized")]
+    Uninitialized,
+    #[error("Pool tokens are not held by the SPL Token program")]
+    UnsupportedTokenProgram,
+    #[error("Unsupported curve type {0}, only constant product is routed")]
+    UnsupportedCurve(u8),
+    #[error("Pool bump seed does not derive its authority")]
+    InvalidAuthority,
+    #[error("Pool fees are malformed")]
+    InvalidFees,
+    #[error("Pool fees of {0} bps exceed the routing limit")]
+    FeeTooHigh(u64),
+}
+
+// A fraction of each trade, as spl-token-swap stores it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeRatio {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl FeeRatio {
+    // Fee on `amount`, charging at least one unit when the ratio is nonzero
+    // as spl-token-swap does
+    pub fn fee(&self, amount: u64) -> u64 {
+        if self.numerator == 0 || amount == 0 {
+            return 0;
+        }
+        let fee = (amount as u128 * self.numerator as u128 / self.denominator as u128) as u64;
+        fee.max(1)
+    }
+
+    // The ratio in basis points, rounded up
+    pub fn bps(&self) -> u64 {
+        if self.numerator == 0 {
+            return 0;
+        }
+        (self.numerator as u128 * 10000).div_ceil(self.denominator as u128) as u64
+    }
+
+    fn is_valid(&self) -> bool {
+        self.numerator == 0 || self.numerator < self.denominator
+    }
+}
+
+// The fields of a SwapV1 pool account the router needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenSwapPool {
+    // The spl-token-swap program owning the pool
+    pub program_id: Pubkey,
+    pub address: Pubkey,
+    // PDA signing for the pool's token accounts
+    pub authority: Pubkey,
+    // Reserve token accounts
+    pub token_a: Pubkey,
+    pub token_b: Pubkey,
+    pub pool_mint: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    // Receives the owner trade fee as pool tokens
+    pub fee_account: Pubkey,
+    pub trade_fee: FeeRatio,
+    pub owner_trade_fee: FeeRatio,
+}
+
+impl TokenSwapPool {
+    // Decode and validate the pool account at `address` of `program_id`
+    pub fn unpack(program_id: &Pubkey, address: &Pubkey, data: &[u8]) -> Result<Self, TokenSwapError> {
+        if data.len() != TOKEN_SWAP_POOL_LEN {
+            return Err(TokenSwapError::InvalidLength(data.len()));
+        }
+        if data[0] != SWAP_V1 {
+            return Err(TokenSwapError::UnsupportedVersion(data[0]));
+        }
+        if data[1] != 1 {
+            return Err(TokenSwapError::Uninitialized);
+        }
+        let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        if pubkey_at(3) != spl_token::id() {
+            return Err(TokenSwapError::UnsupportedTokenProgram);
+        }
+        if data[291] != CONSTANT_PRODUCT_CURVE {
+            return Err(TokenSwapError::UnsupportedCurve(data[291]));
+        }
+        let authority = Pubkey::create_program_address(&[address.as_ref(), &[data[2]]], program_id)
+            .map_err(|_| TokenSwapError::InvalidAuthority)?;
+
+        // Trade and owner trade fees come first among the eight fee fields;
+        // withdraw and host fees do not affect a swap's output
+        let trade_fee = FeeRatio {
+            numerator: u64_at(227),
+            denominator: u64_at(235),
+        };
+        let owner_trade_fee = FeeRatio {
+            numerator: u64_at(243),
+            denominator: u64_at(251),
+        };
+        if !trade_fee.is_valid() || !owner_trade_fee.is_valid() {
+            return Err(TokenSwapError::InvalidFees);
+        }
+        let fee_bps = trade_fee.bps() + owner_trade_fee.bps();
+        if fee_bps > MAX_TOKEN_SWAP_FEE_BPS {
+            return Err(TokenSwapError::FeeTooHigh(fee_bps));
+        }
+
+        Ok(TokenSwapPool {
+            program_id: *program_id,
+            address: *address,
+            authority,
+            token_a: pubkey_at(35),
+            token_b: pubkey_at(67),
+            pool_mint: pubkey_at(99),
+            mint_a: pubkey_at(131),
+            mint_b: pubkey_at(163),
+            fee_account: pubkey_at(195),
+            trade_fee,
+            owner_trade_fee,
+        })
+    }
+
+    // Whether the pool trades `mint_in` for `mint_out`
+    pub fn trades(&self, mint_in: &Pubkey, mint_out: &Pubkey) -> bool {
+        (self.mint_a, self.mint_b) == (*mint_in, *mint_out) || (self.mint_b, self.mint_a) == (*mint_in, *mint_out)
+    }
+
+    // Output of swapping `amount_in` of `mint_in`, given the balances of the
+    // pool's token_a and token_b accounts. None when the pool does not hold
+    // `mint_in`.
+    pub fn quote(&self, amount_in: u64, mint_in: &Pubkey, balances: (u64, u64)) -> Option<Result<u64, MathError>> {
+        let (reserve_in, reserve_out) = if *mint_in == self.mint_a {
+            balances
+        } else if *mint_in == self.mint_b {
+            (balances.1, balances.0)
+        } else {
+            return None;
+        };
+        let fees = self.trade_fee.fee(amount_in) + self.owner_trade_fee.fee(amount_in);
+        Some(constant_product_amount_out(reserve_in, reserve_out, amount_in.saturating_sub(fees)))
+    }
+
+}
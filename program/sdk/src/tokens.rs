@@ -0,0 +1,65 @@
+// Mainnet token registry: mints the swap front ends list, with their decimals
+// and canonical Pyth price accounts. Wormhole (Portal) wrapped assets keep
+// the 8 decimals of their bridged representation rather than the 9 used by
+// YOT, YOS and SOL, so amounts must always be scaled by the registered
+// decimals and never by TOKEN_DECIMALS.
+use solana_program::pubkey::Pubkey;
+
+// A token known to the registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenInfo {
+    pub symbol: &'static str,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    // Pyth price account quoting the token in USD
+    pub oracle: Pubkey,
+    // Bridged from another chain through Wormhole
+    pub wormhole_wrapped: bool,
+}
+
+pub const SOL: TokenInfo = TokenInfo {
+    symbol: "SOL",
+    mint: spl_token::native_mint::ID,
+    decimals: 9,
+    oracle: solana_program::pubkey!("H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG"),
+    wormhole_wrapped: false,
+};
+
+pub const USDC: TokenInfo = TokenInfo {
+    symbol: "USDC",
+    mint: solana_program::pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+    decimals: 6,
+    oracle: solana_program::pubkey!("Gnt27xtC473ZT2Mw5u8wZ68Z3gULkSTb5DuxJy7eJotD"),
+    wormhole_wrapped: false,
+};
+
+// Wrapped BTC (Portal)
+pub const WBTC: TokenInfo = TokenInfo {
+    symbol: "WBTC",
+    mint: solana_program::pubkey!("3NZ9JMVBmGAqocybic2c7LQCJScmgsAZ6vQqTDzcqmJh"),
+    decimals: 8,
+    oracle: solana_program::pubkey!("GVXRSBjFk6e6J3NbVPXohDJetcTjaeeuykUpbQF8UoMU"),
+    wormhole_wrapped: true,
+};
+
+// Wrapped Ether (Portal)
+pub const WETH: TokenInfo = TokenInfo {
+    symbol: "WETH",
+    mint: solana_program::pubkey!("7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs"),
+    decimals: 8,
+    oracle: solana_program::pubkey!("JBu1AL4obBcCMqKBBxhpWCNUt136ijcuMZLFvTP7iWdB"),
+    wormhole_wrapped: true,
+};
+
+// Every registered token
+pub const TOKENS: &[TokenInfo] = &[SOL, USDC, WBTC, WETH];
+
+// Registry entry for `mint`
+pub fn find_token(mint: &Pubkey) -> Option<&'static TokenInfo> {
+    TOKENS.iter().find(|token| token.mint == *mint)
+}
+
+// `whole` tokens in base units of a mint with `decimals`, None on overflow
+pub fn base_units(whole: u64, decimals: u8) -> Option<u64> {
+    10u64.checked_pow(decimals as u32)?.checked_mul(whole)
+}
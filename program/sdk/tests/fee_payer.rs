@@ -0,0 +1,28 @@
+use solana_program::pubkey::Pubkey;
+use tswap_sdk::{staking, swap, with_fee_payer};
+
+#[test]
+fn sponsored_instructions_end_with_the_signing_fee_payer() {
+    let [user, sponsor, yot_mint, yos_mint] = [(); 4].map(|_| Pubkey::new_unique());
+    let staking_program_id = staking::PROGRAM_ID;
+    let swap_program_id = swap::program_id();
+    let instructions = [
+        staking::stake(&staking_program_id, &user, &yot_mint, 1),
+        staking::stake_with_sol(&staking_program_id, &user, &yot_mint, &yos_mint, 1, 1, 0),
+        staking::claim_snapshot_bonus(&staking_program_id, &user, &yos_mint, 7, 10, vec![]),
+        swap::swap_through_pool(&swap_program_id, &user, &yot_mint, &yos_mint, &yos_mint, 1, 1),
+    ];
+    for instruction in instructions {
+        let documented = instruction.accounts.len();
+        let sponsored = with_fee_payer(instruction, &sponsor);
+        assert_eq!(sponsored.accounts.len(), documented + 1);
+        let fee_payer = sponsored.accounts.last().unwrap();
+        assert_eq!(fee_payer.pubkey, sponsor);
+        assert!(fee_payer.is_signer && fee_payer.is_writable);
+    }
+
+    // Associated token accounts are funded by the payer but owned by the user
+    let wrapped_sol_account = staking::create_wrapped_sol_account(&sponsor, &user);
+    assert_eq!(wrapped_sol_account.accounts[0].pubkey, sponsor);
+    assert_eq!(wrapped_sol_account.accounts[2].pubkey, user);
+}
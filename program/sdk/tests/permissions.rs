@@ -0,0 +1,55 @@
+use borsh::BorshDeserialize;
+use solana_program::pubkey::Pubkey;
+use tswap_sdk::swap::{self, admin_instruction};
+
+// Accounts are allocated with size_of and start zeroed
+fn empty_matrix() -> swap::PermissionMatrix {
+    let data = vec![0u8; std::mem::size_of::<swap::PermissionMatrix>()];
+    swap::PermissionMatrix::deserialize(&mut &data[..]).unwrap()
+}
+
+#[test]
+fn admin_discriminants_match_the_encoded_instructions() {
+    let program_id = swap::program_id();
+    let [admin, mint] = [(); 2].map(|_| Pubkey::new_unique());
+    let cases = [
+        (swap::set_admin(&program_id, &admin, &mint), admin_instruction::SET_ADMIN),
+        (swap::set_hub_token(&program_id, &admin, &mint, true), admin_instruction::SET_HUB_TOKEN),
+        (swap::close_vault(&program_id, &admin, &mint), admin_instruction::CLOSE_VAULT),
+        (swap::set_allowed_routes(&program_id, &admin, &mint, &mint, vec![]), admin_instruction::SET_ALLOWED_ROUTES),
+        (swap::initialize_pool_twap(&program_id, &admin, &mint, &admin), admin_instruction::INITIALIZE_POOL_TWAP),
+        (swap::withdraw_treasury(&program_id, &admin, &mint, &admin, 1, &[]), admin_instruction::WITHDRAW_TREASURY),
+        (
+            swap::update_distribution_thread(&program_id, &admin, &mint, &admin, &[]),
+            admin_instruction::UPDATE_DISTRIBUTION_THREAD,
+        ),
+    ];
+    for (instruction, discriminant) in cases {
+        assert_eq!(instruction.data[0], discriminant);
+    }
+}
+
+#[test]
+fn grants_are_per_holder_and_instruction() {
+    let [keeper, pauser, stranger] = [(); 3].map(|_| Pubkey::new_unique());
+    let mut matrix = empty_matrix();
+    assert!(matrix.set(keeper, 1 << admin_instruction::SET_HUB_TOKEN | 1 << admin_instruction::CLOSE_VAULT));
+    assert!(matrix.set(pauser, 1 << admin_instruction::SET_PAUSER));
+    assert!(matrix.allows(&keeper, admin_instruction::CLOSE_VAULT));
+    assert!(!matrix.allows(&keeper, admin_instruction::SET_PAUSER));
+    assert!(!matrix.allows(&stranger, admin_instruction::CLOSE_VAULT));
+    assert!(!matrix.allows(&keeper, u8::MAX));
+
+    // Revoking drops the holder and keeps the others in place
+    assert!(matrix.set(keeper, 0));
+    assert_eq!(matrix.holder_count, 1);
+    assert!(!matrix.allows(&keeper, admin_instruction::CLOSE_VAULT));
+    assert!(matrix.allows(&pauser, admin_instruction::SET_PAUSER));
+
+    // A full matrix refuses new holders but still updates existing ones
+    for _ in 1..swap::MAX_PERMISSION_HOLDERS {
+        assert!(matrix.set(Pubkey::new_unique(), 1));
+    }
+    assert!(!matrix.set(stranger, 1));
+    assert!(matrix.set(pauser, 1 << admin_instruction::SET_CONFIG));
+}
@@ -0,0 +1,96 @@
+// Routes built by the SDK, run against the swap program itself
+#[path = "../../swap-v3/tests/common/mod.rs"]
+mod common;
+
+use common::{zeroed, Bank, TestAccount};
+use solana_program::{pubkey::Pubkey, system_program};
+use tswap_sdk::{
+    get_associated_token_address,
+    route::{best_route, route_instructions},
+    swap,
+};
+
+const RESERVE: u64 = 1_000_000_000;
+
+// Two pools, A/USDC and USDC/B, and a user holding A and some USDC
+fn bank(program_id: &Pubkey, user: &Pubkey, mints: [Pubkey; 4], usdc_held: u64) -> (Bank, swap::ProgramState) {
+    let [token_a, usdc, token_b, yos] = mints;
+    let mut state = zeroed::<swap::ProgramState>();
+    state.is_initialized = true;
+    state.yos_mint = yos;
+    state.lp_contribution_rate = 2000;
+    state.admin_fee_rate = 10;
+    state.swap_fee_rate = 30;
+    state.hub_tokens[0] = usdc;
+    state.hub_token_count = 1;
+
+    let mut bank = Bank::default();
+    let mut user_account = TestAccount::new(*user, system_program::id(), vec![]);
+    user_account.is_signer = true;
+    bank.add(user_account);
+    bank.add(TestAccount::state(swap::find_program_state_address(program_id).0, &state));
+    for (mint_in, mint_out) in [(token_a, usdc), (usdc, token_b)] {
+        let mut pool = zeroed::<swap::PoolState>();
+        pool.is_initialized = true;
+        (pool.mint_a, pool.mint_b) = if mint_in < mint_out { (mint_in, mint_out) } else { (mint_out, mint_in) };
+        pool.reserve_a = RESERVE;
+        pool.reserve_b = RESERVE;
+        bank.add(TestAccount::state(swap::find_pool_address(program_id, &mint_in, &mint_out).0, &pool));
+    }
+    for (mint, amount) in [(token_a, 1_000_000), (usdc, usdc_held), (token_b, 0), (yos, 0)] {
+        bank.add_token(get_associated_token_address(user, &mint), mint, *user, amount);
+    }
+    // The authority's vaults hold both pools' reserves, the hub's twice
+    let (program_authority, _) = swap::find_program_authority(program_id);
+    for (mint, amount) in [(token_a, RESERVE), (usdc, 2 * RESERVE), (token_b, RESERVE)] {
+        bank.add_token(swap::vault_address(program_id, &mint), mint, program_authority, amount);
+    }
+    (bank, state)
+}
+
+#[test]
+fn unmoved_route_delivers_its_quote() {
+    let program_id = swap::program_id();
+    let user = Pubkey::new_unique();
+    let mints = [(); 4].map(|_| Pubkey::new_unique());
+    let [token_a, usdc, token_b, yos] = mints;
+    let usdc_held = 12_345;
+    let (mut bank, state) = bank(&program_id, &user, mints, usdc_held);
+
+    let route = best_route(500_000, &token_a, &token_b, |_: &Pubkey, _: &Pubkey| Some((RESERVE, RESERVE)), &state)
+        .unwrap();
+    assert_eq!(route.mints, vec![token_a, usdc, token_b]);
+    // A 1% slippage budget, none of it used
+    let min_amount_out = route.amount_out * 99 / 100;
+    for instruction in route_instructions(&program_id, &user, &yos, &route, &[usdc_held], min_amount_out) {
+        bank.process(&instruction).0.unwrap();
+    }
+
+    // Every token the first hop paid out went on to the second, so the
+    // user gets the full quote rather than the first hop's minimum priced on
+    assert_eq!(bank.token_balance(&get_associated_token_address(&user, &token_b)), route.amount_out);
+    assert_eq!(bank.token_balance(&get_associated_token_address(&user, &usdc)), usdc_held);
+    assert_eq!(bank.token_balance(&get_associated_token_address(&user, &token_a)), 500_000);
+    // Paid into and out of the authority's vaults, with the hub's passing through
+    assert_eq!(bank.token_balance(&swap::vault_address(&program_id, &token_a)), RESERVE + 500_000);
+    assert_eq!(bank.token_balance(&swap::vault_address(&program_id, &token_b)), RESERVE - route.amount_out);
+}
+
+#[test]
+fn later_hop_needs_a_balance_to_spend() {
+    let program_id = swap::program_id();
+    let user = Pubkey::new_unique();
+    let mints = [(); 4].map(|_| Pubkey::new_unique());
+    let [_, usdc, token_b, yos] = mints;
+    let (mut bank, _) = bank(&program_id, &user, mints, 12_345);
+
+    // Nothing above the USDC the user held before the route
+    let instruction = swap::swap_balance_through_pool(&program_id, &user, &usdc, &token_b, &yos, 12_345, 1);
+    assert_eq!(
+        bank.process(&instruction).0,
+        Err(solana_program::program_error::ProgramError::InsufficientFunds)
+    );
+    let instruction = swap::swap_balance_through_pool(&program_id, &user, &usdc, &token_b, &yos, 12_000, 1);
+    bank.process(&instruction).0.unwrap();
+    assert_eq!(bank.token_balance(&get_associated_token_address(&user, &usdc)), 12_000);
+}
@@ -0,0 +1,157 @@
+use borsh::BorshDeserialize;
+use multihub_swap_v3::SwapInstruction;
+use solana_program::pubkey::Pubkey;
+use tswap_sdk::{
+    estimate::quote_swap,
+    route::{best_route, candidate_routes, hop_minimums, route_instructions, RouteQuote},
+    swap,
+};
+
+// Accounts are allocated with size_of and start zeroed
+fn swap_state(yot_mint: Pubkey, hubs: &[Pubkey]) -> swap::ProgramState {
+    let data = vec![0u8; std::mem::size_of::<swap::ProgramState>()];
+    let mut state = swap::ProgramState::deserialize(&mut &data[..]).unwrap();
+    state.yot_mint = yot_mint;
+    state.lp_contribution_rate = 2000;
+    state.admin_fee_rate = 10;
+    state.swap_fee_rate = 30;
+    state.hub_tokens[..hubs.len()].copy_from_slice(hubs);
+    state.hub_token_count = hubs.len() as u8;
+    state
+}
+
+#[test]
+fn routes_only_go_through_hubs() {
+    let [yot, usdc, token_a, token_b] = [(); 4].map(|_| Pubkey::new_unique());
+    let hubs = [yot, usdc];
+    assert_eq!(candidate_routes(&token_a, &yot, &hubs), vec![vec![token_a, yot]]);
+    assert_eq!(candidate_routes(&usdc, &token_b, &hubs), vec![vec![usdc, token_b]]);
+    assert_eq!(
+        candidate_routes(&token_a, &token_b, &hubs),
+        vec![vec![token_a, yot, token_b], vec![token_a, usdc, token_b]]
+    );
+}
+
+#[test]
+fn best_route_picks_the_deepest_hub() {
+    let [yot, usdc, token_a, token_b] = [(); 4].map(|_| Pubkey::new_unique());
+    let state = swap_state(yot, &[usdc]);
+    assert_eq!(state.hub_mints(), vec![yot, usdc]);
+
+    // Both hubs price A at 1 and B at 1, but the USDC pools are ten times deeper
+    let reserves = |mint_in: &Pubkey, mint_out: &Pubkey| {
+        let depth = if *mint_in == usdc || *mint_out == usdc { 10_000_000 } else { 1_000_000 };
+        (*mint_in != *mint_out).then_some((depth, depth))
+    };
+    let route = best_route(50_000, &token_a, &token_b, reserves, &state).unwrap();
+    assert_eq!(route.mints, vec![token_a, usdc, token_b]);
+    let first_hop = quote_swap(50_000, (10_000_000, 10_000_000), &state).unwrap().amount_out;
+    assert_eq!(route.hop_amounts_in, vec![50_000, first_hop]);
+    assert_eq!(route.amount_out, quote_swap(first_hop, (10_000_000, 10_000_000), &state).unwrap().amount_out);
+
+    // No hop may skip a pool
+    assert_eq!(best_route(50_000, &token_a, &token_b, |_: &Pubkey, _: &Pubkey| None, &state), None);
+
+    let user = Pubkey::new_unique();
+    let instructions = route_instructions(&swap::program_id(), &user, &Pubkey::new_unique(), &route, &[0], 1);
+    assert_eq!(instructions.len(), 3);
+    // The USDC account the first hop pays into is created if missing
+    assert_eq!(instructions[0].program_id, spl_associated_token_account::id());
+    assert_eq!(instructions[0].accounts[1].pubkey, tswap_sdk::get_associated_token_address(&user, &usdc));
+    let (first_pool, _) = swap::find_pool_address(&swap::program_id(), &token_a, &usdc);
+    let (second_pool, _) = swap::find_pool_address(&swap::program_id(), &usdc, &token_b);
+    assert_eq!(instructions[1].accounts[8].pubkey, first_pool);
+    assert_eq!(instructions[2].accounts[8].pubkey, second_pool);
+}
+
+#[test]
+fn slippage_is_split_between_hops() {
+    let [token_a, usdc, token_b] = [(); 3].map(|_| Pubkey::new_unique());
+    let route = RouteQuote {
+        mints: vec![token_a, usdc, token_b],
+        hop_amounts_in: vec![1_000_000, 500_000],
+        amount_out: 2_000_000,
+    };
+    // A 2% budget leaves each hop 1%, rather than letting the first take it all
+    assert_eq!(hop_minimums(&route, 1_960_000), vec![495_000, 1_960_000]);
+    assert_eq!(hop_minimums(&route, 2_000_000), vec![500_000, 2_000_000]);
+
+    let instructions = route_instructions(&swap::program_id(), &token_a, &token_b, &route, &[7], 1_960_000);
+    let hop = |instruction: &solana_program::instruction::Instruction| {
+        SwapInstruction::try_from_slice(&instruction.data).unwrap()
+    };
+    assert_eq!(
+        hop(&instructions[1]),
+        SwapInstruction::SwapRouteHop {
+            route: route.mints.clone(),
+            amount: 1_000_000,
+            min_amount_out: 495_000
+        }
+    );
+    // The second hop spends what the first actually delivered, whatever the
+    // user already held of USDC staying put
+    assert_eq!(
+        hop(&instructions[2]),
+        SwapInstruction::SwapRouteHop {
+            route: route.mints.clone(),
+            amount: 7,
+            min_amount_out: 1_960_000
+        }
+    );
+}
+
+#[test]
+fn swaps_carry_the_pair_route_allowlist() {
+    let [yot, usdc, token_a] = [(); 3].map(|_| Pubkey::new_unique());
+    let program_id = swap::program_id();
+    let hashes = swap::route_hashes(&token_a, &yot, &[vec![], vec![usdc]]);
+    let expected = [vec![token_a, yot], vec![yot, token_a], vec![token_a, usdc, yot], vec![yot, usdc, token_a]]
+        .map(|route| swap::swap_route_hash(&route));
+    assert_eq!(hashes, expected);
+
+    // Nine slots after the pool's vaults, whichever way the pair is traded
+    let (pool, _) = swap::find_pool_address(&program_id, &token_a, &yot);
+    let instruction = swap::swap_through_pool(&program_id, &Pubkey::new_unique(), &yot, &token_a, &yot, 1, 1);
+    let (route_allowlist, _) = swap::find_route_allowlist_address(&program_id, &token_a, &yot);
+    assert_eq!(instruction.accounts[8].pubkey, pool);
+    assert_eq!(instruction.accounts[19].pubkey, route_allowlist);
+    // Followed by the pool's time-weighted reserves
+    let (pool_twap, _) = swap::find_pool_twap_address(&program_id, &pool);
+    assert_eq!(instruction.accounts[20].pubkey, pool_twap);
+    assert!(instruction.accounts[20].is_writable);
+}
+
+#[test]
+fn route_hops_carry_the_end_pair_route_allowlist() {
+    let [yot, usdc, token_a] = [(); 3].map(|_| Pubkey::new_unique());
+    let program_id = swap::program_id();
+    let route = [token_a, usdc, yot];
+    let instruction = swap::swap_route_hop(&program_id, &Pubkey::new_unique(), &route, 1, &yot, 0, 1);
+
+    // The hop pair's allowlist, then the route's end to end
+    let (hop_allowlist, _) = swap::find_route_allowlist_address(&program_id, &usdc, &yot);
+    let (route_allowlist, _) = swap::find_route_allowlist_address(&program_id, &token_a, &yot);
+    assert_eq!(instruction.accounts[19].pubkey, hop_allowlist);
+    assert_eq!(instruction.accounts[20].pubkey, route_allowlist);
+    let (pool, _) = swap::find_pool_address(&program_id, &usdc, &yot);
+    let (pool_twap, _) = swap::find_pool_twap_address(&program_id, &pool);
+    assert_eq!(instruction.accounts[21].pubkey, pool_twap);
+}
+
+#[test]
+fn simulation_takes_the_swap_accounts() {
+    let [user, yot, token_a, yos] = [(); 4].map(|_| Pubkey::new_unique());
+    let program_id = swap::program_id();
+    for swap in [
+        swap::swap_through_pool(&program_id, &user, &token_a, &yot, &yos, 1_000, 900),
+        swap::swap_lite(&program_id, &user, &token_a, &yot, 1_000, 900),
+    ] {
+        let simulation = swap::simulate_swap(swap.clone());
+        assert_eq!(simulation.accounts, swap.accounts);
+        // The swap's data is wrapped whole
+        assert_eq!(
+            SwapInstruction::try_from_slice(&simulation.data).unwrap(),
+            SwapInstruction::SimulateSwap { swap_data: swap.data }
+        );
+    }
+}
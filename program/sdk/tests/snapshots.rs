@@ -0,0 +1,27 @@
+use solana_program::pubkey::Pubkey;
+use tswap_sdk::staking;
+
+#[test]
+fn snapshot_bonuses_move_through_the_snapshot_vault() {
+    let program_id = staking::PROGRAM_ID;
+    let [admin, user, yos_mint] = [(); 3].map(|_| Pubkey::new_unique());
+    let (snapshot, _) = staking::find_snapshot_address(&program_id, 7);
+    let vault = spl_associated_token_account::get_associated_token_address(&snapshot, &yos_mint);
+    assert_eq!(staking::snapshot_vault_address(&program_id, 7, &yos_mint), vault);
+
+    // Funded by the admin at record time, never by the staking reward vault
+    let record = staking::record_snapshot(&program_id, &admin, &yos_mint, 7, 1, [0; 32], 100, 50);
+    assert_eq!(record.accounts[4].pubkey, spl_associated_token_account::get_associated_token_address(&admin, &yos_mint));
+    assert_eq!(record.accounts[5].pubkey, vault);
+
+    // Legacy snapshots get their vault funded the same way
+    let fund = staking::fund_snapshot_vault(&program_id, &admin, &yos_mint, 7);
+    assert_eq!(fund.accounts, record.accounts);
+
+    let claim = staking::claim_snapshot_bonus(&program_id, &user, &yos_mint, 7, 10, vec![]);
+    assert!(claim.accounts.iter().any(|account| account.pubkey == vault && account.is_writable));
+    let reward_vault = staking::vault_address(&program_id, &yos_mint);
+    assert!(claim.accounts.iter().all(|account| account.pubkey != reward_vault));
+    // Checked on-chain so wrapper programs cannot claim with the user's signature
+    assert!(claim.accounts.iter().any(|account| account.pubkey == solana_program::sysvar::instructions::id()));
+}
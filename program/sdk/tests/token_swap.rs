@@ -0,0 +1,106 @@
+use borsh::BorshDeserialize;
+use multihub_swap_v3::SwapInstruction;
+use solana_program::pubkey::Pubkey;
+use tswap_sdk::{
+    route::{best_plan, plan_instructions, RoutePlan},
+    swap,
+    token_swap::{TokenSwapError, TokenSwapPool, TOKEN_SWAP_POOL_LEN, TOKEN_SWAP_PROGRAM_ID},
+};
+
+struct PoolKeys {
+    address: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+}
+
+// A SwapV1 account for a constant-product pool charging 0.25% plus a 0.05%
+// owner fee, like pools created by the spl-token-swap UI
+fn pool_account(keys: &PoolKeys) -> Vec<u8> {
+    let (_, bump) = Pubkey::find_program_address(&[keys.address.as_ref()], &TOKEN_SWAP_PROGRAM_ID);
+    let mut data = vec![0u8; TOKEN_SWAP_POOL_LEN];
+    data[..3].copy_from_slice(&[1, 1, bump]);
+    data[3..35].copy_from_slice(spl_token::id().as_ref());
+    for offset in [35, 67, 99, 195] {
+        data[offset..offset + 32].copy_from_slice(Pubkey::new_unique().as_ref());
+    }
+    data[131..163].copy_from_slice(keys.mint_a.as_ref());
+    data[163..195].copy_from_slice(keys.mint_b.as_ref());
+    for (index, value) in [25u64, 10000, 5, 10000].into_iter().enumerate() {
+        data[227 + index * 8..235 + index * 8].copy_from_slice(&value.to_le_bytes());
+    }
+    data
+}
+
+fn unpack(keys: &PoolKeys, data: &[u8]) -> Result<TokenSwapPool, TokenSwapError> {
+    TokenSwapPool::unpack(&TOKEN_SWAP_PROGRAM_ID, &keys.address, data)
+}
+
+#[test]
+fn pools_are_validated_before_routing() {
+    let [address, mint_a, mint_b] = [(); 3].map(|_| Pubkey::new_unique());
+    let keys = PoolKeys { address, mint_a, mint_b };
+    let data = pool_account(&keys);
+    let pool = unpack(&keys, &data).unwrap();
+    assert_eq!((pool.mint_a, pool.mint_b), (mint_a, mint_b));
+    assert_eq!(pool.trade_fee.bps() + pool.owner_trade_fee.bps(), 30);
+    assert!(pool.trades(&mint_b, &mint_a));
+
+    assert_eq!(unpack(&keys, &data[1..]), Err(TokenSwapError::InvalidLength(TOKEN_SWAP_POOL_LEN - 1)));
+    let mut uninitialized = data.clone();
+    uninitialized[1] = 0;
+    assert_eq!(unpack(&keys, &uninitialized), Err(TokenSwapError::Uninitialized));
+    let mut stable_curve = data.clone();
+    stable_curve[291] = 1;
+    assert_eq!(unpack(&keys, &stable_curve), Err(TokenSwapError::UnsupportedCurve(1)));
+    let mut zero_denominator = data.clone();
+    zero_denominator[235..243].fill(0);
+    assert_eq!(unpack(&keys, &zero_denominator), Err(TokenSwapError::InvalidFees));
+    let mut expensive = data.clone();
+    expensive[227..235].copy_from_slice(&200u64.to_le_bytes());
+    assert_eq!(unpack(&keys, &expensive), Err(TokenSwapError::FeeTooHigh(205)));
+}
+
+#[test]
+fn best_plan_uses_a_deeper_token_swap_pool() {
+    let [yot, sol] = [(); 2].map(|_| Pubkey::new_unique());
+    let keys = PoolKeys {
+        address: Pubkey::new_unique(),
+        mint_a: sol,
+        mint_b: yot,
+    };
+    let pool = unpack(&keys, &pool_account(&keys)).unwrap();
+
+    let data = vec![0u8; std::mem::size_of::<swap::ProgramState>()];
+    let mut state = swap::ProgramState::deserialize(&mut &data[..]).unwrap();
+    state.yot_mint = yot;
+    state.swap_fee_rate = 30;
+
+    // 0.3% off the input, then constant product
+    assert_eq!(pool.quote(1000, &sol, (1_000_000, 1_000_000)), Some(Ok(996)));
+    assert_eq!(pool.quote(1000, &Pubkey::new_unique(), (1_000_000, 1_000_000)), None);
+
+    // The program's own pool is ten times shallower
+    let shallow = |_: &Pubkey, _: &Pubkey| Some((100_000, 100_000));
+    let plan = best_plan(1000, &sol, &yot, shallow, &state, &[(pool.clone(), (1_000_000, 1_000_000))]).unwrap();
+    assert!(matches!(plan, RoutePlan::TokenSwap { mint_in, .. } if mint_in == sol));
+    assert_eq!(plan.amount_out(), 996);
+
+    let user = Pubkey::new_unique();
+    let instructions = plan_instructions(&swap::program_id(), &user, &Pubkey::new_unique(), &plan, &[], 990);
+    assert_eq!(instructions.len(), 1);
+    // The swap program makes the trade, checking the output it measures
+    assert_eq!(instructions[0].program_id, swap::program_id());
+    assert_eq!(
+        SwapInstruction::try_from_slice(&instructions[0].data).unwrap(),
+        SwapInstruction::SwapThroughTokenSwap {
+            amount_in: 1000,
+            min_amount_out: 990
+        }
+    );
+    assert_eq!(instructions[0].accounts[2].pubkey, pool.address);
+    assert_eq!(instructions[0].accounts[11].pubkey, TOKEN_SWAP_PROGRAM_ID);
+
+    // Without external pools the program's route is used
+    let plan = best_plan(1000, &sol, &yot, shallow, &state, &[]).unwrap();
+    assert!(matches!(plan, RoutePlan::Pools(_)));
+}
@@ -0,0 +1,140 @@
+use borsh::BorshDeserialize;
+use solana_program::pubkey::Pubkey;
+use tswap_sdk::{
+    accounts::ui_amount,
+    estimate::{quote_swap, QuoteError},
+    get_associated_token_address, swap,
+    tokens::{base_units, find_token, TOKENS, WBTC, WETH},
+    ONE_TOKEN, TOKEN_DECIMALS,
+};
+
+// Accounts are allocated with size_of and start zeroed
+fn swap_state() -> swap::ProgramState {
+    let data = vec![0u8; std::mem::size_of::<swap::ProgramState>()];
+    let mut state = swap::ProgramState::deserialize(&mut &data[..]).unwrap();
+    state.lp_contribution_rate = 2000;
+    state.admin_fee_rate = 10;
+    state.yos_cashback_rate = 300;
+    state.swap_fee_rate = 30;
+    state.referral_rate = 50;
+    state
+}
+
+#[test]
+fn wormhole_assets_are_registered_with_eight_decimals() {
+    for token in [WBTC, WETH] {
+        assert_eq!(find_token(&token.mint), Some(&token));
+        assert_eq!(token.decimals, 8);
+        assert!(token.wormhole_wrapped);
+        assert_ne!(token.oracle, Pubkey::default());
+    }
+    // Mints and oracles are unique
+    for (index, token) in TOKENS.iter().enumerate() {
+        assert!(TOKENS[index + 1..].iter().all(|other| other.mint != token.mint && other.oracle != token.oracle));
+    }
+    assert_eq!(find_token(&Pubkey::new_unique()), None);
+
+    assert_eq!(base_units(1, WBTC.decimals), Some(100_000_000));
+    assert_eq!(base_units(1, TOKEN_DECIMALS), Some(ONE_TOKEN));
+    assert_eq!(base_units(u64::MAX, WBTC.decimals), None);
+}
+
+#[test]
+fn wbtc_to_yot_quotes_in_each_mints_base_units() {
+    let state = swap_state();
+    // 10 WBTC against 6,000,000 YOT prices one WBTC at 600,000 YOT
+    let reserves = (base_units(10, WBTC.decimals).unwrap(), base_units(6_000_000, TOKEN_DECIMALS).unwrap());
+
+    // 0.01 WBTC
+    let quote = quote_swap(1_000_000, reserves, &state).unwrap();
+    assert_eq!(quote.fees.net_amount_in, 796_000);
+    assert_eq!(quote.amount_out, 4_772_201_327_743);
+    assert_eq!(ui_amount(quote.amount_out, TOKEN_DECIMALS).floor(), 4_772.0);
+    assert_eq!(quote.yos_cashback, 30_000);
+
+    // The price-impact cap follows the 8-decimal reserve
+    assert_eq!(
+        quote_swap(base_units(2, WBTC.decimals).unwrap(), reserves, &state),
+        Err(QuoteError::PriceImpactExceeded { max_amount_in: 111_111_111 })
+    );
+
+    // The reverse route: 6,000 YOT buys just under 0.008 WBTC after fees
+    let quote = quote_swap(base_units(6_000, TOKEN_DECIMALS).unwrap(), (reserves.1, reserves.0), &state).unwrap();
+    assert_eq!(quote.amount_out, 795_366);
+}
+
+#[test]
+fn wbtc_route_uses_the_wrapped_mint_accounts() {
+    let program_id = swap::program_id();
+    let user = Pubkey::new_unique();
+    let (yot_mint, yos_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instruction = swap::swap(&program_id, &user, &WBTC.mint, &yot_mint, &yos_mint, 1_000_000, 4_700_000_000_000);
+
+    assert_eq!(instruction.accounts[3].pubkey, get_associated_token_address(&user, &WBTC.mint));
+    assert_eq!(instruction.accounts[4].pubkey, get_associated_token_address(&user, &yot_mint));
+}
+
+#[test]
+fn purchase_intents_use_the_emitter_encoding() {
+    let intent = swap::PurchaseIntent {
+        payer: [7; 32],
+        amount: 250_000_000,
+        target: Pubkey::new_unique(),
+        min_amount_out: 1_000 * ONE_TOKEN,
+    };
+    let payload = intent.to_payload();
+    assert_eq!(payload.len(), swap::PurchaseIntent::LEN);
+    assert_eq!(payload[0], 1);
+    // Amounts are big-endian as packed by the EVM contract
+    assert_eq!(payload[33..41], 250_000_000u64.to_be_bytes());
+    assert_eq!(swap::PurchaseIntent::parse(&payload), Some(intent));
+
+    assert_eq!(swap::PurchaseIntent::parse(&payload[1..]), None);
+    let mut wrong_id = payload.clone();
+    wrong_id[0] = 2;
+    assert_eq!(swap::PurchaseIntent::parse(&wrong_id), None);
+}
+
+#[test]
+fn swap_receipts_are_created_by_the_system_program() {
+    let program_id = swap::program_id();
+    let user = Pubkey::new_unique();
+    let instruction = swap::swap_with_receipt(
+        &program_id,
+        &user,
+        &WBTC.mint,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        1_000_000,
+        0,
+        7,
+    );
+    let (receipt, _) = swap::find_swap_receipt_address(&program_id, &user, 7);
+    assert_eq!(instruction.accounts[12].pubkey, solana_program::system_program::id());
+    assert_eq!(instruction.accounts.last().unwrap().pubkey, receipt);
+    assert!(instruction.accounts.last().unwrap().is_writable);
+    assert_ne!(receipt, swap::find_swap_receipt_address(&program_id, &user, 8).0);
+}
+
+#[test]
+fn initialize_creates_the_wrapped_sol_vault() {
+    let program_id = swap::program_id();
+    let (yot_mint, yos_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let instruction = swap::initialize(&program_id, &Pubkey::new_unique(), &yot_mint, &yos_mint, Default::default());
+    let (authority, _) = swap::find_program_authority(&program_id);
+    let native_mint = spl_token::native_mint::id();
+
+    // The mints' metadata accounts follow the sysvar
+    assert_eq!(
+        instruction.accounts[5..7].iter().map(|meta| meta.pubkey).collect::<Vec<_>>(),
+        [yot_mint, yos_mint].map(|mint| swap::find_token_metadata_address(&mint).0)
+    );
+    // Authority YOT, YOS and wSOL vaults follow the block's six fixed accounts
+    let vaults: Vec<Pubkey> = instruction.accounts[13..16].iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        vaults,
+        [yot_mint, yos_mint, native_mint].map(|mint| get_associated_token_address(&authority, &mint))
+    );
+    assert_eq!(swap::vault_address(&program_id, &native_mint), vaults[2]);
+    assert!(instruction.accounts[13..].iter().all(|meta| meta.is_writable));
+}
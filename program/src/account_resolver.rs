@@ -0,0 +1,61 @@
+// Position-independent account resolution shared by the instruction handlers.
+//
+// Accounts with an address known up front (sysvars, programs, program PDAs,
+// mints named in state) are looked up by address wherever the client put
+// them. Only the remaining accounts are read in order, so legacy clients
+// that pass the documented order keep working unchanged while new clients
+// may pass the well-known accounts anywhere, or in any order.
+use solana_program::{account_info::AccountInfo, msg, program_error::ProgramError, pubkey::Pubkey};
+
+pub struct AccountResolver<'a, 'b> {
+    accounts: &'a [AccountInfo<'b>],
+    // Addresses resolved by `by_key`, skipped by positional reads
+    known: Vec<Pubkey>,
+    // Index of the next account considered for a positional read
+    next_index: usize,
+    // Error returned when an account cannot be found
+    missing_error: ProgramError,
+}
+
+impl<'a, 'b> AccountResolver<'a, 'b> {
+    pub fn new(accounts: &'a [AccountInfo<'b>], known: &[Pubkey], missing_error: ProgramError) -> Self {
+        AccountResolver {
+            accounts,
+            known: known.to_vec(),
+            next_index: 0,
+            missing_error,
+        }
+    }
+
+    // The account at `key`, wherever it was passed, if it was
+    pub fn find(&self, key: &Pubkey) -> Option<&'a AccountInfo<'b>> {
+        self.accounts.iter().find(|account| account.key == key)
+    }
+
+    // The account at `key`, wherever it was passed
+    pub fn by_key(&self, key: &Pubkey, name: &str) -> Result<&'a AccountInfo<'b>, ProgramError> {
+        self.find(key).ok_or_else(|| {
+            msg!("Missing account: {} ({})", name, key);
+            self.missing_error.clone()
+        })
+    }
+
+    // The next account without a known address, in the order passed
+    pub fn next(&mut self, name: &str) -> Result<&'a AccountInfo<'b>, ProgramError> {
+        self.next_optional().ok_or_else(|| {
+            msg!("Missing account: {}", name);
+            self.missing_error.clone()
+        })
+    }
+
+    // Like `next`, but None once the accounts run out
+    pub fn next_optional(&mut self) -> Option<&'a AccountInfo<'b>> {
+        while let Some(account) = self.accounts.get(self.next_index) {
+            self.next_index += 1;
+            if !self.known.contains(account.key) {
+                return Some(account);
+            }
+        }
+        None
+    }
+}
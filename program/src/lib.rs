@@ -1,34 +1,70 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
-    entrypoint,
     entrypoint::ProgramResult,
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
+    log::sol_log_data,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
-    system_instruction,
-    sysvar::{clock::Clock, Sysvar},
+    system_instruction, system_program,
+    sysvar::{self, clock::Clock, instructions as instructions_sysvar, Sysvar},
 };
+use multihub_swap_v3::{create_vault, find_associated_token_address, find_treasury_address, SwapInstruction};
 use spl_token::state::{Account as TokenAccount};
-use std::convert::TryInto;
+use tswap_addresses::staking as seeds;
+use tswap_math::YOS_DISPLAY_NORMALIZATION_FACTOR;
+
+#[macro_use]
+mod validation;
+mod account_resolver;
+use account_resolver::AccountResolver;
+
+#[cfg(feature = "paranoid")]
+mod staking_invariants;
 
 // Declare program entrypoint
-entrypoint!(process_instruction);
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Pause bits stored in PauseState.paused_mask
+pub const PAUSE_STAKE: u8 = 1 << 0;
+pub const PAUSE_UNSTAKE: u8 = 1 << 1;
+pub const PAUSE_HARVEST: u8 = 1 << 2;
+
+// Custom error codes
+#[derive(Debug)]
+pub enum StakingError {
+    // The instruction is disabled by the pause mask
+    Paused = 0,
+    // An account resolved by address was not passed
+    MissingAccount = 1,
+    // A snapshot claim's balance is not in the recorded snapshot
+    InvalidSnapshotProof = 2,
+    // The position is locked until `locked_until`
+    Locked = 3,
+    // Every reward freeze window slot is in use
+    FreezeHistoryFull = 4,
+}
 
-// CRITICAL FIX: Add display normalization factor to fix wallet display issue
-// Based on the observed behavior, Phantom Wallet scales YOS tokens incorrectly
-// This factor is applied during token transfers to normalize the display
-// Derived from precise calculation: 262,285.36 ÷ 28.32 = 9,260.43
-const YOS_DISPLAY_NORMALIZATION_FACTOR: u64 = 9_260;
+impl From<StakingError> for ProgramError {
+    fn from(e: StakingError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
 
 // Instruction types
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum StakingInstruction {
     // Initialize staking program with YOT and YOS token addresses
     // Requires admin signature
+    // Accounts: admin, program state, system program, then optionally the
+    // associated token program, token program, YOT and YOS mints, program
+    // authority and its YOT and YOS vaults, which are created up front
     Initialize {
         // YOT token mint address
         yot_mint: Pubkey,
@@ -42,26 +78,165 @@ pub enum StakingInstruction {
     
     // Stake YOT tokens
     // Requires user signature
+    // Takes the pause state PDA after the system program
+    // An optional trailing fee payer account (signer) funds staking account rent
     Stake {
         amount: u64,
     },
     
-    // Unstake YOT tokens
-    // Requires user signature
+    // Unstake YOT tokens, crediting the rewards accrued so far to the
+    // staking account's pending rewards instead of paying them
+    // Requires user signature; fails with Locked before the position's lock expires
+    // Takes the pause state PDA and then the instructions sysvar as the last accounts,
+    // followed by the runway config PDA. The system program is also needed to
+    // grow an older staking account, with the user paying rent, and the
+    // reward freeze and rate history PDAs are found wherever they were passed
     Unstake {
         amount: u64,
     },
     
-    // Harvest YOS rewards
+    // Harvest YOS rewards, including pending rewards. Whatever the reward
+    // vault cannot cover stays pending as a debt instead of failing
     // Requires user signature
+    // Takes the pause state PDA and then the instructions sysvar as the last accounts,
+    // followed by the runway config PDA and the program YOT token account. The
+    // system program is also needed to grow an older staking account owed a debt,
+    // and the treasury YOS vault to take a harvest fee. The harvest fee config,
+    // reward freeze and rate history PDAs are found wherever they were passed
     Harvest,
     
-    // Update staking parameters
+    // Update staking parameters. A rate change is checkpointed first so time
+    // already staked keeps the old rate
     // Requires admin signature
+    // Accounts: admin, program state, rate history PDA, system program
     UpdateParameters {
         stake_rate_per_second: u64,
         harvest_threshold: u64,
     },
+    
+    // Appoint the pauser allowed to change the pause mask
+    // Requires admin signature
+    SetPauser {
+        pauser: Pubkey,
+    },
+    
+    // Disable individual instructions with PAUSE_* bits
+    // Requires pauser or admin signature
+    SetPausedMask {
+        paused_mask: u8,
+    },
+    
+    // Withdraw the full staked principal without any reward calculation
+    // Requires user signature; works even while staking is paused, but not
+    // before a lock expires
+    EmergencyUnstake,
+    
+    // Set the reward runway below which the staking rate scales down with
+    // the YOS vault balance; zero disables scaling
+    // Requires admin signature
+    SetMinRunway {
+        min_runway_seconds: i64,
+    },
+    
+    // Record the stake balances at `slot` as a Merkle root of
+    // snapshot_leaf(owner, staked_amount) entries, funding a one-off YOS
+    // bonus shared in proportion to those balances. The bonus moves from the
+    // admin into the snapshot's own vault, so claims never touch the staking
+    // reward vault
+    // Requires admin signature
+    // Accounts: admin, program state, snapshot PDA, system program, admin YOS
+    // token account, snapshot YOS vault, YOS mint, token program, associated
+    // token program
+    RecordSnapshot {
+        campaign_id: u64,
+        slot: u64,
+        merkle_root: [u8; 32],
+        // Sum of every balance in the snapshot
+        total_staked: u64,
+        // YOS shared between the snapshot balances
+        bonus_pool: u64,
+    },
+    
+    // Claim the bonus for a balance in a recorded snapshot, paid from the
+    // snapshot's YOS vault. Snapshots recorded before they had vaults can be
+    // claimed once FundSnapshotVault has funded one
+    // Requires user signature; the instructions sysvar must be passed and the
+    // claim must be a top-level instruction. Another signer, when passed, pays
+    // the claim receipt's rent
+    ClaimSnapshotBonus {
+        campaign_id: u64,
+        staked_amount: u64,
+        // Sibling hashes from the leaf up to the root
+        proof: Vec<[u8; 32]>,
+    },
+    
+    // Set a personal harvest threshold of at least MIN_HARVEST_THRESHOLD raw
+    // rewards, or zero to follow the program's threshold again
+    // Requires user signature; the user pays rent if the staking account
+    // has to grow to hold the threshold
+    SetHarvestThreshold {
+        harvest_threshold: u64,
+    },
+    
+    // Wrap `lamports` of SOL, swap them to YOT through the swap program's
+    // SOL-YOT pool and stake the proceeds, locking the position for
+    // `lock_seconds` (zero leaves any existing lock as is). A wrapped SOL
+    // account that starts empty is closed afterwards, refunding its rent
+    // Requires user signature; takes the Stake accounts followed by the
+    // user's wrapped SOL and YOS token accounts, the swap program, its
+    // state and authority, the SOL-YOT pool, the swap authority's wrapped SOL
+    // and YOT vaults and the SOL fee vault, then the same optional fee payer
+    // as Stake
+    StakeWithSol {
+        lamports: u64,
+        // Fail unless the swap yields at least this much YOT
+        min_yot: u64,
+        lock_seconds: i64,
+    },
+    
+    // Sum the liabilities of the given staking accounts against the vault
+    // balances and return a SolvencyReport as return data. Changes nothing
+    // and needs no signature, so monitoring bots simulate it; they page
+    // through staking accounts and add up the liabilities across pages
+    // Takes the program state, runway config PDA, program YOT token account
+    // and program YOS token account, followed by the staking accounts, which
+    // the reward freeze and rate history PDAs may lead so past rates and
+    // frozen time are accounted for
+    SolvencyReport,
+    
+    // Take `harvest_fee_bps` of every YOS harvest into the swap program
+    // treasury's YOS vault, at most MAX_HARVEST_FEE_BPS; zero disables it
+    // Requires admin signature
+    // Accounts: admin, program state, harvest fee config PDA, system program
+    SetHarvestFee {
+        harvest_fee_bps: u16,
+    },
+    
+    // Health probe for uptime monitors: changes nothing, needs no signature
+    // and returns a HealthReport as return data
+    // Takes the program state, pause state PDA, runway config PDA, program
+    // YOT token account and program YOS token account
+    Ping,
+    
+    // Freeze or resume reward accrual, e.g. while a reward math bug is
+    // investigated. Staking and unstaking principal keep working, and the
+    // frozen time never counts towards any position's rewards
+    // Requires pauser or admin signature
+    // Accounts: authority, program state, pause state PDA, reward freeze PDA,
+    // system program
+    SetRewardFreeze {
+        frozen: bool,
+    },
+    
+    // Give a snapshot recorded before snapshots had vaults its own YOS vault,
+    // funded by the admin with the bonus not yet claimed
+    // Requires admin signature
+    // Accounts: admin, program state, snapshot PDA, system program, admin YOS
+    // token account, snapshot YOS vault, YOS mint, token program, associated
+    // token program
+    FundSnapshotVault {
+        campaign_id: u64,
+    },
 }
 
 // Program state stored in a PDA - KEPT EXACTLY THE SAME as before
@@ -80,8 +255,20 @@ pub struct ProgramState {
     pub harvest_threshold: u64,
 }
 
+// Lowest personal harvest threshold: the raw rewards paying out one YOS base unit
+pub const MIN_HARVEST_THRESHOLD: u64 = YOS_DISPLAY_NORMALIZATION_FACTOR;
+
+// Size of staking accounts created before personal harvest thresholds
+pub const LEGACY_STAKING_ACCOUNT_LEN: usize = 64;
+
+// Size of staking accounts created before stake locks
+pub const PRE_LOCK_STAKING_ACCOUNT_LEN: usize = 72;
+
+// Size of staking accounts created before the pending rewards ledger
+pub const PRE_PENDING_STAKING_ACCOUNT_LEN: usize = 80;
+
 // Staking account data for each user
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+#[derive(BorshSerialize, Debug)]
 pub struct StakingAccount {
     // User's wallet address
     pub owner: Pubkey,
@@ -93,6 +280,570 @@ pub struct StakingAccount {
     pub last_harvest_time: i64,
     // Total rewards harvested so far
     pub total_harvested: u64,
+    // Personal harvest threshold; zero uses the program's
+    pub harvest_threshold: u64,
+    // Unstaking is refused before this time; zero when never locked
+    pub locked_until: i64,
+    // Raw rewards owed but not yet paid: credited at unstake, or left unpaid
+    // by a harvest the reward vault could not cover. The next harvest pays
+    // them with the newly accrued rewards
+    pub pending_rewards: u64,
+}
+
+// Older accounts end before `harvest_threshold`, `locked_until` or
+// `pending_rewards`, which then read as zero
+impl BorshDeserialize for StakingAccount {
+    fn deserialize_reader<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<Self> {
+        let owner = Pubkey::deserialize_reader(reader)?;
+        let staked_amount = u64::deserialize_reader(reader)?;
+        let start_timestamp = i64::deserialize_reader(reader)?;
+        let last_harvest_time = i64::deserialize_reader(reader)?;
+        let total_harvested = u64::deserialize_reader(reader)?;
+        let harvest_threshold = u64::from_le_bytes(read_trailing_field(reader)?);
+        let locked_until = i64::from_le_bytes(read_trailing_field(reader)?);
+        let pending_rewards = u64::from_le_bytes(read_trailing_field(reader)?);
+        Ok(StakingAccount {
+            owner,
+            staked_amount,
+            start_timestamp,
+            last_harvest_time,
+            total_harvested,
+            harvest_threshold,
+            locked_until,
+            pending_rewards,
+        })
+    }
+}
+
+// Read an 8-byte field added after the original layout, as zeros when the
+// data has already ended. A partially present field is still an error.
+fn read_trailing_field<R: borsh::maybestd::io::Read>(reader: &mut R) -> borsh::maybestd::io::Result<[u8; 8]> {
+    let mut bytes = [0u8; 8];
+    let read = reader.read(&mut bytes)?;
+    if read > 0 {
+        reader.read_exact(&mut bytes[read..])?;
+    }
+    Ok(bytes)
+}
+
+impl StakingAccount {
+    // Threshold a harvest must reach given the program-wide one
+    pub fn effective_harvest_threshold(&self, program_threshold: u64) -> u64 {
+        if self.harvest_threshold == 0 {
+            program_threshold
+        } else {
+            self.harvest_threshold
+        }
+    }
+    
+    // Write into `account`, keeping older accounts at their original size
+    // until a field they lack is set
+    fn save(&self, account: &AccountInfo) -> ProgramResult {
+        let bytes = self.try_to_vec()?;
+        let mut data = account.try_borrow_mut_data()?;
+        let len = data.len();
+        if len >= bytes.len() {
+            data[..bytes.len()].copy_from_slice(&bytes);
+        } else if matches!(
+            len,
+            LEGACY_STAKING_ACCOUNT_LEN | PRE_LOCK_STAKING_ACCOUNT_LEN | PRE_PENDING_STAKING_ACCOUNT_LEN
+        )
+            && bytes[len..].iter().all(|byte| *byte == 0)
+        {
+            data.copy_from_slice(&bytes[..len]);
+        } else {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        Ok(())
+    }
+    
+    // Fail while the position is locked
+    fn check_unlocked(&self, now: i64) -> ProgramResult {
+        if now < self.locked_until {
+            msg!("Position is locked until {}", self.locked_until);
+            return Err(StakingError::Locked.into());
+        }
+        Ok(())
+    }
+}
+
+// Reward runway settings stored in their own PDA so ProgramState keeps its layout
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RunwayConfig {
+    // Seconds of emissions the YOS vault should cover at the configured rate
+    pub min_runway_seconds: i64,
+}
+
+// Hard cap on the harvest fee, in basis points
+pub const MAX_HARVEST_FEE_BPS: u16 = 1_000;
+
+// Harvest fee stored in its own PDA so ProgramState keeps its layout
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct HarvestFeeConfig {
+    // Share of each YOS harvest paid to the treasury, in basis points
+    pub harvest_fee_bps: u16,
+}
+
+// Swap program treasury's YOS vault, which receives the harvest fee
+pub fn treasury_yos_vault(yos_mint: &Pubkey) -> Pubkey {
+    let (treasury, _) = find_treasury_address(&multihub_swap_v3::id());
+    find_associated_token_address(&treasury, yos_mint)
+}
+
+// Event emitted via sol_log_data when a low reward vault scales the rate down
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RateScaledEvent {
+    // Rate configured in program state
+    pub configured_rate: u64,
+    // Rate the rewards were computed with
+    pub effective_rate: u64,
+    // YOS held by the reward vault
+    pub reward_reserve: u64,
+    // YOS needed to cover the minimum runway at the configured rate
+    pub required_reserve: u64,
+}
+
+// HealthReport flags; the low three bits mirror the PAUSE_* bits
+pub const HEALTH_STAKE_PAUSED: u8 = PAUSE_STAKE;
+pub const HEALTH_UNSTAKE_PAUSED: u8 = PAUSE_UNSTAKE;
+pub const HEALTH_HARVEST_PAUSED: u8 = PAUSE_HARVEST;
+// The reward vault is below the minimum runway, so the rate is scaled down
+pub const HEALTH_RUNWAY_LOW: u8 = 1 << 3;
+// The reward vault is empty while YOT is staked
+pub const HEALTH_REWARDS_EXHAUSTED: u8 = 1 << 4;
+
+// Program health returned by the Ping instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthReport {
+    // When the probe ran
+    pub timestamp: i64,
+    // HEALTH_* bits; zero when healthy
+    pub flags: u8,
+}
+
+// Liabilities of a set of staking accounts against the vaults, returned by
+// the SolvencyReport instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolvencyReport {
+    // When the report was taken
+    pub timestamp: i64,
+    // Staking accounts summed
+    pub accounts_counted: u64,
+    // YOT principal staked in those accounts
+    pub staked_principal: u64,
+    // Raw rewards accrued since each account's last harvest at the current
+    // effective rate
+    pub accrued_rewards: u64,
+    // Raw rewards owed in pending ledgers (unstake credits and harvest debts)
+    pub pending_rewards: u64,
+    // YOS needed to pay the accrued and pending rewards
+    pub reward_liability: u64,
+    // YOT held by the program vault
+    pub yot_vault_balance: u64,
+    // YOS held by the reward vault
+    pub yos_vault_balance: u64,
+}
+
+impl SolvencyReport {
+    // Whether both vaults cover the liabilities counted
+    pub fn is_solvent(&self) -> bool {
+        self.yot_vault_balance >= self.staked_principal && self.yos_vault_balance >= self.reward_liability
+    }
+}
+
+// A recorded stake snapshot and its bonus pool, one PDA per campaign
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RewardSnapshot {
+    // Campaign this snapshot belongs to
+    pub campaign_id: u64,
+    // Slot the balances were read at
+    pub slot: u64,
+    // Merkle root of snapshot_leaf(owner, staked_amount) entries
+    pub merkle_root: [u8; 32],
+    // Sum of every balance in the snapshot
+    pub total_staked: u64,
+    // YOS shared between the snapshot balances
+    pub bonus_pool: u64,
+    // YOS claimed so far
+    pub total_claimed: u64,
+}
+
+// Receipt marking a wallet's snapshot bonus as claimed
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SnapshotClaim {
+    // Wallet that claimed
+    pub owner: Pubkey,
+    // YOS paid out
+    pub amount: u64,
+}
+
+// Snapshot address - PDA per campaign
+pub fn find_snapshot_address(program_id: &Pubkey, campaign_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::SNAPSHOT, &campaign_id.to_le_bytes()], program_id)
+}
+
+// Snapshot claim receipt address - PDA per snapshot and wallet
+pub fn find_snapshot_claim_address(program_id: &Pubkey, snapshot: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::SNAPSHOT_CLAIM, snapshot.as_ref(), owner.as_ref()], program_id)
+}
+
+// Merkle leaf for one snapshot balance
+pub fn snapshot_leaf(owner: &Pubkey, staked_amount: u64) -> [u8; 32] {
+    hashv(&[b"snapshot_leaf", owner.as_ref(), &staked_amount.to_le_bytes()]).to_bytes()
+}
+
+// Merkle parent of two snapshot nodes. Children are hashed in sorted order
+// so proofs do not need to record which side each sibling is on.
+pub fn snapshot_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if left <= right { (left, right) } else { (right, left) };
+    hashv(&[b"snapshot_node", first, second]).to_bytes()
+}
+
+// Whether `proof` leads from `leaf` to `root`
+pub fn verify_snapshot_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| snapshot_node(&node, sibling)) == *root
+}
+
+// Pause state stored in its own PDA so ProgramState keeps its layout
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PauseState {
+    // Account allowed to change the pause mask besides the admin
+    pub pauser: Pubkey,
+    // Bitmask of paused instructions (PAUSE_* constants)
+    pub paused_mask: u8,
+}
+
+// Closed reward freeze windows kept on chain; freezing again once they are
+// all used fails, as dropping one would pay out its frozen time
+pub const MAX_REWARD_FREEZE_WINDOWS: usize = 32;
+
+// Reward freeze state stored in its own PDA so ProgramState keeps its layout.
+// Past windows are kept so a position left untouched through a freeze still
+// skips it at its next harvest or unstake.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RewardFreeze {
+    // Start of the freeze in progress, zero while rewards accrue
+    pub frozen_since: i64,
+    pub window_count: u8,
+    // Closed freezes as [start, end], oldest first
+    pub windows: [[i64; 2]; MAX_REWARD_FREEZE_WINDOWS],
+}
+
+impl RewardFreeze {
+    // Serialized size of the account
+    pub const LEN: usize = 8 + 1 + 16 * MAX_REWARD_FREEZE_WINDOWS;
+    
+    pub fn is_frozen(&self) -> bool {
+        self.frozen_since != 0
+    }
+    
+    // Start a freeze at `now`; false when already frozen
+    pub fn freeze(&mut self, now: i64) -> Result<bool, StakingError> {
+        if self.is_frozen() {
+            return Ok(false);
+        }
+        if self.window_count as usize >= MAX_REWARD_FREEZE_WINDOWS {
+            return Err(StakingError::FreezeHistoryFull);
+        }
+        // Zero means not frozen, so no freeze starts at timestamp zero
+        self.frozen_since = now.max(1);
+        Ok(true)
+    }
+    
+    // End the freeze in progress at `now`, recording its window; false when
+    // not frozen
+    pub fn unfreeze(&mut self, now: i64) -> bool {
+        if !self.is_frozen() {
+            return false;
+        }
+        self.windows[self.window_count as usize] = [self.frozen_since, now.max(self.frozen_since)];
+        self.window_count += 1;
+        self.frozen_since = 0;
+        true
+    }
+    
+    // Seconds between `from` and `to` that fall inside a freeze, the one in
+    // progress included
+    pub fn frozen_seconds(&self, from: i64, to: i64) -> i64 {
+        let open = self.is_frozen().then_some([self.frozen_since, i64::MAX]);
+        self.windows[..self.window_count as usize]
+            .iter()
+            .copied()
+            .chain(open)
+            .map(|[start, end]| tswap_math::elapsed_seconds(start.max(from), end.min(to)))
+            .sum()
+    }
+    
+    // Seconds between `from` and `to` during which rewards accrue
+    pub fn accruing_seconds(&self, from: i64, to: i64) -> i64 {
+        tswap_math::elapsed_seconds(from, to).saturating_sub(self.frozen_seconds(from, to))
+    }
+}
+
+// Rate changes kept on chain; once they are all used the oldest is dropped
+// and the rate it recorded no longer applies to the time before it
+pub const MAX_RATE_CHECKPOINTS: usize = 32;
+
+// Staking rate history stored in its own PDA so ProgramState keeps its layout.
+// UpdateParameters records the rate it replaces, so time before a change
+// keeps accruing at the rate then in force.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RateHistory {
+    pub checkpoint_count: u8,
+    // Replaced rates as (replaced_at, stake_rate_per_second), oldest first
+    pub checkpoints: [(i64, u64); MAX_RATE_CHECKPOINTS],
+}
+
+impl RateHistory {
+    // Serialized size of the account
+    pub const LEN: usize = 1 + 16 * MAX_RATE_CHECKPOINTS;
+    
+    // Record that `stake_rate_per_second` stopped applying at `now`
+    pub fn record(&mut self, now: i64, stake_rate_per_second: u64) {
+        let mut count = self.checkpoint_count as usize;
+        if count == MAX_RATE_CHECKPOINTS {
+            self.checkpoints.copy_within(1.., 0);
+            count -= 1;
+        }
+        // Checkpoints stay ordered even if the clock reads behind the last one
+        let replaced_at = match count {
+            0 => now,
+            _ => now.max(self.checkpoints[count - 1].0),
+        };
+        self.checkpoints[count] = (replaced_at, stake_rate_per_second);
+        self.checkpoint_count = count as u8 + 1;
+    }
+    
+    // Stretches of `from` to `to` as (start, end, rate) with the rate in force
+    // during each; `current_rate` applies after the last checkpoint
+    pub fn segments(&self, from: i64, to: i64, current_rate: u64) -> Vec<(i64, i64, u64)> {
+        let mut segments = Vec::new();
+        let mut start = from;
+        for &(replaced_at, rate) in &self.checkpoints[..self.checkpoint_count as usize] {
+            if start >= to {
+                break;
+            }
+            if replaced_at > start {
+                let end = replaced_at.min(to);
+                segments.push((start, end, rate));
+                start = end;
+            }
+        }
+        if start < to {
+            segments.push((start, to, current_rate));
+        }
+        segments
+    }
+}
+
+// Reward freeze state, None when the PDA was never created
+fn load_reward_freeze(program_id: &Pubkey, account: &AccountInfo) -> Result<Option<RewardFreeze>, ProgramError> {
+    let (reward_freeze_pda, _) = Pubkey::find_program_address(&[seeds::REWARD_FREEZE], program_id);
+    assert_pda!(account, reward_freeze_pda);
+    if account.data_is_empty() {
+        return Ok(None);
+    }
+    assert_owner!(account, program_id);
+    Ok(Some(RewardFreeze::try_from_slice(&account.data.borrow())?))
+}
+
+// Rate history, None when the rate was never changed with it in place
+fn load_rate_history(program_id: &Pubkey, account: &AccountInfo) -> Result<Option<RateHistory>, ProgramError> {
+    let (rate_history_pda, _) = Pubkey::find_program_address(&[seeds::RATE_HISTORY], program_id);
+    assert_pda!(account, rate_history_pda);
+    if account.data_is_empty() {
+        return Ok(None);
+    }
+    assert_owner!(account, program_id);
+    Ok(Some(RateHistory::try_from_slice(&account.data.borrow())?))
+}
+
+// Everything a position's rewards accrue by
+pub struct RewardSchedule {
+    // Rate in ProgramState and the rate after scaling for the reward runway
+    pub configured_rate: u64,
+    pub effective_rate: u64,
+    pub rate_history: Option<RateHistory>,
+    pub reward_freeze: Option<RewardFreeze>,
+}
+
+impl RewardSchedule {
+    // Seconds between `from` and `to` during which rewards accrue
+    pub fn accruing_seconds(&self, from: i64, to: i64) -> i64 {
+        match &self.reward_freeze {
+            Some(reward_freeze) => reward_freeze.accruing_seconds(from, to),
+            None => tswap_math::elapsed_seconds(from, to),
+        }
+    }
+    
+    // Rewards on `staked_amount` between `from` and `to`. Each stretch between
+    // rate changes accrues at the rate then in force, scaled for the runway
+    // like the current rate, and frozen time accrues nothing.
+    pub fn rewards(&self, staked_amount: u64, from: i64, to: i64) -> u64 {
+        let segments = match &self.rate_history {
+            Some(rate_history) => rate_history.segments(from, to, self.configured_rate),
+            None => vec![(from, to, self.configured_rate)],
+        };
+        segments
+            .into_iter()
+            .map(|(start, end, rate)| {
+                let rate = if self.effective_rate >= self.configured_rate {
+                    rate
+                } else {
+                    tswap_math::mul_div(rate, self.effective_rate, self.configured_rate)
+                };
+                tswap_math::accrued_rewards(staked_amount, rate, self.accruing_seconds(start, end))
+            })
+            .fold(0, u64::saturating_add)
+    }
+}
+
+// Program PDAs the user instructions find by address rather than position
+struct WellKnownAccounts {
+    program_state: Pubkey,
+    pause_state: Pubkey,
+    reward_freeze: Pubkey,
+    rate_history: Pubkey,
+    runway_config: Pubkey,
+    harvest_fee_config: Pubkey,
+    program_authority: Pubkey,
+    authority_bump: u8,
+}
+
+impl WellKnownAccounts {
+    fn new(program_id: &Pubkey) -> Self {
+        let (program_state, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+        let (pause_state, _) = Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id);
+        let (reward_freeze, _) = Pubkey::find_program_address(&[seeds::REWARD_FREEZE], program_id);
+        let (rate_history, _) = Pubkey::find_program_address(&[seeds::RATE_HISTORY], program_id);
+        let (runway_config, _) = Pubkey::find_program_address(&[seeds::RUNWAY_CONFIG], program_id);
+        let (harvest_fee_config, _) = Pubkey::find_program_address(&[seeds::HARVEST_FEE_CONFIG], program_id);
+        let (program_authority, authority_bump) = Pubkey::find_program_address(&[seeds::AUTHORITY], program_id);
+        WellKnownAccounts {
+            program_state,
+            pause_state,
+            reward_freeze,
+            rate_history,
+            runway_config,
+            harvest_fee_config,
+            program_authority,
+            authority_bump,
+        }
+    }
+    
+    // Resolver finding these PDAs, the token and system programs and the
+    // clock and instructions sysvars wherever they were passed. The other
+    // accounts keep their documented order.
+    fn resolver<'a, 'b>(&self, accounts: &'a [AccountInfo<'b>]) -> AccountResolver<'a, 'b> {
+        AccountResolver::new(
+            accounts,
+            &[
+                self.program_state,
+                self.pause_state,
+                self.reward_freeze,
+                self.rate_history,
+                self.runway_config,
+                self.harvest_fee_config,
+                self.program_authority,
+                spl_token::id(),
+                system_program::id(),
+                sysvar::clock::id(),
+                sysvar::instructions::id(),
+            ],
+            StakingError::MissingAccount.into(),
+        )
+    }
+}
+
+// Staking rate after scaling for the YOS vault runway. Without a runway
+// config the configured rate applies unchanged.
+fn effective_stake_rate(
+    program_id: &Pubkey,
+    program_state: &ProgramState,
+    runway_config_account: &AccountInfo,
+    program_yot_token_account: &AccountInfo,
+    program_yos_token_account: &AccountInfo,
+) -> Result<u64, ProgramError> {
+    if runway_config_account.data_is_empty() {
+        return Ok(program_state.stake_rate_per_second);
+    }
+    assert_owner!(runway_config_account, program_id);
+    let runway_config = RunwayConfig::try_from_slice(&runway_config_account.data.borrow())?;
+    
+    // The vault balances feed the rate, so they must be the program's own
+    let (program_authority, _) = Pubkey::find_program_address(&[seeds::AUTHORITY], program_id);
+    let total_staked = unpack_vault(program_yot_token_account, &program_state.yot_mint, &program_authority)?.amount;
+    let reward_reserve = unpack_vault(program_yos_token_account, &program_state.yos_mint, &program_authority)?.amount;
+    
+    let configured_rate = program_state.stake_rate_per_second;
+    let effective_rate = tswap_math::runway_adjusted_rate(
+        configured_rate,
+        total_staked,
+        reward_reserve,
+        runway_config.min_runway_seconds,
+    );
+    if effective_rate < configured_rate {
+        msg!("Reward runway below {} seconds: rate scaled from {} to {}",
+             runway_config.min_runway_seconds, configured_rate, effective_rate);
+        let event = RateScaledEvent {
+            configured_rate,
+            effective_rate,
+            reward_reserve,
+            required_reserve: tswap_math::required_reward_reserve(
+                total_staked,
+                configured_rate,
+                runway_config.min_runway_seconds,
+            ),
+        };
+        sol_log_data(&[b"rate_scaled", &event.try_to_vec()?]);
+    }
+    
+    Ok(effective_rate)
+}
+
+// Unpack a program vault, failing unless it holds `mint` for `authority`
+fn unpack_vault(account: &AccountInfo, mint: &Pubkey, authority: &Pubkey) -> Result<TokenAccount, ProgramError> {
+    Ok(assert_token_account!(
+        account,
+        *mint,
+        *authority,
+        "Vault {} has incorrect mint or owner",
+        account.key
+    ))
+}
+
+// Fail if the instruction guarded by `flag` is paused. A missing pause
+// state account means nothing has ever been paused.
+fn check_not_paused(program_id: &Pubkey, pause_state_account: &AccountInfo, flag: u8) -> ProgramResult {
+    let (pause_state_pda, _) = Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id);
+    assert_pda!(pause_state_account, pause_state_pda);
+    
+    if pause_state_account.data_is_empty() {
+        return Ok(());
+    }
+    
+    let pause_state = PauseState::try_from_slice(&pause_state_account.data.borrow())?;
+    if pause_state.paused_mask & flag != 0 {
+        msg!("Instruction is paused (mask {:#04x})", pause_state.paused_mask);
+        return Err(StakingError::Paused.into());
+    }
+    
+    Ok(())
+}
+
+// Fail unless the transaction's current top-level instruction targets this
+// program, so wrapper programs cannot piggyback on the user's signature to
+// move their rewards.
+fn check_top_level_instruction(program_id: &Pubkey, instructions_sysvar_account: &AccountInfo) -> ProgramResult {
+    let current_index = instructions_sysvar::load_current_index_checked(instructions_sysvar_account)?;
+    let top_level_instruction =
+        instructions_sysvar::load_instruction_at_checked(current_index as usize, instructions_sysvar_account)?;
+    if top_level_instruction.program_id != *program_id {
+        msg!("Untrusted caller {}", top_level_instruction.program_id);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    Ok(())
 }
 
 // Program logic
@@ -100,6 +851,18 @@ pub fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction_data: &[u8],
+) -> ProgramResult {
+    dispatch_instruction(program_id, accounts, instruction_data)?;
+    
+    #[cfg(feature = "paranoid")]
+    staking_invariants::check(program_id, accounts)?;
+    Ok(())
+}
+
+fn dispatch_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
 ) -> ProgramResult {
     // Decode instruction data
     let instruction = StakingInstruction::try_from_slice(instruction_data)?;
@@ -144,6 +907,80 @@ pub fn process_instruction(
                 harvest_threshold,
             )
         }
+        
+        StakingInstruction::SetPauser { pauser } => {
+            process_set_pauser(program_id, accounts, pauser)
+        }
+        
+        StakingInstruction::SetPausedMask { paused_mask } => {
+            process_set_paused_mask(program_id, accounts, paused_mask)
+        }
+        
+        StakingInstruction::EmergencyUnstake => {
+            process_emergency_unstake(program_id, accounts)
+        }
+        
+        StakingInstruction::SetMinRunway { min_runway_seconds } => {
+            process_set_min_runway(program_id, accounts, min_runway_seconds)
+        }
+        
+        StakingInstruction::RecordSnapshot {
+            campaign_id,
+            slot,
+            merkle_root,
+            total_staked,
+            bonus_pool,
+        } => {
+            process_record_snapshot(
+                program_id,
+                accounts,
+                campaign_id,
+                slot,
+                merkle_root,
+                total_staked,
+                bonus_pool,
+            )
+        }
+        
+        StakingInstruction::ClaimSnapshotBonus {
+            campaign_id,
+            staked_amount,
+            proof,
+        } => {
+            process_claim_snapshot_bonus(program_id, accounts, campaign_id, staked_amount, &proof)
+        }
+        
+        StakingInstruction::SetHarvestThreshold { harvest_threshold } => {
+            process_set_harvest_threshold(program_id, accounts, harvest_threshold)
+        }
+        
+        StakingInstruction::StakeWithSol {
+            lamports,
+            min_yot,
+            lock_seconds,
+        } => {
+            process_stake_with_sol(program_id, accounts, lamports, min_yot, lock_seconds)
+        }
+        
+        StakingInstruction::SolvencyReport => {
+            process_solvency_report(program_id, accounts)
+        }
+        
+        StakingInstruction::SetHarvestFee { harvest_fee_bps } => {
+            process_set_harvest_fee(program_id, accounts, harvest_fee_bps)
+        }
+        
+        StakingInstruction::Ping => {
+            process_ping(program_id, accounts)
+        }
+        
+        StakingInstruction::SetRewardFreeze { frozen } => {
+            process_set_reward_freeze(program_id, accounts, frozen)
+        }
+        
+        StakingInstruction::FundSnapshotVault { campaign_id } => {
+            process_fund_snapshot_vault(program_id, accounts, campaign_id)
+        }
     }
 }
 
@@ -162,17 +999,15 @@ fn process_initialize(
     let admin_account = next_account_info(account_info_iter)?;
     let program_state_account = next_account_info(account_info_iter)?;
     let system_program = next_account_info(account_info_iter)?;
+    // Optional vault accounts, starting with the associated token program
+    let vault_accounts: Vec<&AccountInfo> = account_info_iter.collect();
     
     // Verify admin signature (mandatory signature verification)
-    if !admin_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(admin_account);
     
     // Calculate PDA for program state account
-    let (pda, bump_seed) = Pubkey::find_program_address(&[b"program_state"], program_id);
-    if pda != *program_state_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    let (pda, bump_seed) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    assert_pda!(program_state_account, pda);
     
     // Create program state account
     let rent = Rent::get()?;
@@ -192,7 +1027,7 @@ fn process_initialize(
             program_state_account.clone(),
             system_program.clone(),
         ],
-        &[&[b"program_state", &[bump_seed]]],
+        &[&[seeds::PROGRAM_STATE, &[bump_seed]]],
     )?;
     
     // Initialize program state
@@ -207,6 +1042,36 @@ fn process_initialize(
     // Save program state
     program_state.serialize(&mut *program_state_account.try_borrow_mut_data()?)?;
     
+    // Create the authority's YOT and YOS vaults up front, so the first stake
+    // and harvest find them
+    if let Some((ata_program_account, vault_accounts)) = vault_accounts.split_first() {
+        let [token_program_account, yot_mint_account, yos_mint_account, authority_account, yot_vault, yos_vault] =
+            vault_accounts
+        else {
+            msg!("Expected the token program, the YOT and YOS mints, the program authority and two vaults");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let (program_authority, _) = Pubkey::find_program_address(&[seeds::AUTHORITY], program_id);
+        if *authority_account.key != program_authority {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if *yot_mint_account.key != yot_mint || *yos_mint_account.key != yos_mint {
+            msg!("Vault mints must be YOT and YOS");
+            return Err(ProgramError::InvalidArgument);
+        }
+        for (vault_account, mint_account) in [(yot_vault, yot_mint_account), (yos_vault, yos_mint_account)] {
+            create_vault(
+                admin_account,
+                vault_account,
+                authority_account,
+                mint_account,
+                system_program,
+                token_program_account,
+                ata_program_account,
+            )?;
+        }
+    }
+    
     msg!("Staking program initialized successfully");
     
     Ok(())
@@ -218,22 +1083,31 @@ fn process_stake(
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    let known = WellKnownAccounts::new(program_id);
+    let mut resolver = known.resolver(accounts);
     
     // Get accounts
-    let user_account = next_account_info(account_info_iter)?;
-    let user_yot_token_account = next_account_info(account_info_iter)?;
-    let program_yot_token_account = next_account_info(account_info_iter)?;
-    let user_staking_account = next_account_info(account_info_iter)?;
-    let program_state_account = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-    let clock = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
+    let user_account = resolver.next("user")?;
+    let user_yot_token_account = resolver.next("user YOT token account")?;
+    let program_yot_token_account = resolver.next("program YOT token account")?;
+    let user_staking_account = resolver.next("user staking account")?;
+    let program_state_account = resolver.by_key(&known.program_state, "program state")?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    let clock = resolver.by_key(&sysvar::clock::id(), "clock sysvar")?;
+    let system_program = resolver.by_key(&system_program::id(), "system program")?;
+    let pause_state_account = resolver.by_key(&known.pause_state, "pause state")?;
+    // Optional rent payer for the staking account; defaults to the user.
+    // Lets custodial frontends sponsor account creation while the user
+    // still signs for the token transfer and owns the position.
+    let fee_payer = resolver.next_optional().unwrap_or(user_account);
     
     // Verify user signature (mandatory signature verification)
-    if !user_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(user_account);
+    
+    // Verify fee payer signature (only matters when a separate payer is passed)
+    assert_signer!(fee_payer);
+    
+    check_not_paused(program_id, pause_state_account, PAUSE_STAKE)?;
     
     // Get program state
     let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
@@ -257,80 +1131,334 @@ fn process_stake(
     let clock = Clock::from_account_info(clock)?;
     let current_time = clock.unix_timestamp;
     
-    // Calculate staking account PDA
-    let seeds = [
-        b"staking", 
-        user_account.key.as_ref(),
-    ];
-    let (staking_pda, bump_seed) = Pubkey::find_program_address(&seeds, program_id);
+    StakeAccounts {
+        user: user_account,
+        user_yot_token_account,
+        program_yot_token_account,
+        user_staking_account,
+        token_program,
+        system_program,
+        fee_payer,
+    }
+    .deposit(program_id, amount, current_time, 0)?;
+    
+    msg!("Staked {} YOT tokens", amount as f64 / 1_000_000_000.0);
     
-    // Check if staking account exists, if not create it
-    if user_staking_account.data_is_empty() {
-        let rent = Rent::get()?;
-        let rent_lamports = rent.minimum_balance(std::mem::size_of::<StakingAccount>());
+    Ok(())
+}
+
+// Accounts that move YOT into a user's position
+struct StakeAccounts<'a, 'b> {
+    user: &'a AccountInfo<'b>,
+    user_yot_token_account: &'a AccountInfo<'b>,
+    program_yot_token_account: &'a AccountInfo<'b>,
+    user_staking_account: &'a AccountInfo<'b>,
+    token_program: &'a AccountInfo<'b>,
+    system_program: &'a AccountInfo<'b>,
+    // Funds the staking account rent
+    fee_payer: &'a AccountInfo<'b>,
+}
+
+impl StakeAccounts<'_, '_> {
+    // Add `amount` to the position, creating the staking account on the
+    // first stake, and transfer the YOT into the program vault. A
+    // `locked_until` later than the position's current lock extends it.
+    fn deposit(&self, program_id: &Pubkey, amount: u64, current_time: i64, locked_until: i64) -> ProgramResult {
+        let user_account = self.user;
+        let user_staking_account = self.user_staking_account;
         
-        // Create staking account
-        invoke_signed(
-            &system_instruction::create_account(
-                user_account.key,
-                &staking_pda,
-                rent_lamports,
-                std::mem::size_of::<StakingAccount>() as u64,
-                program_id,
-            ),
-            &[
-                user_account.clone(),
-                user_staking_account.clone(),
-                system_program.clone(),
-            ],
-            &[&[b"staking", user_account.key.as_ref(), &[bump_seed]]],
-        )?;
-        
-        // Initialize new staking account
-        let staking_data = StakingAccount {
-            owner: *user_account.key,
-            staked_amount: amount,
-            start_timestamp: current_time,
-            last_harvest_time: current_time,
-            total_harvested: 0,
-        };
-        
-        staking_data.serialize(&mut *user_staking_account.try_borrow_mut_data()?)?;
-    } else {
-        // Update existing staking account
-        let mut staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
+        // Calculate staking account PDA
+        let seeds = [
+            seeds::STAKING, 
+            user_account.key.as_ref(),
+        ];
+        let (staking_pda, bump_seed) = Pubkey::find_program_address(&seeds, program_id);
         
-        // Verify the owner
-        if staking_data.owner != *user_account.key {
-            return Err(ProgramError::InvalidAccountData);
+        // Check if staking account exists, if not create it
+        if user_staking_account.data_is_empty() {
+            let rent = Rent::get()?;
+            let rent_lamports = rent.minimum_balance(std::mem::size_of::<StakingAccount>());
+            
+            // Create staking account (rent funded by the fee payer)
+            invoke_signed(
+                &system_instruction::create_account(
+                    self.fee_payer.key,
+                    &staking_pda,
+                    rent_lamports,
+                    std::mem::size_of::<StakingAccount>() as u64,
+                    program_id,
+                ),
+                &[
+                    self.fee_payer.clone(),
+                    user_staking_account.clone(),
+                    self.system_program.clone(),
+                ],
+                &[&[seeds::STAKING, user_account.key.as_ref(), &[bump_seed]]],
+            )?;
+            
+            // Initialize new staking account
+            let staking_data = StakingAccount {
+                owner: *user_account.key,
+                staked_amount: amount,
+                start_timestamp: current_time,
+                last_harvest_time: current_time,
+                total_harvested: 0,
+                harvest_threshold: 0,
+                locked_until,
+                pending_rewards: 0,
+            };
+            
+            staking_data.save(user_staking_account)?;
+        } else {
+            // Update existing staking account
+            let mut staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
+            
+            // Verify the owner
+            if staking_data.owner != *user_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            
+            // Update staked amount
+            staking_data.staked_amount = staking_data.staked_amount.checked_add(amount)
+                .ok_or(ProgramError::InvalidArgument)?; // Use InvalidArgument instead of ArithmeticOverflow
+            
+            // Locks only ever extend
+            if locked_until > staking_data.locked_until {
+                grow_staking_account(self.fee_payer, user_staking_account, self.system_program)?;
+                staking_data.locked_until = locked_until;
+            }
+            
+            staking_data.save(user_staking_account)?;
         }
         
-        // Update staked amount
-        staking_data.staked_amount = staking_data.staked_amount.checked_add(amount)
-            .ok_or(ProgramError::InvalidArgument)?; // Use InvalidArgument instead of ArithmeticOverflow
+        // Transfer tokens from user to program
+        invoke(
+            &spl_token::instruction::transfer(
+                self.token_program.key,
+                self.user_yot_token_account.key,
+                self.program_yot_token_account.key,
+                user_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                self.user_yot_token_account.clone(),
+                self.program_yot_token_account.clone(),
+                user_account.clone(),
+                self.token_program.clone(),
+            ],
+        )?;
         
-        staking_data.serialize(&mut *user_staking_account.try_borrow_mut_data()?)?;
+        Ok(())
+    }
+}
+
+// Grow an older staking account to the current layout, with `payer`
+// topping up the rent
+fn grow_staking_account<'a>(
+    payer: &AccountInfo<'a>,
+    user_staking_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> ProgramResult {
+    let full_len = std::mem::size_of::<StakingAccount>();
+    if user_staking_account.data_len() >= full_len {
+        return Ok(());
+    }
+    
+    let rent_lamports = Rent::get()?.minimum_balance(full_len);
+    let top_up = rent_lamports.saturating_sub(user_staking_account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, user_staking_account.key, top_up),
+            &[
+                payer.clone(),
+                user_staking_account.clone(),
+                system_program.clone(),
+            ],
+        )?;
+    }
+    user_staking_account.realloc(full_len, false)
+}
+
+// Process stake-with-SOL instruction: wrap the SOL, swap it to YOT through
+// the swap program and stake whatever YOT the swap returned
+fn process_stake_with_sol(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lamports: u64,
+    min_yot: u64,
+    lock_seconds: i64,
+) -> ProgramResult {
+    let known = WellKnownAccounts::new(program_id);
+    let mut resolver = known.resolver(accounts);
+    
+    // Get accounts
+    let user_account = resolver.next("user")?;
+    let user_yot_token_account = resolver.next("user YOT token account")?;
+    let program_yot_token_account = resolver.next("program YOT token account")?;
+    let user_staking_account = resolver.next("user staking account")?;
+    let user_wsol_token_account = resolver.next("user wrapped SOL token account")?;
+    let user_yos_token_account = resolver.next("user YOS token account")?;
+    let swap_program = resolver.next("swap program")?;
+    let swap_state_account = resolver.next("swap program state")?;
+    let swap_authority = resolver.next("swap program authority")?;
+    let sol_yot_pool_account = resolver.next("SOL-YOT pool")?;
+    let swap_wsol_vault_account = resolver.next("swap wrapped SOL vault")?;
+    let swap_yot_vault_account = resolver.next("swap YOT vault")?;
+    let sol_fee_vault_account = resolver.next("SOL fee vault")?;
+    let program_state_account = resolver.by_key(&known.program_state, "program state")?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    let clock = resolver.by_key(&sysvar::clock::id(), "clock sysvar")?;
+    let system_program = resolver.by_key(&system_program::id(), "system program")?;
+    let pause_state_account = resolver.by_key(&known.pause_state, "pause state")?;
+    // Optional rent payer for the staking account; defaults to the user
+    let fee_payer = resolver.next_optional().unwrap_or(user_account);
+    
+    // Verify user signature (mandatory signature verification)
+    assert_signer!(user_account);
+    
+    // Verify fee payer signature (only matters when a separate payer is passed)
+    assert_signer!(fee_payer);
+    
+    if lamports == 0 || lock_seconds < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    check_not_paused(program_id, pause_state_account, PAUSE_STAKE)?;
+    
+    // The user signs for the swap, so it must be the real router
+    if *swap_program.key != multihub_swap_v3::id() {
+        msg!("Unknown swap program {}", swap_program.key);
+        return Err(ProgramError::IncorrectProgramId);
     }
     
-    // Transfer tokens from user to program
+    // Get program state
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    
+    // Verify token accounts
+    let user_token_account = TokenAccount::unpack(&user_yot_token_account.data.borrow())?;
+    if user_token_account.owner != *user_account.key || user_token_account.mint != program_state.yot_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let user_wsol_token = assert_token_account!(
+        user_wsol_token_account,
+        spl_token::native_mint::id(),
+        *user_account.key,
+        "Wrapped SOL account has incorrect mint or owner"
+    );
+    let yot_before = user_token_account.amount;
+    // A wrapped SOL account that starts empty only exists for this stake
+    let ephemeral_wsol_account = user_wsol_token.amount == 0;
+    
+    // Wrap the SOL
     invoke(
-        &spl_token::instruction::transfer(
-            token_program.key,
-            user_yot_token_account.key,
-            program_yot_token_account.key,
-            user_account.key,
-            &[],
-            amount,
-        )?,
+        &system_instruction::transfer(user_account.key, user_wsol_token_account.key, lamports),
         &[
-            user_yot_token_account.clone(),
-            program_yot_token_account.clone(),
             user_account.clone(),
+            user_wsol_token_account.clone(),
+            system_program.clone(),
+        ],
+    )?;
+    invoke(
+        &spl_token::instruction::sync_native(token_program.key, user_wsol_token_account.key)?,
+        &[
+            user_wsol_token_account.clone(),
             token_program.clone(),
         ],
     )?;
     
-    msg!("Staked {} YOT tokens", amount as f64 / 1_000_000_000.0);
+    // Swap it to YOT; the swap program's SOL admin commission is charged in
+    // lamports, so that much wrapped SOL stays in the user's account
+    invoke(
+        &Instruction {
+            program_id: *swap_program.key,
+            accounts: vec![
+                AccountMeta::new(*user_account.key, true),
+                AccountMeta::new(*swap_state_account.key, false),
+                AccountMeta::new(*swap_authority.key, false),
+                AccountMeta::new(*user_wsol_token_account.key, false),
+                AccountMeta::new(*user_yot_token_account.key, false),
+                AccountMeta::new(*user_yos_token_account.key, false),
+                AccountMeta::new_readonly(*token_program.key, false),
+                // Swap history placeholder
+                AccountMeta::new_readonly(*swap_program.key, false),
+                AccountMeta::new(*sol_yot_pool_account.key, false),
+                AccountMeta::new(*swap_wsol_vault_account.key, false),
+                AccountMeta::new(*swap_yot_vault_account.key, false),
+                AccountMeta::new(*sol_fee_vault_account.key, false),
+                AccountMeta::new_readonly(*system_program.key, false),
+            ],
+            data: SwapInstruction::Swap {
+                amount_in: lamports,
+                min_amount_out: min_yot,
+            }
+            .try_to_vec()?,
+        },
+        &[
+            user_account.clone(),
+            swap_state_account.clone(),
+            swap_authority.clone(),
+            user_wsol_token_account.clone(),
+            user_yot_token_account.clone(),
+            user_yos_token_account.clone(),
+            token_program.clone(),
+            swap_program.clone(),
+            sol_yot_pool_account.clone(),
+            swap_wsol_vault_account.clone(),
+            swap_yot_vault_account.clone(),
+            sol_fee_vault_account.clone(),
+            system_program.clone(),
+        ],
+    )?;
+    
+    // Close the ephemeral wrapped SOL account, refunding its rent and any
+    // wrapped SOL the swap left behind to the user instead of orphaning it
+    if ephemeral_wsol_account {
+        invoke(
+            &spl_token::instruction::close_account(
+                token_program.key,
+                user_wsol_token_account.key,
+                user_account.key,
+                user_account.key,
+                &[],
+            )?,
+            &[
+                user_wsol_token_account.clone(),
+                user_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+    }
+    
+    // Stake exactly what the swap paid out
+    let yot_after = TokenAccount::unpack(&user_yot_token_account.data.borrow())?.amount;
+    let amount = yot_after.saturating_sub(yot_before);
+    if amount == 0 || amount < min_yot {
+        msg!("Swap returned {} YOT, expected at least {}", amount, min_yot);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    
+    // Get clock for timestamp
+    let clock = Clock::from_account_info(clock)?;
+    let current_time = clock.unix_timestamp;
+    let locked_until = if lock_seconds > 0 {
+        current_time.saturating_add(lock_seconds)
+    } else {
+        0
+    };
+    
+    StakeAccounts {
+        user: user_account,
+        user_yot_token_account,
+        program_yot_token_account,
+        user_staking_account,
+        token_program,
+        system_program,
+        fee_payer,
+    }
+    .deposit(program_id, amount, current_time, locked_until)?;
+    
+    msg!("Staked {} YOT bought with {} lamports", amount as f64 / 1_000_000_000.0, lamports);
     
     Ok(())
 }
@@ -341,30 +1469,33 @@ fn process_unstake(
     accounts: &[AccountInfo],
     amount: u64,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    let known = WellKnownAccounts::new(program_id);
+    let mut resolver = known.resolver(accounts);
     
     // Get accounts
-    let user_account = next_account_info(account_info_iter)?;
-    let user_yot_token_account = next_account_info(account_info_iter)?;
-    let program_yot_token_account = next_account_info(account_info_iter)?;
-    let user_yos_token_account = next_account_info(account_info_iter)?;
-    let program_yos_token_account = next_account_info(account_info_iter)?;
-    let user_staking_account = next_account_info(account_info_iter)?;
-    let program_state_account = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-    let program_authority = next_account_info(account_info_iter)?;
-    let clock = next_account_info(account_info_iter)?;
+    let user_account = resolver.next("user")?;
+    let user_yot_token_account = resolver.next("user YOT token account")?;
+    let program_yot_token_account = resolver.next("program YOT token account")?;
+    // The user YOS token account is no longer paid at unstake
+    let _user_yos_token_account = resolver.next("user YOS token account")?;
+    let program_yos_token_account = resolver.next("program YOS token account")?;
+    let user_staking_account = resolver.next("user staking account")?;
+    let program_state_account = resolver.by_key(&known.program_state, "program state")?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    let program_authority = resolver.by_key(&known.program_authority, "program authority")?;
+    let clock = resolver.by_key(&sysvar::clock::id(), "clock sysvar")?;
+    let pause_state_account = resolver.by_key(&known.pause_state, "pause state")?;
+    let instructions_sysvar_account = resolver.by_key(&sysvar::instructions::id(), "instructions sysvar")?;
+    let runway_config_account = resolver.by_key(&known.runway_config, "runway config")?;
+    let reward_freeze_account = resolver.by_key(&known.reward_freeze, "reward freeze")?;
+    let rate_history_account = resolver.by_key(&known.rate_history, "rate history")?;
+    let authority_bump = known.authority_bump;
     
     // Verify user signature (mandatory signature verification)
-    if !user_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(user_account);
     
-    // Calculate PDA for program authority
-    let (authority_pda, authority_bump) = Pubkey::find_program_address(&[b"authority"], program_id);
-    if authority_pda != *program_authority.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    check_not_paused(program_id, pause_state_account, PAUSE_UNSTAKE)?;
+    check_top_level_instruction(program_id, instructions_sysvar_account)?;
     
     // Get staking data
     let mut staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
@@ -385,40 +1516,51 @@ fn process_unstake(
     // Get current time
     let clock = Clock::from_account_info(clock)?;
     let current_time = clock.unix_timestamp;
+    staking_data.check_unlocked(current_time)?;
+    
+    // Rate after scaling for the reward vault runway
+    let stake_rate = effective_stake_rate(
+        program_id,
+        &program_state,
+        runway_config_account,
+        program_yot_token_account,
+        program_yos_token_account,
+    )?;
+    let schedule = RewardSchedule {
+        configured_rate: program_state.stake_rate_per_second,
+        effective_rate: stake_rate,
+        rate_history: load_rate_history(program_id, rate_history_account)?,
+        reward_freeze: load_reward_freeze(program_id, reward_freeze_account)?,
+    };
     
-    // Calculate time staked since last harvest
-    let time_staked_seconds = current_time.checked_sub(staking_data.last_harvest_time)
-        .ok_or(ProgramError::InvalidArgument)?;
-    
-    // EMERGENCY LINEAR FIX: Using linear interest calculation
-    // Convert staking rate from basis points to percentage
-    let rate_percentage = (program_state.stake_rate_per_second as f64) / 1_000_000.0;
-    
-    // Convert from percentage to decimal
-    let rate_decimal = rate_percentage / 100.0;
-    
-    // Convert raw amount to token units for calculation
-    let principal_tokens = staking_data.staked_amount as f64 / 1_000_000_000.0;
-    
-    // SIMPLE LINEAR INTEREST: principal * rate * time
-    // No exponentiation, no compounding
-    let rewards_token_units = principal_tokens * rate_decimal * time_staked_seconds as f64;
+    // Calculate time staked since last harvest; a clock reading behind the
+    // last harvest accrues nothing, and neither does a reward freeze
+    let time_staked_seconds = schedule.accruing_seconds(staking_data.last_harvest_time, current_time);
     
-    // Convert back to raw token units for blockchain storage - this is critical for proper results
-    let raw_rewards = (rewards_token_units * 1_000_000_000.0) as u64;
+    // SIMPLE LINEAR INTEREST: principal * rate * time for each stretch between
+    // rate changes, computed by the shared math crate so client estimates match
+    let raw_rewards = schedule.rewards(staking_data.staked_amount, staking_data.last_harvest_time, current_time);
     
     // Log all values for transparency and debugging
-    msg!("Unstake: Staked amount: {} tokens ({} raw units)", principal_tokens, staking_data.staked_amount);
-    msg!("Unstake: Rate: {}% per second ({} decimal)", rate_percentage, rate_decimal);
+    msg!("Unstake: Staked amount: {} tokens ({} raw units)", staking_data.staked_amount as f64 / 1_000_000_000.0, staking_data.staked_amount);
+    msg!("Unstake: Rate: {}% per second", stake_rate as f64 / 1_000_000.0);
     msg!("Unstake: Time staked: {} seconds", time_staked_seconds);
-    msg!("Unstake: Calculated rewards: {} tokens ({} raw units)", rewards_token_units, raw_rewards);
+    msg!("Unstake: Calculated rewards: {} tokens ({} raw units)", raw_rewards as f64 / 1_000_000_000.0, raw_rewards);
     
     // Update staking data
-    staking_data.last_harvest_time = current_time;
+    // Never move the reward clock backwards, or the skewed interval would be
+    // paid out a second time
+    staking_data.last_harvest_time = staking_data.last_harvest_time.max(current_time);
     
-    // Only add to total harvested if there are rewards to claim
+    // Credit the rewards to the pending ledger rather than paying them here,
+    // so a short vault or the harvest threshold cannot lose them; the next
+    // harvest pays them
     if raw_rewards > 0 {
-        staking_data.total_harvested = staking_data.total_harvested.checked_add(raw_rewards)
+        if user_staking_account.data_len() < std::mem::size_of::<StakingAccount>() {
+            let system_program = resolver.by_key(&system_program::id(), "system program")?;
+            grow_staking_account(user_account, user_staking_account, system_program)?;
+        }
+        staking_data.pending_rewards = staking_data.pending_rewards.checked_add(raw_rewards)
             .ok_or(ProgramError::InvalidArgument)?;
     }
     
@@ -427,10 +1569,7 @@ fn process_unstake(
         .ok_or(ProgramError::InvalidArgument)?;
     
     // Save updated staking data
-    staking_data.serialize(&mut *user_staking_account.try_borrow_mut_data()?)?;
-    
-    // Transfer YOT tokens back to user (this should ALWAYS happen)
-    let transfer_amount = amount; // No division - use raw amount directly
+    staking_data.save(user_staking_account)?;
     
     // Transfer YOT tokens back to user (this should ALWAYS happen)
     invoke_signed(
@@ -440,7 +1579,7 @@ fn process_unstake(
             user_yot_token_account.key,
             program_authority.key,
             &[],
-            transfer_amount, // No division - use the raw amount directly
+            amount,
         )?,
         &[
             program_yot_token_account.clone(),
@@ -448,86 +1587,11 @@ fn process_unstake(
             program_authority.clone(),
             token_program.clone(),
         ],
-        &[&[b"authority", &[authority_bump]]],
+        &[&[seeds::AUTHORITY, &[authority_bump]]],
     )?;
     
-    // Only attempt to transfer YOS rewards if there are rewards to claim
-    if raw_rewards > 0 {
-        let program_yos_info = match spl_token::state::Account::unpack(&program_yos_token_account.data.borrow()) {
-            Ok(token_account) => token_account,
-            Err(error) => {
-                msg!("Error unpacking program YOS token account: {:?}", error);
-                msg!("Unstaked {} YOT tokens but YOS rewards transfer failed", amount as f64 / 1_000_000_000.0);
-                return Ok(());
-            }
-        };
-        
-        let program_yos_balance = program_yos_info.amount;
-        
-        // Check if program has enough YOS tokens to transfer rewards
-        if program_yos_balance >= raw_rewards {
-            // CRITICAL FIX: Adjust the YOS reward amount to fix wallet display issue
-            // We keep the internal accounting in full precision, but adjust the
-            // actual transfer amount to fix the wallet display
-            
-            // CRITICAL FIX: Apply display normalization factor to raw rewards
-            // This will make the rewards display correctly in Phantom Wallet
-            // while maintaining proper accounting internally
-            let display_adjusted_rewards = raw_rewards / YOS_DISPLAY_NORMALIZATION_FACTOR;
-            
-            // Log the adjustment for transparency
-            msg!("DISPLAY FIX: Normalizing YOS display by dividing raw amount {} by factor {}",
-                 raw_rewards, YOS_DISPLAY_NORMALIZATION_FACTOR);
-            msg!("DISPLAY FIX: Transfer amount after adjustment: {} YOS", 
-                 display_adjusted_rewards as f64 / 1_000_000_000.0);
-            
-            // Only attempt to transfer if there's a non-zero amount after adjustment
-            if display_adjusted_rewards > 0 {
-                // Attempt to transfer YOS rewards, but handle errors without failing
-                match invoke_signed(
-                    &spl_token::instruction::transfer(
-                        token_program.key,
-                        program_yos_token_account.key,
-                        user_yos_token_account.key,
-                        program_authority.key,
-                        &[],
-                        display_adjusted_rewards, // CRITICAL FIX: Use normalized amount
-                    )?,
-                    &[
-                        program_yos_token_account.clone(),
-                        user_yos_token_account.clone(),
-                        program_authority.clone(),
-                        token_program.clone(),
-                    ],
-                    &[&[b"authority", &[authority_bump]]],
-                ) {
-                    Ok(_) => {
-                        msg!("Unstaked {} YOT tokens and transferred {} YOS rewards", 
-                             amount as f64 / 1_000_000_000.0, 
-                             display_adjusted_rewards as f64 / 1_000_000_000.0);
-                        msg!("Original YOS rewards (internal accounting): {} YOS", 
-                             raw_rewards as f64 / 1_000_000_000.0);
-                    },
-                    Err(error) => {
-                        // If YOS transfer fails, log the error but don't fail the entire unstaking process
-                        msg!("WARNING: Failed to transfer YOS rewards: {:?}", error);
-                        msg!("Unstaked {} YOT tokens but YOS rewards transfer failed", 
-                             amount as f64 / 1_000_000_000.0);
-                    }
-                }
-            } else {
-                msg!("YOS rewards too small after display adjustment (would be 0). Skipping YOS transfer.");
-            }
-        } else {
-            // Not enough YOS in program account - log the issue but continue with unstaking
-            msg!("WARNING: Insufficient YOS tokens in program account for rewards. Available: {}, Required: {}", 
-                 program_yos_balance, raw_rewards);
-            msg!("Unstaked {} YOT tokens but YOS rewards were not transferred due to insufficient program balance", 
-                 amount as f64 / 1_000_000_000.0);
-        }
-    } else {
-        msg!("Unstaked {} YOT tokens", amount as f64 / 1_000_000_000.0);
-    }
+    msg!("Unstaked {} YOT tokens; {} raw rewards pending harvest", 
+         amount as f64 / 1_000_000_000.0, staking_data.pending_rewards);
     
     Ok(())
 }
@@ -537,28 +1601,31 @@ fn process_harvest(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    let known = WellKnownAccounts::new(program_id);
+    let mut resolver = known.resolver(accounts);
     
     // Get accounts
-    let user_account = next_account_info(account_info_iter)?;
-    let user_yos_token_account = next_account_info(account_info_iter)?;
-    let program_yos_token_account = next_account_info(account_info_iter)?;
-    let user_staking_account = next_account_info(account_info_iter)?;
-    let program_state_account = next_account_info(account_info_iter)?;
-    let token_program = next_account_info(account_info_iter)?;
-    let program_authority = next_account_info(account_info_iter)?;
-    let clock = next_account_info(account_info_iter)?;
+    let user_account = resolver.next("user")?;
+    let user_yos_token_account = resolver.next("user YOS token account")?;
+    let program_yos_token_account = resolver.next("program YOS token account")?;
+    let user_staking_account = resolver.next("user staking account")?;
+    let program_state_account = resolver.by_key(&known.program_state, "program state")?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    let program_authority = resolver.by_key(&known.program_authority, "program authority")?;
+    let clock = resolver.by_key(&sysvar::clock::id(), "clock sysvar")?;
+    let pause_state_account = resolver.by_key(&known.pause_state, "pause state")?;
+    let instructions_sysvar_account = resolver.by_key(&sysvar::instructions::id(), "instructions sysvar")?;
+    let runway_config_account = resolver.by_key(&known.runway_config, "runway config")?;
+    let program_yot_token_account = resolver.next("program YOT token account")?;
+    let reward_freeze_account = resolver.by_key(&known.reward_freeze, "reward freeze")?;
+    let rate_history_account = resolver.by_key(&known.rate_history, "rate history")?;
+    let authority_bump = known.authority_bump;
     
     // Verify user signature (mandatory signature verification)
-    if !user_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(user_account);
     
-    // Calculate authority PDA
-    let (authority_pda, authority_bump) = Pubkey::find_program_address(&[b"authority"], program_id);
-    if authority_pda != *program_authority.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    check_not_paused(program_id, pause_state_account, PAUSE_HARVEST)?;
+    check_top_level_instruction(program_id, instructions_sysvar_account)?;
     
     // Get program state
     let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
@@ -571,8 +1638,8 @@ fn process_harvest(
         return Err(ProgramError::InvalidAccountData);
     }
     
-    // Check if user has staked tokens
-    if staking_data.staked_amount == 0 {
+    // Check if user has staked tokens or rewards pending from an unstake
+    if staking_data.staked_amount == 0 && staking_data.pending_rewards == 0 {
         return Err(ProgramError::InsufficientFunds);
     }
     
@@ -580,35 +1647,46 @@ fn process_harvest(
     let clock = Clock::from_account_info(clock)?;
     let current_time = clock.unix_timestamp;
     
-    // Calculate time staked since last harvest
-    let time_staked_seconds = current_time.checked_sub(staking_data.last_harvest_time)
-        .ok_or(ProgramError::InvalidArgument)?;
-    
-    // EMERGENCY LINEAR FIX: Using linear interest calculation
-    // Convert staking rate from basis points to percentage
-    let rate_percentage = (program_state.stake_rate_per_second as f64) / 1_000_000.0;
-    
-    // Convert from percentage to decimal
-    let rate_decimal = rate_percentage / 100.0;
-    
-    // Convert raw amount to token units for calculation
-    let principal_tokens = staking_data.staked_amount as f64 / 1_000_000_000.0;
+    // Rate after scaling for the reward vault runway
+    let stake_rate = effective_stake_rate(
+        program_id,
+        &program_state,
+        runway_config_account,
+        program_yot_token_account,
+        program_yos_token_account,
+    )?;
+    let schedule = RewardSchedule {
+        configured_rate: program_state.stake_rate_per_second,
+        effective_rate: stake_rate,
+        rate_history: load_rate_history(program_id, rate_history_account)?,
+        reward_freeze: load_reward_freeze(program_id, reward_freeze_account)?,
+    };
     
-    // SIMPLE LINEAR INTEREST: principal * rate * time
-    // No exponentiation, no compounding
-    let rewards_token_units = principal_tokens * rate_decimal * time_staked_seconds as f64;
+    // Calculate time staked since last harvest; a clock reading behind the
+    // last harvest accrues nothing, and neither does a reward freeze
+    let time_staked_seconds = schedule.accruing_seconds(staking_data.last_harvest_time, current_time);
     
-    // Convert back to raw token units for blockchain storage - this is critical for proper results
-    let raw_rewards = (rewards_token_units * 1_000_000_000.0) as u64;
+    // SIMPLE LINEAR INTEREST: principal * rate * time for each stretch between
+    // rate changes, computed by the shared math crate so client estimates match
+    let raw_rewards = schedule.rewards(staking_data.staked_amount, staking_data.last_harvest_time, current_time);
     
     // Log all values for transparency and debugging
-    msg!("Harvest: Staked amount: {} tokens ({} raw units)", principal_tokens, staking_data.staked_amount);
-    msg!("Harvest: Rate: {}% per second ({} decimal)", rate_percentage, rate_decimal);
+    msg!("Harvest: Staked amount: {} tokens ({} raw units)", staking_data.staked_amount as f64 / 1_000_000_000.0, staking_data.staked_amount);
+    msg!("Harvest: Rate: {}% per second", stake_rate as f64 / 1_000_000.0);
     msg!("Harvest: Time staked: {} seconds", time_staked_seconds);
-    msg!("Harvest: Calculated rewards: {} tokens ({} raw units)", rewards_token_units, raw_rewards);
+    msg!("Harvest: Calculated rewards: {} tokens ({} raw units)", raw_rewards as f64 / 1_000_000_000.0, raw_rewards);
+    msg!("Harvest: Pending rewards: {} raw units", staking_data.pending_rewards);
     
-    // Check rewards meet minimum threshold
-    if raw_rewards < program_state.harvest_threshold {
+    // Rewards credited at unstake are paid along with the new ones
+    let raw_rewards = raw_rewards.checked_add(staking_data.pending_rewards)
+        .ok_or(ProgramError::InvalidArgument)?;
+    
+    // Check rewards meet the user's threshold, or the program's by default.
+    // Once nothing is staked nothing more accrues, so the pending rewards are
+    // paid whatever the threshold
+    if staking_data.staked_amount > 0
+        && raw_rewards < staking_data.effective_harvest_threshold(program_state.harvest_threshold)
+    {
         return Err(ProgramError::InsufficientFunds);
     }
     
@@ -623,92 +1701,1113 @@ fn process_harvest(
     
     let program_yos_balance = program_yos_info.amount;
     
-    if program_yos_balance < raw_rewards {
-        return Err(ProgramError::InsufficientFunds);
+    // Pay what the vault can cover and carry the rest as a debt in the
+    // pending rewards, paid by a later harvest once the vault is refunded
+    let paid_rewards = tswap_math::affordable_rewards(raw_rewards, program_yos_balance);
+    let unpaid_rewards = raw_rewards - paid_rewards;
+    if unpaid_rewards > 0 {
+        msg!("WARNING: Reward vault short; {} raw rewards carried as debt", unpaid_rewards);
+        if user_staking_account.data_len() < std::mem::size_of::<StakingAccount>() {
+            let system_program = resolver.by_key(&system_program::id(), "system program")?;
+            grow_staking_account(user_account, user_staking_account, system_program)?;
+        }
     }
     
     // Update staking data
-    staking_data.last_harvest_time = current_time;
-    staking_data.total_harvested = staking_data.total_harvested.checked_add(raw_rewards)
+    // Never move the reward clock backwards, or the skewed interval would be
+    // paid out a second time
+    staking_data.last_harvest_time = staking_data.last_harvest_time.max(current_time);
+    staking_data.total_harvested = staking_data.total_harvested.checked_add(paid_rewards)
         .ok_or(ProgramError::InvalidArgument)?;
+    staking_data.pending_rewards = unpaid_rewards;
     
     // Save updated staking data
-    staking_data.serialize(&mut *user_staking_account.try_borrow_mut_data()?)?;
+    staking_data.save(user_staking_account)?;
     
     // CRITICAL FIX: Apply display normalization factor to raw rewards
     // This will make the rewards display correctly in Phantom Wallet
     // while maintaining proper accounting internally
-    let display_adjusted_rewards = raw_rewards / YOS_DISPLAY_NORMALIZATION_FACTOR;
+    let display_adjusted_rewards = tswap_math::reward_payout(paid_rewards);
     
     // Log the adjustment for transparency
     msg!("DISPLAY FIX: Normalizing YOS display by dividing raw amount {} by factor {}",
-         raw_rewards, YOS_DISPLAY_NORMALIZATION_FACTOR);
+         paid_rewards, YOS_DISPLAY_NORMALIZATION_FACTOR);
     msg!("DISPLAY FIX: Transfer amount after adjustment: {} YOS", 
          display_adjusted_rewards as f64 / 1_000_000_000.0);
     
+    // Take the harvest fee into the treasury when one is configured
+    let harvest_fee = harvest_fee_amount(program_id, &resolver, display_adjusted_rewards)?;
+    if harvest_fee > 0 {
+        let treasury_vault = treasury_yos_vault(&program_state.yos_mint);
+        let treasury_yos_token_account = resolver.by_key(&treasury_vault, "treasury YOS vault")?;
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                program_yos_token_account.key,
+                treasury_yos_token_account.key,
+                program_authority.key,
+                &[],
+                harvest_fee,
+            )?,
+            &[
+                program_yos_token_account.clone(),
+                treasury_yos_token_account.clone(),
+                program_authority.clone(),
+                token_program.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[authority_bump]]],
+        )?;
+        msg!("Harvest fee: {} YOS to the treasury", harvest_fee as f64 / 1_000_000_000.0);
+    }
+    let display_adjusted_rewards = display_adjusted_rewards - harvest_fee;
+    
     // Transfer YOS rewards to user (using display-adjusted amount)
-    invoke_signed(
-        &spl_token::instruction::transfer(
-            token_program.key,
-            program_yos_token_account.key,
-            user_yos_token_account.key,
-            program_authority.key,
-            &[],
-            display_adjusted_rewards, // CRITICAL FIX: Use normalized amount
-        )?,
-        &[
-            program_yos_token_account.clone(),
-            user_yos_token_account.clone(),
-            program_authority.clone(),
-            token_program.clone(),
-        ],
-        &[&[b"authority", &[authority_bump]]],
-    )?;
+    if display_adjusted_rewards > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                program_yos_token_account.key,
+                user_yos_token_account.key,
+                program_authority.key,
+                &[],
+                display_adjusted_rewards, // CRITICAL FIX: Use normalized amount
+            )?,
+            &[
+                program_yos_token_account.clone(),
+                user_yos_token_account.clone(),
+                program_authority.clone(),
+                token_program.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[authority_bump]]],
+        )?;
+    }
     
     // Log the proper decimal format for clarity
     msg!("Harvested {} YOS rewards (raw amount: {})", 
-         display_adjusted_rewards as f64 / 1_000_000_000.0, raw_rewards);
+         display_adjusted_rewards as f64 / 1_000_000_000.0, paid_rewards);
     msg!("Original YOS rewards (internal accounting): {} YOS", 
-         raw_rewards as f64 / 1_000_000_000.0);
+         paid_rewards as f64 / 1_000_000_000.0);
     
     Ok(())
 }
 
-fn process_update_parameters(
+// Harvest fee on a `payout` of YOS; zero while the fee config was never set
+fn harvest_fee_amount(program_id: &Pubkey, resolver: &AccountResolver, payout: u64) -> Result<u64, ProgramError> {
+    let (harvest_fee_config_pda, _) = Pubkey::find_program_address(&[seeds::HARVEST_FEE_CONFIG], program_id);
+    let harvest_fee_config_account = resolver.by_key(&harvest_fee_config_pda, "harvest fee config")?;
+    if harvest_fee_config_account.data_is_empty() {
+        return Ok(0);
+    }
+    assert_owner!(harvest_fee_config_account, program_id);
+    let harvest_fee_bps = HarvestFeeConfig::try_from_slice(&harvest_fee_config_account.data.borrow())?
+        .harvest_fee_bps
+        .min(MAX_HARVEST_FEE_BPS);
+    Ok((payout as u128 * harvest_fee_bps as u128 / 10_000) as u64)
+}
+
+// Process ping instruction: report the pause flags and reward vault health
+fn process_ping(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    stake_rate_per_second: u64,
-    harvest_threshold: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     
     // Get accounts
-    let admin_account = next_account_info(account_info_iter)?;
     let program_state_account = next_account_info(account_info_iter)?;
+    let pause_state_account = next_account_info(account_info_iter)?;
+    let runway_config_account = next_account_info(account_info_iter)?;
+    let program_yot_token_account = next_account_info(account_info_iter)?;
+    let program_yos_token_account = next_account_info(account_info_iter)?;
     
-    // Verify admin signature (mandatory signature verification)
-    if !admin_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Get program state
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let (pause_state_pda, _) = Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id);
+    let (runway_config_pda, _) = Pubkey::find_program_address(&[seeds::RUNWAY_CONFIG], program_id);
+    if pause_state_pda != *pause_state_account.key || runway_config_pda != *runway_config_account.key {
+        return Err(ProgramError::InvalidAccountData);
     }
     
-    // Get program state
-    let mut program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    // A missing pause state means nothing has ever been paused
+    let mut flags = if pause_state_account.data_is_empty() {
+        0
+    } else {
+        PauseState::try_from_slice(&pause_state_account.data.borrow())?.paused_mask
+            & (PAUSE_STAKE | PAUSE_UNSTAKE | PAUSE_HARVEST)
+    };
     
-    // Verify caller is admin
-    if program_state.admin != *admin_account.key {
-        return Err(ProgramError::InvalidAccountData);
+    // Reward vault runway, as harvests would see it
+    let stake_rate = effective_stake_rate(
+        program_id,
+        &program_state,
+        runway_config_account,
+        program_yot_token_account,
+        program_yos_token_account,
+    )?;
+    if stake_rate < program_state.stake_rate_per_second {
+        flags |= HEALTH_RUNWAY_LOW;
+    }
+    let (program_authority, _) = Pubkey::find_program_address(&[seeds::AUTHORITY], program_id);
+    let total_staked = unpack_vault(program_yot_token_account, &program_state.yot_mint, &program_authority)?.amount;
+    let reward_reserve = unpack_vault(program_yos_token_account, &program_state.yos_mint, &program_authority)?.amount;
+    if total_staked > 0 && reward_reserve == 0 {
+        flags |= HEALTH_REWARDS_EXHAUSTED;
     }
     
-    // Update parameters
-    program_state.stake_rate_per_second = stake_rate_per_second;
-    program_state.harvest_threshold = harvest_threshold;
+    let report = HealthReport {
+        timestamp: Clock::get()?.unix_timestamp,
+        flags,
+    };
+    msg!("Health flags {:#04x}", flags);
+    set_return_data(&report.try_to_vec()?);
     
-    // Save updated program state
-    program_state.serialize(&mut *program_state_account.try_borrow_mut_data()?)?;
+    Ok(())
+}
+
+// Process solvency report instruction: sum the liabilities of the passed
+// staking accounts and return them with the vault balances
+fn process_solvency_report(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
     
-    // Log updates
-    msg!("Updated stake rate to {}% per second", stake_rate_per_second as f64 / 1_000_000.0);
-    msg!("Updated harvest threshold to {} YOS tokens", harvest_threshold as f64 / 1_000_000_000.0);
+    // Get accounts
+    let program_state_account = next_account_info(account_info_iter)?;
+    let runway_config_account = next_account_info(account_info_iter)?;
+    let program_yot_token_account = next_account_info(account_info_iter)?;
+    let program_yos_token_account = next_account_info(account_info_iter)?;
+    let mut staking_accounts = account_info_iter.as_slice();
+    
+    // The reward freeze and rate history PDAs may lead the staking accounts.
+    // Without them frozen time counts as accrued and past time accrues at the
+    // current rate, which can misstate the liability
+    let (reward_freeze_pda, _) = Pubkey::find_program_address(&[seeds::REWARD_FREEZE], program_id);
+    let (rate_history_pda, _) = Pubkey::find_program_address(&[seeds::RATE_HISTORY], program_id);
+    let mut reward_freeze = None;
+    let mut rate_history = None;
+    while let Some((account, rest)) = staking_accounts.split_first() {
+        if *account.key == reward_freeze_pda {
+            reward_freeze = load_reward_freeze(program_id, account)?;
+        } else if *account.key == rate_history_pda {
+            rate_history = load_rate_history(program_id, account)?;
+        } else {
+            break;
+        }
+        staking_accounts = rest;
+    }
+    
+    // Get program state
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let (runway_config_pda, _) = Pubkey::find_program_address(&[seeds::RUNWAY_CONFIG], program_id);
+    assert_pda!(runway_config_account, runway_config_pda);
+    
+    // The vaults must be the program's own
+    let (program_authority, _) = Pubkey::find_program_address(&[seeds::AUTHORITY], program_id);
+    let yot_vault_balance =
+        unpack_vault(program_yot_token_account, &program_state.yot_mint, &program_authority)?.amount;
+    let yos_vault_balance =
+        unpack_vault(program_yos_token_account, &program_state.yos_mint, &program_authority)?.amount;
+    
+    // Rewards accrue at the rate harvests would currently pay
+    let stake_rate = effective_stake_rate(
+        program_id,
+        &program_state,
+        runway_config_account,
+        program_yot_token_account,
+        program_yos_token_account,
+    )?;
+    let schedule = RewardSchedule {
+        configured_rate: program_state.stake_rate_per_second,
+        effective_rate: stake_rate,
+        rate_history,
+        reward_freeze,
+    };
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    let mut report = SolvencyReport {
+        timestamp: current_time,
+        accounts_counted: 0,
+        staked_principal: 0,
+        accrued_rewards: 0,
+        pending_rewards: 0,
+        reward_liability: 0,
+        yot_vault_balance,
+        yos_vault_balance,
+    };
+    for (index, user_staking_account) in staking_accounts.iter().enumerate() {
+        // Only genuine staking PDAs, each counted once
+        if user_staking_account.owner != program_id
+            || staking_accounts[..index].iter().any(|account| account.key == user_staking_account.key)
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
+        let (staking_pda, _) = Pubkey::find_program_address(&[seeds::STAKING, staking_data.owner.as_ref()], program_id);
+        assert_pda!(user_staking_account, staking_pda);
+        
+        let accrued_rewards =
+            schedule.rewards(staking_data.staked_amount, staking_data.last_harvest_time, current_time);
+        report.accounts_counted += 1;
+        report.staked_principal = report.staked_principal.saturating_add(staking_data.staked_amount);
+        report.accrued_rewards = report.accrued_rewards.saturating_add(accrued_rewards);
+        report.pending_rewards = report.pending_rewards.saturating_add(staking_data.pending_rewards);
+    }
+    report.reward_liability = tswap_math::reward_payout(report.accrued_rewards.saturating_add(report.pending_rewards));
+    
+    msg!("Solvency: {} accounts, {} YOT staked vs {} held, {} YOS owed vs {} held",
+         report.accounts_counted, report.staked_principal, yot_vault_balance,
+         report.reward_liability, yos_vault_balance);
+    set_return_data(&report.try_to_vec()?);
     
     Ok(())
-}
\ No newline at end of file
+}
+
+// Process emergency unstake: returns principal only, skipping all reward
+// logic so users can exit while reward math is paused for an incident
+fn process_emergency_unstake(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let known = WellKnownAccounts::new(program_id);
+    let mut resolver = known.resolver(accounts);
+    
+    // Get accounts
+    let user_account = resolver.next("user")?;
+    let user_yot_token_account = resolver.next("user YOT token account")?;
+    let program_yot_token_account = resolver.next("program YOT token account")?;
+    let user_staking_account = resolver.next("user staking account")?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    let program_authority = resolver.by_key(&known.program_authority, "program authority")?;
+    let authority_bump = known.authority_bump;
+    
+    // Verify user signature (mandatory signature verification)
+    assert_signer!(user_account);
+    
+    // Get staking data
+    assert_owner!(user_staking_account, program_id);
+    let mut staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
+    
+    // Verify staking account ownership
+    if staking_data.owner != *user_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let amount = staking_data.staked_amount;
+    if amount == 0 {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    
+    // A lock is the user's own commitment, so it holds in emergencies too
+    let current_time = Clock::get()?.unix_timestamp;
+    staking_data.check_unlocked(current_time)?;
+    
+    // Clear the position; unharvested rewards are forfeited and the reward
+    // clock restarts so a later stake does not accrue for the gap
+    staking_data.staked_amount = 0;
+    staking_data.last_harvest_time = staking_data.last_harvest_time.max(current_time);
+    staking_data.save(user_staking_account)?;
+    
+    // Return the principal
+    invoke_signed(
+        &spl_token::instruction::transfer(
+            token_program.key,
+            program_yot_token_account.key,
+            user_yot_token_account.key,
+            program_authority.key,
+            &[],
+            amount,
+        )?,
+        &[
+            program_yot_token_account.clone(),
+            user_yot_token_account.clone(),
+            program_authority.clone(),
+            token_program.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[authority_bump]]],
+    )?;
+    
+    msg!("Emergency unstaked {} YOT tokens (rewards skipped)", amount as f64 / 1_000_000_000.0);
+    
+    Ok(())
+}
+
+fn process_update_parameters(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    stake_rate_per_second: u64,
+    harvest_threshold: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let rate_history_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify admin signature (mandatory signature verification)
+    assert_signer!(admin_account);
+    
+    // Get program state
+    let mut program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    
+    // Verify caller is admin
+    if program_state.admin != *admin_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    // Checkpoint the rate being replaced so rewards accrued under it stay
+    if stake_rate_per_second != program_state.stake_rate_per_second {
+        record_rate_change(
+            program_id,
+            admin_account,
+            rate_history_account,
+            system_program,
+            program_state.stake_rate_per_second,
+        )?;
+    }
+    
+    // Update parameters
+    program_state.stake_rate_per_second = stake_rate_per_second;
+    program_state.harvest_threshold = harvest_threshold;
+    
+    // Save updated program state
+    program_state.serialize(&mut *program_state_account.try_borrow_mut_data()?)?;
+    
+    // Log updates
+    msg!("Updated stake rate to {}% per second", stake_rate_per_second as f64 / 1_000_000.0);
+    msg!("Updated harvest threshold to {} YOS tokens", harvest_threshold as f64 / 1_000_000_000.0);
+    
+    Ok(())
+}
+
+fn process_set_pauser(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    pauser: Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pause_state_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify admin signature (mandatory signature verification)
+    assert_signer!(admin_account);
+    
+    // Verify caller is admin
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    if program_state.admin != *admin_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let mut pause_state = load_or_create_pause_state(program_id, admin_account, pause_state_account, system_program)?;
+    pause_state.pauser = pauser;
+    pause_state.serialize(&mut *pause_state_account.try_borrow_mut_data()?)?;
+    
+    msg!("Pauser set to {}", pauser);
+    
+    Ok(())
+}
+
+fn process_set_paused_mask(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused_mask: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pause_state_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify signature (mandatory signature verification)
+    assert_signer!(authority_account);
+    
+    // Verify caller is the pauser or the admin before the pause state is created
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let (pause_state_pda, _) = Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id);
+    assert_pda!(pause_state_account, pause_state_pda);
+    let is_pauser = !pause_state_account.data_is_empty()
+        && pause_state_account.owner == program_id
+        && PauseState::try_from_slice(&pause_state_account.data.borrow())?.pauser == *authority_account.key;
+    if !is_pauser && program_state.admin != *authority_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let mut pause_state = load_or_create_pause_state(program_id, authority_account, pause_state_account, system_program)?;
+    pause_state.paused_mask = paused_mask;
+    pause_state.serialize(&mut *pause_state_account.try_borrow_mut_data()?)?;
+    
+    msg!("Paused mask set to {:#04x}", paused_mask);
+    
+    Ok(())
+}
+
+// Record in the rate history that `replaced_rate` stops applying now,
+// creating the history on first use with the admin funding the rent
+fn record_rate_change<'a>(
+    program_id: &Pubkey,
+    admin_account: &AccountInfo<'a>,
+    rate_history_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    replaced_rate: u64,
+) -> ProgramResult {
+    let (rate_history_pda, bump_seed) = Pubkey::find_program_address(&[seeds::RATE_HISTORY], program_id);
+    assert_pda!(rate_history_account, rate_history_pda);
+    
+    let mut rate_history = if rate_history_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(RateHistory::LEN);
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                &rate_history_pda,
+                rent_lamports,
+                RateHistory::LEN as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                rate_history_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[seeds::RATE_HISTORY, &[bump_seed]]],
+        )?;
+        
+        RateHistory {
+            checkpoint_count: 0,
+            checkpoints: [(0, 0); MAX_RATE_CHECKPOINTS],
+        }
+    } else {
+        assert_owner!(rate_history_account, program_id);
+        RateHistory::try_from_slice(&rate_history_account.data.borrow())?
+    };
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    rate_history.record(current_time, replaced_rate);
+    rate_history.serialize(&mut *rate_history_account.try_borrow_mut_data()?)?;
+    
+    msg!("Rate {} checkpointed at {}", replaced_rate, current_time);
+    
+    Ok(())
+}
+
+// Freeze or resume reward accrual, creating the reward freeze PDA on first
+// use with the caller funding the rent
+fn process_set_reward_freeze(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    frozen: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let authority_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pause_state_account = next_account_info(account_info_iter)?;
+    let reward_freeze_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify signature (mandatory signature verification)
+    assert_signer!(authority_account);
+    
+    // Verify caller is the pauser or the admin
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let (pause_state_pda, _) = Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id);
+    assert_pda!(pause_state_account, pause_state_pda);
+    let is_pauser = !pause_state_account.data_is_empty()
+        && pause_state_account.owner == program_id
+        && PauseState::try_from_slice(&pause_state_account.data.borrow())?.pauser == *authority_account.key;
+    if !is_pauser && program_state.admin != *authority_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let (reward_freeze_pda, bump_seed) = Pubkey::find_program_address(&[seeds::REWARD_FREEZE], program_id);
+    assert_pda!(reward_freeze_account, reward_freeze_pda);
+    
+    // Create the freeze state on first use with the caller funding the rent
+    let mut reward_freeze = if reward_freeze_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(RewardFreeze::LEN);
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                authority_account.key,
+                &reward_freeze_pda,
+                rent_lamports,
+                RewardFreeze::LEN as u64,
+                program_id,
+            ),
+            &[
+                authority_account.clone(),
+                reward_freeze_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[seeds::REWARD_FREEZE, &[bump_seed]]],
+        )?;
+        
+        RewardFreeze {
+            frozen_since: 0,
+            window_count: 0,
+            windows: [[0; 2]; MAX_REWARD_FREEZE_WINDOWS],
+        }
+    } else {
+        RewardFreeze::try_from_slice(&reward_freeze_account.data.borrow())?
+    };
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let changed = if frozen {
+        reward_freeze.freeze(current_time)?
+    } else {
+        reward_freeze.unfreeze(current_time)
+    };
+    reward_freeze.serialize(&mut *reward_freeze_account.try_borrow_mut_data()?)?;
+    
+    if !changed {
+        msg!("Reward accrual already {}", if frozen { "frozen" } else { "running" });
+    } else if frozen {
+        msg!("Reward accrual frozen at {}", current_time);
+    } else {
+        msg!("Reward accrual resumed at {} ({} freeze windows recorded)", current_time, reward_freeze.window_count);
+    }
+    
+    Ok(())
+}
+
+fn process_set_min_runway(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    min_runway_seconds: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let runway_config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify admin signature (mandatory signature verification)
+    assert_signer!(admin_account);
+    
+    if min_runway_seconds < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify caller is admin
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    if program_state.admin != *admin_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let (runway_config_pda, bump_seed) = Pubkey::find_program_address(&[seeds::RUNWAY_CONFIG], program_id);
+    assert_pda!(runway_config_account, runway_config_pda);
+    
+    // Create the config on first use with the admin funding the rent
+    if runway_config_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(std::mem::size_of::<RunwayConfig>());
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                &runway_config_pda,
+                rent_lamports,
+                std::mem::size_of::<RunwayConfig>() as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                runway_config_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[seeds::RUNWAY_CONFIG, &[bump_seed]]],
+        )?;
+    }
+    
+    let runway_config = RunwayConfig { min_runway_seconds };
+    runway_config.serialize(&mut *runway_config_account.try_borrow_mut_data()?)?;
+    
+    msg!("Minimum reward runway set to {} seconds", min_runway_seconds);
+    
+    Ok(())
+}
+
+// Set the share of YOS harvests paid to the treasury, creating the harvest
+// fee config on first use with the admin funding the rent
+fn process_set_harvest_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    harvest_fee_bps: u16,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let harvest_fee_config_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify admin signature (mandatory signature verification)
+    assert_signer!(admin_account);
+    
+    if harvest_fee_bps > MAX_HARVEST_FEE_BPS {
+        msg!("Harvest fee {} bps exceeds the {} bps cap", harvest_fee_bps, MAX_HARVEST_FEE_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify caller is admin
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    if program_state.admin != *admin_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let (harvest_fee_config_pda, bump_seed) = Pubkey::find_program_address(&[seeds::HARVEST_FEE_CONFIG], program_id);
+    assert_pda!(harvest_fee_config_account, harvest_fee_config_pda);
+    
+    // Create the config on first use with the admin funding the rent
+    if harvest_fee_config_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let rent_lamports = rent.minimum_balance(std::mem::size_of::<HarvestFeeConfig>());
+        
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                &harvest_fee_config_pda,
+                rent_lamports,
+                std::mem::size_of::<HarvestFeeConfig>() as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                harvest_fee_config_account.clone(),
+                system_program.clone(),
+            ],
+            &[&[seeds::HARVEST_FEE_CONFIG, &[bump_seed]]],
+        )?;
+    }
+    
+    let harvest_fee_config = HarvestFeeConfig { harvest_fee_bps };
+    harvest_fee_config.serialize(&mut *harvest_fee_config_account.try_borrow_mut_data()?)?;
+    
+    msg!("Harvest fee set to {} bps", harvest_fee_bps);
+    
+    Ok(())
+}
+
+fn process_record_snapshot(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    campaign_id: u64,
+    slot: u64,
+    merkle_root: [u8; 32],
+    total_staked: u64,
+    bonus_pool: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let snapshot_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let admin_yos_token_account = next_account_info(account_info_iter)?;
+    let snapshot_vault_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let ata_program_account = next_account_info(account_info_iter)?;
+    
+    // Verify admin signature (mandatory signature verification)
+    assert_signer!(admin_account);
+    
+    // Verify caller is admin
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    if program_state.admin != *admin_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    // Snapshots are retroactive and shares are divided by the total
+    if slot > Clock::get()?.slot || total_staked == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    let (snapshot_pda, bump_seed) = find_snapshot_address(program_id, campaign_id);
+    assert_pda!(snapshot_account, snapshot_pda);
+    if !snapshot_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(std::mem::size_of::<RewardSnapshot>());
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            admin_account.key,
+            &snapshot_pda,
+            rent_lamports,
+            std::mem::size_of::<RewardSnapshot>() as u64,
+            program_id,
+        ),
+        &[
+            admin_account.clone(),
+            snapshot_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[seeds::SNAPSHOT, &campaign_id.to_le_bytes(), &[bump_seed]]],
+    )?;
+    
+    let snapshot = RewardSnapshot {
+        campaign_id,
+        slot,
+        merkle_root,
+        total_staked,
+        bonus_pool,
+        total_claimed: 0,
+    };
+    snapshot.serialize(&mut *snapshot_account.try_borrow_mut_data()?)?;
+    
+    // Fund the snapshot's own vault with the whole bonus up front
+    if *yos_mint_account.key != program_state.yos_mint {
+        return Err(ProgramError::InvalidArgument);
+    }
+    create_vault(
+        admin_account,
+        snapshot_vault_account,
+        snapshot_account,
+        yos_mint_account,
+        system_program,
+        token_program,
+        ata_program_account,
+    )?;
+    if bonus_pool > 0 {
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                admin_yos_token_account.key,
+                snapshot_vault_account.key,
+                admin_account.key,
+                &[],
+                bonus_pool,
+            )?,
+            &[
+                admin_yos_token_account.clone(),
+                snapshot_vault_account.clone(),
+                admin_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+    }
+    
+    msg!("Snapshot {} recorded at slot {}: {} YOS over {} YOT",
+         campaign_id, slot, bonus_pool as f64 / 1_000_000_000.0, total_staked as f64 / 1_000_000_000.0);
+    
+    Ok(())
+}
+
+fn process_claim_snapshot_bonus(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    campaign_id: u64,
+    staked_amount: u64,
+    proof: &[[u8; 32]],
+) -> ProgramResult {
+    let known = WellKnownAccounts::new(program_id);
+    let mut resolver = known.resolver(accounts);
+    
+    // Get accounts
+    let user_account = resolver.next("user")?;
+    let user_yos_token_account = resolver.next("user YOS token account")?;
+    let (snapshot_pda, snapshot_bump) = find_snapshot_address(program_id, campaign_id);
+    let snapshot_account = resolver.by_key(&snapshot_pda, "snapshot")?;
+    let (claim_pda, claim_bump) = find_snapshot_claim_address(program_id, &snapshot_pda, user_account.key);
+    let claim_account = resolver.by_key(&claim_pda, "snapshot claim")?;
+    let program_state_account = resolver.by_key(&known.program_state, "program state")?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    let system_program = resolver.by_key(&system_program::id(), "system program")?;
+    let pause_state_account = resolver.by_key(&known.pause_state, "pause state")?;
+    let instructions_sysvar_account = resolver.by_key(&sysvar::instructions::id(), "instructions sysvar")?;
+    // Optional rent payer for the claim receipt, any other signer; defaults to the user
+    let fee_payer = accounts
+        .iter()
+        .find(|account| account.is_signer && account.key != user_account.key)
+        .unwrap_or(user_account);
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let snapshot_vault = find_associated_token_address(&snapshot_pda, &program_state.yos_mint);
+    let snapshot_vault_account = resolver.by_key(&snapshot_vault, "snapshot vault")?;
+    
+    // Verify user signature (mandatory signature verification)
+    assert_signer!(user_account);
+    
+    // Bonuses are rewards, so they stop with harvests
+    check_not_paused(program_id, pause_state_account, PAUSE_HARVEST)?;
+    check_top_level_instruction(program_id, instructions_sysvar_account)?;
+    
+    assert_owner!(snapshot_account, program_id);
+    let mut snapshot = RewardSnapshot::try_from_slice(&snapshot_account.data.borrow())?;
+    
+    if !claim_account.data_is_empty() {
+        msg!("Snapshot {} bonus already claimed", campaign_id);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    if !verify_snapshot_proof(&snapshot.merkle_root, snapshot_leaf(user_account.key, staked_amount), proof) {
+        return Err(StakingError::InvalidSnapshotProof.into());
+    }
+    
+    // Share of the pool in proportion to the snapshot balance
+    let bonus = tswap_math::mul_div(snapshot.bonus_pool, staked_amount, snapshot.total_staked);
+    snapshot.total_claimed = snapshot.total_claimed.checked_add(bonus)
+        .filter(|total_claimed| *total_claimed <= snapshot.bonus_pool)
+        .ok_or(ProgramError::InsufficientFunds)?;
+    
+    // Pay from the snapshot's vault only; a snapshot recorded before
+    // snapshots had vaults waits for FundSnapshotVault
+    if snapshot_vault_account.data_is_empty() {
+        msg!("Snapshot {} has no vault yet", campaign_id);
+        return Err(ProgramError::UninitializedAccount);
+    }
+    unpack_vault(snapshot_vault_account, &program_state.yos_mint, &snapshot_pda)?;
+    
+    // The receipt makes a second claim fail
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(std::mem::size_of::<SnapshotClaim>());
+    invoke_signed(
+        &system_instruction::create_account(
+            fee_payer.key,
+            &claim_pda,
+            rent_lamports,
+            std::mem::size_of::<SnapshotClaim>() as u64,
+            program_id,
+        ),
+        &[
+            fee_payer.clone(),
+            claim_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[seeds::SNAPSHOT_CLAIM, snapshot_pda.as_ref(), user_account.key.as_ref(), &[claim_bump]]],
+    )?;
+    let claim = SnapshotClaim {
+        owner: *user_account.key,
+        amount: bonus,
+    };
+    claim.serialize(&mut *claim_account.try_borrow_mut_data()?)?;
+    snapshot.serialize(&mut *snapshot_account.try_borrow_mut_data()?)?;
+    
+    if bonus > 0 {
+        invoke_signed(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                snapshot_vault_account.key,
+                user_yos_token_account.key,
+                snapshot_account.key,
+                &[],
+                bonus,
+            )?,
+            &[
+                snapshot_vault_account.clone(),
+                user_yos_token_account.clone(),
+                snapshot_account.clone(),
+                token_program.clone(),
+            ],
+            &[&[seeds::SNAPSHOT, &campaign_id.to_le_bytes(), &[snapshot_bump]]],
+        )?;
+    }
+    
+    msg!("Claimed {} YOS snapshot bonus for {} YOT staked at slot {}",
+         bonus as f64 / 1_000_000_000.0, staked_amount as f64 / 1_000_000_000.0, snapshot.slot);
+    
+    Ok(())
+}
+
+fn process_fund_snapshot_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    campaign_id: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let snapshot_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    let admin_yos_token_account = next_account_info(account_info_iter)?;
+    let snapshot_vault_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let token_program = next_account_info(account_info_iter)?;
+    let ata_program_account = next_account_info(account_info_iter)?;
+    
+    // Verify admin signature (mandatory signature verification)
+    assert_signer!(admin_account);
+    
+    // Verify caller is admin
+    let (program_state_pda, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    if program_state_pda != *program_state_account.key || program_state_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    if program_state.admin != *admin_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    let (snapshot_pda, _) = find_snapshot_address(program_id, campaign_id);
+    assert_pda!(snapshot_account, snapshot_pda);
+    assert_owner!(snapshot_account, program_id);
+    let snapshot = RewardSnapshot::try_from_slice(&snapshot_account.data.borrow())?;
+    
+    // Snapshots recorded with a vault were funded in full at record time
+    if !snapshot_vault_account.data_is_empty() {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    if *yos_mint_account.key != program_state.yos_mint {
+        return Err(ProgramError::InvalidArgument);
+    }
+    create_vault(
+        admin_account,
+        snapshot_vault_account,
+        snapshot_account,
+        yos_mint_account,
+        system_program,
+        token_program,
+        ata_program_account,
+    )?;
+    let unclaimed = snapshot.bonus_pool.saturating_sub(snapshot.total_claimed);
+    if unclaimed > 0 {
+        invoke(
+            &spl_token::instruction::transfer(
+                token_program.key,
+                admin_yos_token_account.key,
+                snapshot_vault_account.key,
+                admin_account.key,
+                &[],
+                unclaimed,
+            )?,
+            &[
+                admin_yos_token_account.clone(),
+                snapshot_vault_account.clone(),
+                admin_account.clone(),
+                token_program.clone(),
+            ],
+        )?;
+    }
+    
+    msg!("Snapshot {} vault funded with {} YOS", campaign_id, unclaimed as f64 / 1_000_000_000.0);
+    
+    Ok(())
+}
+
+fn process_set_harvest_threshold(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    harvest_threshold: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    
+    // Get accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let user_staking_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+    
+    // Verify user signature (mandatory signature verification)
+    assert_signer!(user_account);
+    
+    if harvest_threshold != 0 && harvest_threshold < MIN_HARVEST_THRESHOLD {
+        msg!("Harvest threshold must be at least {}", MIN_HARVEST_THRESHOLD);
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    assert_owner!(user_staking_account, program_id);
+    let (staking_pda, _) = Pubkey::find_program_address(&[seeds::STAKING, user_account.key.as_ref()], program_id);
+    assert_pda!(user_staking_account, staking_pda);
+    let mut staking_data = StakingAccount::try_from_slice(&user_staking_account.data.borrow())?;
+    if staking_data.owner != *user_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    // Grow legacy accounts to hold the threshold, topping up rent
+    grow_staking_account(user_account, user_staking_account, system_program)?;
+    
+    staking_data.harvest_threshold = harvest_threshold;
+    staking_data.save(user_staking_account)?;
+    
+    msg!("Harvest threshold set to {} raw rewards", harvest_threshold);
+    
+    Ok(())
+}
+
+// Load the pause state, creating it (unpaused, no pauser)
+// on first use with `payer` funding the rent
+fn load_or_create_pause_state<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    pause_state_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+) -> Result<PauseState, ProgramError> {
+    let (pause_state_pda, bump_seed) = Pubkey::find_program_address(&[seeds::PAUSE_STATE], program_id);
+    assert_pda!(pause_state_account, pause_state_pda);
+    
+    if !pause_state_account.data_is_empty() {
+        return Ok(PauseState::try_from_slice(&pause_state_account.data.borrow())?);
+    }
+    
+    let rent = Rent::get()?;
+    let rent_lamports = rent.minimum_balance(std::mem::size_of::<PauseState>());
+    
+    invoke_signed(
+        &system_instruction::create_account(
+            payer.key,
+            &pause_state_pda,
+            rent_lamports,
+            std::mem::size_of::<PauseState>() as u64,
+            program_id,
+        ),
+        &[
+            payer.clone(),
+            pause_state_account.clone(),
+            system_program.clone(),
+        ],
+        &[&[seeds::PAUSE_STATE, &[bump_seed]]],
+    )?;
+    
+    Ok(PauseState {
+        pauser: Pubkey::default(),
+        paused_mask: 0,
+    })
+}
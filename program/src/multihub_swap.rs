@@ -5,6 +5,7 @@ use solana_program::{
     msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
+    program_pack::Pack,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{rent::Rent, Sysvar},
@@ -15,6 +16,14 @@ use spl_token::{
     instruction as token_instruction,
 };
 
+#[macro_use]
+mod validation;
+mod account_resolver;
+use account_resolver::AccountResolver;
+
+// Custom error for an account resolved by address that was not passed
+const MISSING_ACCOUNT_ERROR: u32 = 5;
+
 // Define entrypoint for this program
 entrypoint!(process_instruction);
 
@@ -127,10 +136,7 @@ pub fn process_initialize(
     let rent_sysvar = next_account_info(account_info_iter)?;
 
     // Validate admin is signer
-    if !admin.is_signer {
-        msg!("Admin must be a signer");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(admin, "Admin must be a signer");
 
     // Create program state account
     let rent = Rent::from_account_info(rent_sysvar)?;
@@ -202,31 +208,30 @@ pub fn process_swap_token(
     msg!("CUSTOM ERROR CODE 11 = InvalidMint in the SPL token program - check if all required token accounts exist");
 
     // ===== ACCOUNT VALIDATION =====
-    if accounts.len() != 8 {
-        msg!("❌ ERROR: Invalid number of accounts: {}, expected 8", accounts.len());
-        return Err(ProgramError::InvalidArgument);
-    }
-
-    let account_info_iter = &mut accounts.iter();
+    // The program state, token program and mints are found by address, so
+    // only the user accounts have to come first and in order
+    let (program_state_pubkey, _) = Pubkey::find_program_address(&[b"program_state"], program_id);
+    let mut resolver = AccountResolver::new(
+        accounts,
+        &[program_state_pubkey, spl_token::id()],
+        ProgramError::Custom(MISSING_ACCOUNT_ERROR),
+    );
     
     // Extract accounts with detailed logging
-    let user_account = next_account_info(account_info_iter)?;
+    let user_account = resolver.next("user")?;
     msg!("User account: {}, is_signer: {}, is_writable: {}", 
         user_account.key, user_account.is_signer, user_account.is_writable);
-    if !user_account.is_signer {
-        msg!("❌ ERROR: User account must be a signer");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(user_account, "❌ ERROR: User account must be a signer");
 
-    let user_input_token_account = next_account_info(account_info_iter)?;
+    let user_input_token_account = resolver.next("user input token account")?;
     msg!("User input token account: {}, is_writable: {}", 
         user_input_token_account.key, user_input_token_account.is_writable);
 
-    let user_output_token_account = next_account_info(account_info_iter)?;
+    let user_output_token_account = resolver.next("user output token account")?;
     msg!("User output token account: {}, is_writable: {}", 
         user_output_token_account.key, user_output_token_account.is_writable);
 
-    let user_yos_token_account = next_account_info(account_info_iter)?;
+    let user_yos_token_account = resolver.next("user YOS token account")?;
     msg!("User YOS token account: {}, is_writable: {}", 
         user_yos_token_account.key, user_yos_token_account.is_writable);
 
@@ -236,22 +241,31 @@ pub fn process_swap_token(
         return Err(ProgramError::UninitializedAccount);
     }
 
-    let program_state_account = next_account_info(account_info_iter)?;
+    let program_state_account = resolver.by_key(&program_state_pubkey, "program state")?;
     msg!("Program state account: {}, is_writable: {}", 
         program_state_account.key, program_state_account.is_writable);
 
-    let token_program = next_account_info(account_info_iter)?;
-    msg!("Token program: {}, expected: {}", 
-        token_program.key, spl_token::id());
-    if token_program.key != &spl_token::id() {
-        msg!("❌ ERROR: Invalid token program ID");
-        return Err(ProgramError::IncorrectProgramId);
-    }
-
-    let input_token_mint = next_account_info(account_info_iter)?;
+    let token_program = resolver.by_key(&spl_token::id(), "token program")?;
+    msg!("Token program: {}", token_program.key);
+
+    // The mints are found by the addresses the user's token accounts hold,
+    // so they may be passed anywhere after the user accounts
+    let input_mint_pubkey = TokenAccount::unpack(&user_input_token_account.data.borrow())
+        .map(|account| account.mint)
+        .map_err(|_| {
+            msg!("❌ ERROR: Failed to unpack input token account data");
+            ProgramError::InvalidAccountData
+        })?;
+    let input_token_mint = resolver.by_key(&input_mint_pubkey, "input token mint")?;
     msg!("Input token mint: {}", input_token_mint.key);
 
-    let output_token_mint = next_account_info(account_info_iter)?;
+    let output_mint_pubkey = TokenAccount::unpack(&user_output_token_account.data.borrow())
+        .map(|account| account.mint)
+        .map_err(|_| {
+            msg!("❌ ERROR: Failed to unpack output token account data");
+            ProgramError::InvalidAccountData
+        })?;
+    let output_token_mint = resolver.by_key(&output_mint_pubkey, "output token mint")?;
     msg!("Output token mint: {}", output_token_mint.key);
 
     // ===== ACCOUNT OWNERSHIP CHECKS =====
@@ -17,6 +17,9 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use spl_token::state::{Account as TokenAccount, Mint};
 use std::convert::TryInto;
 
+#[macro_use]
+mod validation;
+
 // Program ID: Must match the ID in Cargo.toml
 solana_program::declare_id!("3cXKNjtRv8b1HVYU6vRDvmoSMHfXrWATCLFY2Y5wTsps");
 
@@ -125,7 +128,7 @@ pub struct ProgramState {
     // YOS cashback percentage
     pub yos_cashback_percent: u8,
     // Last update timestamp
-    pub last_update_time: u64,
+    pub last_update_time: i64,
 }
 
 impl IsInitialized for ProgramState {
@@ -244,10 +247,7 @@ fn process_initialize(
     }
     
     // Verify admin signature (must be signed)
-    if !admin_account.is_signer {
-        msg!("Error: Admin must sign the transaction");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(admin_account, "Error: Admin must sign the transaction");
 
     // Calculate program state PDA
     let (expected_state_address, state_bump) = find_program_state_address(program_id);
@@ -327,7 +327,7 @@ fn process_initialize(
     }
     
     // Get current time
-    let current_time = Clock::get()?.unix_timestamp as u64;
+    let current_time = Clock::get()?.unix_timestamp;
     
     // Initialize program state
     let program_state = ProgramState {
@@ -397,10 +397,7 @@ fn process_swap_token(
     msg!("Output token mint: {}", output_token_mint.key);
     
     // Verify user signature
-    if !user_account.is_signer {
-        msg!("Error: User must sign the transaction");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(user_account, "Error: User must sign the transaction");
     
     // Verify program state account is owned by our program
     if program_state_account.owner != program_id {
@@ -16,6 +16,9 @@ use spl_token::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
+#[macro_use]
+mod validation;
+
 // Program state structure 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct ProgramState {
@@ -57,10 +60,7 @@ pub fn process_initialize(
     let system_program = next_account_info(account_info_iter)?;
     
     // Verify admin signature
-    if !admin_account.is_signer {
-        msg!("❌ ERROR: Admin signature required");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(admin_account, "❌ ERROR: Admin signature required");
     
     // Calculate PDA for program state - using a new seed to avoid collision with existing state
     let (state_pda, state_bump) = Pubkey::find_program_address(&[b"state_v2"], program_id);
@@ -145,10 +145,7 @@ pub fn process_swap_token(
     let user_account = next_account_info(account_info_iter)?;
     msg!("User account: {}, is_signer: {}, is_writable: {}", 
         user_account.key, user_account.is_signer, user_account.is_writable);
-    if !user_account.is_signer {
-        msg!("❌ ERROR: User account must be a signer");
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(user_account, "❌ ERROR: User account must be a signer");
 
     let user_input_token_account = next_account_info(account_info_iter)?;
     msg!("User input token account: {}, is_writable: {}", 
@@ -1,28 +1,65 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
     clock::Clock,
-    entrypoint,
     entrypoint::ProgramResult,
+    instruction::{get_stack_height, AccountMeta, Instruction, TRANSACTION_LEVEL_STACK_HEIGHT},
+    keccak,
+    log::sol_log_data,
     msg,
-    program::{invoke, invoke_signed},
+    program::{invoke, invoke_signed, set_return_data},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
     rent::Rent,
     system_instruction,
-    sysvar::Sysvar,
+    sysvar::{instructions as instructions_sysvar, Sysvar},
 };
 use spl_token::{instruction as token_instruction, state::Account as TokenAccount};
 use std::convert::TryInto;
+use tswap_addresses::swap as seeds;
+use tswap_math::{
+    apply_bonus_bps, bonding_curve_cost, bps_of, cashback_amount, constant_product_amount_out, contribution_reward,
+    initial_lp_shares, max_amount_in, mul_div, elapsed_seconds, proportional_lp_shares, slippage_min_amount_out,
+    split_amount_in, streamed_amount, time_weighted_reserve, twap_single_sided_lp_shares, withdrawal_amount, FeeSplit,
+    MathError, BPS_DENOMINATOR,
+};
+
+#[macro_use]
+mod validation;
+#[cfg(feature = "paranoid")]
+mod swap_invariants;
+mod token_swap_venue;
+
+use token_swap_venue::{token_balance, Venue};
+pub use token_swap_venue::TOKEN_SWAP_PROGRAM_ID;
 
 // Define the program ID here (will be replaced during deployment)
 solana_program::declare_id!("Cohae9agySEgC9gyJL1QHCJWw4q58R7Wshr3rpPJHU7L");
 
+// Custom error codes
+#[derive(Debug)]
+pub enum SwapError {
+    // A deposit would raise a pool's reserve of a mint above its cap
+    DepositCapExceeded = 0,
+}
+
+impl From<SwapError> for ProgramError {
+    fn from(e: SwapError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
 // Instructions supported by this program
 #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
 pub enum SwapInstruction {
     // Initialize the program state with admin and token addresses
+    // Accounts: payer, program state, program authority, system program, rent
+    // sysvar, the YOT and YOS Metaplex metadata accounts, then optionally the
+    // associated token program, token program, YOT, YOS and wSOL mints,
+    // treasury and the vaults it creates: the authority's YOT, YOS and wSOL and
+    // the treasury's YOT and YOS associated token accounts
     Initialize {
         // Admin who can manage the program
         admin: Pubkey,
@@ -41,7 +78,9 @@ pub enum SwapInstruction {
         // Referral payment rate (e.g., 0.5%)
         referral_rate: u64,
     },
-    // Swap tokens with cashback and liquidity contribution
+    // Swap tokens with cashback and liquidity contribution. A signer passed
+    // among the trailing accounts pays the rent of the user stats and receipt
+    // PDAs in place of the user
     Swap {
         // Amount of input tokens to swap
         amount_in: u64,
@@ -49,6 +88,7 @@ pub enum SwapInstruction {
         min_amount_out: u64,
     },
     // Update program parameters (admin only)
+    // Accounts: admin, program state, parameter history PDA, system program
     UpdateParameters {
         // New contribution rate to liquidity pool
         lp_contribution_rate: Option<u64>,
@@ -68,6 +108,1296 @@ pub enum SwapInstruction {
     },
     // Close and reset program state (admin only)
     CloseProgram {},
+    // Latency-sensitive swap that skips optional features (YOS cashback,
+    // stats, referral, events) but still prices against the pair's pool.
+    // Same accounts as Swap minus the YOS account.
+    // Budgeted at SWAP_LITE_COMPUTE_UNITS against SWAP_COMPUTE_UNITS for Swap.
+    SwapTokenLite {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+    },
+    // Create the swap history ring buffer PDA (anyone can pay for it)
+    InitializeSwapHistory {},
+    // Create the per-pair pool PDA that tracks cumulative counters (admin only)
+    InitializePool {
+        // First token mint of the pair
+        mint_a: Pubkey,
+        // Second token mint of the pair
+        mint_b: Pubkey,
+    },
+    // Deposit both sides of a pair into its pool in exchange for LP shares.
+    // The first deposit permanently locks MINIMUM_LIQUIDITY shares. A signer
+    // passed after the system program pays the new position's rent
+    AddLiquidity {
+        // Amount of mint_a to deposit
+        amount_a: u64,
+        // Amount of mint_b to deposit
+        amount_b: u64,
+        // Minimum LP shares to receive
+        min_shares: u64,
+    },
+    // Retired: swap fees and contributions always compound into the pool's
+    // reserves, where LP shares claim them. Kept so the variants after it
+    // keep their discriminants; always fails.
+    SetPoolFeeReinvest {
+        // Ignored
+        reinvest_fees: bool,
+    },
+    // Create the program-owned vault holding the SOL admin commission
+    InitializeSolFeeVault {},
+    // Withdraw collected SOL commission from the fee vault to the admin
+    // wallet, whoever of the admin and permission holders signs (admin
+    // only). Withdrawals above the treasury's approval amount also need the
+    // signatures of `threshold` treasury approvers.
+    // Accounts: signer, program state, SOL fee vault, treasury PDA, admin
+    // wallet, then approver signers
+    WithdrawSolFees {
+        // Lamports to withdraw
+        amount: u64,
+    },
+    // Swap that may fill only part of a large order. Same accounts as Swap.
+    SwapPartialFill {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens for the full amount_in; scaled
+        // down pro rata when only part of the order fills
+        min_amount_out: u64,
+        // Fill up to the price-impact limit and leave the rest with the
+        // user instead of failing the whole order
+        allow_partial: bool,
+    },
+    // Swap whose output is split among recipient token accounts. Same
+    // accounts as Swap with every optional slot filled (the program id as a
+    // placeholder), followed by one output-mint token account per weight.
+    SwapSplit {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum total amount of output tokens to receive
+        min_amount_out: u64,
+        // Share of the output per recipient in basis points, summing to 10000
+        weights_bps: Vec<u16>,
+    },
+    // Swap tagged with a memo recorded in the swap event. Same accounts as
+    // Swap with every optional slot filled, optionally followed by the SPL
+    // Memo program to also CPI the memo.
+    SwapWithMemo {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+        // Memo bytes, at most MAX_MEMO_LEN
+        memo: Vec<u8>,
+    },
+    // Create or update the per-cluster config PDA (admin only)
+    SetConfig {
+        // Cluster this deployment serves (CLUSTER_* constants)
+        cluster: u8,
+        // SOL-YOT pool used for SOL routes on this cluster
+        sol_yot_pool: Pubkey,
+        // Stablecoin mint (e.g. USDC) on this cluster
+        stable_mint: Pubkey,
+    },
+    // Appoint the pauser allowed to change the pause mask (admin only)
+    SetPauser {
+        // New pauser public key
+        pauser: Pubkey,
+    },
+    // Disable individual instructions with PAUSE_* bits (pauser or admin)
+    SetPausedMask {
+        // Bitmask of paused instructions
+        paused_mask: u8,
+    },
+    // Record the governance/multisig expected to hold the upgrade authority (admin only)
+    SetGovernance {
+        // Governance or multisig public key
+        governance: Pubkey,
+    },
+    // Verify the program's upgrade authority matches the recorded governance
+    // and emit the result. Accounts: program state, program data account.
+    CheckUpgradeAuthority {},
+    // Create the signer's referrer PDA so swaps can pay them referral fees
+    RegisterReferrer {},
+    // Create a time-boxed referral campaign with a boosted rate (admin only)
+    CreateReferralCampaign {
+        // Campaign identifier used in the PDA seeds
+        campaign_id: u64,
+        // Referral payment rate while the campaign runs (basis points)
+        referral_rate: u64,
+        // Unix timestamp after which the campaign no longer applies
+        end_time: i64,
+    },
+    // Attach the signer's referrer PDA to a campaign
+    JoinReferralCampaign {},
+    // Set the share of the signer's referral fee rebated to their referees
+    SetReferrerRebate {
+        // Rebate share of the referral fee in basis points
+        rebate_bps: u16,
+    },
+    // Permissionless crank ranking the supplied user stats PDAs (remaining
+    // accounts) on the epoch leaderboard, creating it on first use
+    UpdateLeaderboard {
+        // Leaderboard epoch to update
+        epoch: u64,
+    },
+    // Close the ranking of a past epoch and set its YOS prize (admin only)
+    FinalizeLeaderboard {
+        // Leaderboard epoch to finalize
+        epoch: u64,
+        // YOS prize shared equally by the ranked wallets
+        prize_amount: u64,
+    },
+    // Mint the signer's share of a finalized leaderboard prize
+    ClaimLeaderboardPrize {
+        // Leaderboard epoch to claim from
+        epoch: u64,
+    },
+    // Set the reverse-swap window used to withhold cashback from wash trades
+    // (admin only, 0 disables detection)
+    SetWashTradeWindow {
+        // Window in slots
+        window_slots: u64,
+    },
+    // Switch between minting cashback per swap and accruing it in ledgers
+    // (admin only)
+    SetCashbackMode {
+        // Accrue cashback in per-user ledgers
+        accrue: bool,
+        // Claim bonus per full week waited (basis points)
+        wait_bonus_bps: u64,
+    },
+    // Create the signer's cashback ledger PDA
+    InitializeCashbackLedger,
+    // Mint the signer's accrued cashback plus any waiting bonus
+    ClaimCashback,
+    // Set the YOS to YOT redemption rate (admin only, 0 uses the YOT/YOS
+    // pool's time-weighted price)
+    SetRedemptionRate {
+        // YOT per YOS, scaled by REDEMPTION_RATE_PRECISION
+        rate: u64,
+    },
+    // Burn YOS for YOT paid from the redemption vault, at the redemption
+    // rate or else the YOT/YOS pool's time-weighted price
+    RedeemYos {
+        // Amount of YOS to redeem
+        amount: u64,
+    },
+    // Set the YOS burned per basis point of cashback boost (admin only)
+    SetBoostPrice {
+        // YOS per basis point (0 disables boosting)
+        yos_per_boost_bps: u64,
+    },
+    // Burn YOS to raise the signer's cashback boost for BOOST_DURATION_SECONDS,
+    // emitting a boost_raised event
+    // Accounts: user, program state (writable), user stats, user YOS
+    // account, YOS mint, token program, system program, then optionally a
+    // signer paying the user stats rent in place of the user
+    BurnYosForBoost {
+        // Amount of YOS to burn
+        amount: u64,
+    },
+    // Buy YOT with the configured stablecoin through its registered pool,
+    // applying the regular contribution and cashback split. Takes the config
+    // PDA followed by the Swap accounts
+    BuyWithStable {
+        // Stablecoin mint paid in; must match the config
+        stable_mint: Pubkey,
+        // Amount of stablecoin to spend
+        amount: u64,
+        // Minimum amount of YOT to receive
+        min_amount_out: u64,
+    },
+    // Register the Metaplex collection whose holders earn a cashback boost
+    // (admin only)
+    SetBoostCollection {
+        // Verified collection mint
+        collection: Pubkey,
+        // Cashback boost for holders (basis points, 0 disables)
+        boost_bps: u64,
+    },
+    // Replace the programs allowed to CPI into privileged flows (admin only)
+    SetCpiAllowlist {
+        // Allowlisted program ids, at most MAX_CPI_CALLERS
+        programs: Vec<Pubkey>,
+    },
+    // Swap without admin, swap or referral fees. Only callable via CPI from
+    // an allowlisted program; takes the instructions sysvar and allowlist PDA
+    // followed by the Swap accounts
+    SwapFeeExempt {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+    },
+    // Replace the pools the liquidity contribution is split between (admin
+    // only). Swaps then take the contribution targets PDA after the NFT
+    // slots, followed by every target pool in order. An empty list keeps
+    // the whole contribution in the swapped pool.
+    // Accounts: admin, program state, contribution targets PDA, system program
+    SetContributionTargets {
+        // Target pools with their share in basis points, summing to 10000
+        targets: Vec<(Pubkey, u16)>,
+    },
+    // Swap without the liquidity contribution, paying the higher opt-out
+    // swap fee instead. Same accounts as Swap
+    SwapWithoutContribution {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+    },
+    // Set the swap fee charged to swaps that skip the liquidity contribution
+    // (admin only)
+    SetOptOutSwapFee {
+        // Swap fee in basis points, above swap_fee_rate (0 disables opting out)
+        swap_fee_rate: u64,
+    },
+    // Create the signer's contribution ledger PDA. Swaps record their
+    // liquidity contribution in it when it follows the contribution targets
+    // Accounts: user, contribution ledger PDA, system program
+    InitializeContributionLedger,
+    // Set the weekly YOS reward on recorded contributions and the loyalty
+    // bonus earned per week a contribution stays in the pool (admin only)
+    SetContributionRewards {
+        // YOS paid per week per contributed unit (basis points, 0 disables)
+        weekly_rate_bps: u64,
+        // Reward bonus per full week of contribution age (basis points)
+        loyalty_bonus_bps_per_week: u64,
+    },
+    // Pay every supplied ledger the YOS earned for its full weeks since the
+    // last distribution. Permissionless until a distribution thread is
+    // registered; from then on the crank must be the thread, signing.
+    // Accounts: program state, YOS mint, program authority, token program,
+    // crank, then (contribution ledger, owner's YOS account) pairs
+    DistributeContributionRewards,
+    // Set how long new contribution tranches stay locked (admin only)
+    SetContributionLock {
+        // Lock period in seconds (0 makes new tranches withdrawable at once)
+        lock_seconds: i64,
+    },
+    // Withdraw LP shares deposited by the signer's swap contributions from a
+    // pool, oldest tranche first. Fails if unlocked tranches cannot cover
+    // `shares`.
+    // Accounts: user, contribution ledger PDA, pool, user token A, user
+    // token B, vault A, vault B, program authority, token program
+    WithdrawContribution {
+        // LP shares to withdraw
+        shares: u64,
+        // Minimum amount of mint_a to receive
+        min_amount_a: u64,
+        // Minimum amount of mint_b to receive
+        min_amount_b: u64,
+    },
+    // Escrow `amount_in` for a limit order (one fill) or a DCA order (fills
+    // of `amount_per_fill` every `interval_seconds`) executed by keepers
+    // against the pair's pool. The escrow is the order PDA's token account
+    // of the input mint.
+    // Accounts: maker, order PDA, maker input token account, escrow, token
+    // program, system program
+    CreateOrder {
+        // Maker-chosen id, unique per maker
+        order_id: u64,
+        // Output token mint
+        mint_out: Pubkey,
+        // Total input to escrow
+        amount_in: u64,
+        // Input sold per fill (amount_in for limit orders)
+        amount_per_fill: u64,
+        // Minimum output the maker receives per full fill, after the bounty
+        min_amount_out_per_fill: u64,
+        // Seconds between fills (0 for limit orders)
+        interval_seconds: i64,
+        // Share of each fill's output paid to the keeper (basis points)
+        bounty_bps: u16,
+        // Time after which the order can no longer fill and may be swept
+        // (0 never expires)
+        expires_at: i64,
+    },
+    // Execute the next fill of an order and take its keeper bounty
+    // (permissionless)
+    // Accounts: keeper, program state, order PDA, escrow, pool, input vault,
+    // output vault, maker output token account, keeper output token
+    // account, program authority, token program
+    FillOrder,
+    // Return an order's remaining escrow and close it (maker only)
+    // Accounts: maker, order PDA, escrow, maker input token account, token
+    // program
+    CancelOrder,
+    // Refund an expired order's escrow to the maker and close it, returning
+    // its rent to the maker minus ORDER_SWEEP_TIP_LAMPORTS for the keeper
+    // (permissionless)
+    // Accounts: keeper, maker, order PDA, escrow, maker input token account,
+    // token program
+    SweepExpiredOrder,
+    // Create a Clockwork thread, with the admin as its authority, that runs
+    // DistributeContributionRewards weekly for the supplied ledgers, and
+    // make it the only crank allowed to distribute (admin only). Ledgers
+    // created later are added with UpdateDistributionThread.
+    // Accounts: admin, program state, program authority, thread, Clockwork
+    // thread program, system program, YOS mint, token program, then
+    // (contribution ledger, owner's YOS account) pairs
+    RegisterDistributionThread {
+        // Thread id, unique per admin
+        thread_id: Vec<u8>,
+        // Lamports the admin moves into the thread to fund its executions
+        amount: u64,
+    },
+    // Move the live statistics counters into the DailyStats PDA of the day
+    // they started and restart them for today (permissionless crank)
+    // Accounts: payer, program state, daily stats PDA, system program
+    SnapshotDailyStats,
+    // Initialize an SPL account compression Merkle tree, with the program
+    // authority as tree authority, and record it as the activity tree that
+    // swaps append compressed receipts to (admin only). The client creates
+    // the tree account, owned by the compression program and sized for
+    // `max_depth` and `max_buffer_size`, beforehand.
+    // Accounts: admin, program state, Merkle tree, program authority,
+    // account compression program, noop program
+    InitializeActivityTree {
+        // Tree depth; the tree holds 2^max_depth records
+        max_depth: u32,
+        // Concurrent changes the tree tolerates per slot
+        max_buffer_size: u32,
+    },
+    // Create the signer's idempotency log PDA used by SwapIdempotent
+    // Accounts: user, idempotency log PDA, system program
+    InitializeIdempotencyLog,
+    // Swap that fails if the signer already swapped with the same
+    // client-generated key within IDEMPOTENCY_WINDOW_SLOTS, so wallets can
+    // safely resubmit after a timeout. Same accounts as Swap, followed by
+    // the signer's idempotency log PDA after the optional accounts.
+    SwapIdempotent {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+        // Client-generated id, unique per intended swap
+        idempotency_key: [u8; 16],
+    },
+    // Set the treasury approver set. The admin creates it; afterwards only
+    // `threshold` signatures from the current approvers can change it.
+    // Accounts: payer, program state, treasury PDA, system program, then
+    // current approver signers
+    SetTreasuryApprovers {
+        // Approver wallets, at most MAX_TREASURY_APPROVERS
+        approvers: Vec<Pubkey>,
+        // Approvals required (K of N)
+        threshold: u8,
+        // Withdrawals above this many lamports, or raw token units from the
+        // treasury's token accounts, require approvals
+        approval_amount: u64,
+    },
+    // Escrow `amount_offered` of one side of a YOT block trade at a fixed
+    // price of `amount_requested` of the other side. The escrow is the
+    // offer PDA's token account of the offered mint.
+    // Accounts: maker, program state, offer PDA, maker offered-mint token
+    // account, escrow, token program, system program
+    CreateOtcOffer {
+        // Maker-chosen id, unique per maker
+        offer_id: u64,
+        // Only wallet allowed to accept (default pubkey for anyone)
+        taker: Pubkey,
+        // Mint the maker wants in return; YOT unless YOT is offered
+        mint_requested: Pubkey,
+        // Amount escrowed
+        amount_offered: u64,
+        // Amount the taker pays the maker
+        amount_requested: u64,
+    },
+    // Pay an offer's price to the maker and receive its escrow minus the
+    // admin fee, closing the offer
+    // Accounts: taker, program state, offer PDA, maker, escrow, taker
+    // offered-mint token account, taker requested-mint token account, maker
+    // requested-mint token account, offered-mint fee vault, token program
+    AcceptOtcOffer,
+    // Return an offer's escrow and close it (maker only)
+    // Accounts: maker, offer PDA, escrow, maker offered-mint token account,
+    // token program
+    CancelOtcOffer,
+    // Open a bonding-curve presale for a new token. The creator deposits
+    // `supply` for sale plus `liquidity_tokens` reserved for the pool into
+    // the launch PDA's token account of the mint.
+    // Accounts: creator, program state, launch PDA, creator token account,
+    // token escrow, token program, system program
+    CreateLaunch {
+        // Mint buyers pay in
+        quote_mint: Pubkey,
+        // Tokens sold along the curve
+        supply: u64,
+        // Tokens paired with the proceeds in the pool at close
+        liquidity_tokens: u64,
+        // Price of the first token, scaled by CURVE_PRICE_PRECISION
+        start_price: u64,
+        // Price of the last token, scaled by CURVE_PRICE_PRECISION
+        end_price: u64,
+        // Sales stop after this time (unix timestamp)
+        end_time: i64,
+    },
+    // Buy `amount` tokens at the current point on a launch's curve. The
+    // proceeds escrow is the launch PDA's token account of the quote mint.
+    // Accounts: buyer, program state, launch PDA, token escrow, proceeds
+    // escrow, buyer quote token account, buyer token account, token program
+    BuyLaunch {
+        // Tokens to buy
+        amount: u64,
+        // Most quote the buyer will pay
+        max_cost: u64,
+    },
+    // Close a launch once it has ended or sold out (anyone can call it). The
+    // proceeds and reserved tokens are deposited into the pair's pool,
+    // created if missing, for LP shares owned by the creator; unsold tokens
+    // go back to the creator. A pool that already holds liquidity must be
+    // priced within LAUNCH_SEED_PRICE_TOLERANCE_BPS of the launch's ratio.
+    // Accounts: payer, launch PDA, creator, token escrow, proceeds escrow,
+    // creator token account, pool PDA, creator LP position PDA, pool vault
+    // of the token, pool vault of the quote mint, program authority, token
+    // program, system program
+    CloseLaunch,
+    // Escrow `amount` YOT paid to `recipient` per second from `start_time`
+    // to `end_time`. The escrow is the stream PDA's YOT token account.
+    // Accounts: sender, program state, stream PDA, sender YOT token account,
+    // escrow, token program, system program
+    CreateStream {
+        // Sender-chosen id, unique per sender
+        stream_id: u64,
+        // Wallet paid by the stream
+        recipient: Pubkey,
+        // YOT escrowed and paid out over the stream
+        amount: u64,
+        // Payment starts at this time (unix timestamp)
+        start_time: i64,
+        // Everything is paid by this time (unix timestamp)
+        end_time: i64,
+    },
+    // Pay the recipient everything streamed so far (recipient only)
+    // Accounts: recipient, stream PDA, escrow, recipient YOT token account,
+    // token program
+    WithdrawStream,
+    // Pay the recipient what has streamed, refund the rest and close the
+    // stream (sender only)
+    // Accounts: sender, stream PDA, escrow, sender YOT token account,
+    // recipient YOT token account, token program
+    CancelStream,
+    // Register a market-maker wallet, or update its entry (admin only).
+    // Swaps by a fee-exempt market maker that pass its registry PDA after
+    // the idempotency log pay no swap fee and earn no cashback.
+    // Accounts: admin, program state, market maker PDA, system program
+    SetMarketMaker {
+        // Market-maker wallet
+        wallet: Pubkey,
+        // Waive the swap fee (and cashback) on this wallet's swaps
+        fee_exempt: bool,
+    },
+    // Start the timelock on unwinding the treasury's LP position in an
+    // spl-token-swap pool, e.g. while that venue is being exploited (admin
+    // only)
+    // Accounts: admin, program state, spl-token-swap pool, emergency
+    // withdrawal PDA, system program
+    ScheduleEmergencyWithdrawal,
+    // Drop a scheduled emergency withdrawal (admin only)
+    // Accounts: admin, program state, spl-token-swap pool, emergency
+    // withdrawal PDA
+    CancelEmergencyWithdrawal,
+    // Once the timelock has passed, withdraw all of the treasury's LP tokens
+    // of the pool for both of its tokens, parked in the treasury's token
+    // accounts (admin only). WithdrawTreasury pays the proceeds out.
+    // Accounts: admin, program state, emergency withdrawal PDA, pool, pool
+    // authority, pool mint, pool token A, pool token B, pool fee account,
+    // treasury PDA, treasury LP token account, treasury token account of the
+    // pool's mint A, treasury token account of its mint B, token program,
+    // spl-token-swap program
+    ExecuteEmergencyWithdrawal,
+    // Withdraw `amount` of the treasury's LP tokens of one spl-token-swap
+    // pool and deposit both tokens received into another pool of the same
+    // pair, in one transaction (admin only). The deposit mints as many LP
+    // tokens as the received amounts cover at the target's ratio; whatever
+    // one side has left over stays in the treasury.
+    // Accounts: admin, program state, treasury PDA, from pool, from pool
+    // authority, from pool mint, from pool token A, from pool token B, from
+    // pool fee account, treasury LP token account of the from pool, to pool,
+    // to pool authority, to pool mint, to pool token A, to pool token B, to
+    // pool fee account, treasury LP token account of the to pool, treasury
+    // token account of the from pool's mint A, treasury token account of its
+    // mint B, token program, spl-token-swap program
+    MigrateLiquidity {
+        // spl-token-swap pool giving up the liquidity
+        from_pool: Pubkey,
+        // spl-token-swap pool receiving it
+        to_pool: Pubkey,
+        // LP tokens of `from_pool` to withdraw
+        amount: u64,
+    },
+    // Trust a Wormhole emitter's purchase intents and set the bridged mint
+    // they are paid in (admin only). Bridged funds are delivered to the
+    // cross-chain config PDA's token account of that mint.
+    // Accounts: admin, program state, cross-chain config PDA, system program
+    SetCrossChainEmitter {
+        // Wormhole chain id of the emitter
+        emitter_chain: u16,
+        // Emitter contract address, left-padded to 32 bytes
+        emitter_address: [u8; 32],
+        // Bridged mint intents are paid in
+        quote_mint: Pubkey,
+    },
+    // Buy YOT for the target wallet of a purchase intent in a Wormhole VAA
+    // already verified and posted by the core bridge (anyone can relay it).
+    // Each VAA executes once.
+    // Accounts: relayer, program state, cross-chain config PDA, posted VAA,
+    // intent receipt PDA, intent escrow, pool, quote vault, YOT vault,
+    // target YOT token account, program authority, token program, system
+    // program
+    ExecutePurchaseIntent,
+    // Swap that also writes a SwapReceipt PDA holding its amounts, fees and
+    // route hash, paid for by the signer, as an on-chain accounting proof.
+    // Same accounts as Swap with the system program supplied, followed by
+    // the receipt PDA after the market maker entry.
+    SwapWithReceipt {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+        // Signer-chosen id, unique per signer
+        receipt_id: u64,
+    },
+    // Swap bounded by a tolerance instead of an absolute minimum: the
+    // program quotes the net input at the pool's price before the swap and
+    // rejects output more than `slippage_bps` below it. Same accounts as
+    // Swap with the pair's pool supplied.
+    SwapWithSlippage {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Tolerated shortfall from the on-chain quote, in basis points
+        slippage_bps: u16,
+    },
+    // Require every swap to trade a hub token on at least one side (admin
+    // only). YOT and the hub list in program state always qualify; other
+    // mints pass their approved hub token registry entry after the swap
+    // receipt.
+    // Accounts: admin, program state
+    SetRequireHubSide {
+        // Enforce the rule
+        required: bool,
+    },
+    // Approve a mint as a hub token, or revoke it (admin only)
+    // Accounts: admin, program state, hub token PDA, system program
+    SetHubToken {
+        // Hub token mint
+        mint: Pubkey,
+        // Whether swaps may use it in place of YOT
+        approved: bool,
+    },
+    // Replace the hub tokens routes are built through besides YOT, e.g.
+    // USDC and wrapped SOL (admin only)
+    // Accounts: admin, program state
+    SetHubTokens {
+        // Hub mints, at most MAX_HUB_TOKENS
+        hub_tokens: Vec<Pubkey>,
+    },
+    // Close an empty authority-owned vault of a delisted mint, sending its
+    // rent to the treasury (admin only)
+    // Accounts: admin, program state, program authority, vault, hub token
+    // registry entry of the vault's mint, treasury, token program
+    CloseVault {},
+    // Cap a pool's reserve of `mint`; deposits past it fail with
+    // DepositCapExceeded (admin only)
+    // Accounts: admin, program state, pool
+    SetPoolDepositCap {
+        // Either of the pool's mints
+        mint: Pubkey,
+        // Largest reserve deposits may reach, 0 for no cap
+        max_reserve: u64,
+    },
+    // Publish the routes a pair may be swapped through and flag it as
+    // sensitive; an empty list clears the flag (admin only). Swaps of a
+    // sensitive pair pass its route allowlist after the hub token registry
+    // entry, and the swap_route_hash of their route must be one of the
+    // published hashes: a standalone swap is the route of its two mints, and
+    // every SwapRouteHop of a route trading the pair end to end carries the
+    // whole route.
+    // Accounts: admin, program state, route allowlist PDA, system program
+    SetAllowedRoutes {
+        // Either mint of the pair
+        mint_a: Pubkey,
+        // The other mint
+        mint_b: Pubkey,
+        // Allowed route hashes, at most MAX_ALLOWED_ROUTES
+        route_hashes: Vec<[u8; 32]>,
+    },
+    // Permissionless crank clearing the expired cashback boosts of the
+    // supplied user stats PDAs (remaining accounts), emitting a
+    // boost_expired event for each, so stored boosts are always in effect
+    // Accounts: program state, then the user stats PDAs
+    ExpireBoosts {},
+    // Grant `holder` the admin instructions with the given discriminants
+    // (see admin_instruction), replacing its earlier grants; an empty list
+    // revokes them all (admin or governance only). A holder calls a granted
+    // instruction in the admin's place, passing the permission matrix PDA
+    // as an extra account
+    // Accounts: admin or governance, program state, permission matrix PDA,
+    // system program
+    SetPermissions {
+        // Wallet the grants are for
+        holder: Pubkey,
+        // Discriminants of the instructions it may call
+        instructions: Vec<u8>,
+    },
+    // Create a pool's time-weighted reserves PDA, seeded with its current
+    // reserves (admin only). Swaps through the pool pass it as an extra
+    // account to keep it current, and only swaps passing it record their
+    // liquidity contribution as the user's LP shares.
+    // Accounts: admin, program state, pool, pool TWAP PDA, system program
+    InitializePoolTwap {},
+    // Run a swap instruction's validation and pricing with its accounts and
+    // return a SwapSimulation via return data, without moving tokens or
+    // writing any account, so clients can preflight any swap variant against
+    // the program itself
+    // Accounts: as the wrapped swap instruction
+    SimulateSwap {
+        // Data of the Swap, SwapTokenLite or other swap variant to run,
+        // SwapFeeExempt and BuyWithStable included
+        swap_data: Vec<u8>,
+    },
+    // Burn LP shares of the signer's position in a pool and pay out both
+    // sides in proportion to the pool's reserves
+    // Accounts: user, pool, LP position PDA, user token A, user token B,
+    // vault A, vault B, program authority, token program
+    RemoveLiquidity {
+        // LP shares to burn
+        shares: u64,
+        // Minimum amount of mint_a to receive
+        min_amount_a: u64,
+        // Minimum amount of mint_b to receive
+        min_amount_b: u64,
+    },
+    // Pay tokens out of one of the treasury PDA's token accounts to a
+    // recipient token account of the same mint (admin only). Amounts above
+    // the treasury's approval amount also need its approvers' signatures.
+    // Accounts: admin, program state, treasury PDA, treasury token account,
+    // recipient token account, token program, then approver signers
+    WithdrawTreasury {
+        // Raw token units to withdraw
+        amount: u64,
+    },
+    // Swap whatever the user's input token account holds above
+    // `keep_balance`, so a later hop of a route spends exactly what the
+    // earlier hops paid into it
+    // Accounts: as Swap
+    SwapBalance {
+        // Input balance left untouched
+        keep_balance: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+    },
+    // Move a pool's admin fees from its vaults to the treasury's token
+    // accounts of the two mints (anyone can crank it)
+    // Accounts: pool, vault A, vault B, treasury token account A, treasury
+    // token account B, program authority, token program
+    CollectAdminFees {},
+    // One hop of a route declared in full, so every hop holds the whole
+    // route to the allowlist of the pair it trades end to end as well as to
+    // its own pair's. The hop must trade one consecutive pair of `route`.
+    // Accounts: as Swap, with the route's end pair allowlist after the
+    // swapped pair's when the end pair is sensitive
+    SwapRouteHop {
+        // The route's mints in order, input first, at most MAX_ROUTE_HOPS
+        // hops
+        route: Vec<Pubkey>,
+        // On the route's first hop, the amount of input tokens to swap; on
+        // later hops, the input balance left untouched as in SwapBalance
+        amount: u64,
+        // Minimum amount of output tokens to receive
+        min_amount_out: u64,
+    },
+    // Swap through an external spl-token-swap pool. The pool's reported
+    // output is never used: the hop pays what the user's destination
+    // account actually gained, which must reach `min_amount_out`.
+    // Accounts: user (signer), program state, pool, pool authority, pool
+    // mint, pool token A, pool token B, pool fee account, user input token
+    // account, user output token account, token program, spl-token-swap
+    // program
+    SwapThroughTokenSwap {
+        // Amount of input tokens to swap
+        amount_in: u64,
+        // Minimum output measured in the user's output token account
+        min_amount_out: u64,
+    },
+    // Replace the ledgers the registered distribution thread distributes to,
+    // so ledgers created since it was registered are settled too (admin
+    // only; the admin must be the thread's authority)
+    // Accounts: as RegisterDistributionThread
+    UpdateDistributionThread,
+}
+
+// Borsh discriminants of the admin instructions, which the permission
+// matrix grants by
+pub mod admin_instruction {
+    pub const UPDATE_PARAMETERS: u8 = 2;
+    pub const SET_ADMIN: u8 = 3;
+    pub const CLOSE_PROGRAM: u8 = 4;
+    pub const INITIALIZE_POOL: u8 = 7;
+    pub const WITHDRAW_SOL_FEES: u8 = 11;
+    pub const SET_CONFIG: u8 = 15;
+    pub const SET_PAUSER: u8 = 16;
+    pub const SET_GOVERNANCE: u8 = 18;
+    pub const CREATE_REFERRAL_CAMPAIGN: u8 = 21;
+    pub const FINALIZE_LEADERBOARD: u8 = 25;
+    pub const SET_WASH_TRADE_WINDOW: u8 = 27;
+    pub const SET_CASHBACK_MODE: u8 = 28;
+    pub const SET_REDEMPTION_RATE: u8 = 31;
+    pub const SET_BOOST_PRICE: u8 = 33;
+    pub const SET_BOOST_COLLECTION: u8 = 36;
+    pub const SET_CPI_ALLOWLIST: u8 = 37;
+    pub const SET_CONTRIBUTION_TARGETS: u8 = 39;
+    pub const SET_OPT_OUT_SWAP_FEE: u8 = 41;
+    pub const SET_CONTRIBUTION_REWARDS: u8 = 43;
+    pub const SET_CONTRIBUTION_LOCK: u8 = 45;
+    pub const REGISTER_DISTRIBUTION_THREAD: u8 = 51;
+    pub const INITIALIZE_ACTIVITY_TREE: u8 = 53;
+    pub const SET_TREASURY_APPROVERS: u8 = 56;
+    pub const SET_MARKET_MAKER: u8 = 66;
+    pub const SCHEDULE_EMERGENCY_WITHDRAWAL: u8 = 67;
+    pub const CANCEL_EMERGENCY_WITHDRAWAL: u8 = 68;
+    pub const EXECUTE_EMERGENCY_WITHDRAWAL: u8 = 69;
+    pub const MIGRATE_LIQUIDITY: u8 = 70;
+    pub const SET_CROSS_CHAIN_EMITTER: u8 = 71;
+    pub const SET_REQUIRE_HUB_SIDE: u8 = 75;
+    pub const SET_HUB_TOKEN: u8 = 76;
+    pub const SET_HUB_TOKENS: u8 = 77;
+    pub const CLOSE_VAULT: u8 = 78;
+    pub const SET_POOL_DEPOSIT_CAP: u8 = 79;
+    pub const SET_ALLOWED_ROUTES: u8 = 80;
+    pub const INITIALIZE_POOL_TWAP: u8 = 83;
+    pub const WITHDRAW_TREASURY: u8 = 86;
+    pub const UPDATE_DISTRIBUTION_THREAD: u8 = 91;
+}
+
+// Compute unit budgets for the swap variants, used by clients when sizing
+// the ComputeBudget instruction for large routes. They are estimates, not
+// measured limits: the host tests only meter syscalls and CPIs, leaving out
+// PDA derivations, Borsh and the rest of the handler's own code.
+pub const SWAP_COMPUTE_UNITS: u32 = 60_000;
+pub const SWAP_LITE_COMPUTE_UNITS: u32 = 30_000;
+
+// Maximum price impact (in basis points) a single swap may have on a pool
+pub const MAX_PRICE_IMPACT_BPS: u64 = 1_000;
+
+// Furthest (in basis points) the price of a pool that already holds
+// liquidity may sit from a closing launch's ratio for the launch to seed it
+pub const LAUNCH_SEED_PRICE_TOLERANCE_BPS: u64 = 100;
+
+// Averaging window of a pool's time-weighted reserves, in seconds
+pub const TWAP_WINDOW_SECONDS: i64 = 1_800;
+
+// Maximum number of recipients a swap output can be split between
+pub const MAX_SPLIT_RECIPIENTS: usize = 8;
+
+// Maximum memo length accepted by SwapWithMemo
+pub const MAX_MEMO_LEN: usize = 256;
+
+// SPL Memo program (v2)
+pub const SPL_MEMO_PROGRAM_ID: Pubkey = solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+// Metaplex Token Metadata program
+pub const TOKEN_METADATA_PROGRAM_ID: Pubkey = solana_program::pubkey!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+
+// SPL Associated Token Account program
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+// Index of the associated token program's CreateIdempotent instruction
+const CREATE_ASSOCIATED_TOKEN_ACCOUNT_IDEMPOTENT: u8 = 1;
+
+// Clockwork thread program (v2)
+pub const CLOCKWORK_THREAD_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("CLoCKyJ6DXBJqqu2VWx9RLbgnwwR6BMHHuyasVmfMzBh");
+
+// Anchor discriminator of the thread program's thread_create instruction
+const CLOCKWORK_THREAD_CREATE_DISCRIMINATOR: [u8; 8] = [54, 1, 238, 224, 71, 244, 252, 173];
+
+// Anchor discriminator of the thread program's thread_update instruction
+const CLOCKWORK_THREAD_UPDATE_DISCRIMINATOR: [u8; 8] = [62, 117, 199, 78, 253, 104, 20, 52];
+
+// Longest thread id Clockwork accepts (it is a PDA seed)
+pub const MAX_THREAD_ID_LEN: usize = 32;
+
+// Clockwork cron schedule of the distribution thread: Mondays 00:00 UTC
+pub const DISTRIBUTION_THREAD_SCHEDULE: &str = "0 0 0 * * MON *";
+
+// Clockwork's SerializableAccount
+#[derive(BorshSerialize)]
+struct ClockworkAccountMeta {
+    pubkey: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+// Clockwork's SerializableInstruction
+#[derive(BorshSerialize)]
+struct ClockworkInstruction {
+    program_id: Pubkey,
+    accounts: Vec<ClockworkAccountMeta>,
+    data: Vec<u8>,
+}
+
+// Clockwork's Trigger, up to the variant we use; variant order must match
+#[derive(BorshSerialize)]
+#[allow(dead_code)]
+enum ClockworkTrigger {
+    Account { address: Pubkey, offset: u64, size: u64 },
+    Cron { schedule: String, skippable: bool },
+}
+
+// Clockwork's ThreadSettings; fields left None keep their current value
+#[derive(BorshSerialize)]
+struct ClockworkThreadSettings {
+    fee: Option<u64>,
+    instructions: Option<Vec<ClockworkInstruction>>,
+    name: Option<String>,
+    rate_limit: Option<u64>,
+    trigger: Option<ClockworkTrigger>,
+}
+
+// Wormhole core bridge
+pub const WORMHOLE_CORE_BRIDGE_ID: Pubkey = solana_program::pubkey!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+
+// Prefix of the core bridge's PostedVAA accounts
+const POSTED_VAA_MAGIC: &[u8; 3] = b"vaa";
+
+// The core bridge's PostedVAA account data after its magic prefix
+#[derive(BorshDeserialize)]
+#[allow(dead_code)]
+struct PostedVaaData {
+    vaa_version: u8,
+    consistency_level: u8,
+    vaa_time: u32,
+    vaa_signature_account: Pubkey,
+    submission_time: u32,
+    nonce: u32,
+    sequence: u64,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+    payload: Vec<u8>,
+}
+
+// First byte of a purchase intent payload
+pub const PURCHASE_INTENT_PAYLOAD_ID: u8 = 1;
+
+// A cross-chain request to buy YOT, encoded by the EVM emitter as the
+// payload id followed by the fields packed big-endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PurchaseIntent {
+    // Paying wallet on the source chain, left-padded to 32 bytes
+    pub payer: [u8; 32],
+    // Bridged tokens spent on the buy
+    pub amount: u64,
+    // Solana wallet receiving the YOT
+    pub target: Pubkey,
+    // Least YOT the payer accepts
+    pub min_amount_out: u64,
+}
+
+impl PurchaseIntent {
+    // Encoded payload length
+    pub const LEN: usize = 1 + 32 + 8 + 32 + 8;
+
+    // Decode a purchase intent payload
+    pub fn parse(payload: &[u8]) -> Option<Self> {
+        if payload.len() != Self::LEN || payload[0] != PURCHASE_INTENT_PAYLOAD_ID {
+            return None;
+        }
+        Some(PurchaseIntent {
+            payer: payload[1..33].try_into().ok()?,
+            amount: u64::from_be_bytes(payload[33..41].try_into().ok()?),
+            target: Pubkey::new_from_array(payload[41..73].try_into().ok()?),
+            min_amount_out: u64::from_be_bytes(payload[73..81].try_into().ok()?),
+        })
+    }
+
+    // Encode as the emitter does
+    pub fn to_payload(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(Self::LEN);
+        payload.push(PURCHASE_INTENT_PAYLOAD_ID);
+        payload.extend_from_slice(&self.payer);
+        payload.extend_from_slice(&self.amount.to_be_bytes());
+        payload.extend_from_slice(self.target.as_ref());
+        payload.extend_from_slice(&self.min_amount_out.to_be_bytes());
+        payload
+    }
+}
+
+// SPL account compression program
+pub const SPL_ACCOUNT_COMPRESSION_PROGRAM_ID: Pubkey =
+    solana_program::pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+
+// Anchor discriminators of the account compression instructions we CPI
+const COMPRESSION_INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR: [u8; 8] = [191, 11, 119, 7, 180, 107, 220, 110];
+const COMPRESSION_APPEND_DISCRIMINATOR: [u8; 8] = [149, 120, 18, 222, 236, 225, 88, 203];
+
+// Kinds of compressed activity records
+pub const ACTIVITY_SWAP_RECEIPT: u8 = 0;
+pub const ACTIVITY_CASHBACK_ACCRUAL: u8 = 1;
+
+// A per-user activity record kept as a leaf of the activity tree instead of
+// its own PDA. The leaf is the keccak hash of the Borsh-encoded record, which
+// is also emitted as an "activity" event so indexers can serve the record
+// and its proof.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ActivityRecord {
+    // ACTIVITY_* kind
+    pub kind: u8,
+    // Index of the leaf in the activity tree
+    pub leaf_index: u64,
+    // Wallet the record belongs to
+    pub user: Pubkey,
+    // Input token mint
+    pub mint_in: Pubkey,
+    // Output token mint
+    pub mint_out: Pubkey,
+    // Amount of input tokens
+    pub amount_in: u64,
+    // Amount of output tokens (YOS accrued for cashback records)
+    pub amount_out: u64,
+    // When the activity happened
+    pub timestamp: i64,
+}
+
+impl ActivityRecord {
+    // Leaf appended to the activity tree for this record
+    pub fn leaf(&self) -> [u8; 32] {
+        keccak::hashv(&[&self.try_to_vec().expect("activity records always serialize")]).to_bytes()
+    }
+}
+
+// Durable per-swap proof written by SwapWithReceipt
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SwapReceipt {
+    // Is this receipt initialized?
+    pub is_initialized: bool,
+    // Wallet that swapped
+    pub user: Pubkey,
+    // Signer-chosen id
+    pub receipt_id: u64,
+    // Input token mint
+    pub mint_in: Pubkey,
+    // Output token mint
+    pub mint_out: Pubkey,
+    // Amount of input tokens taken
+    pub amount_in: u64,
+    // Amount of output tokens paid
+    pub amount_out: u64,
+    // Liquidity contribution taken from the input
+    pub lp_contribution: u64,
+    // Admin fee taken from the input
+    pub admin_fee: u64,
+    // Swap fee taken from the input
+    pub swap_fee: u64,
+    // Referral fee taken from the input
+    pub referral_fee: u64,
+    // Hash of the route the swap took, see `swap_route_hash`
+    pub route_hash: [u8; 32],
+    // Slot of the swap
+    pub slot: u64,
+    // When the swap happened
+    pub timestamp: i64,
+}
+
+// Keccak hash identifying a swap route: the ordered mints it trades through,
+// input first. Every hop prices through its pair's pool PDA, so the mints fix
+// the pools too. A standalone swap is the route of its two mints.
+pub fn swap_route_hash(route: &[Pubkey]) -> [u8; 32] {
+    let mints: Vec<&[u8]> = route.iter().map(|mint| mint.as_ref()).collect();
+    keccak::hashv(&mints).to_bytes()
+}
+
+// Most hops a declared route may take
+pub const MAX_ROUTE_HOPS: usize = 3;
+
+// Maximum number of programs on the CPI caller allowlist
+pub const MAX_CPI_CALLERS: usize = 8;
+
+// External programs permitted to CPI into privileged flows such as
+// fee-exempt swaps
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CpiAllowlist {
+    // Is this allowlist initialized?
+    pub is_initialized: bool,
+    // Number of entries in use
+    pub count: u8,
+    // Allowlisted program ids; only the first `count` are valid
+    pub programs: [Pubkey; MAX_CPI_CALLERS],
+}
+
+impl CpiAllowlist {
+    // Whether `program` may CPI into privileged flows
+    pub fn contains(&self, program: &Pubkey) -> bool {
+        self.programs[..self.count as usize].contains(program)
+    }
+}
+
+// Maximum number of wallets the permission matrix grants instructions to
+pub const MAX_PERMISSION_HOLDERS: usize = 8;
+
+// Admin instructions granted to wallets besides the admin
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PermissionMatrix {
+    // Is this matrix initialized?
+    pub is_initialized: bool,
+    // Number of entries in use
+    pub holder_count: u8,
+    // Wallets holding grants; only the first `holder_count` are valid
+    pub holders: [Pubkey; MAX_PERMISSION_HOLDERS],
+    // Bit d of `grants[i]` lets `holders[i]` call the instruction with
+    // discriminant d
+    pub grants: [u128; MAX_PERMISSION_HOLDERS],
+}
+
+impl PermissionMatrix {
+    // Whether `wallet` may call the instruction with `discriminant`
+    pub fn allows(&self, wallet: &Pubkey, discriminant: u8) -> bool {
+        let count = self.holder_count as usize;
+        self.holders[..count]
+            .iter()
+            .zip(&self.grants[..count])
+            .any(|(holder, grants)| holder == wallet && grants.checked_shr(discriminant as u32).unwrap_or(0) & 1 == 1)
+    }
+
+    // Replace the grants of `holder`, dropping it when `grants` is empty;
+    // false when a new holder does not fit
+    pub fn set(&mut self, holder: Pubkey, grants: u128) -> bool {
+        let count = self.holder_count as usize;
+        match self.holders[..count].iter().position(|entry| *entry == holder) {
+            Some(index) if grants == 0 => {
+                self.holders.copy_within(index + 1..count, index);
+                self.grants.copy_within(index + 1..count, index);
+                self.holders[count - 1] = Pubkey::default();
+                self.grants[count - 1] = 0;
+                self.holder_count -= 1;
+            }
+            Some(index) => self.grants[index] = grants,
+            None if grants == 0 => {}
+            None if count == MAX_PERMISSION_HOLDERS => return false,
+            None => {
+                self.holders[count] = holder;
+                self.grants[count] = grants;
+                self.holder_count += 1;
+            }
+        }
+        true
+    }
+}
+
+// Maximum number of pools the liquidity contribution can be split between
+pub const MAX_CONTRIBUTION_TARGETS: usize = 4;
+
+// Pools receiving the swap liquidity contribution and their weights. A target
+// only takes contributions in a mint it trades; otherwise its share stays in
+// the swapped pool.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ContributionTargets {
+    // Is this target list initialized?
+    pub is_initialized: bool,
+    // Number of entries in use
+    pub count: u8,
+    // Target pool PDAs; only the first `count` are valid
+    pub pools: [Pubkey; MAX_CONTRIBUTION_TARGETS],
+    // Share of the contribution per pool in basis points
+    pub weights_bps: [u16; MAX_CONTRIBUTION_TARGETS],
+}
+
+// Event emitted via sol_log_data after every full swap
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SwapEvent {
+    // Wallet that executed the swap
+    pub user: Pubkey,
+    // Input token mint
+    pub mint_in: Pubkey,
+    // Output token mint
+    pub mint_out: Pubkey,
+    // Amount of input tokens
+    pub amount_in: u64,
+    // Amount of output tokens
+    pub amount_out: u64,
+    // Client-supplied memo (empty when none)
+    pub memo: Vec<u8>,
+}
+
+// Event emitted by CheckUpgradeAuthority
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct UpgradeAuthorityCheckEvent {
+    // Current upgrade authority (None when the program is immutable)
+    pub upgrade_authority: Option<Pubkey>,
+    // Governance recorded in program state
+    pub governance: Pubkey,
+    // Whether the two match
+    pub matches: bool,
+}
+
+// Event emitted when a cashback boost is raised (boost_raised) or cleared
+// on expiry (boost_expired)
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct BoostEvent {
+    // Wallet the boost belongs to
+    pub user: Pubkey,
+    // Boost in effect after the change (basis points)
+    pub boost_bps: u64,
+    // When the boost expires (zero once cleared)
+    pub boost_expiry: i64,
+}
+
+// Rate parameter changed by UpdateParameters
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateParameter {
+    LpContributionRate,
+    AdminFeeRate,
+    YosCashbackRate,
+    SwapFeeRate,
+    ReferralRate,
+}
+
+// One parameter whose value UpdateParameters changed
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterChange {
+    pub parameter: RateParameter,
+    // Value before the update, in basis points
+    pub old_value: u64,
+    // Value after the update, in basis points
+    pub new_value: u64,
+}
+
+// Event emitted by UpdateParameters
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ParametersUpdatedEvent {
+    // Admin that signed the update
+    pub signer: Pubkey,
+    // Parameters whose value changed; unchanged ones are left out
+    pub changes: Vec<ParameterChange>,
+}
+
+// Emit a Borsh-encoded event as program data, prefixed with its tag and the
+// next event sequence number (u64 little-endian) so indexers can detect gaps
+// and order events. The caller persists program_state afterwards.
+fn emit_event<T: BorshSerialize>(program_state: &mut ProgramState, tag: &[u8], event: &T) -> ProgramResult {
+    let data = event.try_to_vec()?;
+    program_state.event_sequence = program_state.event_sequence.saturating_add(1);
+    sol_log_data(&[tag, &program_state.event_sequence.to_le_bytes(), &data]);
+    Ok(())
+}
+
+// Event emitted when an emergency withdrawal is scheduled, cancelled or
+// executed
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EmergencyWithdrawalEvent {
+    // spl-token-swap pool the liquidity is withdrawn from
+    pub pool: Pubkey,
+    // Earliest execution time
+    pub executable_at: i64,
+    // LP tokens burned and the tokens they paid into the treasury, in the
+    // pool's mint order (zero until executed)
+    pub pool_tokens: u64,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+// Event emitted by ExecutePurchaseIntent
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PurchaseIntentEvent {
+    // Paying wallet on the source chain
+    pub payer: [u8; 32],
+    // Wallet that received the YOT
+    pub target: Pubkey,
+    // VAA sequence number
+    pub sequence: u64,
+    // Bridged tokens spent
+    pub amount_in: u64,
+    // YOT delivered
+    pub amount_out: u64,
+}
+
+// Event emitted by MigrateLiquidity
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct LiquidityMigrationEvent {
+    // Pool that gave up the liquidity
+    pub from_pool: Pubkey,
+    // Pool that received it
+    pub to_pool: Pubkey,
+    // LP tokens of `from_pool` burned
+    pub pool_tokens_withdrawn: u64,
+    // LP tokens of `to_pool` minted to the treasury
+    pub pool_tokens_deposited: u64,
+    // Tokens the withdrawal paid out, in `from_pool`'s mint order
+    pub mint_a: Pubkey,
+    pub amount_a: u64,
+    pub mint_b: Pubkey,
+    pub amount_b: u64,
+}
+
+// Event emitted by SwapThroughTokenSwap
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct TokenSwapHopEvent {
+    // spl-token-swap pool the hop traded in
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub amount_in: u64,
+    pub mint_out: Pubkey,
+    // Output measured as the user's destination balance delta
+    pub amount_out: u64,
+}
+
+// Per-call swap behaviour selected by the instruction variant
+#[derive(Clone, Default)]
+struct SwapOptions {
+    // Skip the optional features (cashback, history, stats, referral, events)
+    // while still pricing against the pool
+    lite: bool,
+    // Fill up to the price-impact limit instead of failing large orders
+    allow_partial: bool,
+    // Output split weights in basis points; empty sends everything to the user
+    split_weights_bps: Vec<u16>,
+    // Memo recorded in the swap event
+    memo: Vec<u8>,
+    // Input/output mints the swap must trade, priced through their pool
+    required_pair: Option<(Pubkey, Pubkey)>,
+    // Waive the admin, swap and referral fees (allowlisted CPI callers only)
+    fee_exempt: bool,
+    // Skip the liquidity contribution and pay the opt-out swap fee instead
+    skip_contribution: bool,
+    // Reject the swap if this key was used within the idempotency window
+    idempotency_key: Option<[u8; 16]>,
+    // Write a SwapReceipt PDA with this id
+    receipt_id: Option<u64>,
+    // Derive the minimum output from the pool's price with this tolerance
+    slippage_bps: Option<u16>,
+    // Validate and price the swap, returning a SwapSimulation, without
+    // moving tokens or writing any account
+    simulate: bool,
+    // Spend the input account's balance above this amount instead of the
+    // requested amount
+    keep_balance: Option<u64>,
+    // Mints of the route this swap is a hop of; empty for a standalone swap
+    route: Vec<Pubkey>,
+}
+
+// Result of SimulateSwap, returned via return data
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapSimulation {
+    // Input the swap would take, after any partial-fill trimming
+    pub amount_in: u64,
+    // Output the user would receive
+    pub amount_out: u64,
+    // Liquidity contribution taken from the input
+    pub lp_contribution: u64,
+    // Admin fee taken from the input
+    pub admin_fee: u64,
+    // Swap fee left in the pool
+    pub swap_fee: u64,
+    // Referral fee paid to the referrer
+    pub referral: u64,
+    // YOS cashback after wash-trade checks and boosts
+    pub yos_cashback: u64,
+    // LP shares the contribution would be recorded as
+    pub contribution_shares: u64,
 }
 
 // Program state data stored in the first account
@@ -91,366 +1421,9230 @@ pub struct ProgramState {
     pub referral_rate: u64,
     // Is this program state initialized?
     pub is_initialized: bool,
+    // Lifetime lamports of SOL admin commission collected into the fee vault
+    pub sol_fees_collected: u64,
+    // Account allowed to change the pause mask besides the admin
+    pub pauser: Pubkey,
+    // Bitmask of paused instructions (PAUSE_* constants)
+    pub paused_mask: u8,
+    // Governance/multisig expected to hold the program upgrade authority
+    pub governance: Pubkey,
+    // Slots within which a reverse swap withholds cashback (0 disables
+    // wash-trade detection)
+    pub wash_trade_window_slots: u64,
+    // Accrue cashback in per-user ledgers instead of minting it per swap
+    pub accrue_cashback: bool,
+    // Claim bonus per full CASHBACK_BONUS_PERIOD_SECONDS waited (basis points)
+    pub cashback_wait_bonus_bps: u64,
+    // YOT paid per YOS redeemed, scaled by REDEMPTION_RATE_PRECISION
+    // (0 derives the rate from the YOT/YOS pool reserves)
+    pub yos_redemption_rate: u64,
+    // YOS burned per basis point of cashback boost (0 disables boosting)
+    pub yos_per_boost_bps: u64,
+    // Metaplex metadata account of the YOT mint, verified at Initialize
+    pub yot_metadata: Pubkey,
+    // Metaplex metadata account of the YOS mint, verified at Initialize
+    pub yos_metadata: Pubkey,
+    // Metaplex collection whose holders earn a cashback boost
+    pub boost_collection: Pubkey,
+    // Cashback boost for holders of the boost collection (basis points)
+    pub collection_boost_bps: u64,
+    // Swap fee charged instead of swap_fee_rate when a swap skips the
+    // liquidity contribution (basis points, 0 disables opting out)
+    pub opt_out_swap_fee_rate: u64,
+    // Lifetime swaps that skipped the liquidity contribution
+    pub opt_out_swaps: u64,
+    // Lifetime swap fees collected from those swaps, summed across mints
+    pub opt_out_fees_collected: u64,
+    // YOS paid per week per contributed unit recorded in contribution
+    // ledgers (basis points, 0 disables contribution rewards)
+    pub contribution_reward_rate_bps: u64,
+    // Contribution reward bonus per full week a tranche has stayed in its
+    // pool (basis points, capped at MAX_LOYALTY_BONUS_BPS)
+    pub loyalty_bonus_bps_per_week: u64,
+    // How long a new contribution tranche stays locked before its LP shares
+    // can be withdrawn
+    pub contribution_lock_seconds: i64,
+    // Clockwork thread that must sign DistributeContributionRewards (default
+    // while distribution is permissionless)
+    pub distribution_thread: Pubkey,
+    // Statistics day the live counters started in
+    pub stats_day: u64,
+    // Live counters since the start of `stats_day`
+    pub stats: StatsCounters,
+    // Account compression tree receiving activity records (default when
+    // compressed records are disabled)
+    pub activity_tree: Pubkey,
+    // Records appended to the activity tree, i.e. the next leaf index
+    pub activity_leaves: u64,
+    // Sequence number of the last emitted event
+    pub event_sequence: u64,
+    // Swaps must trade YOT or an approved hub token on one side
+    pub require_hub_side: bool,
+    // Number of entries in use in `hub_tokens`
+    pub hub_token_count: u8,
+    // Hub mints besides YOT; only the first `hub_token_count` are valid
+    pub hub_tokens: [Pubkey; MAX_HUB_TOKENS],
+    // Number of entries in use in `sensitive_pairs`
+    pub sensitive_pair_count: u8,
+    // Route allowlist addresses of the pairs flagged as sensitive; only the
+    // first `sensitive_pair_count` are valid
+    pub sensitive_pairs: [Pubkey; MAX_SENSITIVE_PAIRS],
+    // PROGRAM_STATE_DISCRIMINATOR once initialized, as a second witness
+    // besides `is_initialized`
+    pub discriminator: [u8; 8],
+    // Slot Initialize ran in
+    pub initialized_slot: u64,
 }
 
-// Program authority - PDA that can sign for transactions
-fn find_program_authority(program_id: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"authority"], program_id)
+// Stamped into program state at Initialize
+pub const PROGRAM_STATE_DISCRIMINATOR: [u8; 8] = *b"tswap_v3";
+
+// Maximum number of pairs flagged as sensitive in program state
+pub const MAX_SENSITIVE_PAIRS: usize = 8;
+
+// Maximum number of routes allowed for one sensitive pair
+pub const MAX_ALLOWED_ROUTES: usize = 8;
+
+// Maximum number of hub tokens kept in program state besides YOT
+pub const MAX_HUB_TOKENS: usize = 4;
+
+impl ProgramState {
+    // Hub mints routes are built through, YOT first
+    pub fn hub_mints(&self) -> Vec<Pubkey> {
+        std::iter::once(self.yot_mint)
+            .chain(self.hub_tokens[..self.hub_token_count as usize].iter().copied())
+            .collect()
+    }
+
+    // Whether `mint` is YOT or in the hub list
+    pub fn is_hub(&self, mint: &Pubkey) -> bool {
+        *mint == self.yot_mint || self.hub_tokens[..self.hub_token_count as usize].contains(mint)
+    }
+
+    // Whether the pair with this route allowlist address is flagged sensitive
+    pub fn is_sensitive_pair(&self, route_allowlist: &Pubkey) -> bool {
+        self.sensitive_pairs[..self.sensitive_pair_count as usize].contains(route_allowlist)
+    }
+
+    // Whether Initialize already ran on this account. The discriminator and
+    // slot stamp still count when `is_initialized` was zeroed, e.g. by a
+    // smaller struct serialized over the start of a larger old account
+    pub fn is_stamped(&self) -> bool {
+        self.is_initialized || (self.discriminator == PROGRAM_STATE_DISCRIMINATOR && self.initialized_slot != 0)
+    }
 }
 
-// Program state address - PDA where state is stored
-fn find_program_state_address(program_id: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"state"], program_id)
+// Length of a statistics day in seconds
+pub const STATS_DAY_SECONDS: i64 = 24 * 60 * 60;
+
+// Statistics day containing `timestamp`
+pub fn stats_day(timestamp: i64) -> u64 {
+    (timestamp.max(0) / STATS_DAY_SECONDS) as u64
 }
 
-// Entry point for the program
-entrypoint!(process_instruction);
+// Protocol activity counters. Amounts are summed across mints
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct StatsCounters {
+    // Swaps and order fills
+    pub swaps: u64,
+    // Input volume
+    pub volume: u64,
+    // Admin and swap fees charged
+    pub fees: u64,
+    // YOS minted as cashback and rewards
+    pub emissions: u64,
+    // Wallets that swapped with their user stats supplied
+    pub unique_users: u64,
+}
 
-// Process the program instruction
-pub fn process_instruction(
-    program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    instruction_data: &[u8],
-) -> ProgramResult {
-    // Deserialize the instruction
-    let instruction = SwapInstruction::try_from_slice(instruction_data)?;
+impl StatsCounters {
+    // Count one swap or fill
+    pub fn record_swap(&mut self, volume: u64, fees: u64) {
+        self.swaps = self.swaps.saturating_add(1);
+        self.volume = self.volume.saturating_add(volume);
+        self.fees = self.fees.saturating_add(fees);
+    }
 
-    // Match the instruction to the appropriate handler
-    match instruction {
-        SwapInstruction::Initialize {
-            admin,
-            yot_mint,
-            yos_mint,
-            lp_contribution_rate,
-            admin_fee_rate,
+    // Count minted YOS
+    pub fn record_emission(&mut self, amount: u64) {
+        self.emissions = self.emissions.saturating_add(amount);
+    }
+}
+
+// Snapshot of the statistics counters over [day, end_day)
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct DailyStats {
+    // Is this snapshot initialized?
+    pub is_initialized: bool,
+    // First day the counters cover
+    pub day: u64,
+    // Day the snapshot was taken; more than day + 1 when the crank missed
+    // a day
+    pub end_day: u64,
+    // Counters over the covered days
+    pub counters: StatsCounters,
+}
+
+// Fixed-point precision of ProgramState.yos_redemption_rate
+pub const REDEMPTION_RATE_PRECISION: u64 = 1_000_000_000;
+
+// Pause bits stored in ProgramState.paused_mask
+pub const PAUSE_SWAP: u8 = 1 << 0;
+
+impl ProgramState {
+    // Fail if the instruction guarded by `flag` is paused
+    pub fn check_not_paused(&self, flag: u8) -> ProgramResult {
+        if self.paused_mask & flag != 0 {
+            msg!("Instruction is paused (mask {:#04x})", self.paused_mask);
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(())
+    }
+}
+
+// Number of recent swaps kept in the swap history ring buffer
+pub const SWAP_HISTORY_CAPACITY: usize = 32;
+
+// A single swap recorded in the history ring buffer
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct SwapRecord {
+    // Wallet that executed the swap
+    pub user: Pubkey,
+    // Input token mint
+    pub mint_in: Pubkey,
+    // Output token mint
+    pub mint_out: Pubkey,
+    // Amount of input tokens
+    pub amount_in: u64,
+    // Amount of output tokens
+    pub amount_out: u64,
+    // Admin and swap fees taken from the input
+    pub fee_amount: u64,
+    // Unix timestamp of the swap
+    pub timestamp: i64,
+}
+
+// Fixed-size ring buffer of the last SWAP_HISTORY_CAPACITY swaps, readable by
+// light clients without a websocket indexer
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct SwapHistory {
+    // Is this history account initialized?
+    pub is_initialized: bool,
+    // Index the next record will be written to
+    pub head: u32,
+    // Number of valid records (saturates at capacity)
+    pub count: u32,
+    // Record storage, oldest entry at `head` once full
+    pub records: [SwapRecord; SWAP_HISTORY_CAPACITY],
+}
+
+impl SwapHistory {
+    // Overwrite the oldest record with a new one
+    pub fn push(&mut self, record: SwapRecord) {
+        self.records[self.head as usize] = record;
+        self.head = (self.head + 1) % SWAP_HISTORY_CAPACITY as u32;
+        if (self.count as usize) < SWAP_HISTORY_CAPACITY {
+            self.count += 1;
+        }
+    }
+}
+
+// A fixed-price YOT block trade escrowed by the program until a taker
+// accepts it
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct OtcOffer {
+    // Is this offer initialized?
+    pub is_initialized: bool,
+    // Wallet that made the offer
+    pub maker: Pubkey,
+    // Maker-chosen id
+    pub offer_id: u64,
+    // Only wallet allowed to accept (default pubkey for anyone)
+    pub taker: Pubkey,
+    // Escrowed mint
+    pub mint_offered: Pubkey,
+    // Amount escrowed
+    pub amount_offered: u64,
+    // Mint the taker pays in
+    pub mint_requested: Pubkey,
+    // Amount the taker pays
+    pub amount_requested: u64,
+}
+
+// A bonding-curve presale whose proceeds seed the token's pool at close
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Launch {
+    // Is this launch initialized?
+    pub is_initialized: bool,
+    // Wallet that opened the launch and owns the resulting LP shares
+    pub creator: Pubkey,
+    // Token being launched
+    pub mint: Pubkey,
+    // Mint buyers pay in
+    pub quote_mint: Pubkey,
+    // Tokens sold along the curve
+    pub supply: u64,
+    // Tokens paired with the proceeds in the pool at close
+    pub liquidity_tokens: u64,
+    // Curve prices at zero and `supply` sold, scaled by CURVE_PRICE_PRECISION
+    pub start_price: u64,
+    pub end_price: u64,
+    // Sales stop after this time
+    pub end_time: i64,
+    // Tokens sold so far
+    pub sold: u64,
+    // Quote raised so far
+    pub proceeds: u64,
+}
+
+impl Launch {
+    // Whether sales have stopped at `now`
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.sold == self.supply || now > self.end_time
+    }
+}
+
+// A YOT payment streamed per second from an escrowed balance
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Stream {
+    // Is this stream initialized?
+    pub is_initialized: bool,
+    // Wallet that funded the stream
+    pub sender: Pubkey,
+    // Sender-chosen id
+    pub stream_id: u64,
+    // Wallet paid by the stream
+    pub recipient: Pubkey,
+    // Escrowed mint (YOT)
+    pub mint: Pubkey,
+    // YOT escrowed at creation
+    pub deposit: u64,
+    // Payment runs linearly from `start_time` to `end_time`
+    pub start_time: i64,
+    pub end_time: i64,
+    // YOT already paid to the recipient
+    pub withdrawn: u64,
+}
+
+impl Stream {
+    // YOT streamed but not yet paid at `now`
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        streamed_amount(self.deposit, self.start_time, self.end_time, now).saturating_sub(self.withdrawn)
+    }
+}
+
+// Maximum number of treasury approvers
+pub const MAX_TREASURY_APPROVERS: usize = 8;
+
+// K-of-N approver set guarding withdrawals from the SOL fee vault and the
+// treasury's token accounts, independent of the admin
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Treasury {
+    // Is this treasury initialized?
+    pub is_initialized: bool,
+    // Approvals required
+    pub threshold: u8,
+    // Number of approvers in use
+    pub approver_count: u8,
+    // Approver wallets; only the first `approver_count` are valid
+    pub approvers: [Pubkey; MAX_TREASURY_APPROVERS],
+    // Withdrawals above this many lamports, or raw token units from the
+    // treasury's token accounts, require approvals
+    pub approval_amount: u64,
+}
+
+impl Treasury {
+    // Distinct approvers among the signing `accounts`
+    pub fn approvals(&self, accounts: &[AccountInfo]) -> usize {
+        let approvers = &self.approvers[..self.approver_count as usize];
+        approvers
+            .iter()
+            .filter(|approver| accounts.iter().any(|account| account.is_signer && account.key == *approver))
+            .count()
+    }
+}
+
+// Number of recent idempotency keys kept per user
+pub const IDEMPOTENCY_LOG_CAPACITY: usize = 8;
+
+// Slots an idempotency key blocks duplicates for (about ten minutes, well
+// past the lifetime of a transaction's blockhash)
+pub const IDEMPOTENCY_WINDOW_SLOTS: u64 = 1_500;
+
+// An idempotency key and the slot of the swap that used it
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct IdempotencyEntry {
+    // Client-generated key
+    pub key: [u8; 16],
+    // Slot the key was used in (0 for an empty entry)
+    pub slot: u64,
+}
+
+// Routes a sensitive pair may be swapped through
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct RouteAllowlist {
+    // Is this allowlist initialized?
+    pub is_initialized: bool,
+    // Lower of the two mints (by byte order)
+    pub mint_a: Pubkey,
+    // Higher of the two mints (by byte order)
+    pub mint_b: Pubkey,
+    // Number of entries in use in `route_hashes`
+    pub route_count: u8,
+    // Allowed swap_route_hash values; only the first `route_count` are valid
+    pub route_hashes: [[u8; 32]; MAX_ALLOWED_ROUTES],
+}
+
+impl RouteAllowlist {
+    // Whether a swap with `route_hash` may trade the pair
+    pub fn allows(&self, route_hash: &[u8; 32]) -> bool {
+        self.route_hashes[..self.route_count as usize].contains(route_hash)
+    }
+}
+
+// Registry entry of a mint swaps may pair with in place of YOT
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct HubToken {
+    // Is this entry initialized?
+    pub is_initialized: bool,
+    // Hub token mint
+    pub mint: Pubkey,
+    // Whether the mint is currently approved
+    pub approved: bool,
+}
+
+// Registry entry of a designated market-maker wallet
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct MarketMaker {
+    // Is this entry initialized?
+    pub is_initialized: bool,
+    // Market-maker wallet
+    pub wallet: Pubkey,
+    // Swaps pay no swap fee and earn no cashback
+    pub fee_exempt: bool,
+}
+
+// The Wormhole emitter trusted to send purchase intents
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CrossChainConfig {
+    // Is this config initialized?
+    pub is_initialized: bool,
+    // Wormhole chain id of the emitter
+    pub emitter_chain: u16,
+    // Emitter contract address, left-padded to 32 bytes
+    pub emitter_address: [u8; 32],
+    // Bridged mint intents are paid in
+    pub quote_mint: Pubkey,
+}
+
+// Marks a purchase intent VAA as executed
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct IntentReceipt {
+    // Is this receipt initialized?
+    pub is_initialized: bool,
+    // VAA sequence number
+    pub sequence: u64,
+    // Wallet that received the YOT
+    pub target: Pubkey,
+    // YOT delivered
+    pub amount_out: u64,
+}
+
+// Delay between scheduling and executing an emergency withdrawal
+pub const EMERGENCY_WITHDRAWAL_DELAY_SECONDS: i64 = 48 * 60 * 60;
+
+// A pending emergency withdrawal of the treasury's LP position in an
+// spl-token-swap pool
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct EmergencyWithdrawal {
+    // Is this withdrawal initialized?
+    pub is_initialized: bool,
+    // Pool the liquidity is withdrawn from
+    pub pool: Pubkey,
+    // Earliest execution time
+    pub executable_at: i64,
+}
+
+// Per-user ring buffer of the last IDEMPOTENCY_LOG_CAPACITY idempotency keys
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct IdempotencyLog {
+    // Is this log initialized?
+    pub is_initialized: bool,
+    // Wallet the log belongs to
+    pub owner: Pubkey,
+    // Index the next entry will be written to
+    pub head: u32,
+    // Entry storage, oldest entry at `head` once full
+    pub entries: [IdempotencyEntry; IDEMPOTENCY_LOG_CAPACITY],
+}
+
+impl IdempotencyLog {
+    // Whether `key` was used within the idempotency window ending at `slot`
+    pub fn is_duplicate(&self, key: &[u8; 16], slot: u64) -> bool {
+        self.entries.iter().any(|entry| {
+            entry.slot != 0 && entry.key == *key && slot.saturating_sub(entry.slot) < IDEMPOTENCY_WINDOW_SLOTS
+        })
+    }
+
+    // Overwrite the oldest entry with `key`
+    pub fn push(&mut self, key: [u8; 16], slot: u64) {
+        self.entries[self.head as usize] = IdempotencyEntry { key, slot };
+        self.head = (self.head + 1) % IDEMPOTENCY_LOG_CAPACITY as u32;
+    }
+}
+
+// Per-pair pool account with cumulative counters for fee-APR calculations
+// and transparency dashboards. Amounts are raw token units summed across
+// both swap directions.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PoolState {
+    // Is this pool initialized?
+    pub is_initialized: bool,
+    // Lower of the two mints (by byte order)
+    pub mint_a: Pubkey,
+    // Higher of the two mints (by byte order)
+    pub mint_b: Pubkey,
+    // Total input tokens swapped through the pool
+    pub volume_in: u64,
+    // Total output tokens paid out by the pool
+    pub volume_out: u64,
+    // Total admin and swap fees collected
+    pub fees_collected: u64,
+    // Total liquidity contributed from swaps
+    pub contribution_total: u64,
+    // Internal reserve of mint_a
+    pub reserve_a: u64,
+    // Internal reserve of mint_b
+    pub reserve_b: u64,
+    // Total LP shares outstanding, including the locked minimum liquidity
+    pub total_shares: u64,
+    // Largest reserve_a deposits may reach, 0 for no cap
+    pub max_reserve_a: u64,
+    // Largest reserve_b deposits may reach, 0 for no cap
+    pub max_reserve_b: u64,
+    // Admin fees of mint_a held in its vault until collected to the treasury
+    pub admin_fees_a: u64,
+    // Admin fees of mint_b held in its vault until collected to the treasury
+    pub admin_fees_b: u64,
+}
+
+impl PoolState {
+    // Reserves ordered as (input side, output side) for a swap from `mint_in`
+    pub fn reserves_for(&self, mint_in: &Pubkey) -> (u64, u64) {
+        if *mint_in == self.mint_a {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        }
+    }
+
+    // Whether the pool trades `mint`
+    pub fn trades(&self, mint: &Pubkey) -> bool {
+        *mint == self.mint_a || *mint == self.mint_b
+    }
+
+    // Add a liquidity contribution on the `mint_in` side, compounding it
+    // into the reserves like swap fees
+    pub fn contribute(&mut self, mint_in: &Pubkey, amount: u64) -> ProgramResult {
+        let reserve_in = if *mint_in == self.mint_a { &mut self.reserve_a } else { &mut self.reserve_b };
+        *reserve_in = reserve_in.checked_add(amount).ok_or(ProgramError::InvalidArgument)?;
+        self.contribution_total = self.contribution_total.saturating_add(amount);
+        Ok(())
+    }
+
+    // Set aside the admin fee of a swap from `mint_in` for the treasury
+    pub fn accrue_admin_fees(&mut self, mint_in: &Pubkey, amount: u64) {
+        if *mint_in == self.mint_a {
+            self.admin_fees_a = self.admin_fees_a.saturating_add(amount);
+        } else {
+            self.admin_fees_b = self.admin_fees_b.saturating_add(amount);
+        }
+    }
+
+    // Fail with DepositCapExceeded when a reserve is above its cap
+    pub fn check_deposit_caps(&self) -> ProgramResult {
+        for (mint, reserve, max_reserve) in [
+            (&self.mint_a, self.reserve_a, self.max_reserve_a),
+            (&self.mint_b, self.reserve_b, self.max_reserve_b),
+        ] {
+            if max_reserve > 0 && reserve > max_reserve {
+                msg!("Deposit would raise the {} reserve to {}, above its cap of {}", mint, reserve, max_reserve);
+                return Err(SwapError::DepositCapExceeded.into());
+            }
+        }
+        Ok(())
+    }
+
+    // Constant product of the reserves
+    pub fn k(&self) -> u128 {
+        self.reserve_a as u128 * self.reserve_b as u128
+    }
+
+    // Apply a hop from `mint_in`: the input side grows by `amount_in`
+    // (including any fee left in the pool) and the output side shrinks
+    pub fn apply_hop(&mut self, mint_in: &Pubkey, amount_in: u64, amount_out: u64) -> ProgramResult {
+        let (reserve_in, reserve_out) = if *mint_in == self.mint_a {
+            (&mut self.reserve_a, &mut self.reserve_b)
+        } else {
+            (&mut self.reserve_b, &mut self.reserve_a)
+        };
+        *reserve_in = reserve_in.checked_add(amount_in).ok_or(ProgramError::InvalidArgument)?;
+        *reserve_out = reserve_out.checked_sub(amount_out).ok_or(ProgramError::InsufficientFunds)?;
+        Ok(())
+    }
+}
+
+// Time-weighted reserves of a pool, folded in by the swaps that pass the
+// account. Liquidity contributions are valued at these rather than at the
+// pool's instantaneous reserves, which a swap earlier in the slot may skew.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct PoolTwap {
+    // Is this account initialized?
+    pub is_initialized: bool,
+    // Pool the averages belong to
+    pub pool: Pubkey,
+    // Time-weighted reserve of mint_a
+    pub reserve_a: u64,
+    // Time-weighted reserve of mint_b
+    pub reserve_b: u64,
+    // Unix timestamp of the last observation
+    pub last_update: i64,
+}
+
+impl PoolTwap {
+    // Averages ordered as (input side, output side) for a swap from `mint_in`
+    pub fn reserves_for(&self, pool: &PoolState, mint_in: &Pubkey) -> (u64, u64) {
+        if *mint_in == pool.mint_a {
+            (self.reserve_a, self.reserve_b)
+        } else {
+            (self.reserve_b, self.reserve_a)
+        }
+    }
+
+    // Fold the pool's current reserves into the averages. An empty average,
+    // left by a pool created without liquidity, takes them as they are.
+    pub fn observe(&mut self, pool: &PoolState, now: i64) {
+        let elapsed = now.saturating_sub(self.last_update);
+        if self.reserve_a == 0 || self.reserve_b == 0 {
+            (self.reserve_a, self.reserve_b) = (pool.reserve_a, pool.reserve_b);
+        } else {
+            self.reserve_a = time_weighted_reserve(self.reserve_a, pool.reserve_a, elapsed, TWAP_WINDOW_SECONDS);
+            self.reserve_b = time_weighted_reserve(self.reserve_b, pool.reserve_b, elapsed, TWAP_WINDOW_SECONDS);
+        }
+        self.last_update = self.last_update.max(now);
+    }
+}
+
+// LP shares permanently locked on a pool's first deposit (Uniswap-style) so
+// the share price cannot be manipulated against later depositors
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
+// A user's LP share balance in a pool
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct LiquidityPosition {
+    // Is this position initialized?
+    pub is_initialized: bool,
+    // Wallet that owns the shares
+    pub owner: Pubkey,
+    // Pool the shares belong to
+    pub pool: Pubkey,
+    // LP shares held
+    pub shares: u64,
+}
+
+// Map a shared math failure onto the error the swap handlers have always used
+fn math_error(error: MathError) -> ProgramError {
+    match error {
+        MathError::Overflow => ProgramError::InvalidArgument,
+        MathError::EmptyReserves => ProgramError::InsufficientFunds,
+    }
+}
+
+// Deserialize account data, ignoring the zero padding left over when an
+// account was allocated with size_of rather than its exact Borsh length
+fn load_account<T: BorshDeserialize>(account: &AccountInfo) -> Result<T, ProgramError> {
+    Ok(T::deserialize(&mut &account.data.borrow()[..])?)
+}
+
+// Resolve an optional trailing account. Clients pass the program id as a
+// placeholder to skip an optional account while supplying later ones.
+fn next_optional_account<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+) -> Option<&'a AccountInfo<'b>> {
+    next_account_info(account_info_iter)
+        .ok()
+        .filter(|account| account.key != program_id)
+}
+
+// Resolve the optional contribution targets PDA and the target pool accounts
+// following it. The PDA is only taken when it is the next account, so swaps
+// without it keep their account layout.
+#[allow(clippy::type_complexity)]
+fn next_contribution_targets<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+) -> Result<Option<(ContributionTargets, Vec<&'a AccountInfo<'b>>)>, ProgramError> {
+    let Some(next_account) = account_info_iter.as_slice().first() else {
+        return Ok(None);
+    };
+    let (contribution_targets_address, _) = find_contribution_targets_address(program_id);
+    if *next_account.key != contribution_targets_address || next_account.owner != program_id {
+        return Ok(None);
+    }
+    let contribution_targets = load_account::<ContributionTargets>(next_account_info(account_info_iter)?)?;
+
+    let mut target_pool_accounts = Vec::with_capacity(contribution_targets.count as usize);
+    for target_pool in &contribution_targets.pools[..contribution_targets.count as usize] {
+        let target_pool_account = next_account_info(account_info_iter)?;
+        if target_pool_account.key != target_pool || target_pool_account.owner != program_id {
+            msg!("Contribution target {} is missing", target_pool);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        target_pool_accounts.push(target_pool_account);
+    }
+    Ok(Some((contribution_targets, target_pool_accounts)))
+}
+
+// Take the user's contribution ledger PDA when it is the next account. Only
+// program-owned accounts are derived against, keeping swaps that end with
+// token accounts or the memo program cheap.
+fn next_contribution_ledger<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+) -> Option<&'a AccountInfo<'b>> {
+    let next_account = account_info_iter.as_slice().first()?;
+    if next_account.owner != program_id {
+        return None;
+    }
+    let (contribution_ledger_address, _) = find_contribution_ledger_address(program_id, owner);
+    if *next_account.key != contribution_ledger_address {
+        return None;
+    }
+    account_info_iter.next()
+}
+
+// Take the user's idempotency log PDA when it is the next account
+fn next_idempotency_log<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+) -> Option<&'a AccountInfo<'b>> {
+    let next_account = account_info_iter.as_slice().first()?;
+    if next_account.owner != program_id {
+        return None;
+    }
+    let (idempotency_log_address, _) = find_idempotency_log_address(program_id, owner);
+    if *next_account.key != idempotency_log_address {
+        return None;
+    }
+    account_info_iter.next()
+}
+
+// Load the user's market maker registry entry when it is the next account
+fn next_market_maker<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Option<MarketMaker>, ProgramError> {
+    let Some(next_account) = account_info_iter.as_slice().first() else {
+        return Ok(None);
+    };
+    if next_account.owner != program_id {
+        return Ok(None);
+    }
+    let (market_maker_address, _) = find_market_maker_address(program_id, owner);
+    if *next_account.key != market_maker_address {
+        return Ok(None);
+    }
+    load_account::<MarketMaker>(next_account_info(account_info_iter)?).map(Some)
+}
+
+// Take the user's swap receipt PDA for `receipt_id` when it is the next
+// account. The receipt does not exist yet, so it is recognised by address.
+fn next_swap_receipt<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    receipt_id: Option<u64>,
+) -> Option<(&'a AccountInfo<'b>, u8)> {
+    let next_account = account_info_iter.as_slice().first()?;
+    let (swap_receipt_address, swap_receipt_bump) = find_swap_receipt_address(program_id, owner, receipt_id?);
+    if *next_account.key != swap_receipt_address {
+        return None;
+    }
+    account_info_iter.next().map(|account| (account, swap_receipt_bump))
+}
+
+// Take the hub token registry entry of either swapped mint when it is the
+// next account, recognised by address
+fn next_hub_token<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+) -> Option<&'a AccountInfo<'b>> {
+    let next_account = account_info_iter.as_slice().first()?;
+    let is_hub_token = [mint_in, mint_out]
+        .into_iter()
+        .any(|mint| find_hub_token_address(program_id, mint).0 == *next_account.key);
+    if !is_hub_token {
+        return None;
+    }
+    account_info_iter.next()
+}
+
+// Take the swapped pair's route allowlist when it is the next account,
+// recognised by address
+fn next_route_allowlist<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+    program_id: &Pubkey,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+) -> Option<&'a AccountInfo<'b>> {
+    let next_account = account_info_iter.as_slice().first()?;
+    let (route_allowlist_address, _) = find_route_allowlist_address(program_id, mint_in, mint_out);
+    if *next_account.key != route_allowlist_address {
+        return None;
+    }
+    account_info_iter.next()
+}
+
+// The first signer among the remaining accounts pays the rent of PDAs created
+// on the user's behalf, so a sponsor can fund them; defaults to the user
+fn find_rent_payer<'a, 'b>(
+    account_info_iter: &std::slice::Iter<'a, AccountInfo<'b>>,
+    user_account: &'a AccountInfo<'b>,
+) -> &'a AccountInfo<'b> {
+    account_info_iter
+        .as_slice()
+        .iter()
+        .find(|account| account.is_signer)
+        .unwrap_or(user_account)
+}
+
+// Enforce the route allowlist of a sensitive pair: the hash of `route`, which
+// trades `mint_in` for `mint_out` end to end, must be one the admin
+// published for the pair
+fn check_route_allowlist(
+    program_id: &Pubkey,
+    program_state: &ProgramState,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    route: &[Pubkey],
+    route_allowlist_account: Option<&AccountInfo>,
+) -> ProgramResult {
+    if program_state.sensitive_pair_count == 0 {
+        return Ok(());
+    }
+    let (route_allowlist_address, _) = find_route_allowlist_address(program_id, mint_in, mint_out);
+    if !program_state.is_sensitive_pair(&route_allowlist_address) {
+        return Ok(());
+    }
+    let Some(route_allowlist_account) =
+        route_allowlist_account.filter(|account| *account.key == route_allowlist_address)
+    else {
+        msg!("Swaps of a sensitive pair require its route allowlist");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let route_allowlist = load_account::<RouteAllowlist>(route_allowlist_account)?;
+    if !route_allowlist.is_initialized || !route_allowlist.allows(&swap_route_hash(route)) {
+        msg!("Route is not allowed for this sensitive pair");
+        return Err(ProgramError::InvalidArgument);
+    }
+    Ok(())
+}
+
+// Position of the hop trading `mint_in` for `mint_out` in a declared route,
+// failing unless the route is 1 to MAX_ROUTE_HOPS hops over distinct mints
+// and has that hop
+fn route_hop_index(route: &[Pubkey], mint_in: &Pubkey, mint_out: &Pubkey) -> Result<usize, ProgramError> {
+    let distinct = route.iter().enumerate().all(|(index, mint)| !route[..index].contains(mint));
+    if route.len() < 2 || route.len() > MAX_ROUTE_HOPS + 1 || !distinct {
+        msg!("Routes take 1 to {} hops over distinct mints", MAX_ROUTE_HOPS);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    route.windows(2).position(|hop| hop == [*mint_in, *mint_out]).ok_or_else(|| {
+        msg!("Swap of {} for {} is not a hop of its route", mint_in, mint_out);
+        ProgramError::InvalidArgument
+    })
+}
+
+// Enforce the hub-side rule: a state hub, or a mint with an approved hub token
+// entry, must be on one side of the swap
+fn check_hub_side(
+    program_id: &Pubkey,
+    program_state: &ProgramState,
+    mint_in: &Pubkey,
+    mint_out: &Pubkey,
+    hub_token_account: Option<&AccountInfo>,
+) -> ProgramResult {
+    if !program_state.require_hub_side || program_state.is_hub(mint_in) || program_state.is_hub(mint_out) {
+        return Ok(());
+    }
+    if let Some(hub_token_account) = hub_token_account {
+        let hub_token = load_account::<HubToken>(hub_token_account)?;
+        let (hub_token_address, _) = find_hub_token_address(program_id, &hub_token.mint);
+        if hub_token.is_initialized
+            && hub_token.approved
+            && hub_token_address == *hub_token_account.key
+            && (hub_token.mint == *mint_in || hub_token.mint == *mint_out)
+        {
+            return Ok(());
+        }
+    }
+    msg!("Swaps must trade YOT or an approved hub token on one side");
+    Err(ProgramError::InvalidArgument)
+}
+
+// Activity tree accounts passed after the contribution ledger
+struct ActivityTreeAccounts<'a, 'b> {
+    merkle_tree: &'a AccountInfo<'b>,
+    compression_program: &'a AccountInfo<'b>,
+    noop_program: &'a AccountInfo<'b>,
+}
+
+// Take the activity tree, the compression program and the noop program when
+// the next account is owned by the compression program
+fn next_activity_tree<'a, 'b>(
+    account_info_iter: &mut std::slice::Iter<'a, AccountInfo<'b>>,
+) -> Result<Option<ActivityTreeAccounts<'a, 'b>>, ProgramError> {
+    let Some(next_account) = account_info_iter.as_slice().first() else {
+        return Ok(None);
+    };
+    if *next_account.owner != SPL_ACCOUNT_COMPRESSION_PROGRAM_ID {
+        return Ok(None);
+    }
+    Ok(Some(ActivityTreeAccounts {
+        merkle_tree: next_account_info(account_info_iter)?,
+        compression_program: next_account_info(account_info_iter)?,
+        noop_program: next_account_info(account_info_iter)?,
+    }))
+}
+
+// Append `record` to the program's activity tree and emit it for indexers.
+// The record's leaf index is assigned from program_state.activity_leaves.
+fn append_activity<'a>(
+    program_state: &mut ProgramState,
+    activity_tree: &ActivityTreeAccounts<'_, 'a>,
+    program_authority_account: &AccountInfo<'a>,
+    program_authority_bump: u8,
+    mut record: ActivityRecord,
+) -> ProgramResult {
+    if program_state.activity_tree == Pubkey::default() || program_state.activity_tree != *activity_tree.merkle_tree.key {
+        msg!("Account is not the activity tree");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *activity_tree.compression_program.key != SPL_ACCOUNT_COMPRESSION_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    record.leaf_index = program_state.activity_leaves;
+
+    let mut data = COMPRESSION_APPEND_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&record.leaf());
+    invoke_signed(
+        &Instruction {
+            program_id: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*activity_tree.merkle_tree.key, false),
+                AccountMeta::new_readonly(*program_authority_account.key, true),
+                AccountMeta::new_readonly(*activity_tree.noop_program.key, false),
+            ],
+            data,
+        },
+        &[
+            activity_tree.merkle_tree.clone(),
+            program_authority_account.clone(),
+            activity_tree.noop_program.clone(),
+            activity_tree.compression_program.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+    )?;
+    program_state.activity_leaves = program_state.activity_leaves.saturating_add(1);
+
+    emit_event(program_state, b"activity", &record)
+}
+
+// Cluster identifiers stored in ProgramConfig
+pub const CLUSTER_DEVNET: u8 = 0;
+pub const CLUSTER_TESTNET: u8 = 1;
+pub const CLUSTER_MAINNET: u8 = 2;
+pub const CLUSTER_LOCALNET: u8 = 3;
+
+// Cluster-specific addresses kept on-chain so the same binary can be
+// deployed to any cluster and clients can discover them from one PDA
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ProgramConfig {
+    // Is this config initialized?
+    pub is_initialized: bool,
+    // Cluster this deployment serves
+    pub cluster: u8,
+    // YOT token mint on this cluster (mirrors ProgramState)
+    pub yot_mint: Pubkey,
+    // YOS token mint on this cluster (mirrors ProgramState)
+    pub yos_mint: Pubkey,
+    // SOL-YOT pool used for SOL routes
+    pub sol_yot_pool: Pubkey,
+    // Stablecoin mint (e.g. USDC)
+    pub stable_mint: Pubkey,
+}
+
+// Number of parameter changes kept in the parameter history
+pub const PARAMETER_HISTORY_CAPACITY: usize = 16;
+
+// Fee/rate parameters in effect after an UpdateParameters call
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct ParameterSnapshot {
+    // Unix timestamp of the change
+    pub timestamp: i64,
+    // Admin that signed the change
+    pub signer: Pubkey,
+    // Contribution rate to liquidity pool (basis points)
+    pub lp_contribution_rate: u64,
+    // Admin fee rate (basis points)
+    pub admin_fee_rate: u64,
+    // YOS cashback rate (basis points)
+    pub yos_cashback_rate: u64,
+    // Swap fee rate (basis points)
+    pub swap_fee_rate: u64,
+    // Referral payment rate (basis points)
+    pub referral_rate: u64,
+}
+
+// Append-only history of the last PARAMETER_HISTORY_CAPACITY parameter
+// changes, so auditors can see when economics changed on-chain
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ParameterHistory {
+    // Index the next snapshot will be written to
+    pub head: u32,
+    // Number of valid snapshots (saturates at capacity)
+    pub count: u32,
+    // Total changes ever recorded
+    pub total_changes: u64,
+    // Snapshot storage, oldest entry at `head` once full
+    pub snapshots: [ParameterSnapshot; PARAMETER_HISTORY_CAPACITY],
+}
+
+impl ParameterHistory {
+    // Overwrite the oldest snapshot with a new one
+    pub fn push(&mut self, snapshot: ParameterSnapshot) {
+        self.snapshots[self.head as usize] = snapshot;
+        self.head = (self.head + 1) % PARAMETER_HISTORY_CAPACITY as u32;
+        if (self.count as usize) < PARAMETER_HISTORY_CAPACITY {
+            self.count += 1;
+        }
+        self.total_changes += 1;
+    }
+}
+
+// Referrer account, one per referring wallet
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ReferrerAccount {
+    // Is this referrer initialized?
+    pub is_initialized: bool,
+    // Wallet that receives the referral fees
+    pub owner: Pubkey,
+    // Campaign the referrer is attached to (default pubkey when none)
+    pub campaign: Pubkey,
+    // Share of each referral fee rebated to the referee (basis points)
+    pub rebate_bps: u16,
+    // Total input volume swapped by referees
+    pub referred_volume: u64,
+    // Number of distinct referees bound to this referrer
+    pub referee_count: u64,
+    // Total referral fees paid to this referrer
+    pub lifetime_earnings: u64,
+}
+
+// Per-user swap statistics
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct UserStats {
+    // Is this account initialized?
+    pub is_initialized: bool,
+    // Wallet the stats belong to
+    pub owner: Pubkey,
+    // Referrer the user was first referred by (default pubkey when none)
+    pub referrer: Pubkey,
+    // Lifetime input volume swapped
+    pub total_volume: u64,
+    // Leaderboard epoch `epoch_volume` belongs to
+    pub epoch: u64,
+    // Input volume swapped during `epoch`
+    pub epoch_volume: u64,
+    // Input mint of the user's last swap
+    pub last_mint_in: Pubkey,
+    // Output mint of the user's last swap
+    pub last_mint_out: Pubkey,
+    // Slot of the user's last swap
+    pub last_swap_slot: u64,
+    // Swaps whose cashback was withheld as likely wash trades
+    pub flagged_swaps: u64,
+    // Cashback boost bought by burning YOS (basis points)
+    pub boost_bps: u64,
+    // When the cashback boost expires
+    pub boost_expiry: i64,
+    // Statistics day the user was last counted as unique in
+    pub stats_day: u64,
+}
+
+// How long a cashback boost lasts after the latest burn (30 days)
+pub const BOOST_DURATION_SECONDS: i64 = 30 * 24 * 60 * 60;
+
+// Cap on the cashback boost (basis points, 10000 doubles the cashback)
+pub const MAX_CASHBACK_BOOST_BPS: u64 = 10_000;
+
+impl UserStats {
+    // Whether a swap looks like a wash trade: a same-mint loop, or an
+    // immediate reversal of the previous swap within `window_slots`
+    pub fn is_wash_trade(&self, mint_in: &Pubkey, mint_out: &Pubkey, slot: u64, window_slots: u64) -> bool {
+        if mint_in == mint_out {
+            return true;
+        }
+        window_slots > 0
+            && self.last_mint_in == *mint_out
+            && self.last_mint_out == *mint_in
+            && slot.saturating_sub(self.last_swap_slot) <= window_slots
+    }
+
+    // Cashback boost in effect at `now`
+    pub fn active_boost_bps(&self, now: i64) -> u64 {
+        if now < self.boost_expiry {
+            self.boost_bps
+        } else {
+            0
+        }
+    }
+}
+
+// Waiting period that earns one step of the cashback claim bonus (one week)
+pub const CASHBACK_BONUS_PERIOD_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+// Cap on the total cashback claim bonus (basis points)
+pub const MAX_CASHBACK_WAIT_BONUS_BPS: u64 = 5_000;
+
+// Per-user cashback accrued by swaps in ledger mode, claimed explicitly
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct CashbackLedger {
+    // Is this ledger initialized?
+    pub is_initialized: bool,
+    // Wallet the cashback belongs to
+    pub owner: Pubkey,
+    // YOS accrued and not yet claimed
+    pub accrued: u64,
+    // When the first unclaimed cashback accrued
+    pub accrual_start: i64,
+    // Lifetime YOS claimed, bonus included
+    pub total_claimed: u64,
+}
+
+impl CashbackLedger {
+    // Accrued cashback plus the bonus earned by waiting since `accrual_start`
+    pub fn claimable(&self, now: i64, wait_bonus_bps: u64) -> u64 {
+        let periods = elapsed_seconds(self.accrual_start, now) / CASHBACK_BONUS_PERIOD_SECONDS;
+        let bonus_bps = wait_bonus_bps.saturating_mul(periods as u64).min(MAX_CASHBACK_WAIT_BONUS_BPS);
+        apply_bonus_bps(self.accrued, bonus_bps)
+    }
+}
+
+// Period contribution rewards are distributed for (one week)
+pub const CONTRIBUTION_REWARD_PERIOD_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+// Cap on the contribution loyalty bonus (basis points, 10000 doubles the reward)
+pub const MAX_LOYALTY_BONUS_BPS: u64 = 10_000;
+
+// Number of contribution tranches kept per ledger
+pub const MAX_CONTRIBUTION_TRANCHES: usize = 16;
+
+// Liquidity contributed by one or more swaps into a pool
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct ContributionTranche {
+    // Pool the contribution went into
+    pub pool: Pubkey,
+    // Amount contributed, in the pool's input-mint units
+    pub amount: u64,
+    // LP shares the contribution was deposited for (0 when it was not
+    // deposited as liquidity and only earns rewards)
+    pub lp_shares: u64,
+    // When the contribution was made (amount-weighted once tranches merge)
+    pub timestamp: i64,
+    // When the tranche's shares can be withdrawn
+    pub unlock_time: i64,
+    // End of the last reward period paid for this tranche
+    pub rewarded_until: i64,
+}
+
+// Per-user record of swap liquidity contributions, oldest tranche first
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ContributionLedger {
+    // Is this ledger initialized?
+    pub is_initialized: bool,
+    // Wallet the contributions belong to
+    pub owner: Pubkey,
+    // Number of tranches in use
+    pub count: u8,
+    // Tranche storage; only the first `count` are valid
+    pub tranches: [ContributionTranche; MAX_CONTRIBUTION_TRANCHES],
+    // Lifetime YOS distributed, loyalty bonus included
+    pub total_rewards: u64,
+}
+
+impl ContributionLedger {
+    // Record a contribution as a new tranche. A full ledger folds it into the
+    // newest tranche of the same pool at amount-weighted times and the later
+    // unlock time; returns false when there is none
+    pub fn record(&mut self, pool: Pubkey, amount: u64, lp_shares: u64, now: i64, unlock_time: i64) -> bool {
+        if (self.count as usize) < MAX_CONTRIBUTION_TRANCHES {
+            self.tranches[self.count as usize] = ContributionTranche {
+                pool,
+                amount,
+                lp_shares,
+                timestamp: now,
+                unlock_time,
+                rewarded_until: now,
+            };
+            self.count += 1;
+            return true;
+        }
+        let Some(tranche) = self.tranches.iter_mut().rev().find(|tranche| tranche.pool == pool) else {
+            return false;
+        };
+        let weighted = |time: i64| {
+            let total = tranche.amount as i128 + amount as i128;
+            ((time as i128 * tranche.amount as i128 + now as i128 * amount as i128) / total) as i64
+        };
+        tranche.timestamp = weighted(tranche.timestamp);
+        tranche.rewarded_until = weighted(tranche.rewarded_until);
+        tranche.unlock_time = tranche.unlock_time.max(unlock_time);
+        tranche.amount = tranche.amount.saturating_add(amount);
+        tranche.lp_shares = tranche.lp_shares.saturating_add(lp_shares);
+        true
+    }
+
+    // Whether a contribution to `pool` can be recorded
+    pub fn has_room_for(&self, pool: &Pubkey) -> bool {
+        (self.count as usize) < MAX_CONTRIBUTION_TRANCHES
+            || self.tranches.iter().any(|tranche| tranche.pool == *pool)
+    }
+
+    // Take up to `shares` LP shares of `pool` from its oldest tranches first,
+    // shrinking each tranche's amount in proportion. Stops at the first
+    // tranche of the pool that is still locked; returns the shares taken.
+    pub fn withdraw_shares(&mut self, pool: &Pubkey, shares: u64, now: i64) -> u64 {
+        let mut remaining = shares;
+        for tranche in self.tranches[..self.count as usize]
+            .iter_mut()
+            .filter(|tranche| tranche.pool == *pool && tranche.lp_shares > 0)
+        {
+            if remaining == 0 || tranche.unlock_time > now {
+                break;
+            }
+            let taken = remaining.min(tranche.lp_shares);
+            tranche.amount -= mul_div(tranche.amount, taken, tranche.lp_shares);
+            tranche.lp_shares -= taken;
+            remaining -= taken;
+        }
+
+        // Drop emptied tranches, keeping the rest oldest first
+        let mut kept = 0;
+        for index in 0..self.count as usize {
+            let tranche = self.tranches[index];
+            if tranche.amount > 0 || tranche.lp_shares > 0 {
+                self.tranches[kept] = tranche;
+                kept += 1;
+            }
+        }
+        self.tranches[kept..].fill(ContributionTranche::default());
+        self.count = kept as u8;
+
+        shares - remaining
+    }
+
+    // YOS earned by every tranche for its full reward periods since it was
+    // last rewarded, raised by a loyalty bonus for the tranche's age. Marks
+    // those periods as rewarded.
+    pub fn settle_rewards(&mut self, now: i64, weekly_rate_bps: u64, loyalty_bonus_bps_per_week: u64) -> u64 {
+        let mut reward = 0u64;
+        for tranche in &mut self.tranches[..self.count as usize] {
+            let periods = elapsed_seconds(tranche.rewarded_until, now) / CONTRIBUTION_REWARD_PERIOD_SECONDS;
+            if periods == 0 {
+                continue;
+            }
+            let age_periods = elapsed_seconds(tranche.timestamp, now) / CONTRIBUTION_REWARD_PERIOD_SECONDS;
+            let loyalty_bps = loyalty_bonus_bps_per_week
+                .saturating_mul(age_periods as u64)
+                .min(MAX_LOYALTY_BONUS_BPS);
+            reward = reward.saturating_add(contribution_reward(tranche.amount, weekly_rate_bps, periods as u64, loyalty_bps));
+            tranche.rewarded_until += periods * CONTRIBUTION_REWARD_PERIOD_SECONDS;
+        }
+        self.total_rewards = self.total_rewards.saturating_add(reward);
+        reward
+    }
+}
+
+// Cap on the keeper bounty a maker can offer per fill (basis points)
+pub const MAX_KEEPER_BOUNTY_BPS: u16 = 500;
+
+// Lamports of an expired order's rent paid to the keeper that sweeps it
+pub const ORDER_SWEEP_TIP_LAMPORTS: u64 = 10_000;
+
+// A limit or DCA order whose input is escrowed in the order PDA's token
+// account. Fills pay the pool's swap fee; the liquidity contribution,
+// cashback and referrals only apply to interactive swaps.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Order {
+    // Is this order initialized?
+    pub is_initialized: bool,
+    // Wallet that placed the order
+    pub maker: Pubkey,
+    // Maker-chosen id
+    pub order_id: u64,
+    // Input token mint
+    pub mint_in: Pubkey,
+    // Output token mint
+    pub mint_out: Pubkey,
+    // Input still escrowed
+    pub amount_remaining: u64,
+    // Input sold per fill
+    pub amount_per_fill: u64,
+    // Minimum output the maker receives per full fill, after the bounty
+    pub min_amount_out_per_fill: u64,
+    // Seconds between fills (0 for limit orders)
+    pub interval_seconds: i64,
+    // Earliest time of the next fill
+    pub next_fill_time: i64,
+    // Share of each fill's output paid to the keeper (basis points)
+    pub bounty_bps: u16,
+    // Time after which the order can no longer fill (0 never expires)
+    pub expires_at: i64,
+}
+
+impl Order {
+    // Whether the order has expired at `now`
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now > self.expires_at
+    }
+
+    // Input sold by the next fill and the maker's minimum output for it
+    pub fn next_fill(&self) -> (u64, u64) {
+        let amount_in = self.amount_per_fill.min(self.amount_remaining);
+        (amount_in, mul_div(self.min_amount_out_per_fill, amount_in, self.amount_per_fill))
+    }
+}
+
+// Length of a leaderboard epoch in seconds (one week)
+pub const LEADERBOARD_EPOCH_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+// Number of ranked wallets kept per leaderboard epoch
+pub const LEADERBOARD_SIZE: usize = 10;
+
+// Leaderboard epoch containing `timestamp`
+fn leaderboard_epoch(timestamp: i64) -> u64 {
+    (timestamp.max(0) / LEADERBOARD_EPOCH_SECONDS) as u64
+}
+
+// A ranked wallet on an epoch leaderboard
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, Default)]
+pub struct LeaderboardEntry {
+    // Ranked wallet
+    pub wallet: Pubkey,
+    // Swap volume during the epoch
+    pub volume: u64,
+    // Whether the wallet has claimed its prize share
+    pub claimed: bool,
+}
+
+// Top swappers by volume for one epoch, updated by a permissionless crank
+// and finalized by the admin with a YOS prize shared by the ranked wallets
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct Leaderboard {
+    // Is this leaderboard initialized?
+    pub is_initialized: bool,
+    // Epoch the leaderboard ranks
+    pub epoch: u64,
+    // Whether the admin has finalized the ranking and prize
+    pub is_finalized: bool,
+    // YOS prize shared equally by the ranked wallets
+    pub prize_amount: u64,
+    // Ranked wallets, highest volume first; empty slots have zero volume
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE],
+}
+
+impl Leaderboard {
+    // Insert or raise a wallet's volume, keeping only the top entries
+    pub fn record(&mut self, wallet: Pubkey, volume: u64) {
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.wallet == wallet && entry.volume > 0) {
+            entry.volume = entry.volume.max(volume);
+        } else if let Some(lowest) = self.entries.iter_mut().min_by_key(|entry| entry.volume) {
+            if volume > lowest.volume {
+                *lowest = LeaderboardEntry { wallet, volume, claimed: false };
+            }
+        }
+        self.entries.sort_by_key(|entry| std::cmp::Reverse(entry.volume));
+    }
+
+    // Number of ranked wallets
+    pub fn ranked_count(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.volume > 0).count()
+    }
+}
+
+// Time-boxed referral campaign with its own referral rate
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub struct ReferralCampaign {
+    // Is this campaign initialized?
+    pub is_initialized: bool,
+    // Campaign identifier
+    pub campaign_id: u64,
+    // Referral payment rate while the campaign runs (basis points)
+    pub referral_rate: u64,
+    // Unix timestamp after which the campaign no longer applies
+    pub end_time: i64,
+}
+
+// Program authority - PDA that can sign for transactions
+pub fn find_program_authority(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::AUTHORITY], program_id)
+}
+
+// Program state address - PDA where state is stored
+pub fn find_program_state_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id)
+}
+
+// Swap history address - PDA holding the recent swaps ring buffer
+pub fn find_swap_history_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::SWAP_HISTORY], program_id)
+}
+
+// Pool address - PDA for a token pair, independent of the mint order
+pub fn find_pool_address(program_id: &Pubkey, mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, u8) {
+    let (mint_a, mint_b) = if mint_x < mint_y { (mint_x, mint_y) } else { (mint_y, mint_x) };
+    Pubkey::find_program_address(&[seeds::POOL, mint_a.as_ref(), mint_b.as_ref()], program_id)
+}
+
+// Liquidity position address - PDA holding a user's shares in a pool
+pub fn find_liquidity_position_address(program_id: &Pubkey, pool: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::LP_POSITION, pool.as_ref(), owner.as_ref()], program_id)
+}
+
+// SOL fee vault address - program-owned PDA holding the SOL admin commission
+pub fn find_sol_fee_vault_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::SOL_FEE_VAULT], program_id)
+}
+
+// Program config address - PDA holding the cluster-specific addresses
+pub fn find_program_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::PROGRAM_CONFIG], program_id)
+}
+
+// Parameter history address - PDA holding recent parameter changes
+pub fn find_parameter_history_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::PARAMETER_HISTORY], program_id)
+}
+
+// Referrer address - PDA per referring wallet
+pub fn find_referrer_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::REFERRER, owner.as_ref()], program_id)
+}
+
+// User stats address - PDA per swapping wallet
+pub fn find_user_stats_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::USER_STATS, owner.as_ref()], program_id)
+}
+
+// Cashback ledger address - PDA per user
+pub fn find_cashback_ledger_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::CASHBACK_LEDGER, owner.as_ref()], program_id)
+}
+
+// CPI caller allowlist address - PDA
+pub fn find_cpi_allowlist_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::CPI_ALLOWLIST], program_id)
+}
+
+// Permission matrix address - PDA
+pub fn find_permission_matrix_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::PERMISSION_MATRIX], program_id)
+}
+
+// Contribution targets address - PDA
+pub fn find_contribution_targets_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::CONTRIBUTION_TARGETS], program_id)
+}
+
+// Contribution ledger address - PDA per user
+pub fn find_contribution_ledger_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::CONTRIBUTION_LEDGER, owner.as_ref()], program_id)
+}
+
+// Order address - PDA per maker and order id
+pub fn find_order_address(program_id: &Pubkey, maker: &Pubkey, order_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::ORDER, maker.as_ref(), &order_id.to_le_bytes()], program_id)
+}
+
+// Clockwork thread address - PDA of the thread program per authority and id
+pub fn find_distribution_thread_address(authority: &Pubkey, thread_id: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"thread", authority.as_ref(), thread_id], &CLOCKWORK_THREAD_PROGRAM_ID)
+}
+
+// Daily stats address - PDA per statistics day
+pub fn find_daily_stats_address(program_id: &Pubkey, day: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::DAILY_STATS, &day.to_le_bytes()], program_id)
+}
+
+// OTC offer address - PDA per maker and offer id
+pub fn find_otc_offer_address(program_id: &Pubkey, maker: &Pubkey, offer_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::OTC_OFFER, maker.as_ref(), &offer_id.to_le_bytes()], program_id)
+}
+
+// Launch address - PDA per launched mint
+pub fn find_launch_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::LAUNCH, mint.as_ref()], program_id)
+}
+
+// Market maker address - PDA per registered wallet
+pub fn find_market_maker_address(program_id: &Pubkey, wallet: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::MARKET_MAKER, wallet.as_ref()], program_id)
+}
+
+// Route allowlist address - PDA for a token pair, independent of the mint order
+pub fn find_route_allowlist_address(program_id: &Pubkey, mint_x: &Pubkey, mint_y: &Pubkey) -> (Pubkey, u8) {
+    let (mint_a, mint_b) = if mint_x < mint_y { (mint_x, mint_y) } else { (mint_y, mint_x) };
+    Pubkey::find_program_address(&[seeds::ROUTE_ALLOWLIST, mint_a.as_ref(), mint_b.as_ref()], program_id)
+}
+
+// Pool TWAP address - PDA per pool
+pub fn find_pool_twap_address(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::POOL_TWAP, pool.as_ref()], program_id)
+}
+
+// Hub token registry entry address - PDA per mint
+pub fn find_hub_token_address(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::HUB_TOKEN, mint.as_ref()], program_id)
+}
+
+// Swap receipt address - PDA per user and receipt id
+pub fn find_swap_receipt_address(program_id: &Pubkey, user: &Pubkey, receipt_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::SWAP_RECEIPT, user.as_ref(), &receipt_id.to_le_bytes()], program_id)
+}
+
+// Cross-chain config address - PDA
+pub fn find_cross_chain_config_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::CROSS_CHAIN_CONFIG], program_id)
+}
+
+// Intent receipt address - PDA per emitter and VAA sequence
+pub fn find_intent_receipt_address(
+    program_id: &Pubkey,
+    emitter_chain: u16,
+    emitter_address: &[u8; 32],
+    sequence: u64,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[seeds::INTENT_RECEIPT, &emitter_chain.to_le_bytes(), emitter_address, &sequence.to_le_bytes()],
+        program_id,
+    )
+}
+
+// Emergency withdrawal address - PDA per pool
+pub fn find_emergency_withdrawal_address(program_id: &Pubkey, pool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::EMERGENCY_WITHDRAWAL, pool.as_ref()], program_id)
+}
+
+// Stream address - PDA per sender and stream id
+pub fn find_stream_address(program_id: &Pubkey, sender: &Pubkey, stream_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::STREAM, sender.as_ref(), &stream_id.to_le_bytes()], program_id)
+}
+
+// Treasury address - PDA
+pub fn find_treasury_address(program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::TREASURY], program_id)
+}
+
+// Idempotency log address - PDA per user
+pub fn find_idempotency_log_address(program_id: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::IDEMPOTENCY_LOG, owner.as_ref()], program_id)
+}
+
+// Leaderboard address - PDA per leaderboard epoch
+pub fn find_leaderboard_address(program_id: &Pubkey, epoch: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::LEADERBOARD, &epoch.to_le_bytes()], program_id)
+}
+
+// Referral campaign address - PDA per campaign id
+pub fn find_referral_campaign_address(program_id: &Pubkey, campaign_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds::REFERRAL_CAMPAIGN, &campaign_id.to_le_bytes()], program_id)
+}
+
+// Entry point for the program
+#[cfg(not(feature = "no-entrypoint"))]
+solana_program::entrypoint!(process_instruction);
+
+// Process the program instruction
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    #[cfg(feature = "paranoid")]
+    let snapshot = swap_invariants::Snapshot::take(program_id, accounts);
+
+    dispatch_instruction(program_id, accounts, instruction_data)?;
+
+    #[cfg(feature = "paranoid")]
+    snapshot.check(program_id, accounts)?;
+    Ok(())
+}
+
+fn dispatch_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    // Deserialize the instruction
+    let instruction = SwapInstruction::try_from_slice(instruction_data)?;
+
+    // Match the instruction to the appropriate handler
+    match instruction {
+        SwapInstruction::Initialize {
+            admin,
+            yot_mint,
+            yos_mint,
+            lp_contribution_rate,
+            admin_fee_rate,
             yos_cashback_rate,
             swap_fee_rate,
             referral_rate,
         } => process_initialize(
             program_id,
-            accounts,
-            admin,
-            yot_mint,
-            yos_mint,
-            lp_contribution_rate,
-            admin_fee_rate,
-            yos_cashback_rate,
-            swap_fee_rate,
-            referral_rate,
+            accounts,
+            admin,
+            yot_mint,
+            yos_mint,
+            lp_contribution_rate,
+            admin_fee_rate,
+            yos_cashback_rate,
+            swap_fee_rate,
+            referral_rate,
+        ),
+        swap @ (SwapInstruction::Swap { .. }
+        | SwapInstruction::SwapTokenLite { .. }
+        | SwapInstruction::SwapPartialFill { .. }
+        | SwapInstruction::SwapSplit { .. }
+        | SwapInstruction::SwapWithMemo { .. }
+        | SwapInstruction::SwapWithoutContribution { .. }
+        | SwapInstruction::SwapWithReceipt { .. }
+        | SwapInstruction::SwapWithSlippage { .. }
+        | SwapInstruction::SwapIdempotent { .. }
+        | SwapInstruction::SwapBalance { .. }
+        | SwapInstruction::SwapRouteHop { .. }
+        | SwapInstruction::SwapFeeExempt { .. }
+        | SwapInstruction::BuyWithStable { .. }) => process_swap_variant(program_id, accounts, swap, false),
+        SwapInstruction::UpdateParameters {
+            lp_contribution_rate,
+            admin_fee_rate,
+            yos_cashback_rate,
+            swap_fee_rate,
+            referral_rate,
+        } => process_update_parameters(
+            program_id,
+            accounts,
+            lp_contribution_rate,
+            admin_fee_rate,
+            yos_cashback_rate,
+            swap_fee_rate,
+            referral_rate,
+        ),
+        SwapInstruction::SetAdmin { new_admin } => process_set_admin(program_id, accounts, new_admin),
+        SwapInstruction::CloseProgram {} => process_close_program(program_id, accounts),
+        SwapInstruction::SetConfig {
+            cluster,
+            sol_yot_pool,
+            stable_mint,
+        } => process_set_config(program_id, accounts, cluster, sol_yot_pool, stable_mint),
+        SwapInstruction::SetPauser { pauser } => process_set_pauser(program_id, accounts, pauser),
+        SwapInstruction::SetPausedMask { paused_mask } => process_set_paused_mask(program_id, accounts, paused_mask),
+        SwapInstruction::SetGovernance { governance } => process_set_governance(program_id, accounts, governance),
+        SwapInstruction::CheckUpgradeAuthority {} => process_check_upgrade_authority(program_id, accounts),
+        SwapInstruction::RegisterReferrer {} => process_register_referrer(program_id, accounts),
+        SwapInstruction::CreateReferralCampaign {
+            campaign_id,
+            referral_rate,
+            end_time,
+        } => process_create_referral_campaign(program_id, accounts, campaign_id, referral_rate, end_time),
+        SwapInstruction::JoinReferralCampaign {} => process_join_referral_campaign(program_id, accounts),
+        SwapInstruction::SetReferrerRebate { rebate_bps } => process_set_referrer_rebate(program_id, accounts, rebate_bps),
+        SwapInstruction::UpdateLeaderboard { epoch } => process_update_leaderboard(program_id, accounts, epoch),
+        SwapInstruction::FinalizeLeaderboard { epoch, prize_amount } => {
+            process_finalize_leaderboard(program_id, accounts, epoch, prize_amount)
+        }
+        SwapInstruction::ClaimLeaderboardPrize { epoch } => process_claim_leaderboard_prize(program_id, accounts, epoch),
+        SwapInstruction::SetWashTradeWindow { window_slots } => {
+            process_set_wash_trade_window(program_id, accounts, window_slots)
+        }
+        SwapInstruction::SetCashbackMode { accrue, wait_bonus_bps } => {
+            process_set_cashback_mode(program_id, accounts, accrue, wait_bonus_bps)
+        }
+        SwapInstruction::InitializeCashbackLedger => process_initialize_cashback_ledger(program_id, accounts),
+        SwapInstruction::ClaimCashback => process_claim_cashback(program_id, accounts),
+        SwapInstruction::SetRedemptionRate { rate } => process_set_redemption_rate(program_id, accounts, rate),
+        SwapInstruction::RedeemYos { amount } => process_redeem_yos(program_id, accounts, amount),
+        SwapInstruction::SetBoostPrice { yos_per_boost_bps } => {
+            process_set_boost_price(program_id, accounts, yos_per_boost_bps)
+        }
+        SwapInstruction::BurnYosForBoost { amount } => process_burn_yos_for_boost(program_id, accounts, amount),
+        SwapInstruction::SetBoostCollection { collection, boost_bps } => {
+            process_set_boost_collection(program_id, accounts, collection, boost_bps)
+        }
+        SwapInstruction::SetCpiAllowlist { programs } => process_set_cpi_allowlist(program_id, accounts, programs),
+        SwapInstruction::SetContributionTargets { targets } => {
+            process_set_contribution_targets(program_id, accounts, targets)
+        }
+        SwapInstruction::SetOptOutSwapFee { swap_fee_rate } => {
+            process_set_opt_out_swap_fee(program_id, accounts, swap_fee_rate)
+        }
+        SwapInstruction::InitializeContributionLedger => process_initialize_contribution_ledger(program_id, accounts),
+        SwapInstruction::SetContributionRewards {
+            weekly_rate_bps,
+            loyalty_bonus_bps_per_week,
+        } => process_set_contribution_rewards(program_id, accounts, weekly_rate_bps, loyalty_bonus_bps_per_week),
+        SwapInstruction::DistributeContributionRewards => process_distribute_contribution_rewards(program_id, accounts),
+        SwapInstruction::SetContributionLock { lock_seconds } => {
+            process_set_contribution_lock(program_id, accounts, lock_seconds)
+        }
+        SwapInstruction::WithdrawContribution {
+            shares,
+            min_amount_a,
+            min_amount_b,
+        } => process_withdraw_contribution(program_id, accounts, shares, min_amount_a, min_amount_b),
+        SwapInstruction::CreateOrder {
+            order_id,
+            mint_out,
+            amount_in,
+            amount_per_fill,
+            min_amount_out_per_fill,
+            interval_seconds,
+            bounty_bps,
+            expires_at,
+        } => process_create_order(
+            program_id,
+            accounts,
+            order_id,
+            mint_out,
+            amount_in,
+            amount_per_fill,
+            min_amount_out_per_fill,
+            interval_seconds,
+            bounty_bps,
+            expires_at,
+        ),
+        SwapInstruction::FillOrder => process_fill_order(program_id, accounts),
+        SwapInstruction::CancelOrder => process_cancel_order(program_id, accounts),
+        SwapInstruction::SweepExpiredOrder => process_sweep_expired_order(program_id, accounts),
+        SwapInstruction::RegisterDistributionThread { thread_id, amount } => {
+            process_register_distribution_thread(program_id, accounts, thread_id, amount)
+        }
+        SwapInstruction::UpdateDistributionThread => process_update_distribution_thread(program_id, accounts),
+        SwapInstruction::SnapshotDailyStats => process_snapshot_daily_stats(program_id, accounts),
+        SwapInstruction::InitializeActivityTree { max_depth, max_buffer_size } => {
+            process_initialize_activity_tree(program_id, accounts, max_depth, max_buffer_size)
+        }
+        SwapInstruction::InitializeIdempotencyLog => process_initialize_idempotency_log(program_id, accounts),
+        SwapInstruction::SetTreasuryApprovers {
+            approvers,
+            threshold,
+            approval_amount,
+        } => process_set_treasury_approvers(program_id, accounts, approvers, threshold, approval_amount),
+        SwapInstruction::CreateOtcOffer {
+            offer_id,
+            taker,
+            mint_requested,
+            amount_offered,
+            amount_requested,
+        } => process_create_otc_offer(
+            program_id,
+            accounts,
+            offer_id,
+            taker,
+            mint_requested,
+            amount_offered,
+            amount_requested,
+        ),
+        SwapInstruction::AcceptOtcOffer => process_accept_otc_offer(program_id, accounts),
+        SwapInstruction::CancelOtcOffer => process_cancel_otc_offer(program_id, accounts),
+        SwapInstruction::CreateLaunch {
+            quote_mint,
+            supply,
+            liquidity_tokens,
+            start_price,
+            end_price,
+            end_time,
+        } => process_create_launch(
+            program_id,
+            accounts,
+            quote_mint,
+            supply,
+            liquidity_tokens,
+            start_price,
+            end_price,
+            end_time,
+        ),
+        SwapInstruction::BuyLaunch { amount, max_cost } => process_buy_launch(program_id, accounts, amount, max_cost),
+        SwapInstruction::CloseLaunch => process_close_launch(program_id, accounts),
+        SwapInstruction::CreateStream {
+            stream_id,
+            recipient,
+            amount,
+            start_time,
+            end_time,
+        } => process_create_stream(program_id, accounts, stream_id, recipient, amount, start_time, end_time),
+        SwapInstruction::WithdrawStream => process_withdraw_stream(program_id, accounts),
+        SwapInstruction::CancelStream => process_cancel_stream(program_id, accounts),
+        SwapInstruction::SetMarketMaker { wallet, fee_exempt } => {
+            process_set_market_maker(program_id, accounts, wallet, fee_exempt)
+        }
+        SwapInstruction::ScheduleEmergencyWithdrawal => process_schedule_emergency_withdrawal(program_id, accounts),
+        SwapInstruction::CancelEmergencyWithdrawal => process_cancel_emergency_withdrawal(program_id, accounts),
+        SwapInstruction::ExecuteEmergencyWithdrawal => process_execute_emergency_withdrawal(program_id, accounts),
+        SwapInstruction::MigrateLiquidity {
+            from_pool,
+            to_pool,
+            amount,
+        } => process_migrate_liquidity(program_id, accounts, from_pool, to_pool, amount),
+        SwapInstruction::SwapThroughTokenSwap {
+            amount_in,
+            min_amount_out,
+        } => process_swap_through_token_swap(program_id, accounts, amount_in, min_amount_out),
+        SwapInstruction::SetCrossChainEmitter {
+            emitter_chain,
+            emitter_address,
+            quote_mint,
+        } => process_set_cross_chain_emitter(program_id, accounts, emitter_chain, emitter_address, quote_mint),
+        SwapInstruction::ExecutePurchaseIntent => process_execute_purchase_intent(program_id, accounts),
+        SwapInstruction::SetRequireHubSide { required } => process_set_require_hub_side(program_id, accounts, required),
+        SwapInstruction::SetHubToken { mint, approved } => process_set_hub_token(program_id, accounts, mint, approved),
+        SwapInstruction::SetHubTokens { hub_tokens } => process_set_hub_tokens(program_id, accounts, hub_tokens),
+        SwapInstruction::CloseVault {} => process_close_vault(program_id, accounts),
+        SwapInstruction::SetAllowedRoutes {
+            mint_a,
+            mint_b,
+            route_hashes,
+        } => process_set_allowed_routes(program_id, accounts, mint_a, mint_b, route_hashes),
+        SwapInstruction::ExpireBoosts {} => process_expire_boosts(program_id, accounts),
+        SwapInstruction::SetPermissions { holder, instructions } => {
+            process_set_permissions(program_id, accounts, holder, instructions)
+        }
+        SwapInstruction::InitializePoolTwap {} => process_initialize_pool_twap(program_id, accounts),
+        SwapInstruction::SimulateSwap { swap_data } => {
+            process_swap_variant(program_id, accounts, SwapInstruction::try_from_slice(&swap_data)?, true)
+        }
+        SwapInstruction::RemoveLiquidity {
+            shares,
+            min_amount_a,
+            min_amount_b,
+        } => process_remove_liquidity(program_id, accounts, shares, min_amount_a, min_amount_b),
+        SwapInstruction::WithdrawTreasury { amount } => process_withdraw_treasury(program_id, accounts, amount),
+        SwapInstruction::CollectAdminFees {} => process_collect_admin_fees(program_id, accounts),
+        SwapInstruction::InitializeSwapHistory {} => process_initialize_swap_history(program_id, accounts),
+        SwapInstruction::InitializePool { mint_a, mint_b } => {
+            process_initialize_pool(program_id, accounts, mint_a, mint_b)
+        }
+        SwapInstruction::AddLiquidity {
+            amount_a,
+            amount_b,
+            min_shares,
+        } => process_add_liquidity(program_id, accounts, amount_a, amount_b, min_shares),
+        SwapInstruction::SetPoolFeeReinvest { .. } => {
+            msg!("SetPoolFeeReinvest is retired; swap fees always compound into the reserves");
+            Err(ProgramError::InvalidInstructionData)
+        }
+        SwapInstruction::SetPoolDepositCap { mint, max_reserve } => {
+            process_set_pool_deposit_cap(program_id, accounts, mint, max_reserve)
+        }
+        SwapInstruction::InitializeSolFeeVault {} => process_initialize_sol_fee_vault(program_id, accounts),
+        SwapInstruction::WithdrawSolFees { amount } => process_withdraw_sol_fees(program_id, accounts, amount),
+    }
+}
+
+/// Initialize the program state with admin and token addresses
+#[allow(clippy::too_many_arguments)]
+fn process_initialize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: Pubkey,
+    yot_mint: Pubkey,
+    yos_mint: Pubkey,
+    lp_contribution_rate: u64,
+    admin_fee_rate: u64,
+    yos_cashback_rate: u64,
+    swap_fee_rate: u64,
+    referral_rate: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let _rent_sysvar_account = next_account_info(account_info_iter)?;
+    // Metaplex metadata accounts of the YOT and YOS mints
+    let yot_metadata_account = next_account_info(account_info_iter)?;
+    let yos_metadata_account = next_account_info(account_info_iter)?;
+    // Optional vault accounts, starting with the associated token program
+    let vault_accounts: Vec<&AccountInfo> = account_info_iter.collect();
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Both mints must already carry their token metadata
+    let yot_metadata = verify_token_metadata(yot_metadata_account, &yot_mint)?;
+    let yos_metadata = verify_token_metadata(yos_metadata_account, &yos_mint)?;
+
+    // Calculate PDAs
+    let (program_state_address, program_state_bump) = find_program_state_address(program_id);
+    let (program_authority_address, _) = find_program_authority(program_id);
+
+    // Verify PDAs
+    assert_pda!(program_state_account, program_state_address);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Check if the program state account already exists
+    if program_state_account.data_len() > 0 {
+        // If it exists, check if it's already initialized
+        let program_state = load_account::<ProgramState>(program_state_account)?;
+        if program_state.is_stamped() {
+            msg!("Program state was initialized in slot {}", program_state.initialized_slot);
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+    } else {
+        // Create program state account
+        let rent = Rent::get()?;
+        let state_size = std::mem::size_of::<ProgramState>();
+        let lamports = rent.minimum_balance(state_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account.key,
+                program_state_account.key,
+                lamports,
+                state_size as u64,
+                program_id,
+            ),
+            &[
+                payer_account.clone(),
+                program_state_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::PROGRAM_STATE, &[program_state_bump]]],
+        )?;
+    }
+
+    // Initialize program state
+    let program_state = ProgramState {
+        admin,
+        yot_mint,
+        yos_mint,
+        lp_contribution_rate,
+        admin_fee_rate,
+        yos_cashback_rate,
+        swap_fee_rate,
+        referral_rate,
+        is_initialized: true,
+        sol_fees_collected: 0,
+        pauser: admin,
+        paused_mask: 0,
+        governance: admin,
+        wash_trade_window_slots: 0,
+        accrue_cashback: false,
+        cashback_wait_bonus_bps: 0,
+        yos_redemption_rate: 0,
+        yos_per_boost_bps: 0,
+        yot_metadata,
+        yos_metadata,
+        boost_collection: Pubkey::default(),
+        collection_boost_bps: 0,
+        opt_out_swap_fee_rate: 0,
+        opt_out_swaps: 0,
+        opt_out_fees_collected: 0,
+        contribution_reward_rate_bps: 0,
+        loyalty_bonus_bps_per_week: 0,
+        contribution_lock_seconds: 0,
+        distribution_thread: Pubkey::default(),
+        stats_day: stats_day(Clock::get()?.unix_timestamp),
+        stats: StatsCounters::default(),
+        activity_tree: Pubkey::default(),
+        activity_leaves: 0,
+        event_sequence: 0,
+        require_hub_side: false,
+        hub_token_count: 0,
+        hub_tokens: [Pubkey::default(); MAX_HUB_TOKENS],
+        sensitive_pair_count: 0,
+        sensitive_pairs: [Pubkey::default(); MAX_SENSITIVE_PAIRS],
+        discriminator: PROGRAM_STATE_DISCRIMINATOR,
+        initialized_slot: Clock::get()?.slot,
+    };
+
+    // Serialize and store program state
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    // Create the authority's YOT, YOS and wSOL vaults and the treasury's YOT
+    // and YOS vaults up front, so the first swap finds them
+    if let Some((ata_program_account, vault_accounts)) = vault_accounts.split_first() {
+        let [
+            token_program_account,
+            yot_mint_account,
+            yos_mint_account,
+            native_mint_account,
+            treasury_account,
+            authority_yot_vault,
+            authority_yos_vault,
+            authority_wsol_vault,
+            treasury_yot_vault,
+            treasury_yos_vault,
+        ] = vault_accounts else {
+            msg!("Expected the token program, the YOT, YOS and wSOL mints, the treasury and five vaults");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let (treasury_address, _) = find_treasury_address(program_id);
+        assert_pda!(treasury_account, treasury_address);
+        if *yot_mint_account.key != yot_mint
+            || *yos_mint_account.key != yos_mint
+            || *native_mint_account.key != spl_token::native_mint::id()
+        {
+            msg!("Vault mints must be YOT, YOS and wrapped SOL");
+            return Err(ProgramError::InvalidArgument);
+        }
+        for (vault_account, owner_account, mint_account) in [
+            (authority_yot_vault, program_authority_account, yot_mint_account),
+            (authority_yos_vault, program_authority_account, yos_mint_account),
+            (authority_wsol_vault, program_authority_account, native_mint_account),
+            (treasury_yot_vault, treasury_account, yot_mint_account),
+            (treasury_yos_vault, treasury_account, yos_mint_account),
+        ] {
+            create_vault(
+                payer_account,
+                vault_account,
+                owner_account,
+                mint_account,
+                system_program_account,
+                token_program_account,
+                ata_program_account,
+            )?;
+        }
+    }
+
+    msg!("Program initialized successfully");
+    Ok(())
+}
+
+/// Metaplex metadata account of `mint`
+pub fn find_token_metadata_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[b"metadata", TOKEN_METADATA_PROGRAM_ID.as_ref(), mint.as_ref()],
+        &TOKEN_METADATA_PROGRAM_ID,
+    )
+}
+
+/// Check that `metadata_account` is the Metaplex metadata PDA of `mint` and
+/// has been created, returning its address
+fn verify_token_metadata(metadata_account: &AccountInfo, mint: &Pubkey) -> Result<Pubkey, ProgramError> {
+    let (metadata_address, _) = find_token_metadata_address(mint);
+    if metadata_address != *metadata_account.key {
+        msg!("Metadata account does not belong to mint {}", mint);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if *metadata_account.owner != TOKEN_METADATA_PROGRAM_ID {
+        msg!("Metadata account for mint {} has not been created", mint);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Metadata layout: key (1), update authority (32), mint (32), ...
+    let data = metadata_account.data.borrow();
+    if data.len() < 65 || data[33..65] != mint.to_bytes() {
+        msg!("Metadata account records a different mint");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(metadata_address)
+}
+
+/// Associated token account of `owner` for `mint`
+pub fn find_associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), spl_token::id().as_ref(), mint.as_ref()],
+        &ASSOCIATED_TOKEN_PROGRAM_ID,
+    )
+    .0
+}
+
+/// Create `vault_account` as the associated token account of `owner_account`
+/// for `mint_account`, paid by `payer_account`. A vault that already exists is
+/// left as it is, so initialization can be retried.
+pub fn create_vault<'a>(
+    payer_account: &AccountInfo<'a>,
+    vault_account: &AccountInfo<'a>,
+    owner_account: &AccountInfo<'a>,
+    mint_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    token_program_account: &AccountInfo<'a>,
+    ata_program_account: &AccountInfo<'a>,
+) -> ProgramResult {
+    if *token_program_account.key != spl_token::id() || *ata_program_account.key != ASSOCIATED_TOKEN_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *vault_account.key != find_associated_token_address(owner_account.key, mint_account.key) {
+        msg!("Vault {} is not the associated token account of {}", vault_account.key, owner_account.key);
+        return Err(ProgramError::InvalidSeeds);
+    }
+    invoke(
+        &Instruction {
+            program_id: ASSOCIATED_TOKEN_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*payer_account.key, true),
+                AccountMeta::new(*vault_account.key, false),
+                AccountMeta::new_readonly(*owner_account.key, false),
+                AccountMeta::new_readonly(*mint_account.key, false),
+                AccountMeta::new_readonly(*system_program_account.key, false),
+                AccountMeta::new_readonly(*token_program_account.key, false),
+            ],
+            data: vec![CREATE_ASSOCIATED_TOKEN_ACCOUNT_IDEMPOTENT],
+        },
+        &[
+            payer_account.clone(),
+            vault_account.clone(),
+            owner_account.clone(),
+            mint_account.clone(),
+            system_program_account.clone(),
+            token_program_account.clone(),
+            ata_program_account.clone(),
+        ],
+    )
+}
+
+/// Read the verified collection recorded in a Metaplex metadata account
+fn metadata_verified_collection(data: &[u8]) -> Option<Pubkey> {
+    // key (1), update authority (32), mint (32)
+    let mut offset = 65;
+    let mut take = |len: usize| -> Option<&[u8]> {
+        let bytes = data.get(offset..offset + len)?;
+        offset += len;
+        Some(bytes)
+    };
+    // name, symbol and uri strings
+    for _ in 0..3 {
+        let len = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        take(len)?;
+    }
+    // seller fee basis points
+    take(2)?;
+    // creators: Option<Vec<{address, verified, share}>>
+    if take(1)?[0] == 1 {
+        let count = u32::from_le_bytes(take(4)?.try_into().ok()?) as usize;
+        take(count.checked_mul(34)?)?;
+    }
+    // primary sale happened, is mutable
+    take(2)?;
+    // edition nonce and token standard: Option<u8>
+    for _ in 0..2 {
+        if take(1)?[0] == 1 {
+            take(1)?;
+        }
+    }
+    // collection: Option<{verified, key}>
+    if take(1)?[0] != 1 {
+        return None;
+    }
+    let verified = take(1)?[0] == 1;
+    let key = Pubkey::new_from_array(take(32)?.try_into().ok()?);
+    verified.then_some(key)
+}
+
+/// Whether `owner` holds an NFT of the verified `collection`, proven by its
+/// token account and Metaplex metadata account
+fn holds_collection_nft(
+    owner: &Pubkey,
+    nft_token_account: &AccountInfo,
+    nft_metadata_account: &AccountInfo,
+    collection: &Pubkey,
+) -> Result<bool, ProgramError> {
+    let nft_token = TokenAccount::unpack(&nft_token_account.data.borrow())?;
+    if nft_token.owner != *owner || nft_token.amount == 0 {
+        return Ok(false);
+    }
+    verify_token_metadata(nft_metadata_account, &nft_token.mint)?;
+    Ok(metadata_verified_collection(&nft_metadata_account.data.borrow()) == Some(*collection))
+}
+
+/// Authorize `signer` to run the admin instruction `discriminant`. The admin
+/// always may; another wallet only when the permission matrix, found anywhere
+/// in `accounts`, grants it that instruction.
+fn authorize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    program_state: &ProgramState,
+    signer: &AccountInfo,
+    discriminant: u8,
+) -> ProgramResult {
+    if program_state.admin != *signer.key {
+        let (permission_matrix_address, _) = find_permission_matrix_address(program_id);
+        let permission_matrix_account = accounts
+            .iter()
+            .find(|account| *account.key == permission_matrix_address && account.owner == program_id);
+        let granted = match permission_matrix_account {
+            Some(account) => load_account::<PermissionMatrix>(account)?.allows(signer.key, discriminant),
+            None => false,
+        };
+        if !granted {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        msg!("Instruction {} granted to {} by the permission matrix", discriminant, signer.key);
+    }
+    assert_signer!(signer);
+    Ok(())
+}
+
+/// Return the program that CPI'd into this instruction, failing unless it is
+/// on the allowlist. The caller is the program of the transaction's current
+/// top-level instruction, read from the instructions sysvar.
+fn require_allowlisted_cpi_caller(
+    program_id: &Pubkey,
+    instructions_sysvar_account: &AccountInfo,
+    cpi_allowlist_account: &AccountInfo,
+) -> Result<Pubkey, ProgramError> {
+    if get_stack_height() <= TRANSACTION_LEVEL_STACK_HEIGHT {
+        msg!("Instruction must be invoked via CPI");
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let (cpi_allowlist_address, _) = find_cpi_allowlist_address(program_id);
+    assert_pda!(cpi_allowlist_account, cpi_allowlist_address);
+    let cpi_allowlist = load_account::<CpiAllowlist>(cpi_allowlist_account)?;
+
+    let current_index = instructions_sysvar::load_current_index_checked(instructions_sysvar_account)?;
+    let top_level_instruction =
+        instructions_sysvar::load_instruction_at_checked(current_index as usize, instructions_sysvar_account)?;
+    if !cpi_allowlist.is_initialized || !cpi_allowlist.contains(&top_level_instruction.program_id) {
+        msg!("CPI caller {} is not allowlisted", top_level_instruction.program_id);
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(top_level_instruction.program_id)
+}
+
+/// Fail unless the transaction's current top-level instruction targets this
+/// program or, when the allowlist is supplied, an allowlisted caller
+fn require_trusted_top_level_instruction(
+    program_id: &Pubkey,
+    instructions_sysvar_account: &AccountInfo,
+    cpi_allowlist_account: Option<&AccountInfo>,
+) -> ProgramResult {
+    let current_index = instructions_sysvar::load_current_index_checked(instructions_sysvar_account)?;
+    let top_level_instruction =
+        instructions_sysvar::load_instruction_at_checked(current_index as usize, instructions_sysvar_account)?;
+    if top_level_instruction.program_id == *program_id {
+        return Ok(());
+    }
+
+    if let Some(cpi_allowlist_account) = cpi_allowlist_account {
+        let (cpi_allowlist_address, _) = find_cpi_allowlist_address(program_id);
+        assert_pda!(cpi_allowlist_account, cpi_allowlist_address);
+        let cpi_allowlist = load_account::<CpiAllowlist>(cpi_allowlist_account)?;
+        if cpi_allowlist.is_initialized && cpi_allowlist.contains(&top_level_instruction.program_id) {
+            return Ok(());
+        }
+    }
+
+    msg!("Untrusted caller {}", top_level_instruction.program_id);
+    Err(ProgramError::IncorrectProgramId)
+}
+
+/// Run any swap variant through the shared swap path, as a simulation
+/// returning a SwapSimulation when `simulate` is set
+fn process_swap_variant(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    swap: SwapInstruction,
+    simulate: bool,
+) -> ProgramResult {
+    let (amount_in, min_amount_out, options) = match swap {
+        SwapInstruction::Swap {
+            amount_in,
+            min_amount_out,
+        } => (amount_in, min_amount_out, SwapOptions::default()),
+        SwapInstruction::SwapTokenLite {
+            amount_in,
+            min_amount_out,
+        } => (amount_in, min_amount_out, SwapOptions { lite: true, ..SwapOptions::default() }),
+        SwapInstruction::SwapPartialFill {
+            amount_in,
+            min_amount_out,
+            allow_partial,
+        } => (amount_in, min_amount_out, SwapOptions { allow_partial, ..SwapOptions::default() }),
+        SwapInstruction::SwapSplit {
+            amount_in,
+            min_amount_out,
+            weights_bps,
+        } => (amount_in, min_amount_out, SwapOptions { split_weights_bps: weights_bps, ..SwapOptions::default() }),
+        SwapInstruction::SwapWithMemo {
+            amount_in,
+            min_amount_out,
+            memo,
+        } => (amount_in, min_amount_out, SwapOptions { memo, ..SwapOptions::default() }),
+        SwapInstruction::SwapWithoutContribution {
+            amount_in,
+            min_amount_out,
+        } => (amount_in, min_amount_out, SwapOptions { skip_contribution: true, ..SwapOptions::default() }),
+        SwapInstruction::SwapWithReceipt {
+            amount_in,
+            min_amount_out,
+            receipt_id,
+        } => (amount_in, min_amount_out, SwapOptions { receipt_id: Some(receipt_id), ..SwapOptions::default() }),
+        SwapInstruction::SwapWithSlippage { amount_in, slippage_bps } => {
+            (amount_in, 0, SwapOptions { slippage_bps: Some(slippage_bps), ..SwapOptions::default() })
+        }
+        SwapInstruction::SwapIdempotent {
+            amount_in,
+            min_amount_out,
+            idempotency_key,
+        } => {
+            let options = SwapOptions { idempotency_key: Some(idempotency_key), ..SwapOptions::default() };
+            (amount_in, min_amount_out, options)
+        }
+        SwapInstruction::SwapBalance {
+            keep_balance,
+            min_amount_out,
+        } => (0, min_amount_out, SwapOptions { keep_balance: Some(keep_balance), ..SwapOptions::default() }),
+        SwapInstruction::SwapRouteHop {
+            route,
+            amount,
+            min_amount_out,
+        } => (amount, min_amount_out, SwapOptions { route, ..SwapOptions::default() }),
+        SwapInstruction::SwapFeeExempt {
+            amount_in,
+            min_amount_out,
+        } => return process_swap_fee_exempt(program_id, accounts, amount_in, min_amount_out, simulate),
+        SwapInstruction::BuyWithStable {
+            stable_mint,
+            amount,
+            min_amount_out,
+        } => return process_buy_with_stable(program_id, accounts, stable_mint, amount, min_amount_out, simulate),
+        _ => {
+            msg!("Only swap instructions can be simulated");
+            return Err(ProgramError::InvalidInstructionData);
+        }
+    };
+    execute_swap(program_id, accounts, amount_in, min_amount_out, SwapOptions { simulate, ..options })
+}
+
+
+/// Shared swap path for every swap variant
+fn execute_swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_in: u64,
+    min_amount_out: u64,
+    options: SwapOptions,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts (the lite variant has no YOS account)
+    let user_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let user_token_in_account = next_account_info(account_info_iter)?;
+    let user_token_out_account = next_account_info(account_info_iter)?;
+    let user_yos_account = if options.lite { None } else { Some(next_account_info(account_info_iter)?) };
+    let token_program_account = next_account_info(account_info_iter)?;
+    // Optional swap history ring buffer (ignored by lite swaps), then the
+    // pair's pool PDA and the authority's vaults of the input and output mints
+    let swap_history_account = next_optional_account(account_info_iter, program_id).filter(|_| !options.lite);
+    let pool_account = next_optional_account(account_info_iter, program_id);
+    let vault_in_account = next_account_info(account_info_iter)?;
+    let vault_out_account = next_account_info(account_info_iter)?;
+    // SOL fee vault and system program, required when swapping from SOL
+    let sol_fee_vault_account = next_optional_account(account_info_iter, program_id);
+    let system_program_account = next_optional_account(account_info_iter, program_id);
+    // Optional referrer PDA, the referrer's input-mint token account and the
+    // referrer's campaign
+    let referrer_account = next_optional_account(account_info_iter, program_id);
+    let referrer_token_account = next_optional_account(account_info_iter, program_id);
+    let campaign_account = next_optional_account(account_info_iter, program_id);
+    // Optional user stats PDA, created on first use when the system program is
+    // supplied (ignored by lite swaps)
+    let user_stats_account = next_optional_account(account_info_iter, program_id).filter(|_| !options.lite);
+    // Optional NFT token account and its metadata, proving a boost collection holding
+    let nft_token_account = next_optional_account(account_info_iter, program_id);
+    let nft_metadata_account = next_optional_account(account_info_iter, program_id);
+    // Optional contribution targets PDA, recognised by address, and its pools
+    let contribution_targets = next_contribution_targets(account_info_iter, program_id)?;
+    // Optional contribution ledger PDA recording the user's contribution
+    let contribution_ledger_account = next_contribution_ledger(account_info_iter, program_id, user_account.key);
+    // Optional activity tree, recognised by owner, with the compression and
+    // noop programs
+    let activity_tree = next_activity_tree(account_info_iter)?;
+    // Idempotency log PDA, required by SwapIdempotent
+    let idempotency_log_account = next_idempotency_log(account_info_iter, program_id, user_account.key);
+    // Optional market maker registry entry of the user
+    let fee_exempt_market_maker = next_market_maker(account_info_iter, program_id, user_account.key)?
+        .is_some_and(|market_maker| market_maker.is_initialized && market_maker.fee_exempt);
+    // Swap receipt PDA, required by SwapWithReceipt
+    let swap_receipt_account = next_swap_receipt(account_info_iter, program_id, user_account.key, options.receipt_id);
+    // Hub token registry entry of a non-YOT side
+    let user_token_in = TokenAccount::unpack(&user_token_in_account.data.borrow())?;
+    let mint_in = user_token_in.mint;
+    let mint_out = TokenAccount::unpack(&user_token_out_account.data.borrow())?.mint;
+    let hub_token_account = next_hub_token(account_info_iter, program_id, &mint_in, &mint_out);
+    // Route allowlists, required for sensitive pairs: the swapped pair's,
+    // then on a route hop the route's end pair's
+    let route_allowlist_account = next_route_allowlist(account_info_iter, program_id, &mint_in, &mint_out);
+    let route_end_allowlist_account = match options.route.as_slice() {
+        [first, .., last] => next_route_allowlist(account_info_iter, program_id, first, last),
+        _ => None,
+    };
+    // Output recipients for split swaps
+    let mut recipient_accounts = Vec::with_capacity(options.split_weights_bps.len());
+    for _ in 0..options.split_weights_bps.len() {
+        recipient_accounts.push(next_account_info(account_info_iter)?);
+    }
+    // Optional SPL Memo program to CPI the memo into
+    let memo_program_account = account_info_iter
+        .as_slice()
+        .first()
+        .filter(|account| *account.key == SPL_MEMO_PROGRAM_ID)
+        .and_then(|_| account_info_iter.next());
+    // Optional rent payer for the user stats and receipt PDAs
+    let rent_payer_account = find_rent_payer(account_info_iter, user_account);
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // A route hop after the first is a balance swap, spending whatever the
+    // input account holds above the kept balance, such as everything an
+    // earlier hop of the route paid into it
+    let route_hop = match options.route.is_empty() {
+        true => None,
+        false => Some(route_hop_index(&options.route, &mint_in, &mint_out)?),
+    };
+    let keep_balance = match route_hop {
+        Some(hop) if hop > 0 => Some(amount_in),
+        _ => options.keep_balance,
+    };
+    let amount_in = match keep_balance {
+        Some(keep_balance) => match user_token_in.amount.checked_sub(keep_balance).filter(|amount| *amount > 0) {
+            Some(amount) => amount,
+            None => {
+                msg!("Input account holds no more than the kept {}", keep_balance);
+                return Err(ProgramError::InsufficientFunds);
+            }
+        },
+        None => amount_in,
+    };
+
+    // Refuse a resubmitted swap, then claim its key
+    if let Some(idempotency_key) = options.idempotency_key {
+        let Some(idempotency_log_account) = idempotency_log_account else {
+            msg!("Idempotent swaps require the idempotency log account");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let mut idempotency_log = load_account::<IdempotencyLog>(idempotency_log_account)?;
+        if !idempotency_log.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let slot = Clock::get()?.slot;
+        if idempotency_log.is_duplicate(&idempotency_key, slot) {
+            msg!("Duplicate swap: idempotency key already used");
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !options.simulate {
+            idempotency_log.push(idempotency_key, slot);
+            idempotency_log.serialize(&mut *idempotency_log_account.data.borrow_mut())?;
+        }
+    }
+
+    // A receipt id is used once, and its PDA is paid for by the signer
+    if options.receipt_id.is_some() {
+        let Some((swap_receipt_account, _)) = swap_receipt_account else {
+            msg!("Swaps with a receipt require the receipt account");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        if swap_receipt_account.data_len() > 0 {
+            msg!("Swap receipt already exists");
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if system_program_account.is_none() {
+            msg!("Swaps with a receipt require the system program");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+    }
+
+    if options.memo.len() > MAX_MEMO_LEN {
+        msg!("Memo exceeds {} bytes", MAX_MEMO_LEN);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Split weights must cover the whole output
+    if !options.split_weights_bps.is_empty() {
+        if options.split_weights_bps.len() > MAX_SPLIT_RECIPIENTS {
+            msg!("At most {} split recipients are supported", MAX_SPLIT_RECIPIENTS);
+            return Err(ProgramError::InvalidArgument);
+        }
+        let total_weight: u64 = options.split_weights_bps.iter().map(|weight| *weight as u64).sum();
+        if total_weight != 10000 {
+            msg!("Split weights sum to {} bps, expected 10000", total_weight);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // In ledger mode the YOS account slot holds the user's cashback ledger
+    let cashback_ledger_account = user_yos_account.filter(|_| program_state.accrue_cashback);
+    if let Some(cashback_ledger_account) = cashback_ledger_account {
+        let (cashback_ledger_address, _) = find_cashback_ledger_address(program_id, user_account.key);
+        if cashback_ledger_address != *cashback_ledger_account.key {
+            msg!("Cashback ledger has incorrect address");
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    // Verify YOS token account
+    // This is the critical part - we ensure the YOS token account exists and is valid
+    if let Some(user_yos_account) = user_yos_account.filter(|_| !program_state.accrue_cashback) {
+        assert_token_account!(user_yos_account, program_state.yos_mint, *user_account.key);
+    }
+
+    // The pair's pool PDA is required, so a swap cannot skip the pool by
+    // passing the placeholder
+    let (pool_address, _) = find_pool_address(program_id, &mint_in, &mint_out);
+    let Some(pool_account) = pool_account else {
+        msg!("Swap requires the pair's pool account");
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    assert_pda!(pool_account, pool_address);
+
+    check_hub_side(program_id, &program_state, &mint_in, &mint_out, hub_token_account)?;
+    let pair_route = [mint_in, mint_out];
+    check_route_allowlist(program_id, &program_state, &mint_in, &mint_out, &pair_route, route_allowlist_account)?;
+    // Each hop of a declared route also holds the whole route to the
+    // allowlist of the pair it trades end to end
+    if let [first, .., last] = options.route.as_slice() {
+        let route_end_allowlist_account = route_end_allowlist_account.or(route_allowlist_account);
+        check_route_allowlist(program_id, &program_state, first, last, &options.route, route_end_allowlist_account)?;
+    }
+
+    // Routes bound to a pair must trade it through its registered pool
+    if let Some((required_mint_in, required_mint_out)) = options.required_pair {
+        if mint_in != required_mint_in || mint_out != required_mint_out {
+            msg!("Swap must trade {} for {}", required_mint_in, required_mint_out);
+            return Err(ProgramError::InvalidAccountData);
+        }
+    }
+
+    // Swaps price only against a created pool holding both reserves, never
+    // at a made-up rate for a pair nobody has funded
+    if pool_account.data_is_empty() {
+        msg!("The pool of {} and {} has not been created", mint_in, mint_out);
+        return Err(ProgramError::UninitializedAccount);
+    }
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized || pool.reserve_a == 0 || pool.reserve_b == 0 {
+        msg!("The pool of {} and {} holds no reserves", mint_in, mint_out);
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Tokens move through the authority's vaults of the two mints, which
+    // hold the pool's reserves and admin fees
+    assert_token_account!(vault_in_account, mint_in, program_authority_address);
+    assert_token_account!(vault_out_account, mint_out, program_authority_address);
+
+    // The pool's time-weighted reserves are found by address anywhere among
+    // the accounts, since the optional accounts before them are positional,
+    // and ignored until the admin creates them
+    let (pool_twap_address, _) = find_pool_twap_address(program_id, pool_account.key);
+    let pool_twap_account =
+        accounts.iter().find(|account| *account.key == pool_twap_address && !account.data_is_empty());
+    let mut pool_twap = match pool_twap_account {
+        Some(pool_twap_account) => {
+            assert_owner!(pool_twap_account, program_id);
+            let pool_twap = load_account::<PoolTwap>(pool_twap_account)?;
+            if !pool_twap.is_initialized {
+                return Err(ProgramError::UninitializedAccount);
+            }
+            Some(pool_twap)
+        }
+        _ => None,
+    };
+
+    // Slippage mode quotes against the pool
+    if let Some(slippage_bps) = options.slippage_bps {
+        if slippage_bps as u64 > BPS_DENOMINATOR {
+            msg!("Slippage of {} bps exceeds 100%", slippage_bps);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    // Load the user's contribution ledger when supplied
+    let mut contribution_ledger = match contribution_ledger_account {
+        Some(contribution_ledger_account) => {
+            let contribution_ledger = load_account::<ContributionLedger>(contribution_ledger_account)?;
+            if !contribution_ledger.is_initialized {
+                return Err(ProgramError::UninitializedAccount);
+            }
+            Some(contribution_ledger)
+        }
+        _ => None,
+    };
+
+    // Enforce the price-impact limit against the pool's reserves, trimming
+    // the order when partial fills are allowed. The unfilled remainder never
+    // leaves the user's account.
+    let (reserve_in, _) = pool.reserves_for(&mint_in);
+    let max_amount_in = max_amount_in(reserve_in, MAX_PRICE_IMPACT_BPS);
+    let (amount_in, min_amount_out) = if amount_in <= max_amount_in {
+        (amount_in, min_amount_out)
+    } else if options.allow_partial && max_amount_in > 0 {
+        let scaled_min_out = mul_div(min_amount_out, max_amount_in, amount_in);
+        msg!("Partial fill: {} of {} input tokens, {} refunded", max_amount_in, amount_in, amount_in - max_amount_in);
+        (max_amount_in, scaled_min_out)
+    } else {
+        msg!("Swap exceeds the {} bps price-impact limit", MAX_PRICE_IMPACT_BPS);
+        return Err(ProgramError::InvalidArgument);
+    };
+
+    // Resolve the referrer, the referral rate that applies to them and the
+    // share they rebate to the referee. Lite swaps pay no referral
+    let mut referral_rate = if options.lite { 0 } else { program_state.referral_rate };
+    let mut referral_rebate_bps = 0u16;
+    let mut referral = match (referrer_account, referrer_token_account) {
+        _ if options.lite => None,
+        (Some(referrer_account), Some(referrer_token_account)) => {
+            let referrer = load_account::<ReferrerAccount>(referrer_account)?;
+            let (referrer_address, _) = find_referrer_address(program_id, &referrer.owner);
+            if !referrer.is_initialized || referrer_address != *referrer_account.key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            if referrer.owner == *user_account.key {
+                msg!("Self-referral is not allowed");
+                return Err(ProgramError::InvalidArgument);
+            }
+            assert_token_account!(
+                referrer_token_account,
+                mint_in,
+                referrer.owner,
+                "Referrer token account has incorrect mint or owner"
+            );
+
+            // An active campaign overrides the global referral rate
+            if let Some(campaign_account) = campaign_account {
+                if *campaign_account.key != referrer.campaign || campaign_account.owner != program_id {
+                    return Err(ProgramError::InvalidAccountData);
+                }
+                let campaign = load_account::<ReferralCampaign>(campaign_account)?;
+                if campaign.is_initialized && Clock::get()?.unix_timestamp <= campaign.end_time {
+                    referral_rate = campaign.referral_rate;
+                }
+            }
+            referral_rebate_bps = referrer.rebate_bps;
+            Some((referrer_account, referrer, referrer_token_account))
+        }
+        (None, None) => None,
+        _ => {
+            msg!("Referrer PDA and referrer token account must be supplied together");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+    };
+
+    // Calculate token amounts
+    let (admin_fee_rate, mut swap_fee_rate) = if options.fee_exempt {
+        (0, 0)
+    } else {
+        (program_state.admin_fee_rate, program_state.swap_fee_rate)
+    };
+    if options.fee_exempt {
+        referral_rate = 0;
+    }
+    if fee_exempt_market_maker {
+        swap_fee_rate = 0;
+    }
+    let mut lp_contribution_rate = program_state.lp_contribution_rate;
+    if options.skip_contribution {
+        if program_state.opt_out_swap_fee_rate == 0 {
+            msg!("Opting out of the liquidity contribution is disabled");
+            return Err(ProgramError::InvalidArgument);
+        }
+        lp_contribution_rate = 0;
+        swap_fee_rate = program_state.opt_out_swap_fee_rate;
+    }
+    let FeeSplit {
+        lp_contribution: lp_contribution_amount,
+        admin_fee: admin_fee_amount,
+        swap_fee: swap_fee_amount,
+        referral: referral_amount,
+        net_amount_in,
+    } = split_amount_in(
+        amount_in,
+        lp_contribution_rate,
+        admin_fee_rate,
+        swap_fee_rate,
+        referral_rate,
+        referral_rebate_bps as u64,
+    );
+
+    // In slippage mode the bound comes from the program's own quote
+    let min_amount_out = match options.slippage_bps {
+        Some(slippage_bps) => {
+            let (reserve_in, reserve_out) = pool.reserves_for(&mint_in);
+            slippage_min_amount_out(net_amount_in, reserve_in, reserve_out, slippage_bps as u64)
+        }
+        None => min_amount_out,
+    };
+
+    // Account for swaps that paid the opt-out fee instead of contributing
+    if options.skip_contribution {
+        program_state.opt_out_swaps = program_state.opt_out_swaps.saturating_add(1);
+        program_state.opt_out_fees_collected = program_state.opt_out_fees_collected.saturating_add(swap_fee_amount);
+    }
+
+    // Split the liquidity contribution between the target pools. Shares of
+    // targets that do not trade the input mint, or that are the swapped pool
+    // itself, stay with the swapped pool.
+    let mut retained_contribution = lp_contribution_amount;
+    if let Some((contribution_targets, target_pool_accounts)) = contribution_targets.as_ref() {
+        let mut remaining = lp_contribution_amount;
+        for (index, target_pool_account) in target_pool_accounts.iter().enumerate() {
+            let share = if index + 1 == target_pool_accounts.len() {
+                remaining
+            } else {
+                bps_of(lp_contribution_amount, contribution_targets.weights_bps[index] as u64)
+            };
+            remaining -= share;
+            if pool_account.key == target_pool_account.key {
+                continue;
+            }
+            let mut target_pool = load_account::<PoolState>(target_pool_account)?;
+            if !target_pool.is_initialized || !target_pool.trades(&mint_in) {
+                continue;
+            }
+            target_pool.contribute(&mint_in, share)?;
+            if !options.simulate {
+                target_pool.serialize(&mut *target_pool_account.data.borrow_mut())?;
+            }
+            retained_contribution -= share;
+        }
+    }
+
+    // Price against the pair's pool
+    let mut contribution_shares = 0;
+    let (reserve_in, reserve_out) = pool.reserves_for(&mint_in);
+    let amount_out = constant_product_amount_out(reserve_in, reserve_out, net_amount_in).map_err(math_error)?;
+
+    // A contribution recorded in the user's ledger is deposited as their
+    // liquidity. It is valued at the pool's time-weighted reserves, so
+    // recording it requires them.
+    let mut pool_fee_amount = swap_fee_amount;
+    let mut reserve_amount_in = net_amount_in;
+    let records_shares = contribution_ledger
+        .as_ref()
+        .is_some_and(|contribution_ledger| contribution_ledger.has_room_for(pool_account.key));
+    let twap_reserves = pool_twap.as_ref().map(|pool_twap| pool_twap.reserves_for(&pool, &mint_in));
+    match twap_reserves.filter(|_| records_shares && pool.total_shares > 0) {
+        Some(twap_reserves) => {
+            contribution_shares = twap_single_sided_lp_shares(
+                retained_contribution,
+                (reserve_in, reserve_out),
+                twap_reserves,
+                pool.total_shares,
+            );
+            pool.total_shares =
+                pool.total_shares.checked_add(contribution_shares).ok_or(ProgramError::InvalidArgument)?;
+            reserve_amount_in += retained_contribution;
+        }
+        None => pool_fee_amount += retained_contribution,
+    }
+
+    // Observe the reserves as they stood before this swap
+    if let Some(pool_twap) = pool_twap.as_mut() {
+        pool_twap.observe(&pool, Clock::get()?.unix_timestamp);
+    }
+
+    // Fees compound into the reserves, raising LP share value
+    reserve_amount_in += pool_fee_amount;
+
+    // k must never decrease. Last-line defense against math bugs silently
+    // leaking reserves.
+    let k_before = pool.k();
+    pool.apply_hop(&mint_in, reserve_amount_in, amount_out)?;
+    if pool.k() < k_before {
+        msg!("K invariant violated: {} < {}", pool.k(), k_before);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Apply YOS cashback; lite swaps and fee-exempt market makers earn none
+    let yos_cashback_amount = if fee_exempt_market_maker || options.lite {
+        0
+    } else {
+        cashback_amount(amount_in, program_state.yos_cashback_rate)
+    };
+
+    // Ensure the swap meets the minimum output requirement
+    if amount_out < min_amount_out {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // The 0.1% admin commission on SOL-input swaps is paid in lamports into
+    // the SOL fee vault rather than taken from the wrapped SOL amount
+    let mut token_amount_in = amount_in;
+    if mint_in == spl_token::native_mint::id() && admin_fee_amount > 0 {
+        let (sol_fee_vault_account, system_program_account) = match (sol_fee_vault_account, system_program_account) {
+            (Some(vault), Some(system_program)) => (vault, system_program),
+            _ => {
+                msg!("SOL-input swaps require the SOL fee vault and system program accounts");
+                return Err(ProgramError::NotEnoughAccountKeys);
+            }
+        };
+        let (sol_fee_vault_address, _) = find_sol_fee_vault_address(program_id);
+        assert_pda!(sol_fee_vault_account, sol_fee_vault_address);
+
+        token_amount_in -= admin_fee_amount;
+        if !options.simulate {
+            invoke(
+                &system_instruction::transfer(user_account.key, sol_fee_vault_account.key, admin_fee_amount),
+                &[
+                    user_account.clone(),
+                    sol_fee_vault_account.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
+            program_state.sol_fees_collected = program_state.sol_fees_collected.saturating_add(admin_fee_amount);
+            msg!("Collected {} lamports SOL admin commission", admin_fee_amount);
+        }
+    } else {
+        // Other admin fees stay in the input vault, set apart for the treasury
+        pool.accrue_admin_fees(&mint_in, admin_fee_amount);
+    }
+
+    // Pay the referral fee straight from the user to the referrer
+    let pays_referral = referral_amount > 0 && !options.simulate;
+    if let Some(&(_, _, referrer_token_account)) = referral.as_ref().filter(|_| pays_referral) {
+        invoke(
+            &token_instruction::transfer(
+                token_program_account.key,
+                user_token_in_account.key,
+                referrer_token_account.key,
+                user_account.key,
+                &[],
+                referral_amount,
+            )?,
+            &[
+                user_token_in_account.clone(),
+                referrer_token_account.clone(),
+                user_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+        token_amount_in -= referral_amount;
+        msg!("Paid {} referral fee", referral_amount);
+    }
+
+    // Load the user's stats when supplied; a simulation starts from fresh
+    // stats rather than creating the PDA
+    let mut user_stats = match user_stats_account {
+        Some(user_stats_account) if options.simulate && user_stats_account.data_is_empty() => {
+            let (user_stats_address, _) = find_user_stats_address(program_id, user_account.key);
+            assert_pda!(user_stats_account, user_stats_address);
+            Some(new_user_stats(*user_account.key))
+        }
+        Some(user_stats_account) => Some(load_or_create_user_stats(
+            program_id,
+            rent_payer_account,
+            user_account,
+            user_stats_account,
+            system_program_account,
+        )?),
+        None => None,
+    };
+
+    // Track affiliate statistics on the referrer
+    if let Some((referrer_account, referrer, _)) = referral.as_mut() {
+        referrer.referred_volume = referrer.referred_volume.saturating_add(amount_in);
+        referrer.lifetime_earnings = referrer.lifetime_earnings.saturating_add(referral_amount);
+
+        // A referee counts once, when first bound through their user stats
+        if let Some(user_stats) = user_stats.as_mut() {
+            if user_stats.referrer == Pubkey::default() {
+                user_stats.referrer = referrer.owner;
+                referrer.referee_count = referrer.referee_count.saturating_add(1);
+            }
+        }
+        if !options.simulate {
+            referrer.serialize(&mut *referrer_account.data.borrow_mut())?;
+        }
+    }
+
+    // Withhold cashback for likely wash trades. Once detection is enabled the
+    // user stats PDA is required, since it holds the previous swap
+    let mut yos_cashback_amount = yos_cashback_amount;
+    let wash_trade = match user_stats.as_ref() {
+        Some(user_stats) => user_stats.is_wash_trade(
+            &mint_in,
+            &mint_out,
+            Clock::get()?.slot,
+            program_state.wash_trade_window_slots,
+        ),
+        None => mint_in == mint_out || program_state.wash_trade_window_slots > 0,
+    };
+    if wash_trade && !options.lite {
+        msg!("Cashback withheld for suspected wash trade");
+        yos_cashback_amount = 0;
+    }
+
+    // Apply the user's burn and collection cashback boosts
+    let mut boost_bps = match user_stats.as_ref() {
+        Some(user_stats) => user_stats.active_boost_bps(Clock::get()?.unix_timestamp),
+        None => 0,
+    };
+    if let (Some(nft_token_account), Some(nft_metadata_account)) = (nft_token_account, nft_metadata_account) {
+        if program_state.collection_boost_bps > 0
+            && holds_collection_nft(user_account.key, nft_token_account, nft_metadata_account, &program_state.boost_collection)?
+        {
+            boost_bps = boost_bps.saturating_add(program_state.collection_boost_bps);
+        }
+    }
+    let boost_bps = boost_bps.min(MAX_CASHBACK_BOOST_BPS);
+    yos_cashback_amount = apply_bonus_bps(yos_cashback_amount, boost_bps);
+
+    // A simulation stops here, before any account is written
+    if options.simulate {
+        let simulation = SwapSimulation {
+            amount_in,
+            amount_out,
+            lp_contribution: lp_contribution_amount,
+            admin_fee: admin_fee_amount,
+            swap_fee: swap_fee_amount,
+            referral: referral_amount,
+            yos_cashback: yos_cashback_amount,
+            contribution_shares,
+        };
+        set_return_data(&simulation.try_to_vec()?);
+        msg!("Simulated swap: {} in, {} out", amount_in, amount_out);
+        return Ok(());
+    }
+
+    // Accumulate the user's lifetime and leaderboard-epoch volume
+    if let (Some(user_stats_account), Some(mut user_stats)) = (user_stats_account, user_stats) {
+        let clock = Clock::get()?;
+        if wash_trade {
+            user_stats.flagged_swaps = user_stats.flagged_swaps.saturating_add(1);
+        }
+        user_stats.last_mint_in = mint_in;
+        user_stats.last_mint_out = mint_out;
+        user_stats.last_swap_slot = clock.slot;
+
+        let epoch = leaderboard_epoch(clock.unix_timestamp);
+        if user_stats.epoch != epoch {
+            user_stats.epoch = epoch;
+            user_stats.epoch_volume = 0;
+        }
+        user_stats.epoch_volume = user_stats.epoch_volume.saturating_add(amount_in);
+        user_stats.total_volume = user_stats.total_volume.saturating_add(amount_in);
+        if user_stats.stats_day != program_state.stats_day {
+            user_stats.stats_day = program_state.stats_day;
+            program_state.stats.unique_users = program_state.stats.unique_users.saturating_add(1);
+        }
+        user_stats.serialize(&mut *user_stats_account.data.borrow_mut())?;
+    }
+
+    // Count the swap, and the cashback it mints, in the live statistics
+    if !options.lite {
+        program_state.stats.record_swap(amount_in, admin_fee_amount + swap_fee_amount);
+    }
+    if user_yos_account.is_some() && cashback_ledger_account.is_none() {
+        program_state.stats.record_emission(yos_cashback_amount);
+    }
+
+    // Keep the receipt, and any cashback accrual, as compressed records
+    if let Some(activity_tree) = activity_tree.as_ref() {
+        let receipt = ActivityRecord {
+            kind: ACTIVITY_SWAP_RECEIPT,
+            leaf_index: 0,
+            user: *user_account.key,
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out,
+            timestamp: Clock::get()?.unix_timestamp,
+        };
+        let accrual = ActivityRecord {
+            kind: ACTIVITY_CASHBACK_ACCRUAL,
+            amount_out: yos_cashback_amount,
+            mint_out: program_state.yos_mint,
+            ..receipt.clone()
+        };
+        append_activity(&mut program_state, activity_tree, program_authority_account, program_authority_bump, receipt)?;
+        if user_yos_account.is_some() && cashback_ledger_account.is_some() && yos_cashback_amount > 0 {
+            append_activity(&mut program_state, activity_tree, program_authority_account, program_authority_bump, accrual)?;
+        }
+    }
+
+    // Write the receipt PDA
+    if let (Some(receipt_id), Some((swap_receipt_account, swap_receipt_bump)), Some(system_program_account)) =
+        (options.receipt_id, swap_receipt_account, system_program_account)
+    {
+        let rent = Rent::get()?;
+        let receipt_size = std::mem::size_of::<SwapReceipt>();
+        invoke_signed(
+            &system_instruction::create_account(
+                rent_payer_account.key,
+                swap_receipt_account.key,
+                rent.minimum_balance(receipt_size),
+                receipt_size as u64,
+                program_id,
+            ),
+            &[
+                rent_payer_account.clone(),
+                swap_receipt_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::SWAP_RECEIPT, user_account.key.as_ref(), &receipt_id.to_le_bytes(), &[swap_receipt_bump]]],
+        )?;
+
+        let clock = Clock::get()?;
+        let swap_receipt = SwapReceipt {
+            is_initialized: true,
+            user: *user_account.key,
+            receipt_id,
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out,
+            lp_contribution: lp_contribution_amount,
+            admin_fee: admin_fee_amount,
+            swap_fee: swap_fee_amount,
+            referral_fee: referral_amount,
+            route_hash: match options.route.is_empty() {
+                true => swap_route_hash(&[mint_in, mint_out]),
+                false => swap_route_hash(&options.route),
+            },
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        };
+        swap_receipt.serialize(&mut *swap_receipt_account.data.borrow_mut())?;
+    }
+    if !options.lite {
+        emit_event(
+            &mut program_state,
+            b"swap",
+            &SwapEvent {
+                user: *user_account.key,
+                mint_in,
+                mint_out,
+                amount_in,
+                amount_out,
+                memo: options.memo.clone(),
+            },
+        )?;
+    }
+    // Program state is written once: serializing advances the account's data
+    // slice, so a second write would not fit
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    // Move the input into the pool's vault
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            user_token_in_account.key,
+            vault_in_account.key,
+            user_account.key,
+            &[],
+            token_amount_in,
+        )?,
+        &[
+            user_token_in_account.clone(),
+            vault_in_account.clone(),
+            user_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    // Pay the output from the pool's vault to the user, or split it between
+    // the recipients with the last one receiving any rounding remainder
+    let mut payouts = Vec::with_capacity(recipient_accounts.len().max(1));
+    if recipient_accounts.is_empty() {
+        payouts.push((user_token_out_account, amount_out));
+    } else {
+        let mut remaining = amount_out;
+        for (index, (recipient_account, weight)) in recipient_accounts.iter().zip(&options.split_weights_bps).enumerate() {
+            if TokenAccount::unpack(&recipient_account.data.borrow())?.mint != mint_out {
+                msg!("Split recipient has incorrect mint");
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let share = if index + 1 == recipient_accounts.len() {
+                remaining
+            } else {
+                bps_of(amount_out, *weight as u64)
+            };
+            remaining -= share;
+            payouts.push((*recipient_account, share));
+        }
+    }
+    for (recipient_account, share) in payouts {
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                vault_out_account.key,
+                recipient_account.key,
+                program_authority_account.key,
+                &[],
+                share,
+            )?,
+            &[
+                vault_out_account.clone(),
+                recipient_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+    }
+
+    // Update the pair's reserves and cumulative counters
+    pool.volume_in = pool.volume_in.saturating_add(amount_in);
+    pool.volume_out = pool.volume_out.saturating_add(amount_out);
+    pool.fees_collected = pool.fees_collected.saturating_add(admin_fee_amount + swap_fee_amount);
+    pool.contribution_total = pool.contribution_total.saturating_add(retained_contribution);
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+    if let (Some(pool_twap_account), Some(pool_twap)) = (pool_twap_account, pool_twap.as_ref()) {
+        pool_twap.serialize(&mut *pool_twap_account.data.borrow_mut())?;
+    }
+
+    // Record the contribution kept in the swapped pool for the user's
+    // weekly rewards
+    if let (Some(contribution_ledger_account), Some(contribution_ledger)) =
+        (contribution_ledger_account, contribution_ledger.as_mut().filter(|_| retained_contribution > 0))
+    {
+        let now = Clock::get()?.unix_timestamp;
+        let unlock_time = now.saturating_add(program_state.contribution_lock_seconds);
+        let recorded =
+            contribution_ledger.record(*pool_account.key, retained_contribution, contribution_shares, now, unlock_time);
+        if !recorded {
+            msg!("Contribution ledger is full; contribution not recorded");
+        }
+        contribution_ledger.serialize(&mut *contribution_ledger_account.data.borrow_mut())?;
+    }
+
+    // Lite swaps stop here - everything below is optional bookkeeping
+    let user_yos_account = match user_yos_account {
+        Some(account) => account,
+        None => {
+            msg!("Lite swap processed successfully");
+            return Ok(());
+        }
+    };
+
+    // Accrue the cashback in the user's ledger, or send it right away
+    if let Some(cashback_ledger_account) = cashback_ledger_account {
+        let mut cashback_ledger = load_account::<CashbackLedger>(cashback_ledger_account)?;
+        if !cashback_ledger.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if cashback_ledger.accrued == 0 {
+            cashback_ledger.accrual_start = Clock::get()?.unix_timestamp;
+        }
+        cashback_ledger.accrued = cashback_ledger.accrued.saturating_add(yos_cashback_amount);
+        cashback_ledger.serialize(&mut *cashback_ledger_account.data.borrow_mut())?;
+    } else {
+        invoke_signed(
+            &token_instruction::mint_to(
+                token_program_account.key,
+                &program_state.yos_mint,
+                user_yos_account.key,
+                program_authority_account.key,
+                &[],
+                yos_cashback_amount,
+            )?,
+            &[
+                user_yos_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+    }
+
+    // Record the swap in the history ring buffer when one is supplied
+    if let Some(swap_history_account) = swap_history_account {
+        let (swap_history_address, _) = find_swap_history_address(program_id);
+        assert_pda!(swap_history_account, swap_history_address);
+        let mut swap_history = load_account::<SwapHistory>(swap_history_account)?;
+        swap_history.push(SwapRecord {
+            user: *user_account.key,
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out,
+            fee_amount: admin_fee_amount + swap_fee_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        swap_history.serialize(&mut *swap_history_account.data.borrow_mut())?;
+    }
+
+    // Forward the memo to the SPL Memo program when it is supplied
+    if let Some(memo_program_account) = memo_program_account.filter(|_| !options.memo.is_empty()) {
+        invoke(
+            &Instruction {
+                program_id: SPL_MEMO_PROGRAM_ID,
+                accounts: vec![AccountMeta::new_readonly(*user_account.key, true)],
+                data: options.memo.clone(),
+            },
+            &[user_account.clone(), memo_program_account.clone()],
+        )?;
+    }
+
+    msg!("Swap processed successfully");
+    Ok(())
+}
+
+/// Create the swap history ring buffer PDA
+fn process_initialize_swap_history(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let swap_history_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Verify PDA
+    let (swap_history_address, swap_history_bump) = find_swap_history_address(program_id);
+    assert_pda!(swap_history_account, swap_history_address);
+    if swap_history_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create swap history account
+    let rent = Rent::get()?;
+    let history_size = std::mem::size_of::<SwapHistory>();
+    let lamports = rent.minimum_balance(history_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            swap_history_account.key,
+            lamports,
+            history_size as u64,
+            program_id,
+        ),
+        &[
+            payer_account.clone(),
+            swap_history_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::SWAP_HISTORY, &[swap_history_bump]]],
+    )?;
+
+    // Initialize an empty ring buffer
+    let swap_history = SwapHistory {
+        is_initialized: true,
+        head: 0,
+        count: 0,
+        records: [SwapRecord::default(); SWAP_HISTORY_CAPACITY],
+    };
+    swap_history.serialize(&mut *swap_history_account.data.borrow_mut())?;
+
+    msg!("Swap history initialized successfully");
+    Ok(())
+}
+
+/// Update program parameters (admin only)
+fn process_update_parameters(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lp_contribution_rate: Option<u64>,
+    admin_fee_rate: Option<u64>,
+    yos_cashback_rate: Option<u64>,
+    swap_fee_rate: Option<u64>,
+    referral_rate: Option<u64>,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let parameter_history_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::UPDATE_PARAMETERS)?;
+
+    // Verify parameter history PDA
+    let (parameter_history_address, parameter_history_bump) = find_parameter_history_address(program_id);
+    assert_pda!(parameter_history_account, parameter_history_address);
+
+    // Update parameters that were provided, noting the ones that changed
+    let mut changes = Vec::new();
+    for (parameter, value, update) in [
+        (RateParameter::LpContributionRate, &mut program_state.lp_contribution_rate, lp_contribution_rate),
+        (RateParameter::AdminFeeRate, &mut program_state.admin_fee_rate, admin_fee_rate),
+        (RateParameter::YosCashbackRate, &mut program_state.yos_cashback_rate, yos_cashback_rate),
+        (RateParameter::SwapFeeRate, &mut program_state.swap_fee_rate, swap_fee_rate),
+        (RateParameter::ReferralRate, &mut program_state.referral_rate, referral_rate),
+    ] {
+        if let Some(rate) = update.filter(|rate| rate != value) {
+            changes.push(ParameterChange {
+                parameter,
+                old_value: *value,
+                new_value: rate,
+            });
+            *value = rate;
+        }
+    }
+
+    // Nothing changed, so there is nothing to publish or record
+    if changes.is_empty() {
+        msg!("Parameters unchanged");
+        return Ok(());
+    }
+
+    // Publish the old and new values, so fee changes are visible to users
+    emit_event(
+        &mut program_state,
+        b"parameters_updated",
+        &ParametersUpdatedEvent {
+            signer: *admin_account.key,
+            changes,
+        },
+    )?;
+
+    // Save updated state
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    // Create the parameter history on the first change
+    let mut parameter_history = if parameter_history_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let history_size = std::mem::size_of::<ParameterHistory>();
+        let lamports = rent.minimum_balance(history_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                parameter_history_account.key,
+                lamports,
+                history_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                parameter_history_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::PARAMETER_HISTORY, &[parameter_history_bump]]],
+        )?;
+
+        ParameterHistory {
+            head: 0,
+            count: 0,
+            total_changes: 0,
+            snapshots: [ParameterSnapshot::default(); PARAMETER_HISTORY_CAPACITY],
+        }
+    } else {
+        load_account::<ParameterHistory>(parameter_history_account)?
+    };
+
+    // Record the parameters now in effect
+    parameter_history.push(ParameterSnapshot {
+        timestamp: Clock::get()?.unix_timestamp,
+        signer: *admin_account.key,
+        lp_contribution_rate: program_state.lp_contribution_rate,
+        admin_fee_rate: program_state.admin_fee_rate,
+        yos_cashback_rate: program_state.yos_cashback_rate,
+        swap_fee_rate: program_state.swap_fee_rate,
+        referral_rate: program_state.referral_rate,
+    });
+    parameter_history.serialize(&mut *parameter_history_account.data.borrow_mut())?;
+
+    msg!("Parameters updated successfully");
+    Ok(())
+}
+
+/// Set a new admin (admin only)
+fn process_set_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_admin: Pubkey,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_ADMIN)?;
+
+    // Update admin
+    program_state.admin = new_admin;
+
+    // Save updated state
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Admin updated successfully");
+    Ok(())
+}
+
+/// Close the program (admin only)
+fn process_close_program(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::CLOSE_PROGRAM)?;
+
+    // Transfer lamports from program state account to admin (closing the account)
+    let lamports = program_state_account.lamports();
+    **program_state_account.lamports.borrow_mut() = 0;
+    **admin_account.lamports.borrow_mut() += lamports;
+
+    // Clear the data
+    program_state_account.data.borrow_mut().fill(0);
+
+    msg!("Program closed successfully");
+    Ok(())
+}
+
+/// Create the pool PDA for a token pair (admin only)
+fn process_initialize_pool(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::INITIALIZE_POOL)?;
+
+    create_pool(program_id, admin_account, pool_account, system_program_account, mint_a, mint_b)?;
+
+    msg!("Pool initialized successfully");
+    Ok(())
+}
+
+/// Create the pool PDA for a mint pair with zeroed counters
+fn create_pool<'a>(
+    program_id: &Pubkey,
+    payer_account: &AccountInfo<'a>,
+    pool_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+) -> Result<PoolState, ProgramError> {
+    // A pool needs two distinct mints
+    if mint_a == mint_b {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (mint_a, mint_b) = if mint_a < mint_b { (mint_a, mint_b) } else { (mint_b, mint_a) };
+
+    // Verify PDA
+    let (pool_address, pool_bump) = find_pool_address(program_id, &mint_a, &mint_b);
+    assert_pda!(pool_account, pool_address);
+    if pool_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create pool account
+    let rent = Rent::get()?;
+    let pool_size = std::mem::size_of::<PoolState>();
+    let lamports = rent.minimum_balance(pool_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            pool_account.key,
+            lamports,
+            pool_size as u64,
+            program_id,
+        ),
+        &[
+            payer_account.clone(),
+            pool_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::POOL, mint_a.as_ref(), mint_b.as_ref(), &[pool_bump]]],
+    )?;
+
+    // Initialize pool with zeroed counters
+    let pool = PoolState {
+        is_initialized: true,
+        mint_a,
+        mint_b,
+        volume_in: 0,
+        volume_out: 0,
+        fees_collected: 0,
+        contribution_total: 0,
+        reserve_a: 0,
+        reserve_b: 0,
+        total_shares: 0,
+        max_reserve_a: 0,
+        max_reserve_b: 0,
+        admin_fees_a: 0,
+        admin_fees_b: 0,
+    };
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+    Ok(pool)
+}
+
+/// Create a pool's time-weighted reserves PDA, seeded with its current
+/// reserves (admin only)
+fn process_initialize_pool_twap(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let pool_twap_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::INITIALIZE_POOL_TWAP)?;
+
+    // Load the pool
+    assert_owner!(pool_account, program_id);
+    let pool = load_account::<PoolState>(pool_account)?;
+    let (pool_address, _) = find_pool_address(program_id, &pool.mint_a, &pool.mint_b);
+    assert_pda!(pool_account, pool_address);
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify PDA
+    let (pool_twap_address, pool_twap_bump) = find_pool_twap_address(program_id, pool_account.key);
+    assert_pda!(pool_twap_account, pool_twap_address);
+    if pool_twap_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create the account
+    let rent = Rent::get()?;
+    let pool_twap_size = std::mem::size_of::<PoolTwap>();
+    let lamports = rent.minimum_balance(pool_twap_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin_account.key,
+            pool_twap_account.key,
+            lamports,
+            pool_twap_size as u64,
+            program_id,
+        ),
+        &[
+            admin_account.clone(),
+            pool_twap_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::POOL_TWAP, pool_account.key.as_ref(), &[pool_twap_bump]]],
+    )?;
+
+    let pool_twap = PoolTwap {
+        is_initialized: true,
+        pool: *pool_account.key,
+        reserve_a: pool.reserve_a,
+        reserve_b: pool.reserve_b,
+        last_update: Clock::get()?.unix_timestamp,
+    };
+    pool_twap.serialize(&mut *pool_twap_account.data.borrow_mut())?;
+
+    msg!("Time-weighted reserves initialized for pool {}", pool_account.key);
+    Ok(())
+}
+
+/// Deposit both sides of a pair into its pool in exchange for LP shares
+fn process_add_liquidity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_a: u64,
+    amount_b: u64,
+    min_shares: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let position_account = next_account_info(account_info_iter)?;
+    let user_token_a_account = next_account_info(account_info_iter)?;
+    let user_token_b_account = next_account_info(account_info_iter)?;
+    let vault_a_account = next_account_info(account_info_iter)?;
+    let vault_b_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    // Optional rent payer for a new position
+    let rent_payer_account = find_rent_payer(account_info_iter, user_account);
+
+    // Validate accounts
+    assert_signer!(user_account);
+    if amount_a == 0 || amount_b == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load pool
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let (pool_address, _) = find_pool_address(program_id, &pool.mint_a, &pool.mint_b);
+    assert_pda!(pool_account, pool_address);
+
+    // Verify the program authority
+    let (program_authority_address, _) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Vaults must be authority-owned token accounts of the pool's mints
+    for (vault_account, mint) in [(vault_a_account, &pool.mint_a), (vault_b_account, &pool.mint_b)] {
+        assert_token_account!(
+            vault_account,
+            *mint,
+            program_authority_address,
+            "Pool vault has incorrect mint or owner"
+        );
+    }
+
+    // Calculate LP shares to mint
+    let shares = deposit_lp_shares(&mut pool, amount_a, amount_b)?;
+    if shares == 0 || shares < min_shares {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // Create the position on first deposit
+    let mut position = load_or_create_position(
+        program_id,
+        rent_payer_account,
+        user_account.key,
+        pool_account,
+        position_account,
+        system_program_account,
+    )?;
+
+    // Transfer both sides into the pool vaults
+    for (user_token_account, vault_account, amount) in [
+        (user_token_a_account, vault_a_account, amount_a),
+        (user_token_b_account, vault_b_account, amount_b),
+    ] {
+        invoke(
+            &token_instruction::transfer(
+                token_program_account.key,
+                user_token_account.key,
+                vault_account.key,
+                user_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                user_token_account.clone(),
+                vault_account.clone(),
+                user_account.clone(),
+                token_program_account.clone(),
+            ],
+        )?;
+    }
+
+    // Store the updated reserves and shares
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    position.shares = position.shares.checked_add(shares).ok_or(ProgramError::InvalidArgument)?;
+    position.serialize(&mut *position_account.data.borrow_mut())?;
+
+    msg!("Added liquidity for {} LP shares", shares);
+    Ok(())
+}
+
+/// Burn LP shares from the signer's position and pay out both sides of the
+/// pool
+fn process_remove_liquidity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    shares: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let position_account = next_account_info(account_info_iter)?;
+    let user_token_a_account = next_account_info(account_info_iter)?;
+    let user_token_b_account = next_account_info(account_info_iter)?;
+    let vault_a_account = next_account_info(account_info_iter)?;
+    let vault_b_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(user_account);
+    if shares == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load pool
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let (pool_address, _) = find_pool_address(program_id, &pool.mint_a, &pool.mint_b);
+    assert_pda!(pool_account, pool_address);
+
+    // Load the signer's position
+    let (position_address, _) = find_liquidity_position_address(program_id, pool_account.key, user_account.key);
+    assert_pda!(position_account, position_address);
+    assert_owner!(position_account, program_id);
+    let mut position = load_account::<LiquidityPosition>(position_account)?;
+    if !position.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if position.shares < shares {
+        msg!("Position holds only {} LP shares", position.shares);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Vaults must be authority-owned token accounts of the pool's mints
+    for (vault_account, mint) in [(vault_a_account, &pool.mint_a), (vault_b_account, &pool.mint_b)] {
+        assert_token_account!(vault_account, *mint, program_authority_address);
+    }
+
+    // Pay out both sides in proportion to the pool
+    let amount_a = withdrawal_amount(shares, pool.reserve_a, pool.total_shares);
+    let amount_b = withdrawal_amount(shares, pool.reserve_b, pool.total_shares);
+    if amount_a < min_amount_a || amount_b < min_amount_b {
+        msg!("Slippage exceeded: {}/{} below {}/{}", amount_a, amount_b, min_amount_a, min_amount_b);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    pool.reserve_a -= amount_a;
+    pool.reserve_b -= amount_b;
+    pool.total_shares -= shares;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+    position.shares -= shares;
+    position.serialize(&mut *position_account.data.borrow_mut())?;
+
+    for (vault_account, user_token_account, amount) in [
+        (vault_a_account, user_token_a_account, amount_a),
+        (vault_b_account, user_token_b_account, amount_b),
+    ] {
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                vault_account.key,
+                user_token_account.key,
+                program_authority_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                vault_account.clone(),
+                user_token_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+    }
+
+    msg!("Removed {} LP shares for {} and {}", shares, amount_a, amount_b);
+    Ok(())
+}
+
+/// Add a two-sided deposit to a pool's reserves and shares, returning the LP
+/// shares minted for it. Fails when a reserve would pass its deposit cap
+fn deposit_lp_shares(pool: &mut PoolState, amount_a: u64, amount_b: u64) -> Result<u64, ProgramError> {
+    let shares = if pool.total_shares == 0 {
+        // First deposit: geometric mean, with MINIMUM_LIQUIDITY locked forever
+        let initial_shares = initial_lp_shares(amount_a, amount_b);
+        if initial_shares <= MINIMUM_LIQUIDITY {
+            msg!("First deposit too small to cover the minimum liquidity lock");
+            return Err(ProgramError::InsufficientFunds);
+        }
+        pool.total_shares = MINIMUM_LIQUIDITY;
+        initial_shares - MINIMUM_LIQUIDITY
+    } else {
+        // Later deposits: proportional to the smaller side
+        proportional_lp_shares(amount_a, amount_b, pool.reserve_a, pool.reserve_b, pool.total_shares)
+    };
+
+    pool.reserve_a = pool.reserve_a.checked_add(amount_a).ok_or(ProgramError::InvalidArgument)?;
+    pool.reserve_b = pool.reserve_b.checked_add(amount_b).ok_or(ProgramError::InvalidArgument)?;
+    pool.total_shares = pool.total_shares.checked_add(shares).ok_or(ProgramError::InvalidArgument)?;
+    pool.check_deposit_caps()?;
+    Ok(shares)
+}
+
+// Whether `amount_a`:`amount_b` is within LAUNCH_SEED_PRICE_TOLERANCE_BPS
+// of `reserve_a`:`reserve_b`
+fn same_price(amount_a: u64, amount_b: u64, reserve_a: u64, reserve_b: u64) -> bool {
+    let price = amount_b as u128 * reserve_a as u128;
+    let pool_price = reserve_b as u128 * amount_a as u128;
+    price.abs_diff(pool_price) <= price / BPS_DENOMINATOR as u128 * LAUNCH_SEED_PRICE_TOLERANCE_BPS as u128
+}
+
+/// Load `owner`'s LP position in a pool, creating it paid by `payer_account`
+/// on first deposit
+fn load_or_create_position<'a>(
+    program_id: &Pubkey,
+    payer_account: &AccountInfo<'a>,
+    owner: &Pubkey,
+    pool_account: &AccountInfo<'a>,
+    position_account: &AccountInfo<'a>,
+    system_program_account: &AccountInfo<'a>,
+) -> Result<LiquidityPosition, ProgramError> {
+    let (position_address, position_bump) = find_liquidity_position_address(program_id, pool_account.key, owner);
+    assert_pda!(position_account, position_address);
+    if !position_account.data_is_empty() {
+        return load_account::<LiquidityPosition>(position_account);
+    }
+
+    let rent = Rent::get()?;
+    let position_size = std::mem::size_of::<LiquidityPosition>();
+    let lamports = rent.minimum_balance(position_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            position_account.key,
+            lamports,
+            position_size as u64,
+            program_id,
+        ),
+        &[
+            payer_account.clone(),
+            position_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::LP_POSITION, pool_account.key.as_ref(), owner.as_ref(), &[position_bump]]],
+    )?;
+
+    Ok(LiquidityPosition {
+        is_initialized: true,
+        owner: *owner,
+        pool: *pool_account.key,
+        shares: 0,
+    })
+}
+
+/// Set the deposit cap on a pool's reserve of `mint` (admin only)
+fn process_set_pool_deposit_cap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    max_reserve: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_POOL_DEPOSIT_CAP)?;
+
+    // Load pool
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let (pool_address, _) = find_pool_address(program_id, &pool.mint_a, &pool.mint_b);
+    assert_pda!(pool_account, pool_address);
+
+    // Update the cap of the matching side
+    if mint == pool.mint_a {
+        pool.max_reserve_a = max_reserve;
+    } else if mint == pool.mint_b {
+        pool.max_reserve_b = max_reserve;
+    } else {
+        msg!("Pool does not trade {}", mint);
+        return Err(ProgramError::InvalidArgument);
+    }
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    msg!("Deposit cap on the {} reserve set to {}", mint, max_reserve);
+    Ok(())
+}
+
+/// Create the program-owned SOL fee vault PDA
+fn process_initialize_sol_fee_vault(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let sol_fee_vault_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Verify PDA
+    let (sol_fee_vault_address, sol_fee_vault_bump) = find_sol_fee_vault_address(program_id);
+    assert_pda!(sol_fee_vault_account, sol_fee_vault_address);
+    if sol_fee_vault_account.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create a data-less, rent-exempt vault owned by the program so the
+    // admin withdrawal can debit it directly
+    let rent = Rent::get()?;
+    let lamports = rent.minimum_balance(0);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            sol_fee_vault_account.key,
+            lamports,
+            0,
+            program_id,
+        ),
+        &[
+            payer_account.clone(),
+            sol_fee_vault_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::SOL_FEE_VAULT, &[sol_fee_vault_bump]]],
+    )?;
+
+    msg!("SOL fee vault initialized successfully");
+    Ok(())
+}
+
+/// Withdraw collected SOL commission from the fee vault (admin only)
+fn process_withdraw_sol_fees(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let sol_fee_vault_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let admin_wallet_account = next_account_info(account_info_iter)?;
+    let approver_accounts = account_info_iter.as_slice();
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::WITHDRAW_SOL_FEES)?;
+
+    // The fees only ever go to the admin wallet, never to whoever signed
+    if *admin_wallet_account.key != program_state.admin {
+        msg!("SOL fees are paid to the admin {}", program_state.admin);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Large withdrawals also need the treasury approvers, once they are set
+    let (treasury_address, _) = find_treasury_address(program_id);
+    assert_pda!(treasury_account, treasury_address);
+    if treasury_account.data_len() > 0 {
+        let treasury = load_account::<Treasury>(treasury_account)?;
+        let approvals = treasury.approvals(approver_accounts);
+        if treasury.is_initialized && amount > treasury.approval_amount && approvals < treasury.threshold as usize {
+            msg!("Withdrawal needs {} treasury approvals, got {}", treasury.threshold, approvals);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    }
+
+    // Verify vault
+    let (sol_fee_vault_address, _) = find_sol_fee_vault_address(program_id);
+    if sol_fee_vault_address != *sol_fee_vault_account.key || sol_fee_vault_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Never withdraw below the vault's rent-exempt minimum
+    let rent = Rent::get()?;
+    let available = sol_fee_vault_account.lamports().saturating_sub(rent.minimum_balance(0));
+    if amount > available {
+        msg!("Requested {} lamports but only {} available", amount, available);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // Move lamports from the vault to the admin
+    **sol_fee_vault_account.lamports.borrow_mut() -= amount;
+    **admin_wallet_account.lamports.borrow_mut() += amount;
+
+    msg!("Withdrew {} lamports of SOL fees", amount);
+    Ok(())
+}
+
+/// Create the treasury approver set (admin) or replace it (current approvers)
+fn process_set_treasury_approvers(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    approvers: Vec<Pubkey>,
+    threshold: u8,
+    approval_amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let approver_accounts = account_info_iter.as_slice();
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Validate the new set
+    if approvers.is_empty() || approvers.len() > MAX_TREASURY_APPROVERS {
+        msg!("Treasury needs between 1 and {} approvers", MAX_TREASURY_APPROVERS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if threshold == 0 || threshold as usize > approvers.len() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    for (index, approver) in approvers.iter().enumerate() {
+        if approvers[..index].contains(approver) {
+            msg!("Duplicate treasury approver {}", approver);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    // Verify PDA
+    let (treasury_address, treasury_bump) = find_treasury_address(program_id);
+    assert_pda!(treasury_account, treasury_address);
+
+    if treasury_account.data_len() == 0 {
+        // The admin bootstraps the first approver set
+        authorize(program_id, accounts, &program_state, payer_account, admin_instruction::SET_TREASURY_APPROVERS)?;
+
+        // Create treasury account
+        let rent = Rent::get()?;
+        let treasury_size = std::mem::size_of::<Treasury>();
+        let lamports = rent.minimum_balance(treasury_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account.key,
+                treasury_account.key,
+                lamports,
+                treasury_size as u64,
+                program_id,
+            ),
+            &[
+                payer_account.clone(),
+                treasury_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::TREASURY, &[treasury_bump]]],
+        )?;
+    } else {
+        // Only the current approvers can replace themselves
+        let treasury = load_account::<Treasury>(treasury_account)?;
+        let approvals = treasury.approvals(approver_accounts);
+        if approvals < treasury.threshold as usize {
+            msg!("Changing approvers needs {} approvals, got {}", treasury.threshold, approvals);
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    }
+
+    let mut treasury = Treasury {
+        is_initialized: true,
+        threshold,
+        approver_count: approvers.len() as u8,
+        approvers: [Pubkey::default(); MAX_TREASURY_APPROVERS],
+        approval_amount,
+    };
+    treasury.approvers[..approvers.len()].copy_from_slice(&approvers);
+    treasury.serialize(&mut *treasury_account.data.borrow_mut())?;
+
+    msg!("Treasury approvers set: {} of {}", threshold, approvers.len());
+    Ok(())
+}
+
+/// Pay tokens out of a treasury token account to a recipient (admin only,
+/// plus the treasury approvers above the approval amount)
+fn process_withdraw_treasury(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let treasury_token_account = next_account_info(account_info_iter)?;
+    let recipient_token_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let approver_accounts = account_info_iter.as_slice();
+
+    // Validate accounts
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::WITHDRAW_TREASURY)?;
+
+    // The treasury's approvers must be set, and sign withdrawals above the
+    // approval amount
+    let (treasury_address, treasury_bump) = find_treasury_address(program_id);
+    assert_pda!(treasury_account, treasury_address);
+    assert_owner!(treasury_account, program_id);
+    let treasury = load_account::<Treasury>(treasury_account)?;
+    if !treasury.is_initialized {
+        msg!("Treasury approvers must be set before withdrawing");
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let approvals = treasury.approvals(approver_accounts);
+    if amount > treasury.approval_amount && approvals < treasury.threshold as usize {
+        msg!("Withdrawal needs {} treasury approvals, got {}", treasury.threshold, approvals);
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify the treasury's token account and a recipient of its mint
+    let treasury_token = TokenAccount::unpack(&treasury_token_account.data.borrow())?;
+    if treasury_token.owner != treasury_address {
+        msg!("Token account is not held by the treasury");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if amount > treasury_token.amount {
+        msg!("Requested {} but the treasury holds {}", amount, treasury_token.amount);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let recipient_token = TokenAccount::unpack(&recipient_token_account.data.borrow())?;
+    if recipient_token.mint != treasury_token.mint {
+        msg!("Recipient token account has incorrect mint");
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The treasury PDA signs for its own token account
+    invoke_signed(
+        &token_instruction::transfer(
+            token_program_account.key,
+            treasury_token_account.key,
+            recipient_token_account.key,
+            treasury_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            treasury_token_account.clone(),
+            recipient_token_account.clone(),
+            treasury_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::TREASURY, &[treasury_bump]]],
+    )?;
+
+    msg!("Withdrew {} of {} from the treasury to {}", amount, treasury_token.mint, recipient_token.owner);
+    Ok(())
+}
+
+/// Create or update the per-cluster config PDA (admin only)
+fn process_set_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    cluster: u8,
+    sol_yot_pool: Pubkey,
+    stable_mint: Pubkey,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_config_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CONFIG)?;
+
+    if cluster > CLUSTER_LOCALNET {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (program_config_address, program_config_bump) = find_program_config_address(program_id);
+    assert_pda!(program_config_account, program_config_address);
+
+    // Create config account on first use
+    if program_config_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let config_size = std::mem::size_of::<ProgramConfig>();
+        let lamports = rent.minimum_balance(config_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                program_config_account.key,
+                lamports,
+                config_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                program_config_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::PROGRAM_CONFIG, &[program_config_bump]]],
+        )?;
+    }
+
+    // Store config, mirroring the mints from program state
+    let program_config = ProgramConfig {
+        is_initialized: true,
+        cluster,
+        yot_mint: program_state.yot_mint,
+        yos_mint: program_state.yos_mint,
+        sol_yot_pool,
+        stable_mint,
+    };
+    program_config.serialize(&mut *program_config_account.data.borrow_mut())?;
+
+    msg!("Program config set for cluster {}", cluster);
+    Ok(())
+}
+
+/// Appoint the pauser (admin only)
+fn process_set_pauser(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    pauser: Pubkey,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_PAUSER)?;
+
+    // Update pauser
+    program_state.pauser = pauser;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Pauser updated successfully");
+    Ok(())
+}
+
+/// Set the paused instruction mask (pauser or admin)
+fn process_set_paused_mask(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    paused_mask: u8,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let authority_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify pauser or admin
+    if program_state.pauser != *authority_account.key && program_state.admin != *authority_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    assert_signer!(authority_account);
+
+    // Update mask
+    program_state.paused_mask = paused_mask;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Paused mask set to {:#04x}", paused_mask);
+    Ok(())
+}
+
+/// Record the governance expected to hold the upgrade authority (admin only)
+fn process_set_governance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    governance: Pubkey,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_GOVERNANCE)?;
+
+    // Update governance
+    program_state.governance = governance;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Governance updated successfully");
+    Ok(())
+}
+
+/// Verify the upgrade authority recorded in the BPF Loader Upgradeable
+/// program data account matches the governance in program state
+fn process_check_upgrade_authority(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_data_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify the program data account belongs to this program
+    let (program_data_address, _) =
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if program_data_address != *program_data_account.key || *program_data_account.owner != bpf_loader_upgradeable::id() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // ProgramData layout: u32 variant (3), u64 slot, Option<Pubkey> authority
+    let data = program_data_account.data.borrow();
+    if data.len() < 13 || data[0..4] != 3u32.to_le_bytes() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let upgrade_authority = match data[12] {
+        0 => None,
+        1 if data.len() >= 45 => Some(Pubkey::new_from_array(
+            data[13..45].try_into().map_err(|_| ProgramError::InvalidAccountData)?,
+        )),
+        _ => return Err(ProgramError::InvalidAccountData),
+    };
+
+    let matches = upgrade_authority == Some(program_state.governance);
+    let governance = program_state.governance;
+    emit_event(
+        &mut program_state,
+        b"upgrade_authority_check",
+        &UpgradeAuthorityCheckEvent {
+            upgrade_authority,
+            governance,
+            matches,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    if !matches {
+        msg!("Upgrade authority {:?} does not match governance {}", upgrade_authority, program_state.governance);
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    msg!("Upgrade authority matches governance");
+    Ok(())
+}
+
+/// Load a user's stats PDA, creating it paid by `payer_account` on first use
+fn load_or_create_user_stats<'a>(
+    program_id: &Pubkey,
+    payer_account: &AccountInfo<'a>,
+    user_account: &AccountInfo<'a>,
+    user_stats_account: &AccountInfo<'a>,
+    system_program_account: Option<&AccountInfo<'a>>,
+) -> Result<UserStats, ProgramError> {
+    let (user_stats_address, user_stats_bump) = find_user_stats_address(program_id, user_account.key);
+    assert_pda!(user_stats_account, user_stats_address);
+    if user_stats_account.data_len() > 0 {
+        return load_account::<UserStats>(user_stats_account);
+    }
+
+    let system_program_account = system_program_account.ok_or_else(|| {
+        msg!("Creating user stats requires the system program account");
+        ProgramError::NotEnoughAccountKeys
+    })?;
+    let rent = Rent::get()?;
+    let user_stats_size = std::mem::size_of::<UserStats>();
+    let lamports = rent.minimum_balance(user_stats_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            user_stats_account.key,
+            lamports,
+            user_stats_size as u64,
+            program_id,
+        ),
+        &[
+            payer_account.clone(),
+            user_stats_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::USER_STATS, user_account.key.as_ref(), &[user_stats_bump]]],
+    )?;
+
+    Ok(new_user_stats(*user_account.key))
+}
+
+// Stats of a wallet that has not swapped yet
+fn new_user_stats(owner: Pubkey) -> UserStats {
+    UserStats {
+        is_initialized: true,
+        owner,
+        referrer: Pubkey::default(),
+        total_volume: 0,
+        epoch: 0,
+        epoch_volume: 0,
+        last_mint_in: Pubkey::default(),
+        last_mint_out: Pubkey::default(),
+        last_swap_slot: 0,
+        flagged_swaps: 0,
+        boost_bps: 0,
+        boost_expiry: 0,
+        stats_day: 0,
+    }
+}
+
+/// Create the signer's referrer PDA
+fn process_register_referrer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let owner_account = next_account_info(account_info_iter)?;
+    let referrer_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(owner_account);
+
+    // Verify PDA
+    let (referrer_address, referrer_bump) = find_referrer_address(program_id, owner_account.key);
+    assert_pda!(referrer_account, referrer_address);
+    if referrer_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create referrer account
+    let rent = Rent::get()?;
+    let referrer_size = std::mem::size_of::<ReferrerAccount>();
+    let lamports = rent.minimum_balance(referrer_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            owner_account.key,
+            referrer_account.key,
+            lamports,
+            referrer_size as u64,
+            program_id,
+        ),
+        &[
+            owner_account.clone(),
+            referrer_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::REFERRER, owner_account.key.as_ref(), &[referrer_bump]]],
+    )?;
+
+    let referrer = ReferrerAccount {
+        is_initialized: true,
+        owner: *owner_account.key,
+        campaign: Pubkey::default(),
+        rebate_bps: 0,
+        referred_volume: 0,
+        referee_count: 0,
+        lifetime_earnings: 0,
+    };
+    referrer.serialize(&mut *referrer_account.data.borrow_mut())?;
+
+    msg!("Referrer registered successfully");
+    Ok(())
+}
+
+/// Create a time-boxed referral campaign (admin only)
+fn process_create_referral_campaign(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    campaign_id: u64,
+    referral_rate: u64,
+    end_time: i64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let campaign_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::CREATE_REFERRAL_CAMPAIGN)?;
+
+    // Campaign must end in the future with a sane rate
+    if referral_rate > 10000 || end_time <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (campaign_address, campaign_bump) = find_referral_campaign_address(program_id, campaign_id);
+    assert_pda!(campaign_account, campaign_address);
+    if campaign_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create campaign account
+    let rent = Rent::get()?;
+    let campaign_size = std::mem::size_of::<ReferralCampaign>();
+    let lamports = rent.minimum_balance(campaign_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin_account.key,
+            campaign_account.key,
+            lamports,
+            campaign_size as u64,
+            program_id,
+        ),
+        &[
+            admin_account.clone(),
+            campaign_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::REFERRAL_CAMPAIGN, &campaign_id.to_le_bytes(), &[campaign_bump]]],
+    )?;
+
+    let campaign = ReferralCampaign {
+        is_initialized: true,
+        campaign_id,
+        referral_rate,
+        end_time,
+    };
+    campaign.serialize(&mut *campaign_account.data.borrow_mut())?;
+
+    msg!("Referral campaign {} created", campaign_id);
+    Ok(())
+}
+
+/// Attach the signer's referrer PDA to a campaign
+fn process_join_referral_campaign(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let owner_account = next_account_info(account_info_iter)?;
+    let referrer_account = next_account_info(account_info_iter)?;
+    let campaign_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(owner_account);
+
+    // Verify referrer PDA
+    let (referrer_address, _) = find_referrer_address(program_id, owner_account.key);
+    assert_pda!(referrer_account, referrer_address);
+    let mut referrer = load_account::<ReferrerAccount>(referrer_account)?;
+    if !referrer.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Only active campaigns can be joined
+    assert_owner!(campaign_account, program_id);
+    let campaign = load_account::<ReferralCampaign>(campaign_account)?;
+    let (campaign_address, _) = find_referral_campaign_address(program_id, campaign.campaign_id);
+    if !campaign.is_initialized || campaign_address != *campaign_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if Clock::get()?.unix_timestamp > campaign.end_time {
+        msg!("Referral campaign has ended");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    referrer.campaign = *campaign_account.key;
+    referrer.serialize(&mut *referrer_account.data.borrow_mut())?;
+
+    msg!("Joined referral campaign {}", campaign.campaign_id);
+    Ok(())
+}
+
+/// Set the share of the signer's referral fee rebated to referees
+fn process_set_referrer_rebate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rebate_bps: u16,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let owner_account = next_account_info(account_info_iter)?;
+    let referrer_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(owner_account);
+    if rebate_bps > 10000 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify referrer PDA
+    let (referrer_address, _) = find_referrer_address(program_id, owner_account.key);
+    assert_pda!(referrer_account, referrer_address);
+    let mut referrer = load_account::<ReferrerAccount>(referrer_account)?;
+    if !referrer.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    referrer.rebate_bps = rebate_bps;
+    referrer.serialize(&mut *referrer_account.data.borrow_mut())?;
+
+    msg!("Referrer rebate set to {} bps", rebate_bps);
+    Ok(())
+}
+
+/// Rank the supplied user stats PDAs on an epoch leaderboard
+fn process_update_leaderboard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Verify PDA
+    let (leaderboard_address, leaderboard_bump) = find_leaderboard_address(program_id, epoch);
+    assert_pda!(leaderboard_account, leaderboard_address);
+
+    // Create the leaderboard on first use
+    let mut leaderboard = if leaderboard_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let leaderboard_size = std::mem::size_of::<Leaderboard>();
+        let lamports = rent.minimum_balance(leaderboard_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_account.key,
+                leaderboard_account.key,
+                lamports,
+                leaderboard_size as u64,
+                program_id,
+            ),
+            &[
+                payer_account.clone(),
+                leaderboard_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::LEADERBOARD, &epoch.to_le_bytes(), &[leaderboard_bump]]],
+        )?;
+
+        Leaderboard {
+            is_initialized: true,
+            epoch,
+            is_finalized: false,
+            prize_amount: 0,
+            entries: [LeaderboardEntry::default(); LEADERBOARD_SIZE],
+        }
+    } else {
+        load_account::<Leaderboard>(leaderboard_account)?
+    };
+    if leaderboard.is_finalized {
+        msg!("Leaderboard for epoch {} is finalized", epoch);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Rank every supplied user stats account that has volume in the epoch
+    for user_stats_account in account_info_iter {
+        assert_owner!(user_stats_account, program_id);
+        let user_stats = load_account::<UserStats>(user_stats_account)?;
+        let (user_stats_address, _) = find_user_stats_address(program_id, &user_stats.owner);
+        if !user_stats.is_initialized || user_stats_address != *user_stats_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if user_stats.epoch == epoch && user_stats.epoch_volume > 0 {
+            leaderboard.record(user_stats.owner, user_stats.epoch_volume);
+        }
+    }
+    leaderboard.serialize(&mut *leaderboard_account.data.borrow_mut())?;
+
+    msg!("Leaderboard for epoch {} has {} ranked wallets", epoch, leaderboard.ranked_count());
+    Ok(())
+}
+
+/// Finalize a past epoch's leaderboard and set its prize (admin only)
+fn process_finalize_leaderboard(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+    prize_amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::FINALIZE_LEADERBOARD)?;
+
+    // Only past epochs can be finalized
+    if epoch >= leaderboard_epoch(Clock::get()?.unix_timestamp) {
+        msg!("Epoch {} has not ended yet", epoch);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (leaderboard_address, _) = find_leaderboard_address(program_id, epoch);
+    assert_pda!(leaderboard_account, leaderboard_address);
+    let mut leaderboard = load_account::<Leaderboard>(leaderboard_account)?;
+    if !leaderboard.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if leaderboard.is_finalized {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    leaderboard.is_finalized = true;
+    leaderboard.prize_amount = prize_amount;
+    leaderboard.serialize(&mut *leaderboard_account.data.borrow_mut())?;
+
+    msg!("Leaderboard for epoch {} finalized with {} YOS prize", epoch, prize_amount);
+    Ok(())
+}
+
+/// Mint the signer's equal share of a finalized leaderboard prize
+fn process_claim_leaderboard_prize(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    epoch: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let leaderboard_account = next_account_info(account_info_iter)?;
+    let user_yos_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let instructions_sysvar_account = next_account_info(account_info_iter)?;
+    // Optional CPI allowlist trusting wrapper programs
+    let cpi_allowlist_account = next_optional_account(account_info_iter, program_id);
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Reject untrusted wrapper programs piggybacking on the user's signature
+    require_trusted_top_level_instruction(program_id, instructions_sysvar_account, cpi_allowlist_account)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if program_state.yos_mint != *yos_mint_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Verify PDA
+    let (leaderboard_address, _) = find_leaderboard_address(program_id, epoch);
+    assert_pda!(leaderboard_account, leaderboard_address);
+    let mut leaderboard = load_account::<Leaderboard>(leaderboard_account)?;
+    if !leaderboard.is_finalized {
+        msg!("Leaderboard for epoch {} is not finalized", epoch);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Split the prize equally between the ranked wallets
+    let ranked_count = leaderboard.ranked_count() as u64;
+    let share = leaderboard.prize_amount / ranked_count.max(1);
+    let entry = leaderboard
+        .entries
+        .iter_mut()
+        .find(|entry| entry.wallet == *user_account.key && entry.volume > 0)
+        .ok_or_else(|| {
+            msg!("Wallet is not ranked on the epoch {} leaderboard", epoch);
+            ProgramError::InvalidArgument
+        })?;
+    if entry.claimed {
+        msg!("Leaderboard prize already claimed");
+        return Err(ProgramError::InvalidArgument);
+    }
+    entry.claimed = true;
+    leaderboard.serialize(&mut *leaderboard_account.data.borrow_mut())?;
+    program_state.stats.record_emission(share);
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    // Mint the prize share to the user
+    invoke_signed(
+        &token_instruction::mint_to(
+            token_program_account.key,
+            yos_mint_account.key,
+            user_yos_account.key,
+            program_authority_account.key,
+            &[],
+            share,
+        )?,
+        &[
+            yos_mint_account.clone(),
+            user_yos_account.clone(),
+            program_authority_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+    )?;
+
+    msg!("Claimed {} YOS leaderboard prize for epoch {}", share, epoch);
+    Ok(())
+}
+
+/// Set the wash-trade detection window (admin only)
+fn process_set_wash_trade_window(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    window_slots: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_WASH_TRADE_WINDOW)?;
+
+    // Update the window
+    program_state.wash_trade_window_slots = window_slots;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Wash-trade window set to {} slots", window_slots);
+    Ok(())
+}
+
+/// Switch the cashback mode and set the ledger claim bonus (admin only)
+fn process_set_cashback_mode(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    accrue: bool,
+    wait_bonus_bps: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CASHBACK_MODE)?;
+
+    if wait_bonus_bps > MAX_CASHBACK_WAIT_BONUS_BPS {
+        msg!("Wait bonus cannot exceed {} bps", MAX_CASHBACK_WAIT_BONUS_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Update the mode
+    program_state.accrue_cashback = accrue;
+    program_state.cashback_wait_bonus_bps = wait_bonus_bps;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Cashback mode updated: accrue {}, wait bonus {} bps", accrue, wait_bonus_bps);
+    Ok(())
+}
+
+/// Create the signer's cashback ledger PDA
+fn process_initialize_cashback_ledger(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let cashback_ledger_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Verify PDA
+    let (cashback_ledger_address, cashback_ledger_bump) =
+        find_cashback_ledger_address(program_id, user_account.key);
+    assert_pda!(cashback_ledger_account, cashback_ledger_address);
+    if cashback_ledger_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create cashback ledger account
+    let rent = Rent::get()?;
+    let ledger_size = std::mem::size_of::<CashbackLedger>();
+    let lamports = rent.minimum_balance(ledger_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            cashback_ledger_account.key,
+            lamports,
+            ledger_size as u64,
+            program_id,
+        ),
+        &[
+            user_account.clone(),
+            cashback_ledger_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::CASHBACK_LEDGER, user_account.key.as_ref(), &[cashback_ledger_bump]]],
+    )?;
+
+    let cashback_ledger = CashbackLedger {
+        is_initialized: true,
+        owner: *user_account.key,
+        accrued: 0,
+        accrual_start: 0,
+        total_claimed: 0,
+    };
+    cashback_ledger.serialize(&mut *cashback_ledger_account.data.borrow_mut())?;
+
+    msg!("Cashback ledger initialized");
+    Ok(())
+}
+
+/// Create the signer's contribution ledger PDA
+fn process_initialize_contribution_ledger(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let contribution_ledger_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Verify PDA
+    let (contribution_ledger_address, contribution_ledger_bump) =
+        find_contribution_ledger_address(program_id, user_account.key);
+    assert_pda!(contribution_ledger_account, contribution_ledger_address);
+    if contribution_ledger_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create contribution ledger account
+    let rent = Rent::get()?;
+    let ledger_size = std::mem::size_of::<ContributionLedger>();
+    let lamports = rent.minimum_balance(ledger_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            contribution_ledger_account.key,
+            lamports,
+            ledger_size as u64,
+            program_id,
+        ),
+        &[
+            user_account.clone(),
+            contribution_ledger_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::CONTRIBUTION_LEDGER, user_account.key.as_ref(), &[contribution_ledger_bump]]],
+    )?;
+
+    let contribution_ledger = ContributionLedger {
+        is_initialized: true,
+        owner: *user_account.key,
+        count: 0,
+        tranches: [ContributionTranche::default(); MAX_CONTRIBUTION_TRANCHES],
+        total_rewards: 0,
+    };
+    contribution_ledger.serialize(&mut *contribution_ledger_account.data.borrow_mut())?;
+
+    msg!("Contribution ledger initialized");
+    Ok(())
+}
+
+/// Create the signer's idempotency log PDA
+fn process_initialize_idempotency_log(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let idempotency_log_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Verify PDA
+    let (idempotency_log_address, idempotency_log_bump) = find_idempotency_log_address(program_id, user_account.key);
+    assert_pda!(idempotency_log_account, idempotency_log_address);
+    if idempotency_log_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create idempotency log account
+    let rent = Rent::get()?;
+    let log_size = std::mem::size_of::<IdempotencyLog>();
+    let lamports = rent.minimum_balance(log_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            user_account.key,
+            idempotency_log_account.key,
+            lamports,
+            log_size as u64,
+            program_id,
+        ),
+        &[
+            user_account.clone(),
+            idempotency_log_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::IDEMPOTENCY_LOG, user_account.key.as_ref(), &[idempotency_log_bump]]],
+    )?;
+
+    let idempotency_log = IdempotencyLog {
+        is_initialized: true,
+        owner: *user_account.key,
+        head: 0,
+        entries: [IdempotencyEntry::default(); IDEMPOTENCY_LOG_CAPACITY],
+    };
+    idempotency_log.serialize(&mut *idempotency_log_account.data.borrow_mut())?;
+
+    msg!("Idempotency log initialized");
+    Ok(())
+}
+
+/// Set the weekly contribution reward rate and loyalty bonus (admin only)
+fn process_set_contribution_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    weekly_rate_bps: u64,
+    loyalty_bonus_bps_per_week: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CONTRIBUTION_REWARDS)?;
+
+    if loyalty_bonus_bps_per_week > MAX_LOYALTY_BONUS_BPS {
+        msg!("Loyalty bonus cannot exceed {} bps", MAX_LOYALTY_BONUS_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Update the rates
+    program_state.contribution_reward_rate_bps = weekly_rate_bps;
+    program_state.loyalty_bonus_bps_per_week = loyalty_bonus_bps_per_week;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!(
+        "Contribution rewards set to {} bps weekly with {} bps loyalty bonus per week",
+        weekly_rate_bps,
+        loyalty_bonus_bps_per_week
+    );
+    Ok(())
+}
+
+/// Set the lock period of new contribution tranches (admin only)
+fn process_set_contribution_lock(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    lock_seconds: i64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CONTRIBUTION_LOCK)?;
+
+    if lock_seconds < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Update the lock
+    program_state.contribution_lock_seconds = lock_seconds;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Contribution lock set to {} seconds", lock_seconds);
+    Ok(())
+}
+
+/// Burn LP shares from the signer's unlocked contribution tranches, oldest
+/// first, and pay out both sides of the pool
+fn process_withdraw_contribution(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    shares: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let contribution_ledger_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let user_token_a_account = next_account_info(account_info_iter)?;
+    let user_token_b_account = next_account_info(account_info_iter)?;
+    let vault_a_account = next_account_info(account_info_iter)?;
+    let vault_b_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(user_account);
+    if shares == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (contribution_ledger_address, _) = find_contribution_ledger_address(program_id, user_account.key);
+    assert_pda!(contribution_ledger_account, contribution_ledger_address);
+    let mut contribution_ledger = load_account::<ContributionLedger>(contribution_ledger_account)?;
+    if !contribution_ledger.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Load pool
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Vaults must be authority-owned token accounts of the pool's mints
+    for (vault_account, mint) in [(vault_a_account, &pool.mint_a), (vault_b_account, &pool.mint_b)] {
+        assert_token_account!(vault_account, *mint, program_authority_address);
+    }
+
+    // Take the shares from the oldest unlocked tranches
+    let withdrawn = contribution_ledger.withdraw_shares(pool_account.key, shares, Clock::get()?.unix_timestamp);
+    if withdrawn < shares {
+        msg!("Only {} contributed shares are unlocked", withdrawn);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // Pay out both sides in proportion to the pool
+    let amount_a = withdrawal_amount(shares, pool.reserve_a, pool.total_shares);
+    let amount_b = withdrawal_amount(shares, pool.reserve_b, pool.total_shares);
+    if amount_a < min_amount_a || amount_b < min_amount_b {
+        msg!("Slippage exceeded: {}/{} below {}/{}", amount_a, amount_b, min_amount_a, min_amount_b);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    pool.reserve_a -= amount_a;
+    pool.reserve_b -= amount_b;
+    pool.total_shares -= shares;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+    contribution_ledger.serialize(&mut *contribution_ledger_account.data.borrow_mut())?;
+
+    for (vault_account, user_token_account, amount) in [
+        (vault_a_account, user_token_a_account, amount_a),
+        (vault_b_account, user_token_b_account, amount_b),
+    ] {
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                vault_account.key,
+                user_token_account.key,
+                program_authority_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                vault_account.clone(),
+                user_token_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+    }
+
+    msg!("Withdrew {} contributed LP shares for {} and {}", shares, amount_a, amount_b);
+    Ok(())
+}
+
+/// Escrow the maker's input and create a limit or DCA order
+#[allow(clippy::too_many_arguments)]
+fn process_create_order(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    order_id: u64,
+    mint_out: Pubkey,
+    amount_in: u64,
+    amount_per_fill: u64,
+    min_amount_out_per_fill: u64,
+    interval_seconds: i64,
+    bounty_bps: u16,
+    expires_at: i64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let maker_account = next_account_info(account_info_iter)?;
+    let order_account = next_account_info(account_info_iter)?;
+    let maker_token_in_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(maker_account);
+    if amount_in == 0 || amount_per_fill == 0 || amount_per_fill > amount_in || interval_seconds < 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if bounty_bps > MAX_KEEPER_BOUNTY_BPS {
+        msg!("Keeper bounty cannot exceed {} bps", MAX_KEEPER_BOUNTY_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if expires_at != 0 && expires_at <= now {
+        msg!("Order would already be expired");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (order_address, order_bump) = find_order_address(program_id, maker_account.key, order_id);
+    assert_pda!(order_account, order_address);
+    if order_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // The escrow must be held by the order itself
+    let mint_in = TokenAccount::unpack(&maker_token_in_account.data.borrow())?.mint;
+    let escrow = assert_token_account!(escrow_account, mint_in, order_address);
+    if escrow.mint == mint_out {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Create order account
+    let rent = Rent::get()?;
+    let order_size = std::mem::size_of::<Order>();
+    let lamports = rent.minimum_balance(order_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            maker_account.key,
+            order_account.key,
+            lamports,
+            order_size as u64,
+            program_id,
+        ),
+        &[
+            maker_account.clone(),
+            order_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::ORDER, maker_account.key.as_ref(), &order_id.to_le_bytes(), &[order_bump]]],
+    )?;
+
+    // Escrow the input
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            maker_token_in_account.key,
+            escrow_account.key,
+            maker_account.key,
+            &[],
+            amount_in,
+        )?,
+        &[
+            maker_token_in_account.clone(),
+            escrow_account.clone(),
+            maker_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    let order = Order {
+        is_initialized: true,
+        maker: *maker_account.key,
+        order_id,
+        mint_in: escrow.mint,
+        mint_out,
+        amount_remaining: amount_in,
+        amount_per_fill,
+        min_amount_out_per_fill,
+        interval_seconds,
+        next_fill_time: now,
+        bounty_bps,
+        expires_at,
+    };
+    order.serialize(&mut *order_account.data.borrow_mut())?;
+
+    msg!("Order {} created for {} input tokens", order_id, amount_in);
+    Ok(())
+}
+
+/// Execute an order's next fill against its pool, paying the keeper its
+/// bounty out of the fill's output
+fn process_fill_order(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let keeper_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let order_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let vault_in_account = next_account_info(account_info_iter)?;
+    let vault_out_account = next_account_info(account_info_iter)?;
+    let maker_token_out_account = next_account_info(account_info_iter)?;
+    let keeper_token_out_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(keeper_account);
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // Load order
+    assert_owner!(order_account, program_id);
+    let mut order = load_account::<Order>(order_account)?;
+    let (order_address, order_bump) = find_order_address(program_id, &order.maker, order.order_id);
+    if !order.is_initialized || order_address != *order_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if order.amount_remaining == 0 {
+        msg!("Order is fully filled");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    if order.is_expired(now) {
+        msg!("Order expired at {}", order.expires_at);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if now < order.next_fill_time {
+        msg!("Next fill is due at {}", order.next_fill_time);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Load pool
+    let (pool_address, _) = find_pool_address(program_id, &order.mint_in, &order.mint_out);
+    assert_pda!(pool_account, pool_address);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized || pool.reserve_a == 0 || pool.reserve_b == 0 {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Vaults must be authority-owned, the escrow and payouts order-bound
+    assert_token_account!(vault_in_account, order.mint_in, program_authority_address);
+    assert_token_account!(vault_out_account, order.mint_out, program_authority_address);
+    assert_token_account!(escrow_account, order.mint_in, order_address);
+    assert_token_account!(maker_token_out_account, order.mint_out, order.maker);
+    assert_token_account!(keeper_token_out_account, order.mint_out, *keeper_account.key);
+
+    // Price the fill against the pool after its swap fee
+    let (amount_in, min_amount_out) = order.next_fill();
+    let (reserve_in, reserve_out) = pool.reserves_for(&order.mint_in);
+    if amount_in > max_amount_in(reserve_in, MAX_PRICE_IMPACT_BPS) {
+        msg!("Fill exceeds the {} bps price-impact limit", MAX_PRICE_IMPACT_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let swap_fee_amount = bps_of(amount_in, program_state.swap_fee_rate);
+    let net_amount_in = amount_in - swap_fee_amount;
+    let amount_out = constant_product_amount_out(reserve_in, reserve_out, net_amount_in).map_err(math_error)?;
+    let bounty = bps_of(amount_out, order.bounty_bps as u64);
+    let maker_amount_out = amount_out - bounty;
+    if maker_amount_out < min_amount_out {
+        msg!("Limit not reached: {} < {}", maker_amount_out, min_amount_out);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    // The swap fee compounds into the reserves
+    let k_before = pool.k();
+    pool.apply_hop(&order.mint_in, amount_in, amount_out)?;
+    if pool.k() < k_before {
+        msg!("K invariant violated: {} < {}", pool.k(), k_before);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    pool.volume_in = pool.volume_in.saturating_add(amount_in);
+    pool.volume_out = pool.volume_out.saturating_add(amount_out);
+    pool.fees_collected = pool.fees_collected.saturating_add(swap_fee_amount);
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    program_state.stats.record_swap(amount_in, swap_fee_amount);
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    order.amount_remaining -= amount_in;
+    order.next_fill_time = now.saturating_add(order.interval_seconds);
+    order.serialize(&mut *order_account.data.borrow_mut())?;
+
+    // Move the input from escrow into the pool vault
+    invoke_signed(
+        &token_instruction::transfer(
+            token_program_account.key,
+            escrow_account.key,
+            vault_in_account.key,
+            order_account.key,
+            &[],
+            amount_in,
+        )?,
+        &[
+            escrow_account.clone(),
+            vault_in_account.clone(),
+            order_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::ORDER, order.maker.as_ref(), &order.order_id.to_le_bytes(), &[order_bump]]],
+    )?;
+
+    // Pay the maker and the keeper
+    for (recipient_account, amount) in [(maker_token_out_account, maker_amount_out), (keeper_token_out_account, bounty)] {
+        if amount == 0 {
+            continue;
+        }
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                vault_out_account.key,
+                recipient_account.key,
+                program_authority_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                vault_out_account.clone(),
+                recipient_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+    }
+
+    msg!("Filled {} of order {}: {} out, {} keeper bounty", amount_in, order.order_id, maker_amount_out, bounty);
+    Ok(())
+}
+
+/// Refund an order's remaining escrow to the maker and close the order and
+/// its escrow
+fn process_cancel_order(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let maker_account = next_account_info(account_info_iter)?;
+    let order_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let maker_token_in_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(maker_account);
+
+    // Load order
+    assert_owner!(order_account, program_id);
+    let order = load_account::<Order>(order_account)?;
+    let (order_address, order_bump) = find_order_address(program_id, maker_account.key, order.order_id);
+    if !order.is_initialized || order_address != *order_account.key || order.maker != *maker_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let refunded = close_escrow(
+        order_account,
+        &[seeds::ORDER, order.maker.as_ref(), &order.order_id.to_le_bytes(), &[order_bump]],
+        &order.mint_in,
+        escrow_account,
+        maker_token_in_account,
+        maker_account,
+        token_program_account,
+    )?;
+
+    msg!("Order {} cancelled, {} input tokens refunded", order.order_id, refunded);
+    Ok(())
+}
+
+/// Refund an expired order's escrow and rent to its maker, tipping the
+/// keeper out of the rent
+fn process_sweep_expired_order(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let keeper_account = next_account_info(account_info_iter)?;
+    let maker_account = next_account_info(account_info_iter)?;
+    let order_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let maker_token_in_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(keeper_account);
+
+    // Load order
+    assert_owner!(order_account, program_id);
+    let order = load_account::<Order>(order_account)?;
+    let (order_address, order_bump) = find_order_address(program_id, maker_account.key, order.order_id);
+    if !order.is_initialized || order_address != *order_account.key || order.maker != *maker_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !order.is_expired(Clock::get()?.unix_timestamp) {
+        msg!("Order does not expire until {}", order.expires_at);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Take the tip before the order's remaining rent goes to the maker
+    let tip = ORDER_SWEEP_TIP_LAMPORTS.min(order_account.lamports());
+    **order_account.lamports.borrow_mut() -= tip;
+    **keeper_account.lamports.borrow_mut() += tip;
+
+    let refunded = close_escrow(
+        order_account,
+        &[seeds::ORDER, order.maker.as_ref(), &order.order_id.to_le_bytes(), &[order_bump]],
+        &order.mint_in,
+        escrow_account,
+        maker_token_in_account,
+        maker_account,
+        token_program_account,
+    )?;
+
+    msg!("Expired order {} swept, {} input tokens refunded", order.order_id, refunded);
+    Ok(())
+}
+
+/// Escrow the maker's side of an OTC block trade
+fn process_create_otc_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    offer_id: u64,
+    taker: Pubkey,
+    mint_requested: Pubkey,
+    amount_offered: u64,
+    amount_requested: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let maker_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let maker_token_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(maker_account);
+    if amount_offered == 0 || amount_requested == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // Verify PDA
+    let (offer_address, offer_bump) = find_otc_offer_address(program_id, maker_account.key, offer_id);
+    assert_pda!(offer_account, offer_address);
+    if offer_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // The escrow must be held by the offer, and one side must be YOT
+    let mint_offered = TokenAccount::unpack(&maker_token_account.data.borrow())?.mint;
+    assert_token_account!(escrow_account, mint_offered, offer_address);
+    if mint_offered == mint_requested || (mint_offered != program_state.yot_mint && mint_requested != program_state.yot_mint) {
+        msg!("OTC offers trade YOT against another mint");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Create offer account
+    let rent = Rent::get()?;
+    let offer_size = std::mem::size_of::<OtcOffer>();
+    let lamports = rent.minimum_balance(offer_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            maker_account.key,
+            offer_account.key,
+            lamports,
+            offer_size as u64,
+            program_id,
+        ),
+        &[
+            maker_account.clone(),
+            offer_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::OTC_OFFER, maker_account.key.as_ref(), &offer_id.to_le_bytes(), &[offer_bump]]],
+    )?;
+
+    // Escrow the offered side
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            maker_token_account.key,
+            escrow_account.key,
+            maker_account.key,
+            &[],
+            amount_offered,
+        )?,
+        &[
+            maker_token_account.clone(),
+            escrow_account.clone(),
+            maker_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    let offer = OtcOffer {
+        is_initialized: true,
+        maker: *maker_account.key,
+        offer_id,
+        taker,
+        mint_offered,
+        amount_offered,
+        mint_requested,
+        amount_requested,
+    };
+    offer.serialize(&mut *offer_account.data.borrow_mut())?;
+
+    msg!("OTC offer {} created: {} for {}", offer_id, amount_offered, amount_requested);
+    Ok(())
+}
+
+/// Settle an OTC offer: the taker pays the maker, receives the escrow minus
+/// the admin fee, and the offer closes to the maker
+fn process_accept_otc_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let taker_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let maker_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let taker_token_offered_account = next_account_info(account_info_iter)?;
+    let taker_token_requested_account = next_account_info(account_info_iter)?;
+    let maker_token_requested_account = next_account_info(account_info_iter)?;
+    let fee_vault_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(taker_account);
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // Load offer
+    assert_owner!(offer_account, program_id);
+    let offer = load_account::<OtcOffer>(offer_account)?;
+    let (offer_address, offer_bump) = find_otc_offer_address(program_id, maker_account.key, offer.offer_id);
+    if !offer.is_initialized || offer_address != *offer_account.key || offer.maker != *maker_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if offer.taker != Pubkey::default() && offer.taker != *taker_account.key {
+        msg!("Offer is reserved for {}", offer.taker);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // The maker is paid in full; the fee comes out of the escrow
+    let (program_authority_address, _) = find_program_authority(program_id);
+    let escrow = assert_token_account!(escrow_account, offer.mint_offered, offer_address);
+    assert_token_account!(maker_token_requested_account, offer.mint_requested, offer.maker);
+    assert_token_account!(fee_vault_account, offer.mint_offered, program_authority_address);
+    let admin_fee_amount = bps_of(offer.amount_offered, program_state.admin_fee_rate);
+    let offer_seeds: &[&[u8]] = &[seeds::OTC_OFFER, offer.maker.as_ref(), &offer.offer_id.to_le_bytes(), &[offer_bump]];
+
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            taker_token_requested_account.key,
+            maker_token_requested_account.key,
+            taker_account.key,
+            &[],
+            offer.amount_requested,
+        )?,
+        &[
+            taker_token_requested_account.clone(),
+            maker_token_requested_account.clone(),
+            taker_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+    for (recipient_account, amount) in [
+        // Anything sent to the escrow on top of the offer goes to the taker
+        (taker_token_offered_account, escrow.amount.saturating_sub(admin_fee_amount)),
+        (fee_vault_account, admin_fee_amount),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                escrow_account.key,
+                recipient_account.key,
+                offer_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                escrow_account.clone(),
+                recipient_account.clone(),
+                offer_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[offer_seeds],
+        )?;
+    }
+
+    // Close the emptied escrow and the offer to the maker
+    invoke_signed(
+        &token_instruction::close_account(
+            token_program_account.key,
+            escrow_account.key,
+            maker_account.key,
+            offer_account.key,
+            &[],
+        )?,
+        &[
+            escrow_account.clone(),
+            maker_account.clone(),
+            offer_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[offer_seeds],
+    )?;
+    let lamports = offer_account.lamports();
+    **offer_account.lamports.borrow_mut() = 0;
+    **maker_account.lamports.borrow_mut() += lamports;
+    offer_account.data.borrow_mut().fill(0);
+
+    msg!("OTC offer {} accepted, {} admin fee", offer.offer_id, admin_fee_amount);
+    Ok(())
+}
+
+/// Refund an OTC offer's escrow to the maker and close it
+fn process_cancel_otc_offer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let maker_account = next_account_info(account_info_iter)?;
+    let offer_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let maker_token_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(maker_account);
+
+    // Load offer
+    assert_owner!(offer_account, program_id);
+    let offer = load_account::<OtcOffer>(offer_account)?;
+    let (offer_address, offer_bump) = find_otc_offer_address(program_id, maker_account.key, offer.offer_id);
+    if !offer.is_initialized || offer_address != *offer_account.key || offer.maker != *maker_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let refunded = close_escrow(
+        offer_account,
+        &[seeds::OTC_OFFER, offer.maker.as_ref(), &offer.offer_id.to_le_bytes(), &[offer_bump]],
+        &offer.mint_offered,
+        escrow_account,
+        maker_token_account,
+        maker_account,
+        token_program_account,
+    )?;
+
+    msg!("OTC offer {} cancelled, {} refunded", offer.offer_id, refunded);
+    Ok(())
+}
+
+/// Open a bonding-curve launch, escrowing the sale supply and the tokens
+/// reserved for the pool
+#[allow(clippy::too_many_arguments)]
+fn process_create_launch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    quote_mint: Pubkey,
+    supply: u64,
+    liquidity_tokens: u64,
+    start_price: u64,
+    end_price: u64,
+    end_time: i64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let creator_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let launch_account = next_account_info(account_info_iter)?;
+    let creator_token_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(creator_account);
+    if supply == 0 || liquidity_tokens == 0 || end_time <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidArgument);
+    }
+    // The whole curve must be priceable
+    bonding_curve_cost(0, supply, supply, start_price, end_price).map_err(math_error)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // Verify PDA
+    let mint = TokenAccount::unpack(&creator_token_account.data.borrow())?.mint;
+    if mint == quote_mint {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (launch_address, launch_bump) = find_launch_address(program_id, &mint);
+    assert_pda!(launch_account, launch_address);
+    if launch_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    assert_token_account!(escrow_account, mint, launch_address);
+
+    // Create launch account
+    let rent = Rent::get()?;
+    let launch_size = std::mem::size_of::<Launch>();
+    let lamports = rent.minimum_balance(launch_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            creator_account.key,
+            launch_account.key,
+            lamports,
+            launch_size as u64,
+            program_id,
+        ),
+        &[
+            creator_account.clone(),
+            launch_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::LAUNCH, mint.as_ref(), &[launch_bump]]],
+    )?;
+
+    // Escrow the sale supply and the pool's share
+    let deposit = supply.checked_add(liquidity_tokens).ok_or(ProgramError::InvalidArgument)?;
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            creator_token_account.key,
+            escrow_account.key,
+            creator_account.key,
+            &[],
+            deposit,
+        )?,
+        &[
+            creator_token_account.clone(),
+            escrow_account.clone(),
+            creator_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    let launch = Launch {
+        is_initialized: true,
+        creator: *creator_account.key,
+        mint,
+        quote_mint,
+        supply,
+        liquidity_tokens,
+        start_price,
+        end_price,
+        end_time,
+        sold: 0,
+        proceeds: 0,
+    };
+    launch.serialize(&mut *launch_account.data.borrow_mut())?;
+
+    msg!("Launch of {} opened: {} tokens until {}", mint, supply, end_time);
+    Ok(())
+}
+
+/// Sell launch tokens at the curve price for the amount already sold
+fn process_buy_launch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    max_cost: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let buyer_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let launch_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let proceeds_account = next_account_info(account_info_iter)?;
+    let buyer_quote_account = next_account_info(account_info_iter)?;
+    let buyer_token_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(buyer_account);
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // Load launch
+    assert_owner!(launch_account, program_id);
+    let mut launch = load_account::<Launch>(launch_account)?;
+    let (launch_address, launch_bump) = find_launch_address(program_id, &launch.mint);
+    if !launch.is_initialized || launch_address != *launch_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if launch.has_ended(Clock::get()?.unix_timestamp) {
+        msg!("Launch has ended");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if amount > launch.supply - launch.sold {
+        msg!("Only {} tokens remain", launch.supply - launch.sold);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // Price the purchase along the curve
+    let cost = bonding_curve_cost(launch.sold, amount, launch.supply, launch.start_price, launch.end_price)
+        .map_err(math_error)?;
+    if cost > max_cost {
+        msg!("Cost {} exceeds the maximum {}", cost, max_cost);
+        return Err(ProgramError::InvalidArgument);
+    }
+    assert_token_account!(escrow_account, launch.mint, launch_address);
+    assert_token_account!(proceeds_account, launch.quote_mint, launch_address);
+
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            buyer_quote_account.key,
+            proceeds_account.key,
+            buyer_account.key,
+            &[],
+            cost,
+        )?,
+        &[
+            buyer_quote_account.clone(),
+            proceeds_account.clone(),
+            buyer_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+    invoke_signed(
+        &token_instruction::transfer(
+            token_program_account.key,
+            escrow_account.key,
+            buyer_token_account.key,
+            launch_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            escrow_account.clone(),
+            buyer_token_account.clone(),
+            launch_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::LAUNCH, launch.mint.as_ref(), &[launch_bump]]],
+    )?;
+
+    launch.sold += amount;
+    launch.proceeds = launch.proceeds.checked_add(cost).ok_or(ProgramError::InvalidArgument)?;
+    launch.serialize(&mut *launch_account.data.borrow_mut())?;
+
+    msg!("Bought {} launch tokens for {}", amount, cost);
+    Ok(())
+}
+
+/// Seed the pair's pool with a finished launch's proceeds and reserved
+/// tokens, refund unsold tokens to the creator and close the launch
+fn process_close_launch(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let launch_account = next_account_info(account_info_iter)?;
+    let creator_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let proceeds_account = next_account_info(account_info_iter)?;
+    let creator_token_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let position_account = next_account_info(account_info_iter)?;
+    let token_vault_account = next_account_info(account_info_iter)?;
+    let quote_vault_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Load launch
+    assert_owner!(launch_account, program_id);
+    let launch = load_account::<Launch>(launch_account)?;
+    let (launch_address, launch_bump) = find_launch_address(program_id, &launch.mint);
+    if !launch.is_initialized || launch_address != *launch_account.key || launch.creator != *creator_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !launch.has_ended(Clock::get()?.unix_timestamp) {
+        msg!("Launch runs until {}", launch.end_time);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let launch_seeds: &[&[u8]] = &[seeds::LAUNCH, launch.mint.as_ref(), &[launch_bump]];
+    assert_token_account!(proceeds_account, launch.quote_mint, launch_address);
+
+    // Seed the pool when anything was raised
+    if launch.proceeds > 0 {
+        // Verify the program authority
+        let (program_authority_address, _) = find_program_authority(program_id);
+        assert_pda!(program_authority_account, program_authority_address);
+        assert_token_account!(token_vault_account, launch.mint, program_authority_address);
+        assert_token_account!(quote_vault_account, launch.quote_mint, program_authority_address);
+
+        // Create the pool on first use
+        let mut pool = if pool_account.data_is_empty() {
+            create_pool(
+                program_id,
+                payer_account,
+                pool_account,
+                system_program_account,
+                launch.mint,
+                launch.quote_mint,
+            )?
+        } else {
+            let (pool_address, _) = find_pool_address(program_id, &launch.mint, &launch.quote_mint);
+            assert_pda!(pool_account, pool_address);
+            assert_owner!(pool_account, program_id);
+            let pool = load_account::<PoolState>(pool_account)?;
+            if !pool.is_initialized {
+                return Err(ProgramError::UninitializedAccount);
+            }
+            pool
+        };
+
+        // Deposit both sides for the creator's LP shares
+        let (amount_a, amount_b) = if pool.mint_a == launch.mint {
+            (launch.liquidity_tokens, launch.proceeds)
+        } else {
+            (launch.proceeds, launch.liquidity_tokens)
+        };
+        // The launch sets the price of an empty pool; liquidity already in
+        // the pool must agree with it, or the deposit would hand the
+        // difference to whoever priced the pool
+        if pool.total_shares > 0 && !same_price(amount_a, amount_b, pool.reserve_a, pool.reserve_b) {
+            msg!(
+                "Pool price {}:{} diverges from the launch's {}:{}",
+                pool.reserve_a,
+                pool.reserve_b,
+                amount_a,
+                amount_b
+            );
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let shares = deposit_lp_shares(&mut pool, amount_a, amount_b)?;
+        let mut position = load_or_create_position(
+            program_id,
+            payer_account,
+            &launch.creator,
+            pool_account,
+            position_account,
+            system_program_account,
+        )?;
+
+        for (source_account, vault_account, amount) in [
+            (escrow_account, token_vault_account, launch.liquidity_tokens),
+            (proceeds_account, quote_vault_account, launch.proceeds),
+        ] {
+            invoke_signed(
+                &token_instruction::transfer(
+                    token_program_account.key,
+                    source_account.key,
+                    vault_account.key,
+                    launch_account.key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    source_account.clone(),
+                    vault_account.clone(),
+                    launch_account.clone(),
+                    token_program_account.clone(),
+                ],
+                &[launch_seeds],
+            )?;
+        }
+
+        pool.serialize(&mut *pool_account.data.borrow_mut())?;
+        position.shares = position.shares.checked_add(shares).ok_or(ProgramError::InvalidArgument)?;
+        position.serialize(&mut *position_account.data.borrow_mut())?;
+        msg!("Launch seeded the pool for {} LP shares", shares);
+    }
+
+    // Close the emptied proceeds escrow, then refund the token escrow and
+    // close the launch
+    invoke_signed(
+        &token_instruction::close_account(
+            token_program_account.key,
+            proceeds_account.key,
+            creator_account.key,
+            launch_account.key,
+            &[],
+        )?,
+        &[
+            proceeds_account.clone(),
+            creator_account.clone(),
+            launch_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[launch_seeds],
+    )?;
+    let refunded = close_escrow(
+        launch_account,
+        launch_seeds,
+        &launch.mint,
+        escrow_account,
+        creator_token_account,
+        creator_account,
+        token_program_account,
+    )?;
+
+    msg!("Launch of {} closed: {} sold for {}, {} refunded", launch.mint, launch.sold, launch.proceeds, refunded);
+    Ok(())
+}
+
+/// Escrow a sender's YOT and open a stream paying it to the recipient
+fn process_create_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    stream_id: u64,
+    recipient: Pubkey,
+    amount: u64,
+    start_time: i64,
+    end_time: i64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let sender_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let sender_token_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(sender_account);
+    if amount == 0 || end_time <= start_time || end_time <= Clock::get()?.unix_timestamp {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify PDA
+    let (stream_address, stream_bump) = find_stream_address(program_id, sender_account.key, stream_id);
+    assert_pda!(stream_account, stream_address);
+    if stream_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    assert_token_account!(sender_token_account, program_state.yot_mint, *sender_account.key);
+    assert_token_account!(escrow_account, program_state.yot_mint, stream_address);
+
+    // Create stream account
+    let rent = Rent::get()?;
+    let stream_size = std::mem::size_of::<Stream>();
+    let lamports = rent.minimum_balance(stream_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            sender_account.key,
+            stream_account.key,
+            lamports,
+            stream_size as u64,
+            program_id,
+        ),
+        &[
+            sender_account.clone(),
+            stream_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::STREAM, sender_account.key.as_ref(), &stream_id.to_le_bytes(), &[stream_bump]]],
+    )?;
+
+    // Escrow the full stream
+    invoke(
+        &token_instruction::transfer(
+            token_program_account.key,
+            sender_token_account.key,
+            escrow_account.key,
+            sender_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            sender_token_account.clone(),
+            escrow_account.clone(),
+            sender_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    let stream = Stream {
+        is_initialized: true,
+        sender: *sender_account.key,
+        stream_id,
+        recipient,
+        mint: program_state.yot_mint,
+        deposit: amount,
+        start_time,
+        end_time,
+        withdrawn: 0,
+    };
+    stream.serialize(&mut *stream_account.data.borrow_mut())?;
+
+    msg!("Stream {} created: {} YOT to {} until {}", stream_id, amount, recipient, end_time);
+    Ok(())
+}
+
+/// Pay a stream's recipient everything streamed since their last withdrawal
+fn process_withdraw_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let recipient_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let recipient_token_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(recipient_account);
+
+    // Load stream
+    assert_owner!(stream_account, program_id);
+    let mut stream = load_account::<Stream>(stream_account)?;
+    let (stream_address, stream_bump) = find_stream_address(program_id, &stream.sender, stream.stream_id);
+    if !stream.is_initialized || stream_address != *stream_account.key || stream.recipient != *recipient_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    assert_token_account!(escrow_account, stream.mint, stream_address);
+
+    let amount = stream.withdrawable(Clock::get()?.unix_timestamp);
+    if amount == 0 {
+        msg!("Nothing has streamed since the last withdrawal");
+        return Err(ProgramError::InsufficientFunds);
+    }
+    invoke_signed(
+        &token_instruction::transfer(
+            token_program_account.key,
+            escrow_account.key,
+            recipient_token_account.key,
+            stream_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            escrow_account.clone(),
+            recipient_token_account.clone(),
+            stream_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::STREAM, stream.sender.as_ref(), &stream.stream_id.to_le_bytes(), &[stream_bump]]],
+    )?;
+
+    stream.withdrawn += amount;
+    stream.serialize(&mut *stream_account.data.borrow_mut())?;
+
+    msg!("Stream {} paid {} YOT, {} of {} withdrawn", stream.stream_id, amount, stream.withdrawn, stream.deposit);
+    Ok(())
+}
+
+/// Settle what a stream owes its recipient, refund the rest to the sender and
+/// close the stream
+fn process_cancel_stream(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let sender_account = next_account_info(account_info_iter)?;
+    let stream_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let sender_token_account = next_account_info(account_info_iter)?;
+    let recipient_token_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(sender_account);
+
+    // Load stream
+    assert_owner!(stream_account, program_id);
+    let stream = load_account::<Stream>(stream_account)?;
+    let (stream_address, stream_bump) = find_stream_address(program_id, sender_account.key, stream.stream_id);
+    if !stream.is_initialized || stream_address != *stream_account.key || stream.sender != *sender_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    assert_token_account!(escrow_account, stream.mint, stream_address);
+    assert_token_account!(recipient_token_account, stream.mint, stream.recipient);
+    let stream_id_bytes = stream.stream_id.to_le_bytes();
+    let stream_seeds: &[&[u8]] = &[seeds::STREAM, stream.sender.as_ref(), &stream_id_bytes, &[stream_bump]];
+
+    // What has already streamed belongs to the recipient
+    let owed = stream.withdrawable(Clock::get()?.unix_timestamp);
+    if owed > 0 {
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                escrow_account.key,
+                recipient_token_account.key,
+                stream_account.key,
+                &[],
+                owed,
+            )?,
+            &[
+                escrow_account.clone(),
+                recipient_token_account.clone(),
+                stream_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[stream_seeds],
+        )?;
+    }
+    let refunded = close_escrow(
+        stream_account,
+        stream_seeds,
+        &stream.mint,
+        escrow_account,
+        sender_token_account,
+        sender_account,
+        token_program_account,
+    )?;
+
+    msg!("Stream {} cancelled: {} YOT paid, {} refunded", stream.stream_id, owed, refunded);
+    Ok(())
+}
+
+/// Refund whatever an escrow holds to the maker, then close the escrow and
+/// the PDA owning it (an order, OTC offer, launch or stream, signing with
+/// `owner_seeds`) to the maker. Returns the refunded amount
+fn close_escrow<'a>(
+    owner_account: &AccountInfo<'a>,
+    owner_seeds: &[&[u8]],
+    mint: &Pubkey,
+    escrow_account: &AccountInfo<'a>,
+    maker_token_account: &AccountInfo<'a>,
+    maker_account: &AccountInfo<'a>,
+    token_program_account: &AccountInfo<'a>,
+) -> Result<u64, ProgramError> {
+    let escrow = assert_token_account!(escrow_account, *mint, *owner_account.key);
+    assert_token_account!(maker_token_account, *mint, *maker_account.key);
+
+    // Refund whatever the escrow holds, then close it
+    if escrow.amount > 0 {
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                escrow_account.key,
+                maker_token_account.key,
+                owner_account.key,
+                &[],
+                escrow.amount,
+            )?,
+            &[
+                escrow_account.clone(),
+                maker_token_account.clone(),
+                owner_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[owner_seeds],
+        )?;
+    }
+    invoke_signed(
+        &token_instruction::close_account(
+            token_program_account.key,
+            escrow_account.key,
+            maker_account.key,
+            owner_account.key,
+            &[],
+        )?,
+        &[
+            escrow_account.clone(),
+            maker_account.clone(),
+            owner_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[owner_seeds],
+    )?;
+
+    // Close the owner, returning its rent to the maker
+    let lamports = owner_account.lamports();
+    **owner_account.lamports.borrow_mut() = 0;
+    **maker_account.lamports.borrow_mut() += lamports;
+    owner_account.data.borrow_mut().fill(0);
+
+    Ok(escrow.amount)
+}
+
+/// Mint each supplied contribution ledger's YOS for the full weeks since its
+/// last distribution, scaled by how long each tranche has been contributed
+fn process_distribute_contribution_rewards(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let program_state_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let crank_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if program_state.yos_mint != *yos_mint_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Once a thread is registered only its signed invocations distribute
+    if program_state.distribution_thread != Pubkey::default() {
+        if program_state.distribution_thread != *crank_account.key {
+            msg!("Distribution is reserved for thread {}", program_state.distribution_thread);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        assert_signer!(crank_account);
+    }
+    if program_state.contribution_reward_rate_bps == 0 {
+        msg!("Contribution rewards are disabled");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    let now = Clock::get()?.unix_timestamp;
+    let mut distributed = 0u64;
+    while let Some(contribution_ledger_account) = account_info_iter.next() {
+        let user_yos_account = next_account_info(account_info_iter)?;
+
+        assert_owner!(contribution_ledger_account, program_id);
+        let mut contribution_ledger = load_account::<ContributionLedger>(contribution_ledger_account)?;
+        let (contribution_ledger_address, _) = find_contribution_ledger_address(program_id, &contribution_ledger.owner);
+        if !contribution_ledger.is_initialized || contribution_ledger_address != *contribution_ledger_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        assert_token_account!(user_yos_account, program_state.yos_mint, contribution_ledger.owner);
+
+        // Settle the ledger before minting
+        let reward = contribution_ledger.settle_rewards(
+            now,
+            program_state.contribution_reward_rate_bps,
+            program_state.loyalty_bonus_bps_per_week,
+        );
+        contribution_ledger.serialize(&mut *contribution_ledger_account.data.borrow_mut())?;
+        if reward == 0 {
+            continue;
+        }
+
+        invoke_signed(
+            &token_instruction::mint_to(
+                token_program_account.key,
+                yos_mint_account.key,
+                user_yos_account.key,
+                program_authority_account.key,
+                &[],
+                reward,
+            )?,
+            &[
+                yos_mint_account.clone(),
+                user_yos_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+        distributed = distributed.saturating_add(reward);
+    }
+
+    program_state.stats.record_emission(distributed);
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Distributed {} YOS contribution rewards", distributed);
+    Ok(())
+}
+
+/// Snapshot the live statistics counters into the DailyStats PDA of the day
+/// they started in, then restart them for today
+fn process_snapshot_daily_stats(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let payer_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let daily_stats_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(payer_account);
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let today = stats_day(Clock::get()?.unix_timestamp);
+    if today <= program_state.stats_day {
+        msg!("Statistics day {} has not ended", program_state.stats_day);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (daily_stats_address, daily_stats_bump) = find_daily_stats_address(program_id, program_state.stats_day);
+    assert_pda!(daily_stats_account, daily_stats_address);
+    if daily_stats_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create daily stats account
+    let rent = Rent::get()?;
+    let daily_stats_size = std::mem::size_of::<DailyStats>();
+    let lamports = rent.minimum_balance(daily_stats_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            payer_account.key,
+            daily_stats_account.key,
+            lamports,
+            daily_stats_size as u64,
+            program_id,
         ),
-        SwapInstruction::Swap {
-            amount_in,
-            min_amount_out,
-        } => process_swap(program_id, accounts, amount_in, min_amount_out),
-        SwapInstruction::UpdateParameters {
-            lp_contribution_rate,
-            admin_fee_rate,
-            yos_cashback_rate,
-            swap_fee_rate,
-            referral_rate,
-        } => process_update_parameters(
+        &[
+            payer_account.clone(),
+            daily_stats_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::DAILY_STATS, &program_state.stats_day.to_le_bytes(), &[daily_stats_bump]]],
+    )?;
+
+    let daily_stats = DailyStats {
+        is_initialized: true,
+        day: program_state.stats_day,
+        end_day: today,
+        counters: program_state.stats,
+    };
+    daily_stats.serialize(&mut *daily_stats_account.data.borrow_mut())?;
+
+    // Restart the live counters
+    program_state.stats_day = today;
+    program_state.stats = StatsCounters::default();
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!(
+        "Statistics for day {} snapshotted: {} swaps, {} volume",
+        daily_stats.day,
+        daily_stats.counters.swaps,
+        daily_stats.counters.volume
+    );
+    Ok(())
+}
+
+/// Initialize the activity tree through the account compression program and
+/// record it in program state
+fn process_initialize_activity_tree(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_depth: u32,
+    max_buffer_size: u32,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let merkle_tree_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let compression_program_account = next_account_info(account_info_iter)?;
+    let noop_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::INITIALIZE_ACTIVITY_TREE)?;
+
+    if *compression_program_account.key != SPL_ACCOUNT_COMPRESSION_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_owner!(merkle_tree_account, &SPL_ACCOUNT_COMPRESSION_PROGRAM_ID);
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // The compression program validates the depth/buffer pair and the
+    // account size
+    let mut data = COMPRESSION_INIT_EMPTY_MERKLE_TREE_DISCRIMINATOR.to_vec();
+    (max_depth, max_buffer_size).serialize(&mut data)?;
+    invoke_signed(
+        &Instruction {
+            program_id: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*merkle_tree_account.key, false),
+                AccountMeta::new_readonly(program_authority_address, true),
+                AccountMeta::new_readonly(*noop_program_account.key, false),
+            ],
+            data,
+        },
+        &[
+            merkle_tree_account.clone(),
+            program_authority_account.clone(),
+            noop_program_account.clone(),
+            compression_program_account.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+    )?;
+
+    // Switch swaps over to the new tree
+    program_state.activity_tree = *merkle_tree_account.key;
+    program_state.activity_leaves = 0;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Activity tree {} initialized with depth {}", merkle_tree_account.key, max_depth);
+    Ok(())
+}
+
+/// Create the weekly distribution thread through Clockwork and reserve
+/// DistributeContributionRewards for it
+fn process_register_distribution_thread(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    thread_id: Vec<u8>,
+    amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let thread_account = next_account_info(account_info_iter)?;
+    let thread_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::REGISTER_DISTRIBUTION_THREAD)?;
+
+    if thread_id.is_empty() || thread_id.len() > MAX_THREAD_ID_LEN {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if *thread_program_account.key != CLOCKWORK_THREAD_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if program_state.yos_mint != *yos_mint_account.key || *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (program_authority_address, _) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+    let (thread_address, _) = find_distribution_thread_address(admin_account.key, &thread_id);
+    assert_pda!(thread_account, thread_address);
+
+    let (distribution, ledgers) = distribution_thread_instruction(
+        program_id,
+        &program_state,
+        account_info_iter,
+        yos_mint_account.key,
+        &program_authority_address,
+        &thread_address,
+    )?;
+
+    // thread_create(amount, id, instructions, trigger), Anchor-encoded
+    let trigger = ClockworkTrigger::Cron {
+        schedule: DISTRIBUTION_THREAD_SCHEDULE.to_string(),
+        skippable: true,
+    };
+    let mut data = CLOCKWORK_THREAD_CREATE_DISCRIMINATOR.to_vec();
+    (amount, &thread_id, vec![distribution], trigger).serialize(&mut data)?;
+
+    invoke(
+        &Instruction {
+            program_id: CLOCKWORK_THREAD_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*admin_account.key, true),
+                AccountMeta::new(*admin_account.key, true),
+                AccountMeta::new_readonly(*system_program_account.key, false),
+                AccountMeta::new(thread_address, false),
+            ],
+            data,
+        },
+        &[
+            admin_account.clone(),
+            system_program_account.clone(),
+            thread_account.clone(),
+            thread_program_account.clone(),
+        ],
+    )?;
+
+    // Reserve distribution for the thread
+    program_state.distribution_thread = thread_address;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Distribution thread {} registered for {} ledgers", thread_address, ledgers);
+    Ok(())
+}
+
+/// Point the registered distribution thread at a new set of ledgers through
+/// Clockwork
+fn process_update_distribution_thread(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let thread_account = next_account_info(account_info_iter)?;
+    let thread_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::UPDATE_DISTRIBUTION_THREAD)?;
+
+    if program_state.distribution_thread == Pubkey::default() {
+        msg!("No distribution thread is registered");
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if *thread_program_account.key != CLOCKWORK_THREAD_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if program_state.yos_mint != *yos_mint_account.key || *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let (program_authority_address, _) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+    assert_pda!(thread_account, program_state.distribution_thread);
+
+    let (distribution, ledgers) = distribution_thread_instruction(
+        program_id,
+        &program_state,
+        account_info_iter,
+        yos_mint_account.key,
+        &program_authority_address,
+        thread_account.key,
+    )?;
+
+    // thread_update(settings), Anchor-encoded, replacing only the instructions
+    let settings = ClockworkThreadSettings {
+        fee: None,
+        instructions: Some(vec![distribution]),
+        name: None,
+        rate_limit: None,
+        trigger: None,
+    };
+    let mut data = CLOCKWORK_THREAD_UPDATE_DISCRIMINATOR.to_vec();
+    settings.serialize(&mut data)?;
+
+    invoke(
+        &Instruction {
+            program_id: CLOCKWORK_THREAD_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new(*admin_account.key, true),
+                AccountMeta::new_readonly(*system_program_account.key, false),
+                AccountMeta::new(*thread_account.key, false),
+            ],
+            data,
+        },
+        &[
+            admin_account.clone(),
+            system_program_account.clone(),
+            thread_account.clone(),
+            thread_program_account.clone(),
+        ],
+    )?;
+
+    msg!("Distribution thread {} updated for {} ledgers", thread_account.key, ledgers);
+    Ok(())
+}
+
+// DistributeContributionRewards as the distribution thread replays it: the
+// thread signs as the crank, followed by the (contribution ledger, owner's
+// YOS account) pairs remaining in `account_info_iter`. Also returns the
+// number of ledgers.
+fn distribution_thread_instruction(
+    program_id: &Pubkey,
+    program_state: &ProgramState,
+    account_info_iter: &mut std::slice::Iter<AccountInfo>,
+    yos_mint: &Pubkey,
+    program_authority_address: &Pubkey,
+    thread_address: &Pubkey,
+) -> Result<(ClockworkInstruction, usize), ProgramError> {
+    let (program_state_address, _) = find_program_state_address(program_id);
+    let mut thread_accounts = vec![
+        ClockworkAccountMeta { pubkey: program_state_address, is_signer: false, is_writable: true },
+        ClockworkAccountMeta { pubkey: *yos_mint, is_signer: false, is_writable: true },
+        ClockworkAccountMeta { pubkey: *program_authority_address, is_signer: false, is_writable: false },
+        ClockworkAccountMeta { pubkey: spl_token::id(), is_signer: false, is_writable: false },
+        ClockworkAccountMeta { pubkey: *thread_address, is_signer: true, is_writable: false },
+    ];
+    let mut ledgers = 0usize;
+    while let Some(contribution_ledger_account) = account_info_iter.next() {
+        let user_yos_account = next_account_info(account_info_iter)?;
+
+        assert_owner!(contribution_ledger_account, program_id);
+        let contribution_ledger = load_account::<ContributionLedger>(contribution_ledger_account)?;
+        let (contribution_ledger_address, _) = find_contribution_ledger_address(program_id, &contribution_ledger.owner);
+        if !contribution_ledger.is_initialized || contribution_ledger_address != *contribution_ledger_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        assert_token_account!(user_yos_account, program_state.yos_mint, contribution_ledger.owner);
+
+        thread_accounts.push(ClockworkAccountMeta {
+            pubkey: *contribution_ledger_account.key,
+            is_signer: false,
+            is_writable: true,
+        });
+        thread_accounts.push(ClockworkAccountMeta {
+            pubkey: *user_yos_account.key,
+            is_signer: false,
+            is_writable: true,
+        });
+        ledgers += 1;
+    }
+    if ledgers == 0 {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+
+    let distribution = ClockworkInstruction {
+        program_id: *program_id,
+        accounts: thread_accounts,
+        data: SwapInstruction::DistributeContributionRewards.try_to_vec()?,
+    };
+    Ok((distribution, ledgers))
+}
+
+/// Mint the signer's accrued cashback plus the bonus earned by waiting
+fn process_claim_cashback(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let cashback_ledger_account = next_account_info(account_info_iter)?;
+    let user_yos_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let instructions_sysvar_account = next_account_info(account_info_iter)?;
+    // Optional CPI allowlist trusting wrapper programs
+    let cpi_allowlist_account = next_optional_account(account_info_iter, program_id);
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Reject untrusted wrapper programs piggybacking on the user's signature
+    require_trusted_top_level_instruction(program_id, instructions_sysvar_account, cpi_allowlist_account)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if program_state.yos_mint != *yos_mint_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // Verify PDA
+    let (cashback_ledger_address, _) = find_cashback_ledger_address(program_id, user_account.key);
+    assert_pda!(cashback_ledger_account, cashback_ledger_address);
+    let mut cashback_ledger = load_account::<CashbackLedger>(cashback_ledger_account)?;
+    if !cashback_ledger.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if cashback_ledger.accrued == 0 {
+        msg!("No cashback to claim");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Settle the ledger before minting
+    let claim_amount = cashback_ledger.claimable(Clock::get()?.unix_timestamp, program_state.cashback_wait_bonus_bps);
+    cashback_ledger.accrued = 0;
+    cashback_ledger.total_claimed = cashback_ledger.total_claimed.saturating_add(claim_amount);
+    cashback_ledger.serialize(&mut *cashback_ledger_account.data.borrow_mut())?;
+    program_state.stats.record_emission(claim_amount);
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    // Mint the cashback to the user
+    invoke_signed(
+        &token_instruction::mint_to(
+            token_program_account.key,
+            yos_mint_account.key,
+            user_yos_account.key,
+            program_authority_account.key,
+            &[],
+            claim_amount,
+        )?,
+        &[
+            yos_mint_account.clone(),
+            user_yos_account.clone(),
+            program_authority_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+    )?;
+
+    msg!("Claimed {} YOS cashback", claim_amount);
+    Ok(())
+}
+
+/// Set the YOS to YOT redemption rate (admin only)
+fn process_set_redemption_rate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    rate: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_REDEMPTION_RATE)?;
+
+    // Update the rate
+    program_state.yos_redemption_rate = rate;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("YOS redemption rate set to {}", rate);
+    Ok(())
+}
+
+/// Burn YOS and pay YOT from the redemption vault at the admin-set rate, or
+/// at the YOT/YOS pool price when no rate is set
+fn process_redeem_yos(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let user_yos_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let user_yot_account = next_account_info(account_info_iter)?;
+    let redemption_vault_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let instructions_sysvar_account = next_account_info(account_info_iter)?;
+    // YOT/YOS pool and its time-weighted reserves, required when no
+    // redemption rate is set
+    let pool_account = next_optional_account(account_info_iter, program_id);
+    let pool_twap_account = next_optional_account(account_info_iter, program_id);
+    // Optional CPI allowlist trusting wrapper programs
+    let cpi_allowlist_account = next_optional_account(account_info_iter, program_id);
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Reject untrusted wrapper programs piggybacking on the user's signature
+    require_trusted_top_level_instruction(program_id, instructions_sysvar_account, cpi_allowlist_account)?;
+
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if program_state.yos_mint != *yos_mint_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Verify the program authority
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+
+    // The vault must be a YOT account held by the program authority
+    let redemption_vault = assert_token_account!(
+        redemption_vault_account,
+        program_state.yot_mint,
+        program_authority_address,
+        "Invalid redemption vault"
+    );
+
+    // Price the redemption
+    let yot_amount = if program_state.yos_redemption_rate > 0 {
+        mul_div(amount, program_state.yos_redemption_rate, REDEMPTION_RATE_PRECISION)
+    } else {
+        let (Some(pool_account), Some(pool_twap_account)) = (pool_account, pool_twap_account) else {
+            msg!("No redemption rate set and no YOT/YOS pool and time-weighted reserves supplied");
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let (pool_address, _) = find_pool_address(program_id, &program_state.yos_mint, &program_state.yot_mint);
+        assert_pda!(pool_account, pool_address);
+        let pool = load_account::<PoolState>(pool_account)?;
+        // Priced at the time-weighted reserves, which a swap earlier in the
+        // transaction cannot move the way it moves the pool's own
+        let (pool_twap_address, _) = find_pool_twap_address(program_id, pool_account.key);
+        assert_pda!(pool_twap_account, pool_twap_address);
+        assert_owner!(pool_twap_account, program_id);
+        let pool_twap = load_account::<PoolTwap>(pool_twap_account)?;
+        if !pool_twap.is_initialized {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        let (reserve_yos, reserve_yot) = pool_twap.reserves_for(&pool, &program_state.yos_mint);
+        if reserve_yos == 0 {
+            msg!("YOT/YOS pool has no liquidity");
+            return Err(ProgramError::InvalidAccountData);
+        }
+        mul_div(amount, reserve_yot, reserve_yos)
+    };
+    if yot_amount == 0 || yot_amount > redemption_vault.amount {
+        msg!("Redemption vault cannot cover {} YOT", yot_amount);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // Burn the user's YOS
+    invoke(
+        &token_instruction::burn(
+            token_program_account.key,
+            user_yos_account.key,
+            yos_mint_account.key,
+            user_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            user_yos_account.clone(),
+            yos_mint_account.clone(),
+            user_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    // Pay YOT from the vault
+    invoke_signed(
+        &token_instruction::transfer(
+            token_program_account.key,
+            redemption_vault_account.key,
+            user_yot_account.key,
+            program_authority_account.key,
+            &[],
+            yot_amount,
+        )?,
+        &[
+            redemption_vault_account.clone(),
+            user_yot_account.clone(),
+            program_authority_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+    )?;
+
+    msg!("Redeemed {} YOS for {} YOT", amount, yot_amount);
+    Ok(())
+}
+
+/// Set the YOS burned per basis point of cashback boost (admin only)
+fn process_set_boost_price(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    yos_per_boost_bps: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_BOOST_PRICE)?;
+
+    // Update the price
+    program_state.yos_per_boost_bps = yos_per_boost_bps;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Boost price set to {} YOS per bps", yos_per_boost_bps);
+    Ok(())
+}
+
+/// Burn YOS to raise the signer's cashback boost. Burning while a boost is
+/// active stacks onto it and restarts the expiry
+fn process_burn_yos_for_boost(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let user_stats_account = next_account_info(account_info_iter)?;
+    let user_yos_account = next_account_info(account_info_iter)?;
+    let yos_mint_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+    // Optional rent payer for new user stats
+    let rent_payer_account = find_rent_payer(account_info_iter, user_account);
+
+    // Validate accounts
+    assert_signer!(user_account);
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if program_state.yos_mint != *yos_mint_account.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if program_state.yos_per_boost_bps == 0 {
+        msg!("Cashback boosting is disabled");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Convert the burn into boost basis points
+    let added_bps = amount / program_state.yos_per_boost_bps;
+    if added_bps == 0 {
+        msg!("Burn must buy at least 1 bps of boost");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Stack onto the active boost and restart the expiry
+    let mut user_stats = load_or_create_user_stats(
+        program_id,
+        rent_payer_account,
+        user_account,
+        user_stats_account,
+        Some(system_program_account),
+    )?;
+    let now = Clock::get()?.unix_timestamp;
+    user_stats.boost_bps = user_stats
+        .active_boost_bps(now)
+        .saturating_add(added_bps)
+        .min(MAX_CASHBACK_BOOST_BPS);
+    user_stats.boost_expiry = now + BOOST_DURATION_SECONDS;
+    user_stats.serialize(&mut *user_stats_account.data.borrow_mut())?;
+    emit_event(
+        &mut program_state,
+        b"boost_raised",
+        &BoostEvent {
+            user: *user_account.key,
+            boost_bps: user_stats.boost_bps,
+            boost_expiry: user_stats.boost_expiry,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    // Burn the user's YOS
+    invoke(
+        &token_instruction::burn(
+            token_program_account.key,
+            user_yos_account.key,
+            yos_mint_account.key,
+            user_account.key,
+            &[],
+            amount,
+        )?,
+        &[
+            user_yos_account.clone(),
+            yos_mint_account.clone(),
+            user_account.clone(),
+            token_program_account.clone(),
+        ],
+    )?;
+
+    msg!("Cashback boost raised to {} bps", user_stats.boost_bps);
+    Ok(())
+}
+
+/// Replace the admin instructions granted to `holder`, creating the permission
+/// matrix on first use (admin or governance only)
+fn process_set_permissions(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    holder: Pubkey,
+    instructions: Vec<u8>,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let authority_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let permission_matrix_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin or governance
+    if *authority_account.key != program_state.admin && *authority_account.key != program_state.governance {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    assert_signer!(authority_account);
+
+    // Collect the grants
+    let mut grants = 0u128;
+    for discriminant in instructions {
+        if discriminant as u32 >= u128::BITS {
+            msg!("Unknown instruction discriminant {}", discriminant);
+            return Err(ProgramError::InvalidArgument);
+        }
+        grants |= 1 << discriminant;
+    }
+
+    // Verify PDA
+    let (permission_matrix_address, permission_matrix_bump) = find_permission_matrix_address(program_id);
+    assert_pda!(permission_matrix_account, permission_matrix_address);
+
+    // Create the matrix on first use
+    let mut permission_matrix = if permission_matrix_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let matrix_size = std::mem::size_of::<PermissionMatrix>();
+        let lamports = rent.minimum_balance(matrix_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                authority_account.key,
+                permission_matrix_account.key,
+                lamports,
+                matrix_size as u64,
+                program_id,
+            ),
+            &[
+                authority_account.clone(),
+                permission_matrix_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::PERMISSION_MATRIX, &[permission_matrix_bump]]],
+        )?;
+
+        PermissionMatrix {
+            is_initialized: true,
+            holder_count: 0,
+            holders: [Pubkey::default(); MAX_PERMISSION_HOLDERS],
+            grants: [0; MAX_PERMISSION_HOLDERS],
+        }
+    } else {
+        load_account::<PermissionMatrix>(permission_matrix_account)?
+    };
+
+    if !permission_matrix.set(holder, grants) {
+        msg!("At most {} wallets can hold grants", MAX_PERMISSION_HOLDERS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    permission_matrix.serialize(&mut *permission_matrix_account.data.borrow_mut())?;
+
+    msg!("{} holds {} instruction grants", holder, grants.count_ones());
+    Ok(())
+}
+
+/// Clear the expired cashback boosts of the supplied user stats PDAs
+fn process_expire_boosts(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Clear every supplied boost that has run out
+    let now = Clock::get()?.unix_timestamp;
+    let mut expired = 0;
+    for user_stats_account in account_info_iter {
+        assert_owner!(user_stats_account, program_id);
+        let mut user_stats = load_account::<UserStats>(user_stats_account)?;
+        let (user_stats_address, _) = find_user_stats_address(program_id, &user_stats.owner);
+        if !user_stats.is_initialized || user_stats_address != *user_stats_account.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if user_stats.boost_bps == 0 || now < user_stats.boost_expiry {
+            continue;
+        }
+        user_stats.boost_bps = 0;
+        user_stats.boost_expiry = 0;
+        user_stats.serialize(&mut *user_stats_account.data.borrow_mut())?;
+        emit_event(
+            &mut program_state,
+            b"boost_expired",
+            &BoostEvent {
+                user: user_stats.owner,
+                boost_bps: 0,
+                boost_expiry: 0,
+            },
+        )?;
+        expired += 1;
+    }
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Cleared {} expired cashback boosts", expired);
+    Ok(())
+}
+
+/// Buy YOT with the configured stablecoin through the stable/YOT pool
+fn process_buy_with_stable(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    stable_mint: Pubkey,
+    amount: u64,
+    min_amount_out: u64,
+    simulate: bool,
+) -> ProgramResult {
+    // The config PDA leads the regular swap accounts
+    let (program_config_account, swap_accounts) = accounts
+        .split_first()
+        .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Verify config address
+    let (program_config_address, _) = find_program_config_address(program_id);
+    assert_pda!(program_config_account, program_config_address);
+    let program_config = load_account::<ProgramConfig>(program_config_account)?;
+    if !program_config.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if program_config.stable_mint == Pubkey::default() || program_config.stable_mint != stable_mint {
+        msg!("Stablecoin {} is not configured", stable_mint);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    execute_swap(
+        program_id,
+        swap_accounts,
+        amount,
+        min_amount_out,
+        SwapOptions {
+            required_pair: Some((stable_mint, program_config.yot_mint)),
+            simulate,
+            ..SwapOptions::default()
+        },
+    )
+}
+
+/// Set the swap fee paid by swaps that skip the liquidity contribution
+/// (admin only)
+fn process_set_opt_out_swap_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    swap_fee_rate: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_OPT_OUT_SWAP_FEE)?;
+
+    // Opting out must cost more than contributing, and the fees together
+    // cannot exceed the input
+    let other_fees = program_state.admin_fee_rate.saturating_add(program_state.referral_rate);
+    if swap_fee_rate != 0
+        && (swap_fee_rate <= program_state.swap_fee_rate || swap_fee_rate.saturating_add(other_fees) > 10000)
+    {
+        msg!(
+            "Opt-out swap fee must exceed {} bps and leave room for the other fees",
+            program_state.swap_fee_rate
+        );
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Update the rate
+    program_state.opt_out_swap_fee_rate = swap_fee_rate;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Opt-out swap fee set to {} bps", swap_fee_rate);
+    Ok(())
+}
+
+/// Register the NFT collection whose holders earn a cashback boost (admin only)
+fn process_set_boost_collection(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    collection: Pubkey,
+    boost_bps: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_BOOST_COLLECTION)?;
+
+    if boost_bps > MAX_CASHBACK_BOOST_BPS {
+        msg!("Collection boost cannot exceed {} bps", MAX_CASHBACK_BOOST_BPS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Update the collection
+    program_state.boost_collection = collection;
+    program_state.collection_boost_bps = boost_bps;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Boost collection {} set with {} bps", collection, boost_bps);
+    Ok(())
+}
+
+/// Replace the CPI caller allowlist, creating it on first use (admin only)
+fn process_set_cpi_allowlist(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    programs: Vec<Pubkey>,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let cpi_allowlist_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CPI_ALLOWLIST)?;
+
+    if programs.len() > MAX_CPI_CALLERS {
+        msg!("At most {} CPI callers can be allowlisted", MAX_CPI_CALLERS);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (cpi_allowlist_address, cpi_allowlist_bump) = find_cpi_allowlist_address(program_id);
+    assert_pda!(cpi_allowlist_account, cpi_allowlist_address);
+
+    // Create the allowlist on first use
+    if cpi_allowlist_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let allowlist_size = std::mem::size_of::<CpiAllowlist>();
+        let lamports = rent.minimum_balance(allowlist_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                cpi_allowlist_account.key,
+                lamports,
+                allowlist_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                cpi_allowlist_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::CPI_ALLOWLIST, &[cpi_allowlist_bump]]],
+        )?;
+    }
+
+    let mut cpi_allowlist = CpiAllowlist {
+        is_initialized: true,
+        count: programs.len() as u8,
+        programs: [Pubkey::default(); MAX_CPI_CALLERS],
+    };
+    cpi_allowlist.programs[..programs.len()].copy_from_slice(&programs);
+    cpi_allowlist.serialize(&mut *cpi_allowlist_account.data.borrow_mut())?;
+
+    msg!("CPI allowlist set to {} programs", programs.len());
+    Ok(())
+}
+
+/// Register a market-maker wallet or update its fee exemption, creating the
+/// registry entry on first use (admin only)
+fn process_set_market_maker(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    wallet: Pubkey,
+    fee_exempt: bool,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let market_maker_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_MARKET_MAKER)?;
+
+    // Verify PDA
+    let (market_maker_address, market_maker_bump) = find_market_maker_address(program_id, &wallet);
+    assert_pda!(market_maker_account, market_maker_address);
+
+    // Create the entry on first use
+    if market_maker_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let market_maker_size = std::mem::size_of::<MarketMaker>();
+        let lamports = rent.minimum_balance(market_maker_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                market_maker_account.key,
+                lamports,
+                market_maker_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                market_maker_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::MARKET_MAKER, wallet.as_ref(), &[market_maker_bump]]],
+        )?;
+    }
+
+    let market_maker = MarketMaker {
+        is_initialized: true,
+        wallet,
+        fee_exempt,
+    };
+    market_maker.serialize(&mut *market_maker_account.data.borrow_mut())?;
+
+    msg!("Market maker {} fee exemption set to {}", wallet, fee_exempt);
+    Ok(())
+}
+
+/// Turn the requirement for YOT or a hub token on one side of every swap on
+/// or off (admin only)
+fn process_set_require_hub_side(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    required: bool,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_REQUIRE_HUB_SIDE)?;
+
+    program_state.require_hub_side = required;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Hub side requirement set to {}", required);
+    Ok(())
+}
+
+/// Replace the hub token list in program state (admin only)
+fn process_set_hub_tokens(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hub_tokens: Vec<Pubkey>,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_HUB_TOKENS)?;
+
+    if hub_tokens.len() > MAX_HUB_TOKENS {
+        msg!("At most {} hub tokens are supported", MAX_HUB_TOKENS);
+        return Err(ProgramError::InvalidArgument);
+    }
+    for (index, mint) in hub_tokens.iter().enumerate() {
+        if *mint == Pubkey::default() || *mint == program_state.yot_mint || hub_tokens[..index].contains(mint) {
+            msg!("Invalid or duplicate hub token {}", mint);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+
+    // Replace the list
+    program_state.hub_tokens = [Pubkey::default(); MAX_HUB_TOKENS];
+    program_state.hub_tokens[..hub_tokens.len()].copy_from_slice(&hub_tokens);
+    program_state.hub_token_count = hub_tokens.len() as u8;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Hub tokens set: {} besides YOT", hub_tokens.len());
+    Ok(())
+}
+
+/// Approve or revoke a hub token, creating its registry entry on first use
+/// (admin only)
+fn process_set_hub_token(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint: Pubkey,
+    approved: bool,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let hub_token_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_HUB_TOKEN)?;
+
+    // Verify PDA
+    let (hub_token_address, hub_token_bump) = find_hub_token_address(program_id, &mint);
+    assert_pda!(hub_token_account, hub_token_address);
+
+    // Create the entry on first use
+    if hub_token_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let hub_token_size = std::mem::size_of::<HubToken>();
+        let lamports = rent.minimum_balance(hub_token_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                hub_token_account.key,
+                lamports,
+                hub_token_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                hub_token_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::HUB_TOKEN, mint.as_ref(), &[hub_token_bump]]],
+        )?;
+    }
+
+    let hub_token = HubToken {
+        is_initialized: true,
+        mint,
+        approved,
+    };
+    hub_token.serialize(&mut *hub_token_account.data.borrow_mut())?;
+
+    msg!("Hub token {} approval set to {}", mint, approved);
+    Ok(())
+}
+
+/// Publish the routes a pair may be swapped through, flagging it as sensitive,
+/// or clear the flag with an empty list (admin only)
+fn process_set_allowed_routes(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    route_hashes: Vec<[u8; 32]>,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let route_allowlist_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_ALLOWED_ROUTES)?;
+
+    if mint_a == mint_b {
+        msg!("A pair needs two different mints");
+        return Err(ProgramError::InvalidArgument);
+    }
+    if route_hashes.len() > MAX_ALLOWED_ROUTES {
+        msg!("At most {} routes can be allowed per pair", MAX_ALLOWED_ROUTES);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Verify PDA
+    let (mint_a, mint_b) = if mint_a < mint_b { (mint_a, mint_b) } else { (mint_b, mint_a) };
+    let (route_allowlist_address, route_allowlist_bump) = find_route_allowlist_address(program_id, &mint_a, &mint_b);
+    assert_pda!(route_allowlist_account, route_allowlist_address);
+
+    // Create the allowlist on first use
+    if route_allowlist_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let route_allowlist_size = std::mem::size_of::<RouteAllowlist>();
+        let lamports = rent.minimum_balance(route_allowlist_size);
+
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                route_allowlist_account.key,
+                lamports,
+                route_allowlist_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                route_allowlist_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::ROUTE_ALLOWLIST, mint_a.as_ref(), mint_b.as_ref(), &[route_allowlist_bump]]],
+        )?;
+    }
+
+    let mut route_allowlist = RouteAllowlist {
+        is_initialized: true,
+        mint_a,
+        mint_b,
+        route_count: route_hashes.len() as u8,
+        route_hashes: [[0; 32]; MAX_ALLOWED_ROUTES],
+    };
+    route_allowlist.route_hashes[..route_hashes.len()].copy_from_slice(&route_hashes);
+    route_allowlist.serialize(&mut *route_allowlist_account.data.borrow_mut())?;
+
+    // Flag the pair in program state while it has allowed routes
+    let count = program_state.sensitive_pair_count as usize;
+    let flagged = program_state.sensitive_pairs[..count].iter().position(|pair| *pair == route_allowlist_address);
+    match (flagged, route_hashes.is_empty()) {
+        (None, false) => {
+            if count == MAX_SENSITIVE_PAIRS {
+                msg!("At most {} pairs can be flagged as sensitive", MAX_SENSITIVE_PAIRS);
+                return Err(ProgramError::InvalidArgument);
+            }
+            program_state.sensitive_pairs[count] = route_allowlist_address;
+            program_state.sensitive_pair_count += 1;
+        }
+        (Some(index), true) => {
+            program_state.sensitive_pairs[index] = program_state.sensitive_pairs[count - 1];
+            program_state.sensitive_pairs[count - 1] = Pubkey::default();
+            program_state.sensitive_pair_count -= 1;
+        }
+        _ => {}
+    }
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("{} routes allowed for the {}/{} pair", route_hashes.len(), mint_a, mint_b);
+    Ok(())
+}
+
+/// Close an empty authority-owned token account of a mint whose hub token
+/// approval was revoked, returning its rent to the treasury (admin only)
+fn process_close_vault(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let vault_account = next_account_info(account_info_iter)?;
+    let hub_token_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::CLOSE_VAULT)?;
+
+    // Verify PDAs
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+    let (treasury_address, _) = find_treasury_address(program_id);
+    assert_pda!(treasury_account, treasury_address);
+
+    // The vault must be an empty token account held by the authority
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    assert_owner!(vault_account, &spl_token::id());
+    let vault = TokenAccount::unpack(&vault_account.data.borrow())?;
+    if vault.owner != program_authority_address {
+        msg!("Vault is not held by the program authority");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if vault.amount > 0 {
+        msg!("Vault still holds {} tokens", vault.amount);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // Only a delisted mint's vault: never YOS or a hub mint, YOT included,
+    // nor a mint whose registry entry is still approved
+    if vault.mint == program_state.yos_mint || program_state.is_hub(&vault.mint) {
+        msg!("Mint {} is still listed", vault.mint);
+        return Err(ProgramError::InvalidArgument);
+    }
+    let (hub_token_address, _) = find_hub_token_address(program_id, &vault.mint);
+    assert_pda!(hub_token_account, hub_token_address);
+    assert_owner!(hub_token_account, program_id);
+    let hub_token = load_account::<HubToken>(hub_token_account)?;
+    if !hub_token.is_initialized || hub_token.approved {
+        msg!("Mint {} has not been delisted", vault.mint);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let rent = vault_account.lamports();
+    invoke_signed(
+        &token_instruction::close_account(
+            token_program_account.key,
+            vault_account.key,
+            treasury_account.key,
+            program_authority_account.key,
+            &[],
+        )?,
+        &[
+            vault_account.clone(),
+            treasury_account.clone(),
+            program_authority_account.clone(),
+            token_program_account.clone(),
+        ],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+    )?;
+
+    msg!("Closed the {} vault, returning {} lamports to the treasury", vault.mint, rent);
+    Ok(())
+}
+
+/// Move a pool's admin fees out of its vaults into the treasury's token
+/// accounts, from which WithdrawTreasury pays them. Anyone may crank it, as
+/// the fees can only go to the treasury.
+fn process_collect_admin_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let pool_account = next_account_info(account_info_iter)?;
+    let vault_a_account = next_account_info(account_info_iter)?;
+    let vault_b_account = next_account_info(account_info_iter)?;
+    let treasury_token_a_account = next_account_info(account_info_iter)?;
+    let treasury_token_b_account = next_account_info(account_info_iter)?;
+    let program_authority_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+
+    // Load pool
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let (pool_address, _) = find_pool_address(program_id, &pool.mint_a, &pool.mint_b);
+    assert_pda!(pool_account, pool_address);
+
+    // Verify PDAs
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+    let (treasury_address, _) = find_treasury_address(program_id);
+    if *token_program_account.key != spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Fees leave the authority's vaults only for the treasury's accounts
+    for (vault_account, treasury_token_account, mint) in [
+        (vault_a_account, treasury_token_a_account, &pool.mint_a),
+        (vault_b_account, treasury_token_b_account, &pool.mint_b),
+    ] {
+        assert_token_account!(vault_account, *mint, program_authority_address);
+        assert_token_account!(treasury_token_account, *mint, treasury_address);
+    }
+
+    let (amount_a, amount_b) = (pool.admin_fees_a, pool.admin_fees_b);
+    pool.admin_fees_a = 0;
+    pool.admin_fees_b = 0;
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    for (vault_account, treasury_token_account, amount) in [
+        (vault_a_account, treasury_token_a_account, amount_a),
+        (vault_b_account, treasury_token_b_account, amount_b),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        invoke_signed(
+            &token_instruction::transfer(
+                token_program_account.key,
+                vault_account.key,
+                treasury_token_account.key,
+                program_authority_account.key,
+                &[],
+                amount,
+            )?,
+            &[
+                vault_account.clone(),
+                treasury_token_account.clone(),
+                program_authority_account.clone(),
+                token_program_account.clone(),
+            ],
+            &[&[seeds::AUTHORITY, &[program_authority_bump]]],
+        )?;
+    }
+
+    msg!("Collected {} and {} in admin fees to the treasury", amount_a, amount_b);
+    Ok(())
+}
+
+/// Schedule an emergency withdrawal of the treasury's LP position in an
+/// spl-token-swap pool, executable after EMERGENCY_WITHDRAWAL_DELAY_SECONDS
+/// (admin only)
+fn process_schedule_emergency_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let emergency_withdrawal_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SCHEDULE_EMERGENCY_WITHDRAWAL)?;
+
+    // The pool is checked in full at execution
+    assert_owner!(pool_account, &TOKEN_SWAP_PROGRAM_ID);
+
+    // Verify PDA
+    let (emergency_withdrawal_address, emergency_withdrawal_bump) =
+        find_emergency_withdrawal_address(program_id, pool_account.key);
+    assert_pda!(emergency_withdrawal_account, emergency_withdrawal_address);
+    if emergency_withdrawal_account.data_len() > 0 {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    // Create emergency withdrawal account
+    let rent = Rent::get()?;
+    let emergency_withdrawal_size = std::mem::size_of::<EmergencyWithdrawal>();
+    let lamports = rent.minimum_balance(emergency_withdrawal_size);
+
+    invoke_signed(
+        &system_instruction::create_account(
+            admin_account.key,
+            emergency_withdrawal_account.key,
+            lamports,
+            emergency_withdrawal_size as u64,
             program_id,
-            accounts,
-            lp_contribution_rate,
-            admin_fee_rate,
-            yos_cashback_rate,
-            swap_fee_rate,
-            referral_rate,
         ),
-        SwapInstruction::SetAdmin { new_admin } => process_set_admin(program_id, accounts, new_admin),
-        SwapInstruction::CloseProgram {} => process_close_program(program_id, accounts),
+        &[
+            admin_account.clone(),
+            emergency_withdrawal_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[seeds::EMERGENCY_WITHDRAWAL, pool_account.key.as_ref(), &[emergency_withdrawal_bump]]],
+    )?;
+
+    let executable_at = Clock::get()?
+        .unix_timestamp
+        .checked_add(EMERGENCY_WITHDRAWAL_DELAY_SECONDS)
+        .ok_or(ProgramError::InvalidArgument)?;
+    let emergency_withdrawal = EmergencyWithdrawal {
+        is_initialized: true,
+        pool: *pool_account.key,
+        executable_at,
+    };
+    emergency_withdrawal.serialize(&mut *emergency_withdrawal_account.data.borrow_mut())?;
+
+    emit_event(
+        &mut program_state,
+        b"emergency_withdrawal_scheduled",
+        &EmergencyWithdrawalEvent {
+            pool: *pool_account.key,
+            executable_at,
+            pool_tokens: 0,
+            amount_a: 0,
+            amount_b: 0,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Emergency withdrawal from pool {} executable at {}", pool_account.key, executable_at);
+    Ok(())
+}
+
+/// Drop a scheduled emergency withdrawal (admin only)
+fn process_cancel_emergency_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let emergency_withdrawal_account = next_account_info(account_info_iter)?;
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::CANCEL_EMERGENCY_WITHDRAWAL)?;
+
+    let emergency_withdrawal = load_emergency_withdrawal(program_id, pool_account, emergency_withdrawal_account)?;
+    close_emergency_withdrawal(emergency_withdrawal_account, admin_account);
+
+    emit_event(
+        &mut program_state,
+        b"emergency_withdrawal_cancelled",
+        &EmergencyWithdrawalEvent {
+            pool: *pool_account.key,
+            executable_at: emergency_withdrawal.executable_at,
+            pool_tokens: 0,
+            amount_a: 0,
+            amount_b: 0,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Emergency withdrawal from pool {} cancelled", pool_account.key);
+    Ok(())
+}
+
+/// Withdraw the treasury's LP position in an spl-token-swap pool into the
+/// treasury once its emergency withdrawal timelock has passed (admin only)
+fn process_execute_emergency_withdrawal(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let emergency_withdrawal_account = next_account_info(account_info_iter)?;
+    let venue = Venue::next(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let treasury_lp_account = next_account_info(account_info_iter)?;
+    let treasury_token_a_account = next_account_info(account_info_iter)?;
+    let treasury_token_b_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let token_swap_program_account = next_account_info(account_info_iter)?;
+
+    if *token_program_account.key != spl_token::id() || *token_swap_program_account.key != TOKEN_SWAP_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::EXECUTE_EMERGENCY_WITHDRAWAL)?;
+
+    let emergency_withdrawal = load_emergency_withdrawal(program_id, venue.pool, emergency_withdrawal_account)?;
+    if Clock::get()?.unix_timestamp < emergency_withdrawal.executable_at {
+        msg!("Emergency withdrawal is timelocked until {}", emergency_withdrawal.executable_at);
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    // The treasury holds the LP tokens and receives both sides
+    let (treasury_address, treasury_bump) = find_treasury_address(program_id);
+    assert_pda!(treasury_account, treasury_address);
+    let pool_tokens = assert_token_account!(treasury_lp_account, *venue.pool_mint.key, treasury_address).amount;
+    assert_token_account!(treasury_token_a_account, venue.mint_a, treasury_address);
+    assert_token_account!(treasury_token_b_account, venue.mint_b, treasury_address);
+    if pool_tokens == 0 {
+        msg!("Treasury holds no LP tokens of pool {}", venue.pool.key);
+        return Err(ProgramError::InsufficientFunds);
+    }
+
+    // Withdraw the whole position, measuring what arrived
+    let (balance_a, balance_b) = (token_balance(treasury_token_a_account)?, token_balance(treasury_token_b_account)?);
+    let withdraw = venue.withdraw_all_token_types(
+        treasury_account.key,
+        treasury_lp_account.key,
+        treasury_token_a_account.key,
+        treasury_token_b_account.key,
+        pool_tokens,
+    );
+    let mut withdraw_accounts = venue.account_infos().to_vec();
+    withdraw_accounts.extend([
+        treasury_account.clone(),
+        treasury_lp_account.clone(),
+        treasury_token_a_account.clone(),
+        treasury_token_b_account.clone(),
+        token_program_account.clone(),
+        token_swap_program_account.clone(),
+    ]);
+    invoke_signed(&withdraw, &withdraw_accounts, &[&[seeds::TREASURY, &[treasury_bump]]])?;
+    let amount_a = token_balance(treasury_token_a_account)?.saturating_sub(balance_a);
+    let amount_b = token_balance(treasury_token_b_account)?.saturating_sub(balance_b);
+    close_emergency_withdrawal(emergency_withdrawal_account, admin_account);
+
+    emit_event(
+        &mut program_state,
+        b"emergency_withdrawal_executed",
+        &EmergencyWithdrawalEvent {
+            pool: *venue.pool.key,
+            executable_at: emergency_withdrawal.executable_at,
+            pool_tokens,
+            amount_a,
+            amount_b,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!(
+        "Emergency withdrawal burned {} LP tokens of pool {} for {} and {} to the treasury",
+        pool_tokens,
+        venue.pool.key,
+        amount_a,
+        amount_b
+    );
+    Ok(())
+}
+
+/// Move treasury-held LP from one spl-token-swap pool to another of the same
+/// pair (admin only)
+fn process_migrate_liquidity(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    from_pool: Pubkey,
+    to_pool: Pubkey,
+    amount: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let admin_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let treasury_account = next_account_info(account_info_iter)?;
+    let from = Venue::next(account_info_iter)?;
+    let treasury_from_lp_account = next_account_info(account_info_iter)?;
+    let to = Venue::next(account_info_iter)?;
+    let treasury_to_lp_account = next_account_info(account_info_iter)?;
+    let treasury_token_a_account = next_account_info(account_info_iter)?;
+    let treasury_token_b_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let token_swap_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    if amount == 0 || from_pool == to_pool || *from.pool.key != from_pool || *to.pool.key != to_pool {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !from.same_pair(&to) {
+        msg!("Pools {} and {} trade different pairs", from_pool, to_pool);
+        return Err(ProgramError::InvalidArgument);
+    }
+    if *token_program_account.key != spl_token::id() || *token_swap_program_account.key != TOKEN_SWAP_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::MIGRATE_LIQUIDITY)?;
+
+    // The treasury holds the LP tokens and receives both sides
+    let (treasury_address, treasury_bump) = find_treasury_address(program_id);
+    assert_pda!(treasury_account, treasury_address);
+    assert_token_account!(treasury_from_lp_account, *from.pool_mint.key, treasury_address);
+    assert_token_account!(treasury_to_lp_account, *to.pool_mint.key, treasury_address);
+    assert_token_account!(treasury_token_a_account, from.mint_a, treasury_address);
+    assert_token_account!(treasury_token_b_account, from.mint_b, treasury_address);
+    let treasury_seeds: &[&[u8]] = &[seeds::TREASURY, &[treasury_bump]];
+
+    // Withdraw both sides from the first pool, measuring what arrived
+    let (balance_a, balance_b) = (token_balance(treasury_token_a_account)?, token_balance(treasury_token_b_account)?);
+    let withdraw = from.withdraw_all_token_types(
+        treasury_account.key,
+        treasury_from_lp_account.key,
+        treasury_token_a_account.key,
+        treasury_token_b_account.key,
+        amount,
+    );
+    let mut withdraw_accounts = from.account_infos().to_vec();
+    withdraw_accounts.extend([
+        treasury_account.clone(),
+        treasury_from_lp_account.clone(),
+        treasury_token_a_account.clone(),
+        treasury_token_b_account.clone(),
+        token_program_account.clone(),
+        token_swap_program_account.clone(),
+    ]);
+    invoke_signed(&withdraw, &withdraw_accounts, &[treasury_seeds])?;
+    let amount_a = token_balance(treasury_token_a_account)?.saturating_sub(balance_a);
+    let amount_b = token_balance(treasury_token_b_account)?.saturating_sub(balance_b);
+
+    // Deposit them into the second pool, whose mints may be in either order
+    let (to_source_a, to_amount_a, to_source_b, to_amount_b) = if to.mint_a == from.mint_a {
+        (treasury_token_a_account, amount_a, treasury_token_b_account, amount_b)
+    } else {
+        (treasury_token_b_account, amount_b, treasury_token_a_account, amount_a)
+    };
+    let pool_tokens = to.deposit_pool_tokens(to_amount_a, to_amount_b)?;
+    if pool_tokens == 0 {
+        msg!("Withdrawal of {} LP tokens returned too little to deposit", amount);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    let deposit = to.deposit_all_token_types(
+        treasury_account.key,
+        to_source_a.key,
+        to_source_b.key,
+        treasury_to_lp_account.key,
+        pool_tokens,
+        to_amount_a,
+        to_amount_b,
+    );
+    let mut deposit_accounts = to.account_infos().to_vec();
+    deposit_accounts.extend([
+        treasury_account.clone(),
+        to_source_a.clone(),
+        to_source_b.clone(),
+        treasury_to_lp_account.clone(),
+        token_program_account.clone(),
+        token_swap_program_account.clone(),
+    ]);
+    invoke_signed(&deposit, &deposit_accounts, &[treasury_seeds])?;
+
+    emit_event(
+        &mut program_state,
+        b"liquidity_migrated",
+        &LiquidityMigrationEvent {
+            from_pool,
+            to_pool,
+            pool_tokens_withdrawn: amount,
+            pool_tokens_deposited: pool_tokens,
+            mint_a: from.mint_a,
+            amount_a,
+            mint_b: from.mint_b,
+            amount_b,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!(
+        "Migrated {} LP tokens of pool {} into {} LP tokens of pool {}",
+        amount,
+        from_pool,
+        pool_tokens,
+        to_pool
+    );
+    Ok(())
+}
+
+/// Swap through an external spl-token-swap pool, paying out and checking
+/// slippage against the measured output rather than the pool's claim
+fn process_swap_through_token_swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount_in: u64,
+    min_amount_out: u64,
+) -> ProgramResult {
+    // Get account iterator
+    let account_info_iter = &mut accounts.iter();
+
+    // Extract accounts
+    let user_account = next_account_info(account_info_iter)?;
+    let program_state_account = next_account_info(account_info_iter)?;
+    let venue = Venue::next(account_info_iter)?;
+    let user_token_in_account = next_account_info(account_info_iter)?;
+    let user_token_out_account = next_account_info(account_info_iter)?;
+    let token_program_account = next_account_info(account_info_iter)?;
+    let token_swap_program_account = next_account_info(account_info_iter)?;
+
+    // Validate accounts
+    assert_signer!(user_account);
+    if amount_in == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if *token_program_account.key != spl_token::id() || *token_swap_program_account.key != TOKEN_SWAP_PROGRAM_ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    program_state.check_not_paused(PAUSE_SWAP)?;
+
+    // The user's accounts must hold the pool's two mints, the output one
+    // owned by the user so the measured delta is theirs
+    let mint_in = TokenAccount::unpack(&user_token_in_account.data.borrow())?.mint;
+    let Some(mint_out) = venue.other_mint(&mint_in) else {
+        msg!("Pool {} does not trade {}", venue.pool.key, mint_in);
+        return Err(ProgramError::InvalidAccountData);
+    };
+    assert_token_account!(user_token_out_account, mint_out, *user_account.key);
+
+    // Swap, measuring what the pool actually paid into the output account
+    let balance_before = token_balance(user_token_out_account)?;
+    let swap = venue.swap(user_account.key, user_token_in_account.key, user_token_out_account.key, &mint_in, amount_in);
+    let mut swap_accounts = venue.account_infos().to_vec();
+    swap_accounts.extend([
+        user_account.clone(),
+        user_token_in_account.clone(),
+        user_token_out_account.clone(),
+        token_program_account.clone(),
+        token_swap_program_account.clone(),
+    ]);
+    invoke(&swap, &swap_accounts)?;
+    let amount_out = token_balance(user_token_out_account)?.checked_sub(balance_before).ok_or_else(|| {
+        msg!("Pool {} drained the output account", venue.pool.key);
+        ProgramError::InvalidAccountData
+    })?;
+    if amount_out < min_amount_out {
+        msg!("Pool {} paid {} below the minimum {}", venue.pool.key, amount_out, min_amount_out);
+        return Err(ProgramError::InvalidInstructionData);
     }
+
+    emit_event(
+        &mut program_state,
+        b"token_swap_hop",
+        &TokenSwapHopEvent {
+            pool: *venue.pool.key,
+            user: *user_account.key,
+            mint_in,
+            amount_in,
+            mint_out,
+            amount_out,
+        },
+    )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+
+    msg!("Swapped {} for {} through pool {}", amount_in, amount_out, venue.pool.key);
+    Ok(())
 }
 
-/// Initialize the program state with admin and token addresses
-fn process_initialize(
+/// Set the Wormhole emitter trusted for purchase intents, creating the config
+/// on first use (admin only)
+fn process_set_cross_chain_emitter(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    admin: Pubkey,
-    yot_mint: Pubkey,
-    yos_mint: Pubkey,
-    lp_contribution_rate: u64,
-    admin_fee_rate: u64,
-    yos_cashback_rate: u64,
-    swap_fee_rate: u64,
-    referral_rate: u64,
+    emitter_chain: u16,
+    emitter_address: [u8; 32],
+    quote_mint: Pubkey,
 ) -> ProgramResult {
     // Get account iterator
     let account_info_iter = &mut accounts.iter();
 
     // Extract accounts
-    let payer_account = next_account_info(account_info_iter)?;
+    let admin_account = next_account_info(account_info_iter)?;
     let program_state_account = next_account_info(account_info_iter)?;
-    let program_authority_account = next_account_info(account_info_iter)?;
+    let cross_chain_config_account = next_account_info(account_info_iter)?;
     let system_program_account = next_account_info(account_info_iter)?;
-    let rent_sysvar_account = next_account_info(account_info_iter)?;
 
-    // Validate accounts
-    if !payer_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
+
+    // Load program state
+    let program_state = load_account::<ProgramState>(program_state_account)?;
+    if !program_state.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
     }
 
-    // Calculate PDAs
-    let (program_state_address, program_state_bump) = find_program_state_address(program_id);
-    let (program_authority_address, _) = find_program_authority(program_id);
+    // Verify admin
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CROSS_CHAIN_EMITTER)?;
 
-    // Verify PDAs
-    if program_state_address != *program_state_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
-    if program_authority_address != *program_authority_account.key {
-        return Err(ProgramError::InvalidAccountData);
+    if quote_mint == program_state.yot_mint {
+        return Err(ProgramError::InvalidArgument);
     }
 
-    // Check if the program state account already exists
-    if program_state_account.data_len() > 0 {
-        // If it exists, check if it's already initialized
-        let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
-        if program_state.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
-    } else {
-        // Create program state account
+    // Verify PDA
+    let (cross_chain_config_address, cross_chain_config_bump) = find_cross_chain_config_address(program_id);
+    assert_pda!(cross_chain_config_account, cross_chain_config_address);
+
+    // Create the config on first use
+    if cross_chain_config_account.data_is_empty() {
         let rent = Rent::get()?;
-        let state_size = std::mem::size_of::<ProgramState>();
-        let lamports = rent.minimum_balance(state_size);
+        let config_size = std::mem::size_of::<CrossChainConfig>();
+        let lamports = rent.minimum_balance(config_size);
 
         invoke_signed(
             &system_instruction::create_account(
-                payer_account.key,
-                program_state_account.key,
+                admin_account.key,
+                cross_chain_config_account.key,
                 lamports,
-                state_size as u64,
+                config_size as u64,
                 program_id,
             ),
             &[
-                payer_account.clone(),
-                program_state_account.clone(),
+                admin_account.clone(),
+                cross_chain_config_account.clone(),
                 system_program_account.clone(),
             ],
-            &[&[b"state", &[program_state_bump]]],
+            &[&[seeds::CROSS_CHAIN_CONFIG, &[cross_chain_config_bump]]],
         )?;
     }
 
-    // Initialize program state
-    let program_state = ProgramState {
-        admin,
-        yot_mint,
-        yos_mint,
-        lp_contribution_rate,
-        admin_fee_rate,
-        yos_cashback_rate,
-        swap_fee_rate,
-        referral_rate,
+    let cross_chain_config = CrossChainConfig {
         is_initialized: true,
+        emitter_chain,
+        emitter_address,
+        quote_mint,
     };
+    cross_chain_config.serialize(&mut *cross_chain_config_account.data.borrow_mut())?;
 
-    // Serialize and store program state
-    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
-
-    msg!("Program initialized successfully");
+    msg!("Purchase intents accepted from chain {} paid in {}", emitter_chain, quote_mint);
     Ok(())
 }
 
-/// Process a token swap with cashback and liquidity contribution
-fn process_swap(
+/// Execute a posted purchase intent VAA: spend its bridged tokens from the
+/// intent escrow on YOT through the pool and deliver it to the target wallet
+fn process_execute_purchase_intent(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    amount_in: u64,
-    min_amount_out: u64,
 ) -> ProgramResult {
     // Get account iterator
     let account_info_iter = &mut accounts.iter();
 
     // Extract accounts
-    let user_account = next_account_info(account_info_iter)?;
+    let relayer_account = next_account_info(account_info_iter)?;
     let program_state_account = next_account_info(account_info_iter)?;
+    let cross_chain_config_account = next_account_info(account_info_iter)?;
+    let posted_vaa_account = next_account_info(account_info_iter)?;
+    let intent_receipt_account = next_account_info(account_info_iter)?;
+    let escrow_account = next_account_info(account_info_iter)?;
+    let pool_account = next_account_info(account_info_iter)?;
+    let quote_vault_account = next_account_info(account_info_iter)?;
+    let yot_vault_account = next_account_info(account_info_iter)?;
+    let target_token_account = next_account_info(account_info_iter)?;
     let program_authority_account = next_account_info(account_info_iter)?;
-    let user_token_in_account = next_account_info(account_info_iter)?;
-    let user_token_out_account = next_account_info(account_info_iter)?;
-    let user_yos_account = next_account_info(account_info_iter)?;
     let token_program_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
 
     // Validate accounts
-    if !user_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    assert_signer!(relayer_account);
+
+    // Verify state address
+    let (program_state_address, _) = find_program_state_address(program_id);
+    assert_pda!(program_state_account, program_state_address);
 
     // Load program state
-    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let mut program_state = load_account::<ProgramState>(program_state_account)?;
     if !program_state.is_initialized {
         return Err(ProgramError::UninitializedAccount);
     }
+    program_state.check_not_paused(PAUSE_SWAP)?;
 
-    // Verify the program authority
-    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
-    if program_authority_address != *program_authority_account.key {
-        return Err(ProgramError::InvalidAccountData);
+    // Load cross-chain config
+    let (cross_chain_config_address, cross_chain_config_bump) = find_cross_chain_config_address(program_id);
+    assert_pda!(cross_chain_config_account, cross_chain_config_address);
+    let cross_chain_config = load_account::<CrossChainConfig>(cross_chain_config_account)?;
+    if !cross_chain_config.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
     }
 
-    // Verify YOS token account
-    // This is the critical part - we ensure the YOS token account exists and is valid
-    if let Ok(user_yos_account_data) = TokenAccount::unpack(&user_yos_account.data.borrow()) {
-        if user_yos_account_data.mint != program_state.yos_mint {
-            msg!("YOS token account has incorrect mint");
+    // The VAA must be posted by the core bridge, which verified its
+    // guardian signatures, and come from the trusted emitter
+    assert_owner!(posted_vaa_account, &WORMHOLE_CORE_BRIDGE_ID);
+    let vaa = {
+        let data = posted_vaa_account.data.borrow();
+        if data.len() < POSTED_VAA_MAGIC.len() || &data[..POSTED_VAA_MAGIC.len()] != POSTED_VAA_MAGIC {
             return Err(ProgramError::InvalidAccountData);
         }
-        if user_yos_account_data.owner != *user_account.key {
-            msg!("YOS token account has incorrect owner");
-            return Err(ProgramError::InvalidAccountData);
-        }
-    } else {
-        msg!("Invalid YOS token account - account may not exist");
+        PostedVaaData::deserialize(&mut &data[POSTED_VAA_MAGIC.len()..])?
+    };
+    if vaa.emitter_chain != cross_chain_config.emitter_chain || vaa.emitter_address != cross_chain_config.emitter_address {
+        msg!("VAA is not from the trusted emitter");
         return Err(ProgramError::InvalidAccountData);
     }
+    let Some(intent) = PurchaseIntent::parse(&vaa.payload) else {
+        msg!("VAA does not carry a purchase intent");
+        return Err(ProgramError::InvalidAccountData);
+    };
+    if intent.amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    // Calculate token amounts
-    // In a real implementation, this would interact with external liquidity pools
-    // For this example, we'll just use a simplified estimation
-    let lp_contribution_amount = (amount_in * program_state.lp_contribution_rate) / 10000;
-    let admin_fee_amount = (amount_in * program_state.admin_fee_rate) / 10000;
-    let swap_fee_amount = (amount_in * program_state.swap_fee_rate) / 10000;
-    let referral_amount = (amount_in * program_state.referral_rate) / 10000;
-
-    let net_amount_in = amount_in - lp_contribution_amount - admin_fee_amount - swap_fee_amount - referral_amount;
-    let amount_out = net_amount_in; // Simplified 1:1 conversion
-    
-    // Apply YOS cashback
-    let yos_cashback_amount = (amount_in * program_state.yos_cashback_rate) / 10000;
+    // Claim the VAA so it executes once
+    let (intent_receipt_address, intent_receipt_bump) =
+        find_intent_receipt_address(program_id, vaa.emitter_chain, &vaa.emitter_address, vaa.sequence);
+    assert_pda!(intent_receipt_account, intent_receipt_address);
+    if intent_receipt_account.data_len() > 0 {
+        msg!("Purchase intent {} was already executed", vaa.sequence);
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    let rent = Rent::get()?;
+    let receipt_size = std::mem::size_of::<IntentReceipt>();
+    invoke_signed(
+        &system_instruction::create_account(
+            relayer_account.key,
+            intent_receipt_account.key,
+            rent.minimum_balance(receipt_size),
+            receipt_size as u64,
+            program_id,
+        ),
+        &[
+            relayer_account.clone(),
+            intent_receipt_account.clone(),
+            system_program_account.clone(),
+        ],
+        &[&[
+            seeds::INTENT_RECEIPT,
+            &vaa.emitter_chain.to_le_bytes(),
+            &vaa.emitter_address,
+            &vaa.sequence.to_le_bytes(),
+            &[intent_receipt_bump],
+        ]],
+    )?;
 
-    // Ensure the swap meets the minimum output requirement
-    if amount_out < min_amount_out {
+    // Verify the program authority, escrow and vaults
+    let quote_mint = cross_chain_config.quote_mint;
+    let (program_authority_address, program_authority_bump) = find_program_authority(program_id);
+    assert_pda!(program_authority_account, program_authority_address);
+    assert_token_account!(escrow_account, quote_mint, cross_chain_config_address);
+    assert_token_account!(quote_vault_account, quote_mint, program_authority_address);
+    assert_token_account!(yot_vault_account, program_state.yot_mint, program_authority_address);
+    assert_token_account!(target_token_account, program_state.yot_mint, intent.target);
+
+    // Price the buy against the YOT pool like a swap without a referrer
+    let (pool_address, _) = find_pool_address(program_id, &quote_mint, &program_state.yot_mint);
+    assert_pda!(pool_account, pool_address);
+    assert_owner!(pool_account, program_id);
+    let mut pool = load_account::<PoolState>(pool_account)?;
+    if !pool.is_initialized || pool.reserve_a == 0 || pool.reserve_b == 0 {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    let FeeSplit {
+        lp_contribution: lp_contribution_amount,
+        admin_fee: admin_fee_amount,
+        swap_fee: swap_fee_amount,
+        net_amount_in,
+        ..
+    } = split_amount_in(
+        intent.amount,
+        program_state.lp_contribution_rate,
+        program_state.admin_fee_rate,
+        program_state.swap_fee_rate,
+        0,
+        0,
+    );
+    let (reserve_in, reserve_out) = pool.reserves_for(&quote_mint);
+    if intent.amount > max_amount_in(reserve_in, MAX_PRICE_IMPACT_BPS) {
+        msg!("Purchase intent exceeds the price impact limit");
+        return Err(ProgramError::InvalidArgument);
+    }
+    let amount_out = constant_product_amount_out(reserve_in, reserve_out, net_amount_in).map_err(math_error)?;
+    if amount_out < intent.min_amount_out {
+        msg!("Purchase intent wants at least {} YOT, pool pays {}", intent.min_amount_out, amount_out);
         return Err(ProgramError::InvalidInstructionData);
     }
 
-    // Transfer tokens from user to destination accounts
-    // (simplified for example - real implementation would do the full multi-hub swap)
-    
-    // Transfer tokens from user account to program authority
-    invoke(
+    // The contribution and swap fee stay with the pool; the admin fee stays
+    // in the vault as for any token-input swap
+    let reserve_amount_in = net_amount_in + swap_fee_amount + lp_contribution_amount;
+    pool.contribution_total = pool.contribution_total.saturating_add(lp_contribution_amount);
+    let k_before = pool.k();
+    pool.apply_hop(&quote_mint, reserve_amount_in, amount_out)?;
+    if pool.k() < k_before {
+        msg!("K invariant violated: {} < {}", pool.k(), k_before);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    pool.volume_in = pool.volume_in.saturating_add(intent.amount);
+    pool.volume_out = pool.volume_out.saturating_add(amount_out);
+    pool.fees_collected = pool.fees_collected.saturating_add(admin_fee_amount + swap_fee_amount);
+    pool.serialize(&mut *pool_account.data.borrow_mut())?;
+
+    // Move the bridged tokens into the vault and deliver the YOT
+    invoke_signed(
         &token_instruction::transfer(
             token_program_account.key,
-            user_token_in_account.key,
-            program_authority_account.key,
-            user_account.key,
+            escrow_account.key,
+            quote_vault_account.key,
+            cross_chain_config_account.key,
             &[],
-            amount_in,
+            intent.amount,
         )?,
         &[
-            user_token_in_account.clone(),
-            program_authority_account.clone(),
-            user_account.clone(),
+            escrow_account.clone(),
+            quote_vault_account.clone(),
+            cross_chain_config_account.clone(),
             token_program_account.clone(),
         ],
+        &[&[seeds::CROSS_CHAIN_CONFIG, &[cross_chain_config_bump]]],
     )?;
-
-    // Transfer output tokens to user
-    // (simplified - in real implementation, this would come from the actual swap)
     invoke_signed(
         &token_instruction::transfer(
             token_program_account.key,
-            program_authority_account.key,
-            user_token_out_account.key,
+            yot_vault_account.key,
+            target_token_account.key,
             program_authority_account.key,
             &[],
             amount_out,
         )?,
         &[
+            yot_vault_account.clone(),
+            target_token_account.clone(),
             program_authority_account.clone(),
-            user_token_out_account.clone(),
             token_program_account.clone(),
         ],
-        &[&[b"authority", &[program_authority_bump]]],
+        &[&[seeds::AUTHORITY, &[program_authority_bump]]],
     )?;
 
-    // Send YOS cashback to user
-    invoke_signed(
-        &token_instruction::mint_to(
-            token_program_account.key,
-            &program_state.yos_mint,
-            user_yos_account.key,
-            program_authority_account.key,
-            &[],
-            yos_cashback_amount,
-        )?,
-        &[
-            user_yos_account.clone(),
-            program_authority_account.clone(),
-            token_program_account.clone(),
-        ],
-        &[&[b"authority", &[program_authority_bump]]],
+    let intent_receipt = IntentReceipt {
+        is_initialized: true,
+        sequence: vaa.sequence,
+        target: intent.target,
+        amount_out,
+    };
+    intent_receipt.serialize(&mut *intent_receipt_account.data.borrow_mut())?;
+
+    program_state.stats.record_swap(intent.amount, admin_fee_amount + swap_fee_amount);
+    emit_event(
+        &mut program_state,
+        b"purchase_intent",
+        &PurchaseIntentEvent {
+            payer: intent.payer,
+            target: intent.target,
+            sequence: vaa.sequence,
+            amount_in: intent.amount,
+            amount_out,
+        },
     )?;
+    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
 
-    msg!("Swap processed successfully");
+    msg!("Purchase intent {} delivered {} YOT to {}", vaa.sequence, amount_out, intent.target);
     Ok(())
 }
 
-/// Update program parameters (admin only)
-fn process_update_parameters(
+/// Load the pending emergency withdrawal of `pool_account`
+fn load_emergency_withdrawal(
     program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    lp_contribution_rate: Option<u64>,
-    admin_fee_rate: Option<u64>,
-    yos_cashback_rate: Option<u64>,
-    swap_fee_rate: Option<u64>,
-    referral_rate: Option<u64>,
-) -> ProgramResult {
-    // Get account iterator
-    let account_info_iter = &mut accounts.iter();
-
-    // Extract accounts
-    let admin_account = next_account_info(account_info_iter)?;
-    let program_state_account = next_account_info(account_info_iter)?;
-
-    // Verify state address
-    let (program_state_address, _) = find_program_state_address(program_id);
-    if program_state_address != *program_state_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    // Load program state
-    let mut program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
-    if !program_state.is_initialized {
+    pool_account: &AccountInfo,
+    emergency_withdrawal_account: &AccountInfo,
+) -> Result<EmergencyWithdrawal, ProgramError> {
+    let (emergency_withdrawal_address, _) = find_emergency_withdrawal_address(program_id, pool_account.key);
+    assert_pda!(emergency_withdrawal_account, emergency_withdrawal_address);
+    assert_owner!(emergency_withdrawal_account, program_id);
+    let emergency_withdrawal = load_account::<EmergencyWithdrawal>(emergency_withdrawal_account)?;
+    if !emergency_withdrawal.is_initialized {
+        msg!("No emergency withdrawal is scheduled for pool {}", pool_account.key);
         return Err(ProgramError::UninitializedAccount);
     }
+    Ok(emergency_withdrawal)
+}
 
-    // Verify admin
-    if program_state.admin != *admin_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
-    if !admin_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
-
-    // Update parameters that were provided
-    if let Some(rate) = lp_contribution_rate {
-        program_state.lp_contribution_rate = rate;
-    }
-    if let Some(rate) = admin_fee_rate {
-        program_state.admin_fee_rate = rate;
-    }
-    if let Some(rate) = yos_cashback_rate {
-        program_state.yos_cashback_rate = rate;
-    }
-    if let Some(rate) = swap_fee_rate {
-        program_state.swap_fee_rate = rate;
-    }
-    if let Some(rate) = referral_rate {
-        program_state.referral_rate = rate;
-    }
-
-    // Save updated state
-    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
-
-    msg!("Parameters updated successfully");
-    Ok(())
+/// Close an emergency withdrawal, returning its rent to the admin
+fn close_emergency_withdrawal(emergency_withdrawal_account: &AccountInfo, admin_account: &AccountInfo) {
+    let lamports = emergency_withdrawal_account.lamports();
+    **emergency_withdrawal_account.lamports.borrow_mut() = 0;
+    **admin_account.lamports.borrow_mut() += lamports;
+    emergency_withdrawal_account.data.borrow_mut().fill(0);
 }
 
-/// Set a new admin (admin only)
-fn process_set_admin(
+/// Replace the liquidity contribution targets, creating the PDA on first use
+/// (admin only)
+fn process_set_contribution_targets(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    new_admin: Pubkey,
+    targets: Vec<(Pubkey, u16)>,
 ) -> ProgramResult {
     // Get account iterator
     let account_info_iter = &mut accounts.iter();
@@ -458,77 +10652,108 @@ fn process_set_admin(
     // Extract accounts
     let admin_account = next_account_info(account_info_iter)?;
     let program_state_account = next_account_info(account_info_iter)?;
+    let contribution_targets_account = next_account_info(account_info_iter)?;
+    let system_program_account = next_account_info(account_info_iter)?;
 
     // Verify state address
     let (program_state_address, _) = find_program_state_address(program_id);
-    if program_state_address != *program_state_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
+    assert_pda!(program_state_account, program_state_address);
 
     // Load program state
-    let mut program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
+    let program_state = load_account::<ProgramState>(program_state_account)?;
     if !program_state.is_initialized {
         return Err(ProgramError::UninitializedAccount);
     }
 
     // Verify admin
-    if program_state.admin != *admin_account.key {
-        return Err(ProgramError::InvalidAccountData);
+    authorize(program_id, accounts, &program_state, admin_account, admin_instruction::SET_CONTRIBUTION_TARGETS)?;
+
+    if targets.len() > MAX_CONTRIBUTION_TARGETS {
+        msg!("At most {} contribution targets are supported", MAX_CONTRIBUTION_TARGETS);
+        return Err(ProgramError::InvalidArgument);
     }
-    if !admin_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    if !targets.is_empty() {
+        let total_weight: u64 = targets.iter().map(|(_, weight)| *weight as u64).sum();
+        if total_weight != 10000 || targets.iter().any(|(_, weight)| *weight == 0) {
+            msg!("Contribution weights sum to {} bps, expected 10000 with none zero", total_weight);
+            return Err(ProgramError::InvalidArgument);
+        }
+    }
+    for (index, (pool, _)) in targets.iter().enumerate() {
+        if targets[..index].iter().any(|(other, _)| other == pool) {
+            msg!("Contribution target {} is listed twice", pool);
+            return Err(ProgramError::InvalidArgument);
+        }
     }
 
-    // Update admin
-    program_state.admin = new_admin;
+    // Verify PDA
+    let (contribution_targets_address, contribution_targets_bump) = find_contribution_targets_address(program_id);
+    assert_pda!(contribution_targets_account, contribution_targets_address);
 
-    // Save updated state
-    program_state.serialize(&mut *program_state_account.data.borrow_mut())?;
+    // Create the target list on first use
+    if contribution_targets_account.data_is_empty() {
+        let rent = Rent::get()?;
+        let targets_size = std::mem::size_of::<ContributionTargets>();
+        let lamports = rent.minimum_balance(targets_size);
 
-    msg!("Admin updated successfully");
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_account.key,
+                contribution_targets_account.key,
+                lamports,
+                targets_size as u64,
+                program_id,
+            ),
+            &[
+                admin_account.clone(),
+                contribution_targets_account.clone(),
+                system_program_account.clone(),
+            ],
+            &[&[seeds::CONTRIBUTION_TARGETS, &[contribution_targets_bump]]],
+        )?;
+    }
+
+    let mut contribution_targets = ContributionTargets {
+        is_initialized: true,
+        count: targets.len() as u8,
+        pools: [Pubkey::default(); MAX_CONTRIBUTION_TARGETS],
+        weights_bps: [0; MAX_CONTRIBUTION_TARGETS],
+    };
+    for (index, (pool, weight)) in targets.iter().enumerate() {
+        contribution_targets.pools[index] = *pool;
+        contribution_targets.weights_bps[index] = *weight;
+    }
+    contribution_targets.serialize(&mut *contribution_targets_account.data.borrow_mut())?;
+
+    msg!("Liquidity contribution split between {} pools", targets.len());
     Ok(())
 }
 
-/// Close the program (admin only)
-fn process_close_program(
+/// Fee-exempt swap reserved for allowlisted CPI callers
+fn process_swap_fee_exempt(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
+    amount_in: u64,
+    min_amount_out: u64,
+    simulate: bool,
 ) -> ProgramResult {
-    // Get account iterator
-    let account_info_iter = &mut accounts.iter();
-
-    // Extract accounts
-    let admin_account = next_account_info(account_info_iter)?;
-    let program_state_account = next_account_info(account_info_iter)?;
-    
-    // Verify state address
-    let (program_state_address, _) = find_program_state_address(program_id);
-    if program_state_address != *program_state_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
-
-    // Load program state
-    let program_state = ProgramState::try_from_slice(&program_state_account.data.borrow())?;
-    if !program_state.is_initialized {
-        return Err(ProgramError::UninitializedAccount);
-    }
-
-    // Verify admin
-    if program_state.admin != *admin_account.key {
-        return Err(ProgramError::InvalidAccountData);
-    }
-    if !admin_account.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
-
-    // Transfer lamports from program state account to admin (closing the account)
-    let lamports = program_state_account.lamports();
-    **program_state_account.lamports.borrow_mut() = 0;
-    **admin_account.lamports.borrow_mut() += lamports;
+    // The instructions sysvar and allowlist lead the regular swap accounts
+    let [instructions_sysvar_account, cpi_allowlist_account, swap_accounts @ ..] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
 
-    // Clear the data
-    program_state_account.data.borrow_mut().fill(0);
+    let caller = require_allowlisted_cpi_caller(program_id, instructions_sysvar_account, cpi_allowlist_account)?;
+    msg!("Fee-exempt swap invoked by {}", caller);
 
-    msg!("Program closed successfully");
-    Ok(())
-}
\ No newline at end of file
+    execute_swap(
+        program_id,
+        swap_accounts,
+        amount_in,
+        min_amount_out,
+        SwapOptions {
+            fee_exempt: true,
+            simulate,
+            ..SwapOptions::default()
+        },
+    )
+}
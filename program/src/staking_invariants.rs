@@ -0,0 +1,59 @@
+// Global invariants re-checked after every instruction when the `paranoid`
+// feature is enabled, for devnet deployments and audits.
+//
+// Only accounts passed to the instruction are visible, so each check is a
+// lower bound of the real invariant: a violation here is always a real bug.
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError, program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Account as TokenAccount;
+use tswap_addresses::staking as seeds;
+
+use crate::{ProgramState, StakingAccount};
+
+// Highest sane stake rate: 0.001% per second, over 300x the principal a
+// year. Anything above is a unit mistake like the one behind the
+// "millions of tokens" payouts.
+pub const MAX_STAKE_RATE_PER_SECOND: u64 = 1_000;
+
+// Check rate bounds and that the YOT vault covers every visible stake
+pub fn check(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let (program_state_address, _) = Pubkey::find_program_address(&[seeds::PROGRAM_STATE], program_id);
+    let Some(state) = accounts
+        .iter()
+        .find(|account| *account.key == program_state_address && account.owner == program_id)
+        .and_then(|account| ProgramState::try_from_slice(&account.data.borrow()).ok())
+    else {
+        return Ok(());
+    };
+    if state.stake_rate_per_second > MAX_STAKE_RATE_PER_SECOND {
+        msg!("Paranoid: stake rate {} out of bounds", state.stake_rate_per_second);
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    let staked = accounts
+        .iter()
+        .filter(|account| account.owner == program_id)
+        .filter_map(|account| {
+            let staking = StakingAccount::try_from_slice(&account.data.borrow()).ok()?;
+            let (staking_address, _) =
+                Pubkey::find_program_address(&[seeds::STAKING, staking.owner.as_ref()], program_id);
+            (staking_address == *account.key).then_some(staking.staked_amount)
+        })
+        .fold(0u64, u64::saturating_add);
+
+    let (program_authority, _) = Pubkey::find_program_address(&[seeds::AUTHORITY], program_id);
+    let vault_balance = accounts
+        .iter()
+        .filter(|account| *account.owner == spl_token::id())
+        .filter_map(|account| TokenAccount::unpack(&account.data.borrow()).ok())
+        .find(|vault| vault.owner == program_authority && vault.mint == state.yot_mint)
+        .map(|vault| vault.amount);
+    if let Some(vault_balance) = vault_balance.filter(|balance| *balance < staked) {
+        msg!("Paranoid: YOT vault holds {} but {} is staked", vault_balance, staked);
+        return Err(ProgramError::InsufficientFunds);
+    }
+    Ok(())
+}
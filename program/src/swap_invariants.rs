@@ -0,0 +1,142 @@
+// Global invariants re-checked after every instruction when the `paranoid`
+// feature is enabled, for devnet deployments and audits.
+//
+// Only accounts passed to the instruction are visible, so each check is a
+// lower bound of the real invariant: a violation here is always a real bug.
+use solana_program::{
+    account_info::AccountInfo, entrypoint::ProgramResult, msg, program_error::ProgramError, program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::Account as TokenAccount;
+
+use crate::{find_pool_address, find_program_authority, find_program_state_address, load_account, PoolState, ProgramState};
+
+// A pool's k, reserves and shares before the instruction
+struct PoolSnapshot {
+    key: Pubkey,
+    k: u128,
+    reserve_a: u64,
+    reserve_b: u64,
+    total_shares: u64,
+}
+
+// Pools passed to the instruction
+pub struct Snapshot {
+    pools: Vec<PoolSnapshot>,
+}
+
+// Decode `account` as a pool if it is an initialized pool PDA of this program
+fn as_pool(program_id: &Pubkey, account: &AccountInfo) -> Option<PoolState> {
+    if account.owner != program_id {
+        return None;
+    }
+    let pool = load_account::<PoolState>(account).ok()?;
+    let (pool_address, _) = find_pool_address(program_id, &pool.mint_a, &pool.mint_b);
+    (pool.is_initialized && pool_address == *account.key).then_some(pool)
+}
+
+impl Snapshot {
+    // Record every pool's k, reserves and shares before the instruction runs
+    pub fn take(program_id: &Pubkey, accounts: &[AccountInfo]) -> Self {
+        let pools = accounts
+            .iter()
+            .filter_map(|account| {
+                as_pool(program_id, account).map(|pool| PoolSnapshot {
+                    key: *account.key,
+                    k: pool.k(),
+                    reserve_a: pool.reserve_a,
+                    reserve_b: pool.reserve_b,
+                    total_shares: pool.total_shares,
+                })
+            })
+            .collect();
+        Snapshot { pools }
+    }
+
+    // Check rate bounds, k and vault solvency after a successful instruction
+    pub fn check(&self, program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let (program_state_address, _) = find_program_state_address(program_id);
+        if let Some(state_account) = accounts.iter().find(|account| *account.key == program_state_address) {
+            if let Ok(state) = load_account::<ProgramState>(state_account) {
+                if state.is_initialized {
+                    check_rates(&state)?;
+                }
+            }
+        }
+
+        // k never decreases unless shares are burned, and burning shares
+        // never takes more than their proportion of either reserve
+        let mut liabilities: Vec<(Pubkey, u64)> = Vec::new();
+        for account in accounts {
+            let Some(pool) = as_pool(program_id, account) else {
+                continue;
+            };
+            if let Some(before) = self.pools.iter().find(|before| before.key == *account.key) {
+                if pool.total_shares >= before.total_shares {
+                    if pool.k() < before.k {
+                        msg!("Paranoid: k of pool {} fell from {} to {}", account.key, before.k, pool.k());
+                        return Err(ProgramError::InvalidAccountData);
+                    }
+                } else if [(pool.reserve_a, before.reserve_a), (pool.reserve_b, before.reserve_b)]
+                    .iter()
+                    .any(|(after, reserve_before)| {
+                        (*after as u128) * (before.total_shares as u128)
+                            < (*reserve_before as u128) * (pool.total_shares as u128)
+                    })
+                {
+                    msg!("Paranoid: pool {} paid out more than its burned shares", account.key);
+                    return Err(ProgramError::InvalidAccountData);
+                }
+            }
+            for (mint, reserve, admin_fees) in [
+                (pool.mint_a, pool.reserve_a, pool.admin_fees_a),
+                (pool.mint_b, pool.reserve_b, pool.admin_fees_b),
+            ] {
+                let owed = reserve.saturating_add(admin_fees);
+                match liabilities.iter_mut().find(|(liability_mint, _)| *liability_mint == mint) {
+                    Some((_, total)) => *total = total.saturating_add(owed),
+                    None => liabilities.push((mint, owed)),
+                }
+            }
+        }
+
+        // Authority vaults must cover every visible pool's reserves and
+        // admin fees
+        let (program_authority, _) = find_program_authority(program_id);
+        for (mint, owed) in liabilities {
+            let vault_balance = accounts
+                .iter()
+                .filter(|account| *account.owner == spl_token::id())
+                .filter_map(|account| TokenAccount::unpack(&account.data.borrow()).ok())
+                .find(|vault| vault.owner == program_authority && vault.mint == mint)
+                .map(|vault| vault.amount);
+            if let Some(vault_balance) = vault_balance.filter(|balance| *balance < owed) {
+                msg!("Paranoid: vault for {} holds {} but owes {}", mint, vault_balance, owed);
+                return Err(ProgramError::InsufficientFunds);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Every rate is a fraction of the input, and the deductions taken before the
+// pool must leave something to trade
+fn check_rates(state: &ProgramState) -> ProgramResult {
+    let rates = [
+        state.lp_contribution_rate,
+        state.admin_fee_rate,
+        state.yos_cashback_rate,
+        state.swap_fee_rate,
+        state.referral_rate,
+    ];
+    let deductions = state
+        .lp_contribution_rate
+        .saturating_add(state.admin_fee_rate)
+        .saturating_add(state.swap_fee_rate)
+        .saturating_add(state.referral_rate);
+    if rates.iter().any(|rate| *rate > tswap_math::BPS_DENOMINATOR) || deductions >= tswap_math::BPS_DENOMINATOR {
+        msg!("Paranoid: rates out of bounds {:?}", rates);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(())
+}
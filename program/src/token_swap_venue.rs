@@ -0,0 +1,254 @@
+// spl-token-swap pools the program holds liquidity in or routes through, such
+// as the original SOL-YOT pool on devnet.
+//
+// The program's LP tokens of these pools sit in the treasury's token
+// accounts, and liquidity moves in and out of a pool by CPI with the treasury
+// PDA as the transfer authority. Swaps through a pool are CPIs signed by the
+// user, whose output is measured rather than taken from the pool. Pool accounts are decoded by hand against
+// the SwapV1 layout, since the crate does not depend on spl-token-swap.
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    instruction::{AccountMeta, Instruction},
+    msg,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+// The spl-token-swap deployment the program holds liquidity in
+pub const TOKEN_SWAP_PROGRAM_ID: Pubkey = solana_program::pubkey!("SwaPpA9LAaLfeLi3a68M4DjnLqgtticKg6CnyNwgAC8");
+
+// Size of a SwapV1 pool account, including the leading version byte
+const TOKEN_SWAP_POOL_LEN: usize = 324;
+
+// Version byte of SwapV1, the only layout spl-token-swap has shipped
+const SWAP_V1: u8 = 1;
+
+// Tags of the spl-token-swap instructions the program sends
+const SWAP: u8 = 1;
+const DEPOSIT_ALL_TOKEN_TYPES: u8 = 2;
+const WITHDRAW_ALL_TOKEN_TYPES: u8 = 3;
+
+// An spl-token-swap pool and the accounts its instructions take, in the
+// order they are passed: pool, pool authority, pool mint, token A account,
+// token B account, fee account
+pub struct Venue<'a, 'b> {
+    pub pool: &'a AccountInfo<'b>,
+    pub authority: &'a AccountInfo<'b>,
+    pub pool_mint: &'a AccountInfo<'b>,
+    pub token_a: &'a AccountInfo<'b>,
+    pub token_b: &'a AccountInfo<'b>,
+    pub fee_account: &'a AccountInfo<'b>,
+    // Mints of the token A and token B accounts
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+}
+
+impl<'a, 'b> Venue<'a, 'b> {
+    // Take a pool's accounts from `iter`, failing unless the pool is an
+    // initialized SwapV1 pool of TOKEN_SWAP_PROGRAM_ID over the SPL Token
+    // program and the other accounts are the ones it records
+    pub fn next<I: Iterator<Item = &'a AccountInfo<'b>>>(iter: &mut I) -> Result<Self, ProgramError> {
+        let pool = next_account_info(iter)?;
+        let authority = next_account_info(iter)?;
+        let pool_mint = next_account_info(iter)?;
+        let token_a = next_account_info(iter)?;
+        let token_b = next_account_info(iter)?;
+        let fee_account = next_account_info(iter)?;
+
+        if *pool.owner != TOKEN_SWAP_PROGRAM_ID {
+            msg!("Pool {} is not an spl-token-swap pool", pool.key);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let data = pool.data.borrow();
+        if data.len() != TOKEN_SWAP_POOL_LEN || data[0] != SWAP_V1 || data[1] != 1 {
+            msg!("Pool {} is not an initialized SwapV1 pool", pool.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let pubkey_at = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        if pubkey_at(3) != spl_token::id() {
+            msg!("Pool {} holds its tokens outside the SPL Token program", pool.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let pool_authority = Pubkey::create_program_address(&[pool.key.as_ref(), &[data[2]]], &TOKEN_SWAP_PROGRAM_ID)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        for (account, expected) in [
+            (authority, pool_authority),
+            (token_a, pubkey_at(35)),
+            (token_b, pubkey_at(67)),
+            (pool_mint, pubkey_at(99)),
+            (fee_account, pubkey_at(195)),
+        ] {
+            if *account.key != expected {
+                msg!("Pool {} expects account {}, got {}", pool.key, expected, account.key);
+                return Err(ProgramError::InvalidAccountData);
+            }
+        }
+        let (mint_a, mint_b) = (pubkey_at(131), pubkey_at(163));
+        drop(data);
+
+        Ok(Venue {
+            pool,
+            authority,
+            pool_mint,
+            token_a,
+            token_b,
+            fee_account,
+            mint_a,
+            mint_b,
+        })
+    }
+
+    // Whether the pool trades the same two mints as `other`
+    pub fn same_pair(&self, other: &Venue) -> bool {
+        (self.mint_a, self.mint_b) == (other.mint_a, other.mint_b)
+            || (self.mint_a, self.mint_b) == (other.mint_b, other.mint_a)
+    }
+
+    // Pool tokens a deposit of up to `amount_a` and `amount_b` mints at the
+    // pool's current ratio, rounded down so the pool never asks for more
+    pub fn deposit_pool_tokens(&self, amount_a: u64, amount_b: u64) -> Result<u64, ProgramError> {
+        let supply = Mint::unpack(&self.pool_mint.data.borrow())?.supply as u128;
+        let reserve_a = token_balance(self.token_a)? as u128;
+        let reserve_b = token_balance(self.token_b)? as u128;
+        if supply == 0 || reserve_a == 0 || reserve_b == 0 {
+            msg!("Pool {} holds no liquidity to deposit alongside", self.pool.key);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let pool_tokens = (amount_a as u128 * supply / reserve_a).min(amount_b as u128 * supply / reserve_b);
+        Ok(pool_tokens as u64)
+    }
+
+    // Mint of the pool's other token than `mint`, None when the pool does
+    // not hold `mint`
+    pub fn other_mint(&self, mint: &Pubkey) -> Option<Pubkey> {
+        match *mint {
+            mint if mint == self.mint_a => Some(self.mint_b),
+            mint if mint == self.mint_b => Some(self.mint_a),
+            _ => None,
+        }
+    }
+
+    // Swap `amount_in` of `mint_in` from `source` into `destination`;
+    // `owner` signs
+    pub fn swap(
+        &self,
+        owner: &Pubkey,
+        source: &Pubkey,
+        destination: &Pubkey,
+        mint_in: &Pubkey,
+        amount_in: u64,
+    ) -> Instruction {
+        let (pool_source, pool_destination) = match *mint_in == self.mint_a {
+            true => (self.token_a, self.token_b),
+            false => (self.token_b, self.token_a),
+        };
+        Instruction {
+            program_id: TOKEN_SWAP_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*self.pool.key, false),
+                AccountMeta::new_readonly(*self.authority.key, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(*source, false),
+                AccountMeta::new(*pool_source.key, false),
+                AccountMeta::new(*pool_destination.key, false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new(*self.pool_mint.key, false),
+                AccountMeta::new(*self.fee_account.key, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            // Any output is accepted; callers measure what actually arrived
+            data: instruction_data(SWAP, &[amount_in, 0]),
+        }
+    }
+
+    // Burn `pool_tokens` from `pool_token_account` for both of the pool's
+    // tokens, credited to `destination_a` and `destination_b`; `owner` signs
+    pub fn withdraw_all_token_types(
+        &self,
+        owner: &Pubkey,
+        pool_token_account: &Pubkey,
+        destination_a: &Pubkey,
+        destination_b: &Pubkey,
+        pool_tokens: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: TOKEN_SWAP_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*self.pool.key, false),
+                AccountMeta::new_readonly(*self.authority.key, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(*self.pool_mint.key, false),
+                AccountMeta::new(*pool_token_account, false),
+                AccountMeta::new(*self.token_a.key, false),
+                AccountMeta::new(*self.token_b.key, false),
+                AccountMeta::new(*destination_a, false),
+                AccountMeta::new(*destination_b, false),
+                AccountMeta::new(*self.fee_account.key, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            // Any amount of either token is accepted; callers measure what
+            // actually arrived
+            data: instruction_data(WITHDRAW_ALL_TOKEN_TYPES, &[pool_tokens, 0, 0]),
+        }
+    }
+
+    // Deposit at most `max_amount_a` and `max_amount_b` from `source_a` and
+    // `source_b` for `pool_tokens` minted to `pool_token_account`; `owner`
+    // signs
+    #[allow(clippy::too_many_arguments)]
+    pub fn deposit_all_token_types(
+        &self,
+        owner: &Pubkey,
+        source_a: &Pubkey,
+        source_b: &Pubkey,
+        pool_token_account: &Pubkey,
+        pool_tokens: u64,
+        max_amount_a: u64,
+        max_amount_b: u64,
+    ) -> Instruction {
+        Instruction {
+            program_id: TOKEN_SWAP_PROGRAM_ID,
+            accounts: vec![
+                AccountMeta::new_readonly(*self.pool.key, false),
+                AccountMeta::new_readonly(*self.authority.key, false),
+                AccountMeta::new_readonly(*owner, true),
+                AccountMeta::new(*source_a, false),
+                AccountMeta::new(*source_b, false),
+                AccountMeta::new(*self.token_a.key, false),
+                AccountMeta::new(*self.token_b.key, false),
+                AccountMeta::new(*self.pool_mint.key, false),
+                AccountMeta::new(*pool_token_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+            data: instruction_data(DEPOSIT_ALL_TOKEN_TYPES, &[pool_tokens, max_amount_a, max_amount_b]),
+        }
+    }
+
+    // The pool's accounts for a CPI into it
+    pub fn account_infos(&self) -> [AccountInfo<'b>; 6] {
+        [
+            self.pool.clone(),
+            self.authority.clone(),
+            self.pool_mint.clone(),
+            self.token_a.clone(),
+            self.token_b.clone(),
+            self.fee_account.clone(),
+        ]
+    }
+}
+
+// Balance of an SPL token account
+pub fn token_balance(account: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(TokenAccount::unpack(&account.data.borrow())?.amount)
+}
+
+fn instruction_data(tag: u8, amounts: &[u64]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + 8 * amounts.len());
+    data.push(tag);
+    for amount in amounts {
+        data.extend_from_slice(&amount.to_le_bytes());
+    }
+    data
+}
@@ -0,0 +1,87 @@
+// Account validation helpers shared by the instruction handlers.
+//
+// Each macro returns early from the enclosing handler with the same error the
+// handlers have always used for that check, so refactoring a copy-pasted
+// check onto a macro never changes the error a client sees. Each also takes
+// an optional message, logged before returning, so a check keeps the log
+// line it had.
+
+// Fail with MissingRequiredSignature unless `account` signed the transaction
+macro_rules! assert_signer {
+    ($account:expr) => {
+        if !$account.is_signer {
+            return Err(solana_program::program_error::ProgramError::MissingRequiredSignature);
+        }
+    };
+    ($account:expr, $($message:tt)+) => {
+        if !$account.is_signer {
+            solana_program::msg!($($message)+);
+            return Err(solana_program::program_error::ProgramError::MissingRequiredSignature);
+        }
+    };
+}
+
+// Fail with IncorrectProgramId unless `account` is owned by `owner`
+macro_rules! assert_owner {
+    ($account:expr, $owner:expr) => {
+        if $account.owner != $owner {
+            return Err(solana_program::program_error::ProgramError::IncorrectProgramId);
+        }
+    };
+    ($account:expr, $owner:expr, $($message:tt)+) => {
+        if $account.owner != $owner {
+            solana_program::msg!($($message)+);
+            return Err(solana_program::program_error::ProgramError::IncorrectProgramId);
+        }
+    };
+}
+
+// Fail with InvalidAccountData unless `account` is the expected PDA
+macro_rules! assert_pda {
+    ($account:expr, $expected:expr) => {
+        if $expected != *$account.key {
+            return Err(solana_program::program_error::ProgramError::InvalidAccountData);
+        }
+    };
+    ($account:expr, $expected:expr, $($message:tt)+) => {
+        if $expected != *$account.key {
+            solana_program::msg!($($message)+);
+            return Err(solana_program::program_error::ProgramError::InvalidAccountData);
+        }
+    };
+}
+
+// Unpack `account` as an SPL token account of `mint` held by `owner`,
+// failing with InvalidAccountData otherwise. With a message, an account that
+// does not unpack fails with the token program's error instead, and a wrong
+// mint or owner logs the message.
+macro_rules! assert_token_account {
+    ($account:expr, $mint:expr, $owner:expr) => {{
+        let token_account = <spl_token::state::Account as solana_program::program_pack::Pack>::unpack(
+            &$account.data.borrow(),
+        )
+        .map_err(|_| {
+            solana_program::msg!("Invalid token account - account may not exist");
+            solana_program::program_error::ProgramError::InvalidAccountData
+        })?;
+        if token_account.mint != $mint {
+            solana_program::msg!("Token account has incorrect mint");
+            return Err(solana_program::program_error::ProgramError::InvalidAccountData);
+        }
+        if token_account.owner != $owner {
+            solana_program::msg!("Token account has incorrect owner");
+            return Err(solana_program::program_error::ProgramError::InvalidAccountData);
+        }
+        token_account
+    }};
+    ($account:expr, $mint:expr, $owner:expr, $($message:tt)+) => {{
+        let token_account = <spl_token::state::Account as solana_program::program_pack::Pack>::unpack(
+            &$account.data.borrow(),
+        )?;
+        if token_account.mint != $mint || token_account.owner != $owner {
+            solana_program::msg!($($message)+);
+            return Err(solana_program::program_error::ProgramError::InvalidAccountData);
+        }
+        token_account
+    }};
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{process, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_launch_address, find_liquidity_position_address, find_pool_address, find_program_authority, Launch,
+    LiquidityPosition, PoolState, SwapInstruction,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program};
+
+const LIQUIDITY_TOKENS: u64 = 1_000_000;
+const PROCEEDS: u64 = 2_000_000;
+const RESERVE: u64 = 1_000_000_000;
+
+// Index of the pool among CloseLaunch's accounts
+const POOL_INDEX: usize = 6;
+
+// CloseLaunch's accounts for a sold-out launch raising two quote tokens per
+// liquidity token, whose pair's pool already holds `reserves` (token, quote)
+fn accounts(reserves: (u64, u64)) -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let [payer, creator, mint, quote_mint] = [(); 4].map(|_| Pubkey::new_unique());
+    let (launch_address, _) = find_launch_address(&program_id, &mint);
+    let (program_authority, _) = find_program_authority(&program_id);
+    let (pool_address, _) = find_pool_address(&program_id, &mint, &quote_mint);
+    let launch = Launch {
+        is_initialized: true,
+        creator,
+        mint,
+        quote_mint,
+        supply: 1,
+        liquidity_tokens: LIQUIDITY_TOKENS,
+        start_price: 1,
+        end_price: 1,
+        end_time: 0,
+        sold: 1,
+        proceeds: PROCEEDS,
+    };
+    let mut pool = zeroed::<PoolState>();
+    pool.is_initialized = true;
+    (pool.mint_a, pool.mint_b) = (mint.min(quote_mint), mint.max(quote_mint));
+    (pool.reserve_a, pool.reserve_b) = if pool.mint_a == mint { reserves } else { (reserves.1, reserves.0) };
+    pool.total_shares = RESERVE;
+    let position = LiquidityPosition {
+        is_initialized: true,
+        owner: creator,
+        pool: pool_address,
+        shares: 0,
+    };
+
+    let mut signer = TestAccount::new(payer, system_program::id(), vec![]);
+    signer.is_signer = true;
+    vec![
+        signer,
+        TestAccount::state(launch_address, &launch),
+        TestAccount::new(creator, system_program::id(), vec![]),
+        TestAccount::token(Pubkey::new_unique(), mint, launch_address, LIQUIDITY_TOKENS),
+        TestAccount::token(Pubkey::new_unique(), quote_mint, launch_address, PROCEEDS),
+        TestAccount::token(Pubkey::new_unique(), mint, creator, 0),
+        TestAccount::state(pool_address, &pool),
+        TestAccount::state(find_liquidity_position_address(&program_id, &pool_address, &creator).0, &position),
+        TestAccount::token(Pubkey::new_unique(), mint, program_authority, reserves.0),
+        TestAccount::token(Pubkey::new_unique(), quote_mint, program_authority, reserves.1),
+        TestAccount::new(program_authority, system_program::id(), vec![]),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        TestAccount::new(system_program::id(), system_program::id(), vec![]),
+    ]
+}
+
+fn pool(accounts: &[TestAccount]) -> PoolState {
+    PoolState::deserialize(&mut &accounts[POOL_INDEX].data[..]).unwrap()
+}
+
+#[test]
+fn closing_launches_seed_a_pool_at_their_price() {
+    let mut accounts = accounts((RESERVE, 2 * RESERVE));
+    let (result, meter) = process(&mut accounts, &SwapInstruction::CloseLaunch);
+    result.unwrap();
+    assert_eq!(meter.token_transfers()[..2], [LIQUIDITY_TOKENS, PROCEEDS]);
+    let pool = pool(&accounts);
+    assert_eq!(pool.reserve_a + pool.reserve_b, 3 * RESERVE + LIQUIDITY_TOKENS + PROCEEDS);
+}
+
+#[test]
+fn closing_launches_refuse_a_pool_priced_away_from_them() {
+    // The pool was priced at one quote token per token before the close
+    let mut accounts = accounts((RESERVE, RESERVE));
+    let (result, meter) = process(&mut accounts, &SwapInstruction::CloseLaunch);
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+    assert_eq!(pool(&accounts).reserve_a, RESERVE);
+}
@@ -0,0 +1,453 @@
+// Host harness running swap program instructions against in-memory accounts.
+//
+// There is no SBF runtime here, so syscalls are stubbed: every CPI succeeds
+// without reaching its callee and is recorded, the clock and rent sysvars
+// resolve, and return data is kept. The stubs also meter the compute units
+// the runtime charges for these syscalls, plus a typical cost for each
+// callee. Units spent in the handler's own code, PDA derivations included,
+// are not seen, so metered totals are a floor on the real consumption.
+#![allow(dead_code)]
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use multihub_swap_v3::{
+    find_pool_address, find_program_authority, find_program_state_address, PoolState, ProgramState,
+    SwapInstruction, TOKEN_SWAP_PROGRAM_ID,
+};
+use solana_program::{
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::{ProgramResult, SUCCESS},
+    instruction::Instruction,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+};
+use spl_token::{
+    instruction::TokenInstruction,
+    state::{Account as TokenAccount, Mint},
+};
+use std::{cell::RefCell, collections::HashMap, sync::Once};
+
+// Default costs of the runtime's compute budget
+const SYSCALL_BASE_UNITS: u64 = 100;
+const INVOKE_UNITS: u64 = 1_000;
+const SYSVAR_BASE_UNITS: u64 = 100;
+const CPI_BYTES_PER_UNIT: u64 = 250;
+
+// Typical cost of one SPL Token transfer or mint_to in the callee
+const TOKEN_INSTRUCTION_UNITS: u64 = 4_500;
+// Typical cost of a system program transfer or create_account in the callee
+const SYSTEM_INSTRUCTION_UNITS: u64 = 150;
+
+// Reserves of each side of the fixture pool
+pub const POOL_RESERVE: u64 = 1_000_000_000_000;
+// Input balance of the fixture user
+pub const USER_BALANCE: u64 = 1_000_000_000_000;
+
+// What the stubbed syscalls saw while an instruction ran
+#[derive(Default)]
+pub struct Meter {
+    pub units: u64,
+    pub invoked: Vec<Instruction>,
+    pub events: usize,
+    pub return_data: Option<Vec<u8>>,
+}
+
+impl Meter {
+    // Amounts of the SPL Token transfers made, in order
+    pub fn token_transfers(&self) -> Vec<u64> {
+        self.invoked
+            .iter()
+            .filter(|instruction| instruction.program_id == spl_token::id())
+            .filter_map(|instruction| match TokenInstruction::unpack(&instruction.data) {
+                Ok(TokenInstruction::Transfer { amount }) => Some(amount),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+thread_local! {
+    static METER: RefCell<Meter> = RefCell::default();
+}
+
+fn charge(units: u64) {
+    METER.with(|meter| meter.borrow_mut().units += units);
+}
+
+struct MeteredStubs;
+
+impl SyscallStubs for MeteredStubs {
+    fn sol_log(&self, message: &str) {
+        charge(SYSCALL_BASE_UNITS.max(message.len() as u64));
+    }
+
+    fn sol_log_compute_units(&self) {
+        charge(SYSCALL_BASE_UNITS);
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        let bytes: usize = fields.iter().map(|field| field.len()).sum();
+        charge(SYSCALL_BASE_UNITS + bytes as u64);
+        METER.with(|meter| meter.borrow_mut().events += 1);
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        _account_infos: &[AccountInfo],
+        _signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let callee_units = if instruction.program_id == spl_token::id() {
+            TOKEN_INSTRUCTION_UNITS
+        } else if instruction.program_id == system_program::id() {
+            SYSTEM_INSTRUCTION_UNITS
+        } else {
+            0
+        };
+        charge(INVOKE_UNITS + instruction.data.len() as u64 / CPI_BYTES_PER_UNIT + callee_units);
+        METER.with(|meter| meter.borrow_mut().invoked.push(instruction.clone()));
+        Ok(())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        charge(SYSVAR_BASE_UNITS + std::mem::size_of::<Clock>() as u64);
+        let clock = Clock {
+            slot: 250_000_000,
+            unix_timestamp: 1_700_000_000,
+            ..Clock::default()
+        };
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Clock, clock) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        charge(SYSVAR_BASE_UNITS + std::mem::size_of::<Rent>() as u64);
+        unsafe { std::ptr::write_unaligned(var_addr as *mut Rent, Rent::default()) };
+        SUCCESS
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        charge(SYSCALL_BASE_UNITS + data.len() as u64 / CPI_BYTES_PER_UNIT);
+        METER.with(|meter| meter.borrow_mut().return_data = Some(data.to_vec()));
+    }
+}
+
+// Owned backing storage for the AccountInfos handed to the program
+#[derive(Clone)]
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub is_signer: bool,
+}
+
+impl TestAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        TestAccount {
+            key,
+            owner,
+            lamports: 1_000_000_000,
+            data,
+            is_signer: false,
+        }
+    }
+
+    pub fn token(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> Self {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint,
+            owner,
+            amount,
+            delegate: COption::None,
+            state: spl_token::state::AccountState::Initialized,
+            is_native: COption::None,
+            delegated_amount: 0,
+            close_authority: COption::None,
+        }
+        .pack_into_slice(&mut data);
+        TestAccount::new(key, spl_token::id(), data)
+    }
+
+    // Program-owned account holding `state`, sized like the program sizes it
+    pub fn state<T: BorshSerialize>(key: Pubkey, state: &T) -> Self {
+        let mut data = vec![0; std::mem::size_of::<T>()];
+        state.serialize(&mut &mut data[..]).unwrap();
+        TestAccount::new(key, multihub_swap_v3::id(), data)
+    }
+
+    // The program id, standing in for a skipped optional account
+    pub fn placeholder() -> Self {
+        TestAccount::new(multihub_swap_v3::id(), system_program::id(), vec![])
+    }
+}
+
+// Accounts of an initialized spl-token-swap pool of `mint_a` and `mint_b`
+// holding `reserve` of each, in the order the swap program takes them:
+// pool, pool authority, pool mint, token A, token B, fee account
+pub fn token_swap_venue(mint_a: Pubkey, mint_b: Pubkey, reserve: u64) -> Vec<TestAccount> {
+    let pool = Pubkey::new_unique();
+    let (authority, bump) = Pubkey::find_program_address(&[pool.as_ref()], &TOKEN_SWAP_PROGRAM_ID);
+    let [pool_mint, token_a, token_b, fee_account] = [(); 4].map(|_| Pubkey::new_unique());
+
+    // SwapV1: version, is_initialized, bump, then the keys from offset 3
+    let mut data = vec![0; 324];
+    data[..3].copy_from_slice(&[1, 1, bump]);
+    for (offset, key) in [
+        (3, spl_token::id()),
+        (35, token_a),
+        (67, token_b),
+        (99, pool_mint),
+        (131, mint_a),
+        (163, mint_b),
+        (195, fee_account),
+    ] {
+        data[offset..offset + 32].copy_from_slice(key.as_ref());
+    }
+    let mut mint_data = vec![0; Mint::LEN];
+    Mint {
+        mint_authority: COption::Some(authority),
+        supply: reserve,
+        decimals: 9,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    }
+    .pack_into_slice(&mut mint_data);
+
+    vec![
+        TestAccount::new(pool, TOKEN_SWAP_PROGRAM_ID, data),
+        TestAccount::new(authority, system_program::id(), vec![]),
+        TestAccount::new(pool_mint, spl_token::id(), mint_data),
+        TestAccount::token(token_a, mint_a, authority, reserve),
+        TestAccount::token(token_b, mint_b, authority, reserve),
+        TestAccount::token(fee_account, pool_mint, Pubkey::new_unique(), 0),
+    ]
+}
+
+// A state as the program sees it right after allocation
+pub fn zeroed<T: BorshDeserialize>() -> T {
+    let data = vec![0u8; std::mem::size_of::<T>()];
+    T::deserialize(&mut &data[..]).unwrap()
+}
+
+// Accounts of a swap of `mint_in` for `mint_out` through their pool, in
+// Swap's layout
+#[derive(Clone)]
+pub struct SwapFixture {
+    pub user: Pubkey,
+    pub mint_in: Pubkey,
+    pub mint_out: Pubkey,
+    pub yos_mint: Pubkey,
+    pub accounts: Vec<TestAccount>,
+}
+
+// Index of the YOS account in Swap's layout, which SwapTokenLite omits
+const YOS_ACCOUNT_INDEX: usize = 5;
+// Index of the pair's pool in Swap's layout, followed by the authority's
+// vaults of the input and output mints
+const POOL_ACCOUNT_INDEX: usize = 8;
+pub const VAULT_IN_ACCOUNT_INDEX: usize = 9;
+pub const VAULT_OUT_ACCOUNT_INDEX: usize = 10;
+
+impl SwapFixture {
+    pub fn new() -> Self {
+        let program_id = multihub_swap_v3::id();
+        let [user, mint_in, mint_out, yos_mint] = [(); 4].map(|_| Pubkey::new_unique());
+
+        let mut program_state = zeroed::<ProgramState>();
+        program_state.is_initialized = true;
+        program_state.yos_mint = yos_mint;
+        program_state.lp_contribution_rate = 2000;
+        program_state.admin_fee_rate = 10;
+        program_state.yos_cashback_rate = 300;
+        program_state.swap_fee_rate = 30;
+        program_state.referral_rate = 50;
+        program_state.opt_out_swap_fee_rate = 50;
+
+        let (pool_address, _) = find_pool_address(&program_id, &mint_in, &mint_out);
+        let mut pool = zeroed::<PoolState>();
+        pool.is_initialized = true;
+        (pool.mint_a, pool.mint_b) = if mint_in < mint_out { (mint_in, mint_out) } else { (mint_out, mint_in) };
+        pool.reserve_a = POOL_RESERVE;
+        pool.reserve_b = POOL_RESERVE;
+
+        let (program_authority, _) = find_program_authority(&program_id);
+        let mut user_account = TestAccount::new(user, system_program::id(), vec![]);
+        user_account.is_signer = true;
+        let accounts = vec![
+            user_account,
+            TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+            TestAccount::new(program_authority, system_program::id(), vec![]),
+            TestAccount::token(Pubkey::new_unique(), mint_in, user, USER_BALANCE),
+            TestAccount::token(Pubkey::new_unique(), mint_out, user, 0),
+            TestAccount::token(Pubkey::new_unique(), yos_mint, user, 0),
+            TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+            TestAccount::placeholder(),
+            TestAccount::state(pool_address, &pool),
+            TestAccount::token(Pubkey::new_unique(), mint_in, program_authority, POOL_RESERVE),
+            TestAccount::token(Pubkey::new_unique(), mint_out, program_authority, POOL_RESERVE),
+        ];
+        SwapFixture {
+            user,
+            mint_in,
+            mint_out,
+            yos_mint,
+            accounts,
+        }
+    }
+
+    // The fixture's pair before its pool is created
+    pub fn without_pool() -> Self {
+        let mut fixture = SwapFixture::new();
+        let pool_account = &mut fixture.accounts[POOL_ACCOUNT_INDEX];
+        pool_account.owner = system_program::id();
+        pool_account.data.clear();
+        fixture
+    }
+
+    // The fixture's pair with a created pool nobody has funded
+    pub fn with_empty_pool() -> Self {
+        let mut fixture = SwapFixture::new();
+        let mut pool = fixture.pool();
+        (pool.reserve_a, pool.reserve_b) = (0, 0);
+        pool.serialize(&mut &mut fixture.accounts[POOL_ACCOUNT_INDEX].data[..]).unwrap();
+        fixture
+    }
+
+    // The fixture's swap passing the placeholder in the pool's slot
+    pub fn skipping_pool() -> Self {
+        let mut fixture = SwapFixture::new();
+        fixture.accounts[POOL_ACCOUNT_INDEX] = TestAccount::placeholder();
+        fixture
+    }
+
+    // The same swap in SwapTokenLite's layout
+    pub fn lite() -> Self {
+        let mut fixture = SwapFixture::new();
+        fixture.accounts.remove(YOS_ACCOUNT_INDEX);
+        fixture
+    }
+
+    pub fn program_state(&self) -> ProgramState {
+        ProgramState::deserialize(&mut &self.accounts[1].data[..]).unwrap()
+    }
+
+    pub fn pool(&self) -> PoolState {
+        let (pool_address, _) = find_pool_address(&multihub_swap_v3::id(), &self.mint_in, &self.mint_out);
+        let pool_account = self.accounts.iter().find(|account| account.key == pool_address).unwrap();
+        PoolState::deserialize(&mut &pool_account.data[..]).unwrap()
+    }
+
+    // Run `instruction` against the fixture's accounts, returning its result
+    // and what the syscalls saw
+    pub fn process(&mut self, instruction: &SwapInstruction) -> (ProgramResult, Meter) {
+        process(&mut self.accounts, instruction)
+    }
+}
+
+pub fn process(accounts: &mut [TestAccount], instruction: &SwapInstruction) -> (ProgramResult, Meter) {
+    process_data(accounts, &instruction.try_to_vec().unwrap())
+}
+
+fn process_data(accounts: &mut [TestAccount], data: &[u8]) -> (ProgramResult, Meter) {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(MeteredStubs));
+    });
+    METER.with(|meter| meter.take());
+
+    let account_infos: Vec<AccountInfo> = accounts
+        .iter_mut()
+        .map(|account| {
+            AccountInfo::new(
+                &account.key,
+                account.is_signer,
+                true,
+                &mut account.lamports,
+                &mut account.data,
+                &account.owner,
+                false,
+                0,
+            )
+        })
+        .collect();
+    let result = multihub_swap_v3::process_instruction(&multihub_swap_v3::id(), &account_infos, data);
+    (result, METER.with(|meter| meter.take()))
+}
+
+// Accounts by address, against which whole instructions run in order like
+// a ledger would run a transaction's. Addresses an instruction names that
+// were never added read as empty system accounts.
+#[derive(Default)]
+pub struct Bank {
+    pub accounts: HashMap<Pubkey, TestAccount>,
+}
+
+impl Bank {
+    pub fn add(&mut self, account: TestAccount) {
+        self.accounts.insert(account.key, account);
+    }
+
+    pub fn add_token(&mut self, key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) {
+        self.add(TestAccount::token(key, mint, owner, amount));
+    }
+
+    pub fn token_balance(&self, key: &Pubkey) -> u64 {
+        TokenAccount::unpack(&self.accounts[key].data).unwrap().amount
+    }
+
+    // Run a swap program instruction, then settle the SPL Token transfers
+    // it made between token accounts of the bank, standing in for the token
+    // program. Instructions to other programs are skipped.
+    pub fn process(&mut self, instruction: &Instruction) -> (ProgramResult, Meter) {
+        if instruction.program_id != multihub_swap_v3::id() {
+            return (Ok(()), Meter::default());
+        }
+        let mut accounts: Vec<TestAccount> = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let mut account = self.accounts.get(&meta.pubkey).cloned().unwrap_or_else(|| {
+                    TestAccount::new(meta.pubkey, system_program::id(), vec![])
+                });
+                account.is_signer = meta.is_signer;
+                account
+            })
+            .collect();
+        let (result, meter) = process_data(&mut accounts, &instruction.data);
+        if result.is_err() {
+            return (result, meter);
+        }
+        for (meta, account) in instruction.accounts.iter().zip(accounts) {
+            if meta.is_writable && self.accounts.contains_key(&meta.pubkey) {
+                self.accounts.insert(meta.pubkey, account);
+            }
+        }
+        for invoked in &meter.invoked {
+            if let Ok(TokenInstruction::Transfer { amount }) = TokenInstruction::unpack(&invoked.data) {
+                self.move_tokens(&invoked.accounts[0].pubkey, -(amount as i128));
+                self.move_tokens(&invoked.accounts[1].pubkey, amount as i128);
+            }
+        }
+        (result, meter)
+    }
+
+    // Adjust a token account's balance. Both ends of every transfer must be
+    // token accounts of the bank, as the token program would require, and
+    // the source must hold the amount.
+    fn move_tokens(&mut self, key: &Pubkey, delta: i128) {
+        let account = self
+            .accounts
+            .get_mut(key)
+            .filter(|account| account.owner == spl_token::id())
+            .unwrap_or_else(|| panic!("transfer end {key} is not a token account of the bank"));
+        let mut token_account = TokenAccount::unpack(&account.data).unwrap();
+        token_account.amount = u64::try_from(token_account.amount as i128 + delta)
+            .unwrap_or_else(|_| panic!("token account {key} cannot cover the transfer"));
+        token_account.pack_into_slice(&mut account.data);
+    }
+}
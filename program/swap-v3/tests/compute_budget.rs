@@ -0,0 +1,68 @@
+// The harness meters only the syscalls and CPIs a swap makes, so these
+// check that share of the cost against the budgets; the handler's own code,
+// PDA derivations and Borsh included, is not measured here.
+mod common;
+
+use common::SwapFixture;
+use multihub_swap_v3::{SwapInstruction, SWAP_COMPUTE_UNITS, SWAP_LITE_COMPUTE_UNITS};
+
+const AMOUNT_IN: u64 = 1_000_000;
+
+#[test]
+fn swap_syscalls_fit_its_compute_budget() {
+    let mut fixture = SwapFixture::new();
+    let (result, meter) = fixture.process(&SwapInstruction::Swap {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    });
+    result.unwrap();
+    assert!(
+        meter.units <= SWAP_COMPUTE_UNITS as u64,
+        "Swap syscalls metered {} units over its {} budget",
+        meter.units,
+        SWAP_COMPUTE_UNITS
+    );
+}
+
+#[test]
+fn lite_swap_syscalls_fit_its_compute_budget() {
+    let mut fixture = SwapFixture::lite();
+    let (result, meter) = fixture.process(&SwapInstruction::SwapTokenLite {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    });
+    result.unwrap();
+    assert!(
+        meter.units <= SWAP_LITE_COMPUTE_UNITS as u64,
+        "SwapTokenLite syscalls metered {} units over its {} budget",
+        meter.units,
+        SWAP_LITE_COMPUTE_UNITS
+    );
+}
+
+#[test]
+fn lite_swap_skips_optional_features_but_prices_against_the_pool() {
+    let mut full = SwapFixture::new();
+    let (result, full_meter) = full.process(&SwapInstruction::Swap {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    });
+    result.unwrap();
+
+    let mut lite = SwapFixture::lite();
+    let (result, lite_meter) = lite.process(&SwapInstruction::SwapTokenLite {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    });
+    result.unwrap();
+
+    assert!(lite_meter.units < full_meter.units);
+    assert_eq!(lite_meter.events, 0);
+    assert!(full_meter.events > 0);
+
+    // The lite swap paid out what the pool's output reserve gave up
+    let pool = lite.pool();
+    let (reserve_in, reserve_out) = pool.reserves_for(&lite.mint_in);
+    assert!(reserve_in > common::POOL_RESERVE);
+    assert_eq!(lite_meter.token_transfers().last(), Some(&(common::POOL_RESERVE - reserve_out)));
+}
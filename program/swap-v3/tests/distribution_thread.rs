@@ -0,0 +1,82 @@
+mod common;
+
+use borsh::BorshSerialize;
+use common::{process, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_contribution_ledger_address, find_distribution_thread_address, find_program_authority,
+    find_program_state_address, ContributionLedger, ProgramState, SwapInstruction, CLOCKWORK_THREAD_PROGRAM_ID,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program};
+
+// Index of the thread among UpdateDistributionThread's accounts
+const THREAD_INDEX: usize = 3;
+
+// UpdateDistributionThread's accounts for the thread registered by `admin`,
+// pointed at the ledger of a new owner
+fn accounts(registered: bool) -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let [admin, yos_mint, owner] = [(); 3].map(|_| Pubkey::new_unique());
+    let (thread, _) = find_distribution_thread_address(&admin, b"weekly");
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    program_state.admin = admin;
+    program_state.yos_mint = yos_mint;
+    if registered {
+        program_state.distribution_thread = thread;
+    }
+    let mut contribution_ledger = zeroed::<ContributionLedger>();
+    contribution_ledger.is_initialized = true;
+    contribution_ledger.owner = owner;
+
+    let mut signer = TestAccount::new(admin, system_program::id(), vec![]);
+    signer.is_signer = true;
+    vec![
+        signer,
+        TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+        TestAccount::new(find_program_authority(&program_id).0, system_program::id(), vec![]),
+        TestAccount::new(thread, CLOCKWORK_THREAD_PROGRAM_ID, vec![]),
+        TestAccount::new(CLOCKWORK_THREAD_PROGRAM_ID, system_program::id(), vec![]),
+        TestAccount::new(system_program::id(), system_program::id(), vec![]),
+        TestAccount::new(yos_mint, spl_token::id(), vec![]),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        TestAccount::state(find_contribution_ledger_address(&program_id, &owner).0, &contribution_ledger),
+        TestAccount::token(Pubkey::new_unique(), yos_mint, owner, 0),
+    ]
+}
+
+#[test]
+fn distribution_threads_take_ledgers_created_after_registration() {
+    let mut accounts = accounts(true);
+    let (result, meter) = process(&mut accounts, &SwapInstruction::UpdateDistributionThread);
+    result.unwrap();
+
+    let [update] = &meter.invoked[..] else { panic!("expected one thread update") };
+    assert_eq!(update.program_id, CLOCKWORK_THREAD_PROGRAM_ID);
+    assert_eq!(update.accounts[0].pubkey, accounts[0].key);
+    assert!(update.accounts[0].is_signer);
+    assert_eq!(update.accounts[2].pubkey, accounts[THREAD_INDEX].key);
+    // The distribution the thread replays now names the new ledger; the
+    // settings around it are left unchanged
+    let distribution = SwapInstruction::DistributeContributionRewards.try_to_vec().unwrap();
+    let ledger = accounts[8].key.to_bytes();
+    assert!(update.data.windows(32).any(|window| window == ledger));
+    assert!(update.data.ends_with(&[&distribution[..], &[0, 0, 0]].concat()));
+}
+
+#[test]
+fn distribution_thread_updates_need_a_registered_thread() {
+    let mut accounts = accounts(false);
+    let (result, meter) = process(&mut accounts, &SwapInstruction::UpdateDistributionThread);
+    assert_eq!(result, Err(ProgramError::UninitializedAccount));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn distribution_thread_updates_refuse_other_threads() {
+    let mut accounts = accounts(true);
+    let admin = accounts[0].key;
+    accounts[THREAD_INDEX].key = find_distribution_thread_address(&admin, b"other").0;
+    let (result, meter) = process(&mut accounts, &SwapInstruction::UpdateDistributionThread);
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{process, token_swap_venue, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_emergency_withdrawal_address, find_program_state_address, find_treasury_address, EmergencyWithdrawal,
+    ProgramState, SwapInstruction, TOKEN_SWAP_PROGRAM_ID,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+const POOL_TOKENS: u64 = 1_000_000;
+// The stubbed clock's time
+const NOW: i64 = 1_700_000_000;
+
+// Indexes of the emergency withdrawal PDA and the treasury's LP account
+// among ExecuteEmergencyWithdrawal's accounts
+const EMERGENCY_WITHDRAWAL_INDEX: usize = 2;
+const TREASURY_LP_INDEX: usize = 10;
+
+fn program_state(admin: Pubkey) -> TestAccount {
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    program_state.admin = admin;
+    TestAccount::state(find_program_state_address(&multihub_swap_v3::id()).0, &program_state)
+}
+
+fn signer(key: Pubkey) -> TestAccount {
+    let mut signer = TestAccount::new(key, system_program::id(), vec![]);
+    signer.is_signer = true;
+    signer
+}
+
+// ExecuteEmergencyWithdrawal's accounts for a withdrawal executable at
+// `executable_at`, with the treasury holding POOL_TOKENS of the pool
+fn execute_accounts(executable_at: i64) -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let admin = Pubkey::new_unique();
+    let (treasury, _) = find_treasury_address(&program_id);
+    let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let venue = token_swap_venue(mint_a, mint_b, RESERVE);
+    let (pool, pool_mint) = (venue[0].key, venue[2].key);
+    let emergency_withdrawal = EmergencyWithdrawal {
+        is_initialized: true,
+        pool,
+        executable_at,
+    };
+
+    let mut accounts = vec![
+        signer(admin),
+        program_state(admin),
+        TestAccount::state(find_emergency_withdrawal_address(&program_id, &pool).0, &emergency_withdrawal),
+    ];
+    accounts.extend(venue);
+    accounts.extend([
+        TestAccount::new(treasury, system_program::id(), vec![]),
+        TestAccount::token(Pubkey::new_unique(), pool_mint, treasury, POOL_TOKENS),
+        TestAccount::token(Pubkey::new_unique(), mint_a, treasury, 0),
+        TestAccount::token(Pubkey::new_unique(), mint_b, treasury, 0),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        TestAccount::new(TOKEN_SWAP_PROGRAM_ID, system_program::id(), vec![]),
+    ]);
+    accounts
+}
+
+#[test]
+fn emergency_withdrawal_needs_a_token_swap_pool() {
+    let program_id = multihub_swap_v3::id();
+    let admin = Pubkey::new_unique();
+    let pool = Pubkey::new_unique();
+    let mut accounts = vec![
+        signer(admin),
+        program_state(admin),
+        TestAccount::new(pool, program_id, vec![0; 324]),
+        TestAccount::new(find_emergency_withdrawal_address(&program_id, &pool).0, system_program::id(), vec![]),
+        TestAccount::new(system_program::id(), system_program::id(), vec![]),
+    ];
+    let (result, _) = process(&mut accounts, &SwapInstruction::ScheduleEmergencyWithdrawal);
+    assert_eq!(result, Err(ProgramError::IncorrectProgramId));
+}
+
+#[test]
+fn emergency_withdrawal_unwinds_the_whole_treasury_position() {
+    let mut accounts = execute_accounts(NOW);
+    let (result, meter) = process(&mut accounts, &SwapInstruction::ExecuteEmergencyWithdrawal);
+    result.unwrap();
+
+    let [withdraw] = &meter.invoked[..] else { panic!("expected one withdrawal") };
+    assert_eq!(withdraw.program_id, TOKEN_SWAP_PROGRAM_ID);
+    assert_eq!(withdraw.data[0], 3);
+    assert_eq!(withdraw.data[1..9], POOL_TOKENS.to_le_bytes());
+    assert_eq!(withdraw.accounts[2].pubkey, find_treasury_address(&multihub_swap_v3::id()).0);
+    assert!(withdraw.accounts[2].is_signer);
+    assert_eq!(withdraw.accounts[4].pubkey, accounts[TREASURY_LP_INDEX].key);
+    assert_eq!(meter.events, 1);
+    // The schedule is closed
+    assert_eq!(accounts[EMERGENCY_WITHDRAWAL_INDEX].lamports, 0);
+}
+
+#[test]
+fn emergency_withdrawal_waits_for_its_timelock() {
+    let mut accounts = execute_accounts(NOW + 1);
+    let (result, meter) = process(&mut accounts, &SwapInstruction::ExecuteEmergencyWithdrawal);
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn emergency_withdrawal_needs_a_treasury_position() {
+    let mut accounts = execute_accounts(NOW);
+    let (pool_mint, treasury) = (accounts[5].key, accounts[TREASURY_LP_INDEX - 1].key);
+    accounts[TREASURY_LP_INDEX] = TestAccount::token(Pubkey::new_unique(), pool_mint, treasury, 0);
+    let (result, _) = process(&mut accounts, &SwapInstruction::ExecuteEmergencyWithdrawal);
+    assert_eq!(result, Err(ProgramError::InsufficientFunds));
+}
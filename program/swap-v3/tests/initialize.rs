@@ -0,0 +1,83 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{process, TestAccount};
+use multihub_swap_v3::{
+    find_program_authority, find_program_state_address, find_token_metadata_address, ProgramState, SwapInstruction,
+    TOKEN_METADATA_PROGRAM_ID,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program, sysvar};
+
+// Metaplex metadata layout: key (1), update authority (32), mint (32), ...
+fn metadata(mint: Pubkey) -> TestAccount {
+    let mut data = vec![0; 200];
+    data[33..65].copy_from_slice(mint.as_ref());
+    TestAccount::new(find_token_metadata_address(&mint).0, TOKEN_METADATA_PROGRAM_ID, data)
+}
+
+fn initialize(yot_mint: Pubkey, yos_mint: Pubkey) -> SwapInstruction {
+    SwapInstruction::Initialize {
+        admin: Pubkey::new_unique(),
+        yot_mint,
+        yos_mint,
+        lp_contribution_rate: 2000,
+        admin_fee_rate: 10,
+        yos_cashback_rate: 300,
+        swap_fee_rate: 30,
+        referral_rate: 50,
+    }
+}
+
+// Payer, an allocated program state, authority, system program and rent
+// sysvar, ahead of the metadata accounts
+fn fixed_accounts() -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let mut payer = TestAccount::new(Pubkey::new_unique(), system_program::id(), vec![]);
+    payer.is_signer = true;
+    let program_state = vec![0; std::mem::size_of::<ProgramState>()];
+    vec![
+        payer,
+        TestAccount::new(find_program_state_address(&program_id).0, program_id, program_state),
+        TestAccount::new(find_program_authority(&program_id).0, system_program::id(), vec![]),
+        TestAccount::new(system_program::id(), system_program::id(), vec![]),
+        TestAccount::new(sysvar::rent::id(), sysvar::id(), vec![]),
+    ]
+}
+
+#[test]
+fn initialize_records_the_mints_metadata() {
+    let (yot_mint, yos_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = fixed_accounts();
+    accounts.extend([metadata(yot_mint), metadata(yos_mint)]);
+    process(&mut accounts, &initialize(yot_mint, yos_mint)).0.unwrap();
+
+    let state = ProgramState::deserialize(&mut &accounts[1].data[..]).unwrap();
+    assert_eq!(state.yot_metadata, find_token_metadata_address(&yot_mint).0);
+    assert_eq!(state.yos_metadata, find_token_metadata_address(&yos_mint).0);
+}
+
+#[test]
+fn initialize_requires_the_metadata_accounts() {
+    let (yot_mint, yos_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = fixed_accounts();
+    accounts.push(metadata(yot_mint));
+    assert_eq!(
+        process(&mut accounts, &initialize(yot_mint, yos_mint)).0,
+        Err(ProgramError::NotEnoughAccountKeys)
+    );
+    assert!(!ProgramState::deserialize(&mut &accounts[1].data[..]).unwrap().is_initialized);
+}
+
+#[test]
+fn initialize_refuses_metadata_that_was_never_created() {
+    let (yot_mint, yos_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut accounts = fixed_accounts();
+    let mut uncreated = metadata(yos_mint);
+    uncreated.owner = system_program::id();
+    uncreated.data.clear();
+    accounts.extend([metadata(yot_mint), uncreated]);
+    assert_eq!(
+        process(&mut accounts, &initialize(yot_mint, yos_mint)).0,
+        Err(ProgramError::IncorrectProgramId)
+    );
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{process, token_swap_venue, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_program_state_address, find_treasury_address, ProgramState, SwapInstruction, TOKEN_SWAP_PROGRAM_ID,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program};
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT: u64 = 1_000_000;
+
+// Indexes of the two pools and the treasury's LP account of the first
+// among MigrateLiquidity's accounts
+const FROM_POOL_INDEX: usize = 3;
+const FROM_LP_INDEX: usize = 9;
+const TO_POOL_INDEX: usize = 10;
+
+// MigrateLiquidity's accounts moving the treasury's LP between two pools of
+// the same pair, the second listing its mints the other way round
+fn accounts() -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let admin = Pubkey::new_unique();
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    program_state.admin = admin;
+    let (treasury, _) = find_treasury_address(&program_id);
+    let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut signer = TestAccount::new(admin, system_program::id(), vec![]);
+    signer.is_signer = true;
+    let mut accounts = vec![
+        signer,
+        TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+        TestAccount::new(treasury, system_program::id(), vec![]),
+    ];
+    for (mint_x, mint_y) in [(mint_a, mint_b), (mint_b, mint_a)] {
+        let venue = token_swap_venue(mint_x, mint_y, RESERVE);
+        let pool_mint = venue[2].key;
+        accounts.extend(venue);
+        accounts.push(TestAccount::token(Pubkey::new_unique(), pool_mint, treasury, AMOUNT));
+    }
+    accounts.extend([
+        TestAccount::token(Pubkey::new_unique(), mint_a, treasury, 0),
+        TestAccount::token(Pubkey::new_unique(), mint_b, treasury, 0),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        TestAccount::new(TOKEN_SWAP_PROGRAM_ID, system_program::id(), vec![]),
+    ]);
+    accounts
+}
+
+fn migrate(accounts: &[TestAccount]) -> SwapInstruction {
+    SwapInstruction::MigrateLiquidity {
+        from_pool: accounts[FROM_POOL_INDEX].key,
+        to_pool: accounts[TO_POOL_INDEX].key,
+        amount: AMOUNT,
+    }
+}
+
+#[test]
+fn migration_withdraws_the_treasury_lp_before_depositing() {
+    let mut accounts = accounts();
+    let instruction = migrate(&accounts);
+    let (result, meter) = process(&mut accounts, &instruction);
+
+    // The withdrawal burns the treasury's LP tokens, signed by the treasury
+    let withdraw = &meter.invoked[0];
+    assert_eq!(withdraw.program_id, TOKEN_SWAP_PROGRAM_ID);
+    assert_eq!(withdraw.data[0], 3);
+    assert_eq!(withdraw.data[1..9], AMOUNT.to_le_bytes());
+    assert_eq!(withdraw.accounts[2].pubkey, find_treasury_address(&multihub_swap_v3::id()).0);
+    assert!(withdraw.accounts[2].is_signer);
+    assert_eq!(withdraw.accounts[4].pubkey, accounts[FROM_LP_INDEX].key);
+    // The stubbed pool pays nothing out, leaving nothing to deposit
+    assert_eq!(result, Err(ProgramError::InsufficientFunds));
+    assert_eq!(meter.invoked.len(), 1);
+}
+
+#[test]
+fn migration_needs_pools_of_the_same_pair() {
+    let mut accounts = accounts();
+    let treasury = accounts[2].key;
+    let venue = token_swap_venue(Pubkey::new_unique(), Pubkey::new_unique(), RESERVE);
+    let pool_mint = venue[2].key;
+    accounts.splice(TO_POOL_INDEX..TO_POOL_INDEX + 6, venue);
+    accounts[TO_POOL_INDEX + 6] = TestAccount::token(Pubkey::new_unique(), pool_mint, treasury, 0);
+    let instruction = migrate(&accounts);
+    assert_eq!(process(&mut accounts, &instruction).0, Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn migration_refuses_pools_outside_token_swap() {
+    let mut accounts = accounts();
+    accounts[FROM_POOL_INDEX].owner = multihub_swap_v3::id();
+    let instruction = migrate(&accounts);
+    assert_eq!(process(&mut accounts, &instruction).0, Err(ProgramError::IncorrectProgramId));
+}
+
+#[test]
+fn migration_refuses_a_pool_account_the_pool_does_not_record() {
+    let mut accounts = accounts();
+    accounts[FROM_POOL_INDEX + 3].key = Pubkey::new_unique();
+    let instruction = migrate(&accounts);
+    assert_eq!(process(&mut accounts, &instruction).0, Err(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn migration_moves_only_lp_the_treasury_holds() {
+    let mut accounts = accounts();
+    let pool_mint = accounts[FROM_POOL_INDEX + 2].key;
+    accounts[FROM_LP_INDEX] = TestAccount::token(Pubkey::new_unique(), pool_mint, Pubkey::new_unique(), AMOUNT);
+    let instruction = migrate(&accounts);
+    let (result, meter) = process(&mut accounts, &instruction);
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+}
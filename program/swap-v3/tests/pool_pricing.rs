@@ -0,0 +1,113 @@
+mod common;
+
+use common::{SwapFixture, TestAccount, POOL_RESERVE, VAULT_IN_ACCOUNT_INDEX, VAULT_OUT_ACCOUNT_INDEX};
+use multihub_swap_v3::SwapInstruction;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use spl_token::instruction::TokenInstruction;
+
+const AMOUNT_IN: u64 = 1_000_000;
+
+fn swap() -> SwapInstruction {
+    SwapInstruction::Swap {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    }
+}
+
+#[test]
+fn swaps_price_against_the_pairs_pool() {
+    let mut fixture = SwapFixture::new();
+    let (result, meter) = fixture.process(&swap());
+    result.unwrap();
+
+    let (_, reserve_out) = fixture.pool().reserves_for(&fixture.mint_in);
+    assert_eq!(meter.token_transfers().last(), Some(&(POOL_RESERVE - reserve_out)));
+}
+
+#[test]
+fn swaps_cannot_skip_the_pairs_pool() {
+    let mut fixture = SwapFixture::skipping_pool();
+    let (result, meter) = fixture.process(&swap());
+    assert_eq!(result, Err(ProgramError::NotEnoughAccountKeys));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn swaps_need_a_created_pool() {
+    let mut fixture = SwapFixture::without_pool();
+    let (result, meter) = fixture.process(&swap());
+    assert_eq!(result, Err(ProgramError::UninitializedAccount));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn swaps_need_a_funded_pool() {
+    let mut fixture = SwapFixture::with_empty_pool();
+    let (result, meter) = fixture.process(&swap());
+    assert_eq!(result, Err(ProgramError::UninitializedAccount));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn swaps_move_tokens_through_the_pool_vaults() {
+    let mut fixture = SwapFixture::new();
+    let (result, meter) = fixture.process(&swap());
+    result.unwrap();
+
+    let vault_in = fixture.accounts[VAULT_IN_ACCOUNT_INDEX].key;
+    let vault_out = fixture.accounts[VAULT_OUT_ACCOUNT_INDEX].key;
+    let transfers: Vec<_> = meter
+        .invoked
+        .iter()
+        .filter(|instruction| {
+            matches!(TokenInstruction::unpack(&instruction.data), Ok(TokenInstruction::Transfer { .. }))
+        })
+        .map(|instruction| (instruction.accounts[0].pubkey, instruction.accounts[1].pubkey))
+        .collect();
+    assert_eq!(transfers.first().unwrap().1, vault_in);
+    assert_eq!(transfers.last().unwrap().0, vault_out);
+}
+
+#[test]
+fn swaps_refuse_vaults_the_authority_does_not_own() {
+    let mut fixture = SwapFixture::new();
+    let vault_out = &fixture.accounts[VAULT_OUT_ACCOUNT_INDEX];
+    fixture.accounts[VAULT_OUT_ACCOUNT_INDEX] =
+        TestAccount::token(vault_out.key, fixture.mint_out, Pubkey::new_unique(), POOL_RESERVE);
+    let (result, meter) = fixture.process(&swap());
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn swaps_set_the_admin_fee_aside_in_the_pool() {
+    let mut fixture = SwapFixture::new();
+    fixture.process(&swap()).0.unwrap();
+
+    // 10 bps of the input, held in the input vault for the treasury
+    let pool = fixture.pool();
+    let (admin_fees_in, admin_fees_out) = if fixture.mint_in == pool.mint_a {
+        (pool.admin_fees_a, pool.admin_fees_b)
+    } else {
+        (pool.admin_fees_b, pool.admin_fees_a)
+    };
+    assert_eq!((admin_fees_in, admin_fees_out), (AMOUNT_IN / 1_000, 0));
+}
+
+#[test]
+fn swaps_compound_the_pool_fees_into_the_reserves() {
+    let mut fixture = SwapFixture::new();
+    fixture.process(&swap()).0.unwrap();
+
+    // All of the input but the 10 bps admin fee and 50 bps referral cut,
+    // the swap fee and contribution included
+    let (reserve_in, _) = fixture.pool().reserves_for(&fixture.mint_in);
+    assert_eq!(reserve_in, POOL_RESERVE + AMOUNT_IN - AMOUNT_IN * 60 / 10_000);
+}
+
+#[test]
+fn fee_reinvestment_can_no_longer_be_turned_off() {
+    let mut fixture = SwapFixture::new();
+    let (result, _) = fixture.process(&SwapInstruction::SetPoolFeeReinvest { reinvest_fees: false });
+    assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+}
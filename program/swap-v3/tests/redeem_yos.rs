@@ -0,0 +1,85 @@
+mod common;
+
+use common::{process, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_pool_address, find_pool_twap_address, find_program_authority, find_program_state_address, PoolState,
+    PoolTwap, ProgramState, SwapInstruction,
+};
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar::instructions::{self, BorrowedInstruction},
+};
+
+const AMOUNT: u64 = 1_000;
+const RESERVE: u64 = 1_000_000_000;
+
+// Index of the pool's time-weighted reserves among RedeemYos's accounts
+const POOL_TWAP_INDEX: usize = 10;
+
+// RedeemYos's accounts without a redemption rate, for a YOT/YOS pool whose
+// reserves were pushed to 1:1 while it averaged two YOS per YOT
+fn accounts() -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let [user, yos_mint, yot_mint] = [(); 3].map(|_| Pubkey::new_unique());
+    let (program_authority, _) = find_program_authority(&program_id);
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    program_state.yos_mint = yos_mint;
+    program_state.yot_mint = yot_mint;
+
+    let (pool_address, _) = find_pool_address(&program_id, &yos_mint, &yot_mint);
+    let mut pool = zeroed::<PoolState>();
+    pool.is_initialized = true;
+    (pool.mint_a, pool.mint_b) = (yos_mint.min(yot_mint), yos_mint.max(yot_mint));
+    (pool.reserve_a, pool.reserve_b) = (RESERVE, RESERVE);
+    let mut pool_twap = zeroed::<PoolTwap>();
+    pool_twap.is_initialized = true;
+    pool_twap.pool = pool_address;
+    (pool_twap.reserve_a, pool_twap.reserve_b) = match pool.mint_a == yos_mint {
+        true => (2 * RESERVE, RESERVE),
+        false => (RESERVE, 2 * RESERVE),
+    };
+
+    // The instructions sysvar shows RedeemYos called at the top level
+    let sysvar_data = instructions::construct_instructions_data(&[BorrowedInstruction {
+        program_id: &program_id,
+        accounts: vec![],
+        data: &[],
+    }]);
+
+    let mut signer = TestAccount::new(user, system_program::id(), vec![]);
+    signer.is_signer = true;
+    vec![
+        signer,
+        TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+        TestAccount::token(Pubkey::new_unique(), yos_mint, user, AMOUNT),
+        TestAccount::new(yos_mint, spl_token::id(), vec![]),
+        TestAccount::token(Pubkey::new_unique(), yot_mint, user, 0),
+        TestAccount::token(Pubkey::new_unique(), yot_mint, program_authority, RESERVE),
+        TestAccount::new(program_authority, system_program::id(), vec![]),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        TestAccount::new(instructions::id(), system_program::id(), sysvar_data),
+        TestAccount::state(pool_address, &pool),
+        TestAccount::state(find_pool_twap_address(&program_id, &pool_address).0, &pool_twap),
+    ]
+}
+
+#[test]
+fn redemptions_price_at_the_time_weighted_reserves() {
+    let mut accounts = accounts();
+    let (result, meter) = process(&mut accounts, &SwapInstruction::RedeemYos { amount: AMOUNT });
+    result.unwrap();
+    // Half a YOT per YOS, not the one the pool's reserves were pushed to
+    assert_eq!(meter.token_transfers(), vec![AMOUNT / 2]);
+}
+
+#[test]
+fn redemptions_without_a_rate_need_the_time_weighted_reserves() {
+    let mut accounts = accounts();
+    accounts.truncate(POOL_TWAP_INDEX);
+    let (result, meter) = process(&mut accounts, &SwapInstruction::RedeemYos { amount: AMOUNT });
+    assert_eq!(result, Err(ProgramError::NotEnoughAccountKeys));
+    assert!(meter.invoked.is_empty());
+}
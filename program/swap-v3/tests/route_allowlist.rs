@@ -0,0 +1,113 @@
+mod common;
+
+use common::{SwapFixture, TestAccount, USER_BALANCE};
+use multihub_swap_v3::{
+    find_route_allowlist_address, swap_route_hash, ProgramState, RouteAllowlist, SwapInstruction, MAX_ALLOWED_ROUTES,
+};
+use borsh::BorshSerialize;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+
+// `fixture`'s swap with the route allowlists of `pairs` appended after the
+// positional slots, each flagged sensitive and allowing `route`
+fn sensitive(mut fixture: SwapFixture, pairs: &[(Pubkey, Pubkey)], route: &[Pubkey]) -> SwapFixture {
+    let program_id = multihub_swap_v3::id();
+    let mut program_state = fixture.program_state();
+    fixture.accounts.extend((0..8).map(|_| TestAccount::placeholder()));
+    for (index, (mint_x, mint_y)) in pairs.iter().enumerate() {
+        let (address, _) = find_route_allowlist_address(&program_id, mint_x, mint_y);
+        let mut route_hashes = [[0; 32]; MAX_ALLOWED_ROUTES];
+        route_hashes[0] = swap_route_hash(route);
+        let route_allowlist = RouteAllowlist {
+            is_initialized: true,
+            mint_a: *mint_x.min(mint_y),
+            mint_b: *mint_x.max(mint_y),
+            route_count: 1,
+            route_hashes,
+        };
+        fixture.accounts.push(TestAccount::state(address, &route_allowlist));
+        program_state.sensitive_pairs[index] = address;
+        program_state.sensitive_pair_count += 1;
+    }
+    write_program_state(&mut fixture, &program_state);
+    fixture
+}
+
+fn write_program_state(fixture: &mut SwapFixture, program_state: &ProgramState) {
+    program_state.serialize(&mut &mut fixture.accounts[1].data[..]).unwrap();
+}
+
+fn swap() -> SwapInstruction {
+    SwapInstruction::Swap {
+        amount_in: 1_000_000,
+        min_amount_out: 1,
+    }
+}
+
+// The fixture's swap as the second hop of a route from `first`
+fn second_hop(fixture: &SwapFixture, first: Pubkey) -> SwapInstruction {
+    SwapInstruction::SwapRouteHop {
+        route: vec![first, fixture.mint_in, fixture.mint_out],
+        amount: USER_BALANCE - 1_000_000,
+        min_amount_out: 1,
+    }
+}
+
+#[test]
+fn sensitive_pairs_trade_only_along_published_routes() {
+    let fixture = SwapFixture::new();
+    let (mint_in, mint_out) = (fixture.mint_in, fixture.mint_out);
+    let (result, _) = sensitive(fixture.clone(), &[(mint_in, mint_out)], &[mint_in, mint_out]).process(&swap());
+    result.unwrap();
+
+    let (result, _) = sensitive(fixture, &[(mint_in, mint_out)], &[mint_out, mint_in]).process(&swap());
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+}
+
+#[test]
+fn route_hops_are_held_to_the_end_pair_allowlist() {
+    let fixture = SwapFixture::new();
+    let (first, mint_in, mint_out) = (Pubkey::new_unique(), fixture.mint_in, fixture.mint_out);
+    let route = [first, mint_in, mint_out];
+
+    // The route's end pair publishes the direct swap only, so its hop through
+    // the fixture pair is refused
+    let hop = second_hop(&fixture, first);
+    let (result, _) = sensitive(fixture.clone(), &[(first, mint_out)], &[first, mint_out]).process(&hop);
+    assert_eq!(result, Err(ProgramError::InvalidArgument));
+
+    let mut fixture = sensitive(fixture, &[(first, mint_out)], &route);
+    let (result, meter) = fixture.process(&hop);
+    result.unwrap();
+    // A later hop spends the balance above the kept amount
+    assert_eq!(meter.token_transfers()[0], 1_000_000);
+}
+
+#[test]
+fn route_hops_need_the_end_pair_allowlist() {
+    let fixture = SwapFixture::new();
+    let first = Pubkey::new_unique();
+    let route = [first, fixture.mint_in, fixture.mint_out];
+    let mut fixture = sensitive(fixture, &[(first, route[2])], &route);
+    fixture.accounts.pop();
+    let hop = second_hop(&fixture, first);
+    assert_eq!(fixture.process(&hop).0, Err(ProgramError::NotEnoughAccountKeys));
+}
+
+#[test]
+fn route_hops_must_trade_a_hop_of_their_route() {
+    let mut fixture = SwapFixture::new();
+    let hop = SwapInstruction::SwapRouteHop {
+        route: vec![fixture.mint_out, fixture.mint_in, Pubkey::new_unique()],
+        amount: 1_000_000,
+        min_amount_out: 1,
+    };
+    assert_eq!(fixture.process(&hop).0, Err(ProgramError::InvalidArgument));
+
+    let hop = SwapInstruction::SwapRouteHop {
+        route: vec![fixture.mint_in, fixture.mint_out, fixture.mint_in],
+        amount: 1_000_000,
+        min_amount_out: 1,
+    };
+    assert_eq!(fixture.process(&hop).0, Err(ProgramError::InvalidInstructionData));
+}
+
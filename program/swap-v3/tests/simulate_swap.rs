@@ -0,0 +1,84 @@
+mod common;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use common::SwapFixture;
+use multihub_swap_v3::{SwapInstruction, SwapSimulation};
+use solana_program::program_error::ProgramError;
+
+const AMOUNT_IN: u64 = 1_000_000;
+
+fn simulate(swap: &SwapInstruction) -> SwapInstruction {
+    SwapInstruction::SimulateSwap {
+        swap_data: swap.try_to_vec().unwrap(),
+    }
+}
+
+// Simulate `swap`, then execute it on a fresh fixture, returning the
+// simulation and the output the execution paid the user
+fn simulate_and_execute(fixture: fn() -> SwapFixture, swap: SwapInstruction) -> (SwapSimulation, u64) {
+    let mut simulated = fixture();
+    let before: Vec<Vec<u8>> = simulated.accounts.iter().map(|account| account.data.clone()).collect();
+    let (result, meter) = simulated.process(&simulate(&swap));
+    result.unwrap();
+    assert!(meter.invoked.is_empty());
+    assert!(simulated.accounts.iter().map(|account| &account.data).eq(before.iter()));
+    let simulation = SwapSimulation::try_from_slice(&meter.return_data.unwrap()).unwrap();
+
+    let (result, meter) = fixture().process(&swap);
+    result.unwrap();
+    (simulation, *meter.token_transfers().last().unwrap())
+}
+
+#[test]
+fn simulated_swaps_match_executed_output() {
+    let swaps = [
+        SwapInstruction::Swap {
+            amount_in: AMOUNT_IN,
+            min_amount_out: 1,
+        },
+        SwapInstruction::SwapPartialFill {
+            amount_in: AMOUNT_IN,
+            min_amount_out: 1,
+            allow_partial: true,
+        },
+        SwapInstruction::SwapWithoutContribution {
+            amount_in: AMOUNT_IN,
+            min_amount_out: 1,
+        },
+        SwapInstruction::SwapWithSlippage {
+            amount_in: AMOUNT_IN,
+            slippage_bps: 100,
+        },
+    ];
+    for swap in swaps {
+        let (simulation, amount_out) = simulate_and_execute(SwapFixture::new, swap);
+        assert_eq!(simulation.amount_out, amount_out);
+        assert_eq!(simulation.amount_in, AMOUNT_IN);
+    }
+}
+
+#[test]
+fn simulated_lite_swap_matches_executed_output() {
+    let swap = SwapInstruction::SwapTokenLite {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    };
+    let (simulation, amount_out) = simulate_and_execute(SwapFixture::lite, swap);
+    assert_eq!(simulation.amount_out, amount_out);
+    // Lite swaps pay no cashback or referral fee
+    assert_eq!((simulation.yos_cashback, simulation.referral), (0, 0));
+}
+
+#[test]
+fn only_swaps_can_be_simulated() {
+    let mut fixture = SwapFixture::new();
+    let (result, _) = fixture.process(&simulate(&SwapInstruction::CloseProgram {}));
+    assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+
+    let nested = simulate(&SwapInstruction::Swap {
+        amount_in: AMOUNT_IN,
+        min_amount_out: 1,
+    });
+    let (result, _) = fixture.process(&simulate(&nested));
+    assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+}
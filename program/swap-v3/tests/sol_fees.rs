@@ -0,0 +1,62 @@
+mod common;
+
+use common::{process, zeroed, TestAccount};
+use multihub_swap_v3::{
+    admin_instruction, find_permission_matrix_address, find_program_state_address, find_sol_fee_vault_address,
+    find_treasury_address, PermissionMatrix, ProgramState, SwapInstruction,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program};
+
+const AMOUNT: u64 = 1_000;
+
+// Index of the payout destination among the WithdrawSolFees accounts
+const DESTINATION_INDEX: usize = 4;
+
+// WithdrawSolFees's accounts signed by a permission holder granted it,
+// paying the admin
+fn accounts() -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let (admin, holder) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    program_state.admin = admin;
+    let mut permission_matrix = zeroed::<PermissionMatrix>();
+    permission_matrix.is_initialized = true;
+    permission_matrix.set(holder, 1 << admin_instruction::WITHDRAW_SOL_FEES);
+
+    let mut signer = TestAccount::new(holder, system_program::id(), vec![]);
+    signer.is_signer = true;
+    vec![
+        signer,
+        TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+        TestAccount::new(find_sol_fee_vault_address(&program_id).0, program_id, vec![]),
+        TestAccount::new(find_treasury_address(&program_id).0, system_program::id(), vec![]),
+        TestAccount::new(admin, system_program::id(), vec![]),
+        TestAccount::state(find_permission_matrix_address(&program_id).0, &permission_matrix),
+    ]
+}
+
+#[test]
+fn sol_fees_are_paid_to_the_admin() {
+    let mut accounts = accounts();
+    let (balance, vault_balance) = (accounts[DESTINATION_INDEX].lamports, accounts[2].lamports);
+    process(&mut accounts, &SwapInstruction::WithdrawSolFees { amount: AMOUNT }).0.unwrap();
+    assert_eq!(accounts[DESTINATION_INDEX].lamports, balance + AMOUNT);
+    assert_eq!(accounts[2].lamports, vault_balance - AMOUNT);
+}
+
+#[test]
+fn sol_fees_never_go_to_the_signer() {
+    let mut accounts = accounts();
+    accounts[DESTINATION_INDEX].key = accounts[0].key;
+    let (result, _) = process(&mut accounts, &SwapInstruction::WithdrawSolFees { amount: AMOUNT });
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn sol_fees_go_only_to_the_admin() {
+    let mut accounts = accounts();
+    accounts[DESTINATION_INDEX].key = Pubkey::new_unique();
+    let (result, _) = process(&mut accounts, &SwapInstruction::WithdrawSolFees { amount: AMOUNT });
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+}
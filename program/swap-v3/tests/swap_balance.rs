@@ -0,0 +1,44 @@
+mod common;
+
+use common::{SwapFixture, USER_BALANCE};
+use multihub_swap_v3::SwapInstruction;
+use solana_program::program_error::ProgramError;
+
+#[test]
+fn balance_swaps_spend_what_is_held_above_the_kept_balance() {
+    let keep_balance = USER_BALANCE - 250_000;
+    let (result, meter) = SwapFixture::new().process(&SwapInstruction::SwapBalance {
+        keep_balance,
+        min_amount_out: 1,
+    });
+    result.unwrap();
+
+    // Moves the same tokens as swapping the excess outright
+    let (_, swapped) = SwapFixture::new().process(&SwapInstruction::Swap {
+        amount_in: 250_000,
+        min_amount_out: 1,
+    });
+    assert_eq!(meter.token_transfers(), swapped.token_transfers());
+}
+
+#[test]
+fn balance_swaps_need_something_above_the_kept_balance() {
+    for keep_balance in [USER_BALANCE, USER_BALANCE + 1] {
+        let (result, meter) = SwapFixture::new().process(&SwapInstruction::SwapBalance {
+            keep_balance,
+            min_amount_out: 1,
+        });
+        assert_eq!(result, Err(ProgramError::InsufficientFunds));
+        assert!(meter.invoked.is_empty());
+    }
+}
+
+#[test]
+fn balance_swaps_keep_their_minimum() {
+    // Fees alone put 250_000 out of reach of a 250_000 input
+    let (result, _) = SwapFixture::new().process(&SwapInstruction::SwapBalance {
+        keep_balance: USER_BALANCE - 250_000,
+        min_amount_out: 250_000,
+    });
+    assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use common::{process, token_swap_venue, zeroed, TestAccount};
+use multihub_swap_v3::{find_program_state_address, ProgramState, SwapInstruction, TOKEN_SWAP_PROGRAM_ID};
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, system_program};
+use spl_token::state::Account as TokenAccount;
+
+const RESERVE: u64 = 1_000_000_000;
+const AMOUNT_IN: u64 = 1_000_000;
+
+// Index of the user's output token account among SwapThroughTokenSwap's
+// accounts
+const USER_TOKEN_OUT_INDEX: usize = 9;
+
+// SwapThroughTokenSwap's accounts for a user swapping mint A for mint B
+fn accounts() -> Vec<TestAccount> {
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    let user = Pubkey::new_unique();
+    let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let mut signer = TestAccount::new(user, system_program::id(), vec![]);
+    signer.is_signer = true;
+    let mut accounts = vec![
+        signer,
+        TestAccount::state(find_program_state_address(&multihub_swap_v3::id()).0, &program_state),
+    ];
+    accounts.extend(token_swap_venue(mint_a, mint_b, RESERVE));
+    accounts.extend([
+        TestAccount::token(Pubkey::new_unique(), mint_a, user, AMOUNT_IN),
+        TestAccount::token(Pubkey::new_unique(), mint_b, user, 0),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        TestAccount::new(TOKEN_SWAP_PROGRAM_ID, system_program::id(), vec![]),
+    ]);
+    accounts
+}
+
+fn swap(min_amount_out: u64) -> SwapInstruction {
+    SwapInstruction::SwapThroughTokenSwap {
+        amount_in: AMOUNT_IN,
+        min_amount_out,
+    }
+}
+
+#[test]
+fn token_swap_hops_check_the_measured_output() {
+    // The stubbed pool pays nothing, whatever it would report
+    let mut accounts = accounts();
+    let (result, meter) = process(&mut accounts, &swap(1));
+    assert_eq!(result, Err(ProgramError::InvalidInstructionData));
+
+    let [swap] = &meter.invoked[..] else { panic!("expected one swap") };
+    assert_eq!(swap.program_id, TOKEN_SWAP_PROGRAM_ID);
+    assert_eq!(swap.data[0], 1);
+    assert_eq!(swap.data[1..9], AMOUNT_IN.to_le_bytes());
+    assert_eq!(swap.accounts[2].pubkey, accounts[0].key);
+    assert!(swap.accounts[2].is_signer);
+    assert_eq!(swap.accounts[6].pubkey, accounts[USER_TOKEN_OUT_INDEX].key);
+}
+
+#[test]
+fn token_swap_hops_report_the_measured_output() {
+    let mut accounts = accounts();
+    let (result, meter) = process(&mut accounts, &swap(0));
+    result.unwrap();
+    assert_eq!(meter.invoked.len(), 1);
+    assert_eq!(meter.events, 1);
+}
+
+#[test]
+fn token_swap_hops_pay_into_the_users_account_of_the_other_mint() {
+    let mut accounts = accounts();
+    let mint_b = TokenAccount::unpack(&accounts[USER_TOKEN_OUT_INDEX].data).unwrap().mint;
+    accounts[USER_TOKEN_OUT_INDEX] = TestAccount::token(Pubkey::new_unique(), mint_b, Pubkey::new_unique(), 0);
+    let (result, meter) = process(&mut accounts, &swap(0));
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+}
+
+#[test]
+fn token_swap_hops_need_a_pool_of_the_input_mint() {
+    let mut accounts = accounts();
+    let (user, mint) = (accounts[0].key, Pubkey::new_unique());
+    accounts[USER_TOKEN_OUT_INDEX - 1] = TestAccount::token(Pubkey::new_unique(), mint, user, AMOUNT_IN);
+    let (result, meter) = process(&mut accounts, &swap(0));
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+}
@@ -0,0 +1,177 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{process, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_pool_address, find_program_authority, find_program_state_address, find_treasury_address, PoolState,
+    ProgramState, SwapInstruction, Treasury, MAX_TREASURY_APPROVERS,
+};
+use solana_program::{program_error::ProgramError, pubkey::Pubkey, system_program};
+use spl_token::instruction::TokenInstruction;
+
+const APPROVAL_AMOUNT: u64 = 1_000;
+const TREASURY_BALANCE: u64 = 1_000_000;
+
+// Index of the recipient token account among the WithdrawTreasury accounts
+const RECIPIENT_INDEX: usize = 4;
+
+// An admin, a 2-of-3 treasury holding `mint`, a recipient token account of
+// that mint, and the approvers' wallets
+struct TreasuryFixture {
+    admin: Pubkey,
+    approvers: [Pubkey; 3],
+    mint: Pubkey,
+}
+
+impl TreasuryFixture {
+    fn new() -> Self {
+        TreasuryFixture {
+            admin: Pubkey::new_unique(),
+            approvers: [(); 3].map(|_| Pubkey::new_unique()),
+            mint: Pubkey::new_unique(),
+        }
+    }
+
+    // WithdrawTreasury's accounts signed by `signer` and `approvers`
+    fn accounts(&self, signer: Pubkey, approvers: &[Pubkey]) -> Vec<TestAccount> {
+        let program_id = multihub_swap_v3::id();
+        let mut program_state = zeroed::<ProgramState>();
+        program_state.is_initialized = true;
+        program_state.admin = self.admin;
+
+        let (treasury_address, _) = find_treasury_address(&program_id);
+        let mut treasury = Treasury {
+            is_initialized: true,
+            threshold: 2,
+            approver_count: 3,
+            approvers: [Pubkey::default(); MAX_TREASURY_APPROVERS],
+            approval_amount: APPROVAL_AMOUNT,
+        };
+        treasury.approvers[..3].copy_from_slice(&self.approvers);
+
+        let mut accounts = vec![
+            wallet(signer),
+            TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+            TestAccount::state(treasury_address, &treasury),
+            TestAccount::token(Pubkey::new_unique(), self.mint, treasury_address, TREASURY_BALANCE),
+            TestAccount::token(Pubkey::new_unique(), self.mint, Pubkey::new_unique(), 0),
+            TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+        ];
+        accounts.extend(approvers.iter().copied().map(wallet));
+        accounts
+    }
+}
+
+fn wallet(key: Pubkey) -> TestAccount {
+    let mut account = TestAccount::new(key, system_program::id(), vec![]);
+    account.is_signer = true;
+    account
+}
+
+fn withdraw(amount: u64) -> SwapInstruction {
+    SwapInstruction::WithdrawTreasury { amount }
+}
+
+#[test]
+fn treasury_pays_the_chosen_recipient() {
+    let fixture = TreasuryFixture::new();
+    let mut accounts = fixture.accounts(fixture.admin, &[]);
+    let (result, meter) = process(&mut accounts, &withdraw(APPROVAL_AMOUNT));
+    result.unwrap();
+
+    // The treasury PDA signs a transfer from its token account
+    let [transfer] = &meter.invoked[..] else { panic!("expected one transfer") };
+    assert_eq!(
+        TokenInstruction::unpack(&transfer.data).unwrap(),
+        TokenInstruction::Transfer { amount: APPROVAL_AMOUNT }
+    );
+    assert_eq!(transfer.accounts[0].pubkey, accounts[3].key);
+    assert_eq!(transfer.accounts[1].pubkey, accounts[RECIPIENT_INDEX].key);
+    assert_eq!(transfer.accounts[2].pubkey, find_treasury_address(&multihub_swap_v3::id()).0);
+    assert!(transfer.accounts[2].is_signer);
+}
+
+#[test]
+fn large_withdrawals_need_the_approvers() {
+    let fixture = TreasuryFixture::new();
+    for approvers in [&[][..], &fixture.approvers[..1]] {
+        let mut accounts = fixture.accounts(fixture.admin, approvers);
+        let (result, meter) = process(&mut accounts, &withdraw(APPROVAL_AMOUNT + 1));
+        assert_eq!(result, Err(ProgramError::MissingRequiredSignature));
+        assert!(meter.invoked.is_empty());
+    }
+
+    let mut accounts = fixture.accounts(fixture.admin, &fixture.approvers[1..]);
+    let (result, meter) = process(&mut accounts, &withdraw(APPROVAL_AMOUNT + 1));
+    result.unwrap();
+    assert_eq!(meter.token_transfers(), [APPROVAL_AMOUNT + 1]);
+}
+
+#[test]
+fn only_the_admin_withdraws_from_the_treasury() {
+    let fixture = TreasuryFixture::new();
+    let mut accounts = fixture.accounts(fixture.approvers[0], &fixture.approvers[1..]);
+    let (result, _) = process(&mut accounts, &withdraw(1));
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn recipient_must_hold_the_treasury_mint() {
+    let fixture = TreasuryFixture::new();
+    let mut accounts = fixture.accounts(fixture.admin, &[]);
+    accounts[RECIPIENT_INDEX] = TestAccount::token(Pubkey::new_unique(), Pubkey::new_unique(), fixture.admin, 0);
+    let (result, _) = process(&mut accounts, &withdraw(1));
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+}
+
+#[test]
+fn withdrawals_are_bounded_by_the_treasury_balance() {
+    let fixture = TreasuryFixture::new();
+    let mut accounts = fixture.accounts(fixture.admin, &fixture.approvers);
+    let (result, _) = process(&mut accounts, &withdraw(TREASURY_BALANCE + 1));
+    assert_eq!(result, Err(ProgramError::InsufficientFunds));
+}
+
+// CollectAdminFees's accounts for a pool holding `admin_fees` of each mint,
+// with the treasury's token accounts owned by `treasury_owner`
+fn collect_admin_fees_accounts(admin_fees: (u64, u64), treasury_owner: Pubkey) -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let (program_authority, _) = find_program_authority(&program_id);
+    let (mint_x, mint_y) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut pool = zeroed::<PoolState>();
+    pool.is_initialized = true;
+    (pool.mint_a, pool.mint_b) = if mint_x < mint_y { (mint_x, mint_y) } else { (mint_y, mint_x) };
+    (pool.admin_fees_a, pool.admin_fees_b) = admin_fees;
+    vec![
+        TestAccount::state(find_pool_address(&program_id, &mint_x, &mint_y).0, &pool),
+        TestAccount::token(Pubkey::new_unique(), pool.mint_a, program_authority, TREASURY_BALANCE),
+        TestAccount::token(Pubkey::new_unique(), pool.mint_b, program_authority, TREASURY_BALANCE),
+        TestAccount::token(Pubkey::new_unique(), pool.mint_a, treasury_owner, 0),
+        TestAccount::token(Pubkey::new_unique(), pool.mint_b, treasury_owner, 0),
+        TestAccount::new(program_authority, system_program::id(), vec![]),
+        TestAccount::new(spl_token::id(), system_program::id(), vec![]),
+    ]
+}
+
+#[test]
+fn admin_fees_are_collected_to_the_treasury() {
+    let treasury = find_treasury_address(&multihub_swap_v3::id()).0;
+    let mut accounts = collect_admin_fees_accounts((1_000, 0), treasury);
+    let (result, meter) = process(&mut accounts, &SwapInstruction::CollectAdminFees {});
+    result.unwrap();
+
+    let [transfer] = &meter.invoked[..] else { panic!("expected one transfer") };
+    assert_eq!(TokenInstruction::unpack(&transfer.data).unwrap(), TokenInstruction::Transfer { amount: 1_000 });
+    assert_eq!(transfer.accounts[0].pubkey, accounts[1].key);
+    assert_eq!(transfer.accounts[1].pubkey, accounts[3].key);
+    let pool = PoolState::deserialize(&mut &accounts[0].data[..]).unwrap();
+    assert_eq!((pool.admin_fees_a, pool.admin_fees_b), (0, 0));
+}
+
+#[test]
+fn admin_fees_go_only_to_the_treasury() {
+    let mut accounts = collect_admin_fees_accounts((1_000, 1_000), Pubkey::new_unique());
+    let (result, meter) = process(&mut accounts, &SwapInstruction::CollectAdminFees {});
+    assert_eq!(result, Err(ProgramError::InvalidAccountData));
+    assert!(meter.invoked.is_empty());
+}
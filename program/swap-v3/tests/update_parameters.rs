@@ -0,0 +1,62 @@
+mod common;
+
+use borsh::BorshDeserialize;
+use common::{process, zeroed, TestAccount};
+use multihub_swap_v3::{
+    find_parameter_history_address, find_program_state_address, ParameterHistory, ProgramState, SwapInstruction,
+};
+use solana_program::{pubkey::Pubkey, system_program};
+
+const SWAP_FEE_RATE: u64 = 30;
+
+// UpdateParameters's accounts for an admin, with the parameter history
+// already created
+fn accounts() -> Vec<TestAccount> {
+    let program_id = multihub_swap_v3::id();
+    let admin = Pubkey::new_unique();
+    let mut program_state = zeroed::<ProgramState>();
+    program_state.is_initialized = true;
+    program_state.admin = admin;
+    program_state.swap_fee_rate = SWAP_FEE_RATE;
+
+    let mut admin_account = TestAccount::new(admin, system_program::id(), vec![]);
+    admin_account.is_signer = true;
+    vec![
+        admin_account,
+        TestAccount::state(find_program_state_address(&program_id).0, &program_state),
+        TestAccount::state(find_parameter_history_address(&program_id).0, &zeroed::<ParameterHistory>()),
+        TestAccount::new(system_program::id(), system_program::id(), vec![]),
+    ]
+}
+
+fn history(accounts: &[TestAccount]) -> ParameterHistory {
+    ParameterHistory::deserialize(&mut &accounts[2].data[..]).unwrap()
+}
+
+fn update_swap_fee_rate(swap_fee_rate: u64) -> SwapInstruction {
+    SwapInstruction::UpdateParameters {
+        lp_contribution_rate: None,
+        admin_fee_rate: None,
+        yos_cashback_rate: None,
+        swap_fee_rate: Some(swap_fee_rate),
+        referral_rate: None,
+    }
+}
+
+#[test]
+fn changed_parameters_are_published_and_recorded() {
+    let mut accounts = accounts();
+    let (result, meter) = process(&mut accounts, &update_swap_fee_rate(SWAP_FEE_RATE + 1));
+    result.unwrap();
+    assert_eq!(meter.events, 1);
+    assert_eq!(history(&accounts).total_changes, 1);
+}
+
+#[test]
+fn unchanged_parameters_leave_no_event_or_history() {
+    let mut accounts = accounts();
+    let (result, meter) = process(&mut accounts, &update_swap_fee_rate(SWAP_FEE_RATE));
+    result.unwrap();
+    assert_eq!(meter.events, 0);
+    assert_eq!(history(&accounts).total_changes, 0);
+}
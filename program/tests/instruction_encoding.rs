@@ -0,0 +1,131 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use yot_staking::StakingInstruction;
+
+// Golden encodings shared with the web client
+const FIXTURES: &str = include_str!("../../shared/fixtures/staking-instructions.txt");
+
+// Fixture name of a variant; exhaustive so new variants need a fixture
+fn variant_name(instruction: &StakingInstruction) -> &'static str {
+    match instruction {
+        StakingInstruction::Initialize { .. } => "Initialize",
+        StakingInstruction::Stake { .. } => "Stake",
+        StakingInstruction::Unstake { .. } => "Unstake",
+        StakingInstruction::Harvest => "Harvest",
+        StakingInstruction::UpdateParameters { .. } => "UpdateParameters",
+        StakingInstruction::SetPauser { .. } => "SetPauser",
+        StakingInstruction::SetPausedMask { .. } => "SetPausedMask",
+        StakingInstruction::EmergencyUnstake => "EmergencyUnstake",
+        StakingInstruction::SetMinRunway { .. } => "SetMinRunway",
+        StakingInstruction::RecordSnapshot { .. } => "RecordSnapshot",
+        StakingInstruction::ClaimSnapshotBonus { .. } => "ClaimSnapshotBonus",
+        StakingInstruction::SetHarvestThreshold { .. } => "SetHarvestThreshold",
+        StakingInstruction::StakeWithSol { .. } => "StakeWithSol",
+        StakingInstruction::SolvencyReport => "SolvencyReport",
+        StakingInstruction::SetHarvestFee { .. } => "SetHarvestFee",
+        StakingInstruction::Ping => "Ping",
+        StakingInstruction::SetRewardFreeze { .. } => "SetRewardFreeze",
+        StakingInstruction::FundSnapshotVault { .. } => "FundSnapshotVault",
+    }
+}
+
+// One instance of every variant, built from the fixture's sample inputs
+fn samples() -> Vec<StakingInstruction> {
+    vec![
+        StakingInstruction::Initialize {
+            yot_mint: Pubkey::new_from_array([1; 32]),
+            yos_mint: Pubkey::new_from_array([2; 32]),
+            stake_rate_per_second: 12,
+            harvest_threshold: 1_000_000,
+        },
+        StakingInstruction::Stake { amount: 1_000_000_000 },
+        StakingInstruction::Unstake { amount: 500_000_000 },
+        StakingInstruction::Harvest,
+        StakingInstruction::UpdateParameters {
+            stake_rate_per_second: 15,
+            harvest_threshold: 2_000_000,
+        },
+        StakingInstruction::SetPauser {
+            pauser: Pubkey::new_from_array([3; 32]),
+        },
+        StakingInstruction::SetPausedMask { paused_mask: 0b101 },
+        StakingInstruction::EmergencyUnstake,
+        StakingInstruction::SetMinRunway {
+            min_runway_seconds: 30 * 24 * 60 * 60,
+        },
+        StakingInstruction::RecordSnapshot {
+            campaign_id: 1,
+            slot: 250_000_000,
+            merkle_root: [4; 32],
+            total_staked: 5_000_000_000_000,
+            bonus_pool: 1_000_000_000_000,
+        },
+        StakingInstruction::ClaimSnapshotBonus {
+            campaign_id: 1,
+            staked_amount: 2_000_000_000_000,
+            proof: vec![[5; 32], [6; 32]],
+        },
+        StakingInstruction::SetHarvestThreshold {
+            harvest_threshold: 1_000_000,
+        },
+        StakingInstruction::StakeWithSol {
+            lamports: 1_000_000_000,
+            min_yot: 500_000_000,
+            lock_seconds: 30 * 24 * 60 * 60,
+        },
+        StakingInstruction::SolvencyReport,
+        StakingInstruction::SetHarvestFee { harvest_fee_bps: 250 },
+        StakingInstruction::Ping,
+        StakingInstruction::SetRewardFreeze { frozen: true },
+        StakingInstruction::FundSnapshotVault { campaign_id: 1 },
+    ]
+}
+
+fn fixtures() -> Vec<(&'static str, Vec<u8>)> {
+    FIXTURES
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (name, hex) = line.split_once(' ').expect("fixture line is `<variant> <hex>`");
+            (name, decode_hex(hex.trim()))
+        })
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Vec<u8> {
+    assert!(hex.len().is_multiple_of(2), "odd-length hex {}", hex);
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).expect("invalid hex"))
+        .collect()
+}
+
+#[test]
+fn every_variant_matches_its_golden_encoding() {
+    let fixtures = fixtures();
+    let samples = samples();
+    assert_eq!(fixtures.len(), samples.len(), "one fixture per variant");
+
+    for (instruction, (name, expected)) in samples.iter().zip(&fixtures) {
+        assert_eq!(variant_name(instruction), *name, "fixtures follow variant order");
+        assert_eq!(instruction.try_to_vec().unwrap(), *expected, "{} encoding", name);
+    }
+}
+
+#[test]
+fn golden_encodings_decode_to_the_same_variant() {
+    for (name, bytes) in fixtures() {
+        let instruction = StakingInstruction::try_from_slice(&bytes)
+            .unwrap_or_else(|error| panic!("{} does not decode: {}", name, error));
+        assert_eq!(variant_name(&instruction), name);
+        assert_eq!(instruction.try_to_vec().unwrap(), bytes, "{} round trip", name);
+    }
+}
+
+#[test]
+fn trailing_bytes_are_rejected() {
+    // Older clients appended a reward amount to Harvest; the program must
+    // reject it rather than guess
+    assert!(StakingInstruction::try_from_slice(&[3, 0, 0, 0, 0, 0, 0, 0, 0]).is_err());
+}
@@ -0,0 +1,61 @@
+use yot_staking::{RateHistory, RewardFreeze, RewardSchedule, MAX_RATE_CHECKPOINTS, MAX_REWARD_FREEZE_WINDOWS};
+
+fn empty_history() -> RateHistory {
+    RateHistory {
+        checkpoint_count: 0,
+        checkpoints: [(0, 0); MAX_RATE_CHECKPOINTS],
+    }
+}
+
+#[test]
+fn time_before_a_rate_change_keeps_the_old_rate() {
+    let mut rate_history = empty_history();
+    rate_history.record(1_000, 10);
+    rate_history.record(2_000, 20);
+    assert_eq!(rate_history.segments(500, 3_000, 30), vec![(500, 1_000, 10), (1_000, 2_000, 20), (2_000, 3_000, 30)]);
+    assert_eq!(rate_history.segments(1_500, 1_800, 30), vec![(1_500, 1_800, 20)]);
+    assert_eq!(rate_history.segments(2_500, 3_000, 30), vec![(2_500, 3_000, 30)]);
+    assert_eq!(rate_history.segments(3_000, 2_500, 30), vec![]);
+
+    // 1000 YOT earns 10_000 YOS base units a second per unit of rate, at 10
+    // and then 20 rather than 30 throughout
+    let staked = 1_000_000_000_000;
+    let mut schedule = RewardSchedule {
+        configured_rate: 30,
+        effective_rate: 30,
+        rate_history: Some(rate_history),
+        reward_freeze: None,
+    };
+    assert_eq!(schedule.rewards(staked, 0, 2_000), 100_000 * 1_000 + 200_000 * 1_000);
+
+    // Runway scaling applies to past rates in proportion, and frozen time
+    // accrues at no rate
+    schedule.effective_rate = 15;
+    assert_eq!(schedule.rewards(staked, 1_000, 3_000), 100_000 * 1_000 + 150_000 * 1_000);
+    let mut reward_freeze = RewardFreeze {
+        frozen_since: 0,
+        window_count: 0,
+        windows: [[0; 2]; MAX_REWARD_FREEZE_WINDOWS],
+    };
+    reward_freeze.freeze(1_500).unwrap();
+    reward_freeze.unfreeze(2_500);
+    schedule.reward_freeze = Some(reward_freeze);
+    assert_eq!(schedule.rewards(staked, 1_000, 3_000), 100_000 * 500 + 150_000 * 500);
+}
+
+#[test]
+fn a_full_history_drops_its_oldest_checkpoint() {
+    let mut rate_history = empty_history();
+    for change in 1..=MAX_RATE_CHECKPOINTS as i64 + 1 {
+        rate_history.record(change * 100, change as u64);
+    }
+    assert_eq!(rate_history.checkpoint_count as usize, MAX_RATE_CHECKPOINTS);
+    assert_eq!(rate_history.checkpoints[0], (200, 2));
+    assert_eq!(rate_history.segments(0, 250, 0), vec![(0, 200, 2), (200, 250, 3)]);
+
+    // A clock behind the last checkpoint keeps them ordered
+    let mut rate_history = empty_history();
+    rate_history.record(1_000, 10);
+    rate_history.record(900, 20);
+    assert_eq!(rate_history.checkpoints[1], (1_000, 20));
+}
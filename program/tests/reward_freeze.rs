@@ -0,0 +1,45 @@
+use yot_staking::{RewardFreeze, StakingError, MAX_REWARD_FREEZE_WINDOWS};
+
+fn unfrozen() -> RewardFreeze {
+    RewardFreeze {
+        frozen_since: 0,
+        window_count: 0,
+        windows: [[0; 2]; MAX_REWARD_FREEZE_WINDOWS],
+    }
+}
+
+#[test]
+fn frozen_time_does_not_accrue() {
+    let mut reward_freeze = unfrozen();
+    assert_eq!(reward_freeze.accruing_seconds(1_000, 2_000), 1_000);
+
+    // A freeze in progress stops accrual from its start
+    assert!(matches!(reward_freeze.freeze(1_500), Ok(true)));
+    assert!(matches!(reward_freeze.freeze(1_600), Ok(false)));
+    assert_eq!(reward_freeze.accruing_seconds(1_000, 2_000), 500);
+    assert_eq!(reward_freeze.accruing_seconds(1_700, 2_000), 0);
+
+    // Once resumed, a position untouched through the freeze still skips it
+    assert!(reward_freeze.unfreeze(2_500));
+    assert!(!reward_freeze.unfreeze(2_600));
+    assert_eq!(reward_freeze.accruing_seconds(1_000, 3_000), 1_000);
+    assert_eq!(reward_freeze.accruing_seconds(2_000, 3_000), 500);
+    assert_eq!(reward_freeze.accruing_seconds(2_500, 3_000), 500);
+
+    // Each window counts once, and a clock running backwards accrues nothing
+    reward_freeze.freeze(4_000).unwrap();
+    reward_freeze.unfreeze(4_100);
+    assert_eq!(reward_freeze.frozen_seconds(0, 5_000), 1_100);
+    assert_eq!(reward_freeze.accruing_seconds(3_000, 2_000), 0);
+}
+
+#[test]
+fn a_full_history_refuses_new_freezes() {
+    let mut reward_freeze = unfrozen();
+    for window in 0..MAX_REWARD_FREEZE_WINDOWS as i64 {
+        reward_freeze.freeze(window * 10 + 1).unwrap();
+        reward_freeze.unfreeze(window * 10 + 5);
+    }
+    assert!(matches!(reward_freeze.freeze(1_000), Err(StakingError::FreezeHistoryFull)));
+    assert!(!reward_freeze.is_frozen());
+}
@@ -0,0 +1,51 @@
+use solana_program::pubkey::Pubkey;
+use yot_staking::{snapshot_leaf, snapshot_node, verify_snapshot_proof};
+
+fn pubkey(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+// Three balances: the third leaf has no sibling and moves up unchanged
+//
+//          root
+//         /    \
+//       ab      c
+//      /  \
+//     a    b
+fn tree() -> ([[u8; 32]; 3], [u8; 32], [u8; 32]) {
+    let leaves = [
+        snapshot_leaf(&pubkey(1), 100),
+        snapshot_leaf(&pubkey(2), 250),
+        snapshot_leaf(&pubkey(3), 650),
+    ];
+    let ab = snapshot_node(&leaves[0], &leaves[1]);
+    let root = snapshot_node(&ab, &leaves[2]);
+    (leaves, ab, root)
+}
+
+#[test]
+fn every_balance_proves_against_the_root() {
+    let (leaves, ab, root) = tree();
+    assert!(verify_snapshot_proof(&root, leaves[0], &[leaves[1], leaves[2]]));
+    assert!(verify_snapshot_proof(&root, leaves[1], &[leaves[0], leaves[2]]));
+    assert!(verify_snapshot_proof(&root, leaves[2], &[ab]));
+}
+
+#[test]
+fn wrong_balances_and_proofs_are_rejected() {
+    let (leaves, ab, root) = tree();
+    // Claiming more than the snapshot recorded
+    assert!(!verify_snapshot_proof(&root, snapshot_leaf(&pubkey(1), 101), &[leaves[1], leaves[2]]));
+    // Claiming another wallet's balance
+    assert!(!verify_snapshot_proof(&root, snapshot_leaf(&pubkey(4), 100), &[leaves[1], leaves[2]]));
+    // An inner node is not a leaf
+    assert!(!verify_snapshot_proof(&root, ab, &[leaves[2], leaves[2]]));
+    assert!(!verify_snapshot_proof(&root, leaves[0], &[]));
+}
+
+#[test]
+fn node_hashing_ignores_child_order() {
+    let (leaves, _, _) = tree();
+    assert_eq!(snapshot_node(&leaves[0], &leaves[1]), snapshot_node(&leaves[1], &leaves[0]));
+    assert_ne!(snapshot_node(&leaves[0], &leaves[1]), snapshot_leaf(&pubkey(1), 100));
+}
@@ -0,0 +1,257 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::pubkey::Pubkey;
+use yot_staking::{
+    PauseState, ProgramState, RateHistory, RewardFreeze, RewardSnapshot, RunwayConfig, SnapshotClaim, StakingAccount,
+    LEGACY_STAKING_ACCOUNT_LEN, MAX_RATE_CHECKPOINTS, MAX_REWARD_FREEZE_WINDOWS, PRE_LOCK_STAKING_ACCOUNT_LEN,
+    PRE_PENDING_STAKING_ACCOUNT_LEN,
+};
+
+// Accounts are allocated with size_of and decoded with try_from_slice, which
+// rejects trailing bytes, so the Borsh length must match the allocation.
+
+fn pubkey(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+fn u64_at(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn i64_at(bytes: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+#[test]
+fn program_state_layout() {
+    let state = ProgramState {
+        admin: pubkey(1),
+        yot_mint: pubkey(2),
+        yos_mint: pubkey(3),
+        stake_rate_per_second: 12,
+        harvest_threshold: 1_000_000,
+    };
+    let bytes = state.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), 112);
+    assert_eq!(bytes.len(), std::mem::size_of::<ProgramState>());
+    assert_eq!(&bytes[0..32], pubkey(1).as_ref());
+    assert_eq!(&bytes[32..64], pubkey(2).as_ref());
+    assert_eq!(&bytes[64..96], pubkey(3).as_ref());
+    assert_eq!(u64_at(&bytes, 96), 12);
+    assert_eq!(u64_at(&bytes, 104), 1_000_000);
+
+    let decoded = ProgramState::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn staking_account_layout() {
+    let staking = StakingAccount {
+        owner: pubkey(4),
+        staked_amount: 5_000_000_000,
+        start_timestamp: 1_700_000_000,
+        last_harvest_time: -1,
+        total_harvested: 42,
+        harvest_threshold: 10_000,
+        locked_until: 1_702_592_000,
+        pending_rewards: 9_260,
+    };
+    let bytes = staking.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), 88);
+    assert_eq!(bytes.len(), std::mem::size_of::<StakingAccount>());
+    assert_eq!(&bytes[0..32], pubkey(4).as_ref());
+    assert_eq!(u64_at(&bytes, 32), 5_000_000_000);
+    assert_eq!(i64_at(&bytes, 40), 1_700_000_000);
+    assert_eq!(i64_at(&bytes, 48), -1);
+    assert_eq!(u64_at(&bytes, 56), 42);
+    assert_eq!(u64_at(&bytes, 64), 10_000);
+    assert_eq!(i64_at(&bytes, 72), 1_702_592_000);
+    assert_eq!(u64_at(&bytes, 80), 9_260);
+
+    let decoded = StakingAccount::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn legacy_staking_account_decodes_without_threshold() {
+    let staking = StakingAccount {
+        owner: pubkey(4),
+        staked_amount: 5_000_000_000,
+        start_timestamp: 1_700_000_000,
+        last_harvest_time: 1_700_000_000,
+        total_harvested: 42,
+        harvest_threshold: 0,
+        locked_until: 0,
+        pending_rewards: 0,
+    };
+    let bytes = staking.try_to_vec().unwrap();
+    let legacy = &bytes[..LEGACY_STAKING_ACCOUNT_LEN];
+
+    let decoded = StakingAccount::try_from_slice(legacy).unwrap();
+    assert_eq!(decoded.owner, pubkey(4));
+    assert_eq!(decoded.total_harvested, 42);
+    assert_eq!(decoded.harvest_threshold, 0);
+    assert_eq!(decoded.effective_harvest_threshold(1_000_000), 1_000_000);
+    assert_eq!(decoded.locked_until, 0);
+
+    // A partial threshold is corruption, not a legacy account
+    assert!(StakingAccount::try_from_slice(&bytes[..LEGACY_STAKING_ACCOUNT_LEN + 4]).is_err());
+}
+
+#[test]
+fn pre_lock_staking_account_decodes_unlocked() {
+    let staking = StakingAccount {
+        owner: pubkey(4),
+        staked_amount: 5_000_000_000,
+        start_timestamp: 1_700_000_000,
+        last_harvest_time: 1_700_000_000,
+        total_harvested: 42,
+        harvest_threshold: 10_000,
+        locked_until: 0,
+        pending_rewards: 0,
+    };
+    let bytes = staking.try_to_vec().unwrap();
+
+    let decoded = StakingAccount::try_from_slice(&bytes[..PRE_LOCK_STAKING_ACCOUNT_LEN]).unwrap();
+    assert_eq!(decoded.harvest_threshold, 10_000);
+    assert_eq!(decoded.locked_until, 0);
+
+    assert!(StakingAccount::try_from_slice(&bytes[..PRE_LOCK_STAKING_ACCOUNT_LEN + 4]).is_err());
+}
+
+#[test]
+fn pre_pending_staking_account_decodes_with_nothing_pending() {
+    let staking = StakingAccount {
+        owner: pubkey(4),
+        staked_amount: 5_000_000_000,
+        start_timestamp: 1_700_000_000,
+        last_harvest_time: 1_700_000_000,
+        total_harvested: 42,
+        harvest_threshold: 10_000,
+        locked_until: 1_702_592_000,
+        pending_rewards: 0,
+    };
+    let bytes = staking.try_to_vec().unwrap();
+
+    let decoded = StakingAccount::try_from_slice(&bytes[..PRE_PENDING_STAKING_ACCOUNT_LEN]).unwrap();
+    assert_eq!(decoded.locked_until, 1_702_592_000);
+    assert_eq!(decoded.pending_rewards, 0);
+
+    assert!(StakingAccount::try_from_slice(&bytes[..PRE_PENDING_STAKING_ACCOUNT_LEN + 4]).is_err());
+}
+
+#[test]
+fn pause_state_layout() {
+    let pause_state = PauseState {
+        pauser: pubkey(5),
+        paused_mask: 0b110,
+    };
+    let bytes = pause_state.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), 33);
+    assert_eq!(bytes.len(), std::mem::size_of::<PauseState>());
+    assert_eq!(&bytes[0..32], pubkey(5).as_ref());
+    assert_eq!(bytes[32], 0b110);
+
+    let decoded = PauseState::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn reward_freeze_layout() {
+    let mut windows = [[0; 2]; MAX_REWARD_FREEZE_WINDOWS];
+    windows[0] = [1_700_000_000, 1_700_003_600];
+    let reward_freeze = RewardFreeze {
+        frozen_since: 1_700_100_000,
+        window_count: 1,
+        windows,
+    };
+    let bytes = reward_freeze.try_to_vec().unwrap();
+
+    // Allocated with LEN, as padding makes size_of larger
+    assert_eq!(bytes.len(), RewardFreeze::LEN);
+    assert_eq!(i64_at(&bytes, 0), 1_700_100_000);
+    assert_eq!(bytes[8], 1);
+    assert_eq!(i64_at(&bytes, 9), 1_700_000_000);
+    assert_eq!(i64_at(&bytes, 17), 1_700_003_600);
+
+    let decoded = RewardFreeze::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, reward_freeze);
+}
+
+#[test]
+fn rate_history_layout() {
+    let mut checkpoints = [(0, 0); MAX_RATE_CHECKPOINTS];
+    checkpoints[0] = (1_700_000_000, 12);
+    let rate_history = RateHistory {
+        checkpoint_count: 1,
+        checkpoints,
+    };
+    let bytes = rate_history.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), RateHistory::LEN);
+    assert_eq!(bytes[0], 1);
+    assert_eq!(i64_at(&bytes, 1), 1_700_000_000);
+    assert_eq!(u64_at(&bytes, 9), 12);
+
+    let decoded = RateHistory::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded, rate_history);
+}
+
+#[test]
+fn runway_config_layout() {
+    let runway_config = RunwayConfig {
+        min_runway_seconds: 30 * 24 * 60 * 60,
+    };
+    let bytes = runway_config.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), 8);
+    assert_eq!(bytes.len(), std::mem::size_of::<RunwayConfig>());
+    assert_eq!(i64_at(&bytes, 0), 30 * 24 * 60 * 60);
+
+    let decoded = RunwayConfig::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn reward_snapshot_layout() {
+    let snapshot = RewardSnapshot {
+        campaign_id: 7,
+        slot: 250_000_000,
+        merkle_root: [6; 32],
+        total_staked: 5_000_000_000_000,
+        bonus_pool: 1_000_000_000_000,
+        total_claimed: 3,
+    };
+    let bytes = snapshot.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), 72);
+    assert_eq!(bytes.len(), std::mem::size_of::<RewardSnapshot>());
+    assert_eq!(u64_at(&bytes, 0), 7);
+    assert_eq!(u64_at(&bytes, 8), 250_000_000);
+    assert_eq!(&bytes[16..48], &[6; 32]);
+    assert_eq!(u64_at(&bytes, 48), 5_000_000_000_000);
+    assert_eq!(u64_at(&bytes, 56), 1_000_000_000_000);
+    assert_eq!(u64_at(&bytes, 64), 3);
+
+    let decoded = RewardSnapshot::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+}
+
+#[test]
+fn snapshot_claim_layout() {
+    let claim = SnapshotClaim {
+        owner: pubkey(8),
+        amount: 400_000_000,
+    };
+    let bytes = claim.try_to_vec().unwrap();
+
+    assert_eq!(bytes.len(), 40);
+    assert_eq!(bytes.len(), std::mem::size_of::<SnapshotClaim>());
+    assert_eq!(&bytes[0..32], pubkey(8).as_ref());
+    assert_eq!(u64_at(&bytes, 32), 400_000_000);
+
+    let decoded = SnapshotClaim::try_from_slice(&bytes).unwrap();
+    assert_eq!(decoded.try_to_vec().unwrap(), bytes);
+}
@@ -172,7 +172,8 @@ export function setupAuth(app: Express) {
         stakeRatePerSecond,
         harvestThreshold,
         stakeThreshold,    // Added new fields 
-        unstakeThreshold   // Added new fields
+        unstakeThreshold,  // Added new fields
+        minTopup
       } = req.body;
       
       const adminId = (req.user as AdminUser).id;
@@ -188,6 +189,7 @@ export function setupAuth(app: Express) {
         harvestThreshold,
         stakeThreshold,     // Added new fields
         unstakeThreshold,   // Added new fields
+        minTopup,
         updatedBy: adminId
       }, adminId);
       
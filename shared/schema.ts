@@ -26,6 +26,8 @@ export const adminSettings = pgTable("admin_settings", {
   // Adding these new fields to store threshold values that aren't supported by the Solana program
   stakeThreshold: decimal("stake_threshold").notNull().default("10.0"),
   unstakeThreshold: decimal("unstake_threshold").notNull().default("10.0"),
+  // Minimum amount for adding to an existing position; stakeThreshold only gates new positions
+  minTopup: decimal("min_topup").notNull().default("0.0"),
   updatedAt: timestamp("updated_at").defaultNow(),
   updatedBy: integer("updated_by").references(() => adminUsers.id)
 });
@@ -98,6 +100,7 @@ export const insertAdminSettingsSchema = createInsertSchema(adminSettings).pick(
   harvestThreshold: true,
   stakeThreshold: true,  // Added new field
   unstakeThreshold: true, // Added new field
+  minTopup: true,
   updatedBy: true
 });
 